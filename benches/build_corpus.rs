@@ -30,6 +30,15 @@ where
     corpus
 }
 
+fn new_stream_load_corpus<NG>() -> Corpus<Vec<String>, NG, Lowercase<str>>
+where
+    NG: Ngram<G = ASCIIChar> + Debug,
+{
+    let corpus: Corpus<Vec<String>, NG, Lowercase<str>> = Corpus::from_iterator(iter_taxons());
+
+    corpus
+}
+
 fn new_par_load_corpus<NG>() -> Corpus<Vec<String>, NG, Lowercase<str>>
 where
     NG: Ngram<G = ASCIIChar> + Debug,
@@ -253,6 +262,22 @@ fn build_corpus_trigram_new(b: &mut Bencher) {
     });
 }
 
+#[bench]
+fn build_corpus_trigram_new_stream(b: &mut Bencher) {
+    // We load it first once outside the benchmark
+    // to avoid the noise related to not having the
+    // textual file loaded in memory.
+    let _ = new_stream_load_corpus::<TriGram<ASCIIChar>>();
+
+    b.iter(|| {
+        // Then we measure the time it takes to recreate
+        // the corpus from scratch several times.
+        black_box({
+            let _ = new_stream_load_corpus::<TriGram<ASCIIChar>>();
+        });
+    });
+}
+
 #[bench]
 fn build_corpus_trigram_new_webgraph(b: &mut Bencher) {
     // We load it first once outside the benchmark
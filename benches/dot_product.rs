@@ -0,0 +1,63 @@
+#![feature(test)]
+extern crate test;
+use ngrammatic::dot_product::dot_product_scalar;
+#[cfg(feature = "simd")]
+use ngrammatic::dot_product::dot_product_simd;
+use ngrammatic::prelude::*;
+use test::{black_box, Bencher};
+
+/// The number of longest taxon names whose pairwise dot products are measured.
+const NUMBER_OF_TAXONS: usize = 100;
+
+/// Returns an iterator over the taxons in the corpus.
+fn iter_taxons() -> impl Iterator<Item = String> {
+    use flate2::read::GzDecoder;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    let file = File::open("./benchmarks/taxons.csv.gz").unwrap();
+    let reader = BufReader::new(GzDecoder::new(file));
+    reader.lines().take(100_000).map(|line| line.unwrap())
+}
+
+/// Returns the ngram ids and counts of the longest taxon names.
+fn long_taxon_vectors() -> Vec<(Vec<u32>, Vec<u32>)> {
+    let mut taxons: Vec<String> = iter_taxons().collect();
+    taxons.sort_unstable_by_key(|taxon| std::cmp::Reverse(taxon.len()));
+    taxons.truncate(NUMBER_OF_TAXONS);
+
+    let corpus: Corpus<Vec<String>, TriGram<ASCIIChar>, Lowercase<str>> = Corpus::from(taxons);
+    (0..corpus.number_of_keys())
+        .map(|key_id| {
+            corpus
+                .ngram_ids_and_cooccurrences_from_key(key_id)
+                .map(|(ngram_id, count)| (ngram_id as u32, count as u32))
+                .unzip()
+        })
+        .collect()
+}
+
+/// Benchmarks the provided kernel over all of the pairs of long taxon names.
+fn bench_kernel(b: &mut Bencher, kernel: fn(&[u32], &[u32], &[u32], &[u32]) -> u64) {
+    let vectors = long_taxon_vectors();
+    b.iter(|| {
+        let mut total = 0;
+        for (left_ids, left_counts) in &vectors {
+            for (right_ids, right_counts) in &vectors {
+                total += kernel(left_ids, left_counts, right_ids, right_counts);
+            }
+        }
+        black_box(total)
+    });
+}
+
+#[bench]
+fn dot_product_scalar_long_taxons(b: &mut Bencher) {
+    bench_kernel(b, dot_product_scalar);
+}
+
+#[cfg(feature = "simd")]
+#[bench]
+fn dot_product_simd_long_taxons(b: &mut Bencher) {
+    bench_kernel(b, dot_product_simd);
+}
@@ -439,3 +439,16 @@ make_bench!(pentagram, PentaGram<ASCIIChar>);
 make_bench!(hexagram, HexaGram<ASCIIChar>);
 make_bench!(heptagram, HeptaGram<ASCIIChar>);
 make_bench!(octagram, OctaGram<ASCIIChar>);
+
+#[bench]
+/// Benchmark of the trigram search skipping the candidates sharing fewer than three
+/// ngrams with the query, to be compared with `trigram_bitvec_seq_search_vec`.
+fn trigram_bitvec_seq_search_vec_minimum_shared_ngrams(b: &mut Bencher) {
+    ngram_search(
+        b,
+        new_corpus_bitvec_vec::<TriGram<ASCIIChar>>(),
+        |corpus, key, search_config| {
+            corpus.ngram_search(key, search_config.set_minimum_shared_ngrams(3));
+        },
+    );
+}
@@ -70,6 +70,27 @@ where
     corpus
 }
 
+fn load_corpus_stream_new<NG>()
+where
+    NG: Ngram<G = ASCIIChar>,
+{
+    let start_time = std::time::Instant::now();
+    let corpus: Corpus<Vec<String>, NG, Lowercase<str>> = Corpus::from_iterator(iter_taxons());
+
+    let end_time = std::time::Instant::now();
+    let duration: usize = (end_time - start_time).as_millis() as usize;
+
+    // While this is a simple info message, we use the error flag so that the log will
+    // not get polluted by the log messages of the other dependencies which can, at times
+    // be quite significant.
+    log::error!(
+        "STREAM - Arity: {}, Time (ms): {}, memory (B): {}",
+        NG::ARITY,
+        duration.underscored(),
+        corpus.mem_size(SizeFlags::default()).underscored()
+    );
+}
+
 fn load_corpus_par_new<NG>()
 where
     NG: Ngram<G = ASCIIChar>,
@@ -229,6 +250,38 @@ where
     );
 }
 
+/// Logs the memory of the offsets of the weights of the corpus with each coding.
+fn offset_coding_memory<NG>(corpus: &Corpus<Vec<String>, NG, Lowercase<str>>)
+where
+    NG: Ngram<G = ASCIIChar>,
+{
+    use ngrammatic::weights::WeightsBuilder;
+
+    let weights_builder = || {
+        let mut builder = WeightsBuilder::new();
+        // The weights are stored as the co-occurrences minus one, as in the corpus.
+        let mut cooccurrences = corpus.cooccurrences().map(|cooccurrence| cooccurrence - 1);
+        for key_id in 0..corpus.number_of_keys() {
+            let degree = corpus.number_of_ngrams_from_key_id(key_id);
+            let weights: Vec<usize> = cooccurrences.by_ref().take(degree).collect();
+            builder.push(weights.into_iter()).unwrap();
+        }
+        builder
+    };
+    let elias_fano = weights_builder().build().into_inner().1;
+    let gaps = weights_builder().build_gap_coded().into_inner().1;
+
+    // While this is a simple info message, we use the error flag so that the log will
+    // not get polluted by the log messages of the other dependencies which can, at times
+    // be quite significant.
+    log::error!(
+        "WEIGHTS - Arity: {}, Elias-Fano offsets memory (B): {}, gap-coded offsets memory (B): {}",
+        NG::ARITY,
+        elias_fano.mem_size(SizeFlags::default()).underscored(),
+        gaps.mem_size(SizeFlags::default()).underscored(),
+    );
+}
+
 fn experiment<NG>()
 where
     NG: Ngram<G = ASCIIChar>,
@@ -239,6 +292,8 @@ where
         corpus.graph().number_of_edges() * 2,
         corpus.number_of_ngrams()
     );
+    offset_coding_memory(&corpus);
+    load_corpus_stream_new::<NG>();
     load_corpus_par_new::<NG>();
     load_corpus_rcl_par_new::<NG>();
     log::warn!("The webgraph benchmarks are skipped because the necessary version of the webgraph crate is not available.");
@@ -258,4 +313,5 @@ fn main() {
     // experiment::<HexaGram<ASCIIChar>>();
     // experiment::<HeptaGram<ASCIIChar>>();
     // experiment::<OctaGram<ASCIIChar>>();
+    // experiment::<HexadecaGram<ASCIIChar>>();
 }
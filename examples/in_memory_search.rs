@@ -0,0 +1,30 @@
+//! Minimal example building a corpus from a `Vec<String>` and searching it.
+//!
+//! The example only uses the in-memory parts of the crate, so that it also runs without
+//! the features reading and writing files:
+//!
+//! ```bash
+//! cargo run --example in_memory_search --no-default-features
+//! ```
+use ngrammatic::prelude::*;
+
+/// Returns the keys of the corpus closest to the provided query.
+fn search(corpus: &Corpus<Vec<String>, TriGram<char>>, query: &str) -> Vec<String> {
+    corpus
+        .ngram_search(query, NgramSearchConfig::default())
+        .into_iter()
+        .map(|result| result.key().to_string())
+        .collect()
+}
+
+fn main() {
+    let keys: Vec<String> = ["cat", "cattle", "dog", "doggo", "catfish", "banana"]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+
+    for query in ["cat", "doggo", "bananas"] {
+        println!("{}: {:?}", query, search(&corpus, query));
+    }
+}
@@ -1,7 +1,7 @@
 //! Module providing a vector that adaptatively grows in data type.
 
 use sux::dict::{EliasFano, EliasFanoBuilder};
-use sux::prelude::SelectFixed2;
+use sux::prelude::{BitFieldSliceMut, BitFieldVec, SelectFixed2};
 use sux::traits::ConvertTo;
 
 /// Trait defining a bounded type.
@@ -51,10 +51,29 @@ macro_rules! impl_bounded_types {
 
 impl_bounded_types!(u8, u16, u32, u64);
 
-pub(crate) enum AdaptativeVector {
+/// Vector of unsigned integers that adaptatively grows its data type.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let mut vector = AdaptativeVector::with_capacity(3, 0_u8);
+///
+/// assert!(!vector.push(10_u8));
+/// // Pushing a value that does not fit in an `u8` widens the vector.
+/// assert!(vector.push(1_000_u16));
+/// assert!(matches!(vector, AdaptativeVector::U16(_)));
+/// assert_eq!(vector.len(), 2);
+/// ```
+pub enum AdaptativeVector {
+    /// Vector of `u8` values.
     U8(Vec<u8>),
+    /// Vector of `u16` values.
     U16(Vec<u16>),
+    /// Vector of `u32` values.
     U32(Vec<u32>),
+    /// Vector of `u64` values.
     U64(Vec<u64>),
 }
 
@@ -147,7 +166,7 @@ impl AdaptativeVector {
     /// smallest possible data type, i.e. `u8`. As soon as
     /// the data type does not fit any of the provided values,
     /// the vector is converted to the next bigger data type.
-    pub(crate) fn with_capacity<A>(capacity: usize, value_type: A) -> Self
+    pub fn with_capacity<A>(capacity: usize, value_type: A) -> Self
     where
         A: Into<AdaptativeVectorValue>,
     {
@@ -160,7 +179,7 @@ impl AdaptativeVector {
     }
 
     /// Returns the length of the vector.
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         match self {
             AdaptativeVector::U8(vector) => vector.len(),
             AdaptativeVector::U16(vector) => vector.len(),
@@ -169,6 +188,124 @@ impl AdaptativeVector {
         }
     }
 
+    /// Returns whether the vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximal value in the vector, if any.
+    pub fn max(&self) -> Option<usize> {
+        match self {
+            AdaptativeVector::U8(vector) => vector.iter().max().map(|&value| value as usize),
+            AdaptativeVector::U16(vector) => vector.iter().max().map(|&value| value as usize),
+            AdaptativeVector::U32(vector) => vector.iter().max().map(|&value| value as usize),
+            AdaptativeVector::U64(vector) => vector.iter().max().map(|&value| value as usize),
+        }
+    }
+
+    /// Returns the minimal number of bits needed to store any of the values in the vector.
+    ///
+    /// # Implementation details
+    /// The minimal bit width is never smaller than one, even when the vector is empty
+    /// or contains exclusively zeros.
+    pub fn minimal_bit_width(&self) -> usize {
+        let max = self.max().unwrap_or(0);
+        ((usize::BITS - max.leading_zeros()) as usize).max(1)
+    }
+
+    /// Converts the vector into a bit-packed vector with the provided bit width.
+    ///
+    /// # Arguments
+    /// * `bit_width` - The number of bits used to store each value.
+    ///
+    /// # Implementation details
+    /// The bit width is fixed once the conversion happens: while pushing into the
+    /// adaptative vector widens its data type as needed, a bit-packed vector cannot
+    /// be widened. Therefore, the provided bit width must be at least the minimal bit
+    /// width of the values in the vector, as returned by `minimal_bit_width`, or the
+    /// conversion panics instead of silently truncating the values. A larger bit width
+    /// may be provided to pack several vectors consistently.
+    ///
+    /// # Panics
+    /// * If `bit_width` is larger than 64.
+    /// * If any of the values does not fit in `bit_width` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use sux::prelude::*;
+    ///
+    /// let mut vector = AdaptativeVector::with_capacity(3, 0_u8);
+    /// vector.push(1_u8);
+    /// vector.push(5_u8);
+    /// vector.push(3_u8);
+    ///
+    /// let bitvec = vector.into_bitvec(8);
+    ///
+    /// assert_eq!(bitvec.bit_width(), 8);
+    /// assert_eq!(bitvec.len(), 3);
+    /// assert_eq!(bitvec.get(1), 5);
+    /// ```
+    pub fn into_bitvec(self, bit_width: usize) -> BitFieldVec {
+        assert!(bit_width <= 64, "The bit width must be at most 64.");
+        assert!(
+            bit_width >= self.minimal_bit_width() || self.max().unwrap_or(0) == 0,
+            "The values of the vector do not fit in the provided bit width."
+        );
+        let mut bitvec = BitFieldVec::new(bit_width, self.len());
+        match self {
+            AdaptativeVector::U8(vector) => {
+                for (index, value) in vector.into_iter().enumerate() {
+                    bitvec.set(index, value as usize);
+                }
+            }
+            AdaptativeVector::U16(vector) => {
+                for (index, value) in vector.into_iter().enumerate() {
+                    bitvec.set(index, value as usize);
+                }
+            }
+            AdaptativeVector::U32(vector) => {
+                for (index, value) in vector.into_iter().enumerate() {
+                    bitvec.set(index, value as usize);
+                }
+            }
+            AdaptativeVector::U64(vector) => {
+                for (index, value) in vector.into_iter().enumerate() {
+                    bitvec.set(index, value as usize);
+                }
+            }
+        }
+        bitvec
+    }
+
+    /// Converts the vector into a bit-packed vector using the minimal bit width.
+    ///
+    /// # Implementation details
+    /// This is equivalent to calling `into_bitvec` with the bit width returned by
+    /// `minimal_bit_width`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use sux::prelude::*;
+    ///
+    /// let mut vector = AdaptativeVector::with_capacity(3, 0_u8);
+    /// vector.push(1_u8);
+    /// vector.push(300_u16);
+    /// vector.push(3_u8);
+    ///
+    /// let bitvec = vector.into_minimal_bitvec();
+    ///
+    /// assert_eq!(bitvec.bit_width(), 9);
+    /// assert_eq!(bitvec.get(1), 300);
+    /// ```
+    pub fn into_minimal_bitvec(self) -> BitFieldVec {
+        let bit_width = self.minimal_bit_width();
+        self.into_bitvec(bit_width)
+    }
+
     /// Pushes a value to the vector.
     ///
     /// # Arguments
@@ -182,7 +319,7 @@ impl AdaptativeVector {
     /// # Returns
     /// A boolean indicating whether it was necessary to
     /// convert the vector to a bigger data type.
-    pub(crate) fn push<A>(&mut self, value: A) -> bool
+    pub fn push<A>(&mut self, value: A) -> bool
     where
         A: Into<AdaptativeVectorValue>,
     {
@@ -379,15 +516,24 @@ impl AdaptativeVector {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum AdaptativeVectorValue {
+/// Value that can be pushed into an `AdaptativeVector`.
+pub enum AdaptativeVectorValue {
+    /// An `u8` value.
     U8(u8),
+    /// An `u16` value.
     U16(u16),
+    /// An `u32` value.
     U32(u32),
+    /// An `u64` value.
     U64(u64),
 }
 
 impl AdaptativeVectorValue {
-    pub(crate) fn smallest<A>(value: A) -> Self
+    /// Returns the value converted to the smallest data type that can store it.
+    ///
+    /// # Arguments
+    /// * `value` - The value to convert.
+    pub fn smallest<A>(value: A) -> Self
     where
         A: Into<AdaptativeVectorValue>,
     {
@@ -560,3 +706,43 @@ impl From<usize> for AdaptativeVectorValue {
         AdaptativeVectorValue::U64(value as u64)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sux::prelude::*;
+
+    #[test]
+    fn test_into_bitvec_widths() {
+        let values = [0_u64, 7, 255, 70_000, 3];
+        let mut vector = AdaptativeVector::with_capacity(values.len(), 0_u8);
+        for value in values {
+            vector.push(value);
+        }
+        assert_eq!(vector.minimal_bit_width(), 17);
+
+        let minimal = vector.into_minimal_bitvec();
+        assert_eq!(minimal.bit_width(), 17);
+
+        let mut vector = AdaptativeVector::with_capacity(values.len(), 0_u8);
+        for value in values {
+            vector.push(value);
+        }
+        let explicit = vector.into_bitvec(32);
+        assert_eq!(explicit.bit_width(), 32);
+
+        for (index, value) in values.into_iter().enumerate() {
+            assert_eq!(minimal.get(index), value as usize);
+            assert_eq!(explicit.get(index), value as usize);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_into_bitvec_too_narrow() {
+        let mut vector = AdaptativeVector::with_capacity(2, 0_u8);
+        vector.push(1_u8);
+        vector.push(300_u16);
+        vector.into_bitvec(8);
+    }
+}
@@ -3,21 +3,29 @@ use std::iter::Map;
 
 use crate::bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph;
 use crate::lender_bit_field_bipartite_graph::RaggedListIter;
-use crate::traits::graph::WeightedBipartiteGraph;
-use crate::weights::Weights;
+use crate::mmap_reader_factory::MmapReaderFactory;
+use crate::traits::graph::{SrcsFromDstIds, WeightedBipartiteGraph};
+use crate::weights::{CursorReaderFactory, Weights};
 use crate::Corpus;
+use crate::GrowableKeys;
 use crate::Key;
 use crate::Keys;
 use crate::Ngram;
 use crate::Offset;
 use crate::Offsettable;
+use crate::PaddingMode;
+use crate::{SortedNgramStorage, SortedNgramStorageBuilder};
 use dsi_bitstream::traits::BigEndian;
+use std::fs::File;
 use std::hash::Hash;
 use std::hash::Hasher;
-use tempfile::Builder;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use tempfile::{Builder, TempDir};
 use webgraph::prelude::*;
 
 use mem_dbg::MemSize;
+use sux::bits::BitVec;
 
 #[cfg(feature = "rayon")]
 fn num_threads() -> usize {
@@ -37,6 +45,10 @@ type DecoderFactoryType = DynCodesDecoderFactory<
 
 struct LoadedGraph {
     bvgraph: BVGraph<DecoderFactoryType>,
+    /// The basename of the files of the graph.
+    basename: PathBuf,
+    /// The temporary directory holding the files of the graph, if they are not stored.
+    _directory: Option<TempDir>,
 }
 
 impl MemSize for LoadedGraph {
@@ -76,12 +88,15 @@ where
     fn try_from(
         corpus: Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>,
     ) -> Result<Self, Self::Error> {
-        Ok(Self::new(
+        let padding = corpus.padding;
+        let mut converted = Self::new(
             corpus.keys,
             corpus.ngrams,
             corpus.average_key_length,
             corpus.graph.try_into()?,
-        ))
+        );
+        converted.padding = padding;
+        Ok(converted)
     }
 }
 
@@ -108,7 +123,14 @@ impl TryFrom<WeightedBitFieldBipartiteGraph> for BiWebgraph {
             .tempdir()
             .map_err(|_| "Could not create temporary directory")?;
 
-        let basename = seed.to_string();
+        // The files of the graph are kept in their own temporary directory for as long as
+        // the graph is loaded, so that they can be copied when the corpus is stored.
+        let directory = Builder::new()
+            .prefix(&seed.to_string())
+            .tempdir()
+            .map_err(|_| "Could not create temporary directory")?;
+
+        let basename = directory.path().join(seed.to_string());
 
         BVComp::parallel_iter::<BigEndian, RaggedListIter>(
             &basename,
@@ -137,16 +159,12 @@ impl TryFrom<WeightedBitFieldBipartiteGraph> for BiWebgraph {
             .load()
             .map_err(|_| "Could not load BVGraph")?;
 
-        // For the time being, we delete the files associated with the graph.
-        std::fs::remove_file(format!("{}.graph", &basename))
-            .map_err(|_| "Could not remove graph (.graph) file")?;
-        std::fs::remove_file(format!("{}.properties", &basename))
-            .map_err(|_| "Could not remove property (.properties) file")?;
-        std::fs::remove_file(format!("{}.ef", &basename))
-            .map_err(|_| "Could not remove elias-fano (.ef) file")?;
-
         Ok(Self {
-            graph: LoadedGraph { bvgraph },
+            graph: LoadedGraph {
+                bvgraph,
+                basename,
+                _directory: Some(directory),
+            },
             number_of_source_nodes: graph.number_of_source_nodes(),
             number_of_destination_nodes: graph.number_of_destination_nodes(),
             srcs_to_dsts_weights: graph.srcs_to_dsts_weights,
@@ -154,6 +172,324 @@ impl TryFrom<WeightedBitFieldBipartiteGraph> for BiWebgraph {
     }
 }
 
+/// The magic bytes at the start of the file storing the keys and ngrams of a corpus.
+const CORPUS_MAGIC: [u8; 8] = *b"NGCORPUS";
+
+/// Returns the path of the file with the provided extension appended to the basename.
+///
+/// # Arguments
+/// * `basename` - The basename of the files.
+/// * `extension` - The extension of the file, without the leading dot.
+///
+/// # Implementation details
+/// The extension is appended rather than set, as the basename may contain dots.
+fn with_extension(basename: &Path, extension: &str) -> PathBuf {
+    let mut path = basename.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Reads a little-endian `u64` from the provided reader.
+///
+/// # Arguments
+/// * `reader` - The reader to read from.
+fn read_u64(reader: &mut impl Read) -> std::io::Result<usize> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "The value does not fit a usize."))
+}
+
+impl BiWebgraph {
+    /// Writes the files of the graph and of the weights with the provided basename.
+    ///
+    /// # Arguments
+    /// * `basename` - The basename of the files to write.
+    fn store(&self, basename: &Path) -> std::io::Result<()> {
+        for extension in ["graph", "properties", "ef"] {
+            let source = with_extension(&self.graph.basename, extension);
+            let destination = with_extension(basename, extension);
+            // Copying a file onto itself would truncate it.
+            if source != destination {
+                std::fs::copy(source, destination)?;
+            }
+        }
+        self.srcs_to_dsts_weights
+            .write_to(with_extension(basename, "weights"))
+    }
+
+    /// Loads the graph and the weights written by `store` with the provided basename.
+    ///
+    /// # Arguments
+    /// * `basename` - The basename of the files to load.
+    /// * `number_of_source_nodes` - The number of source nodes.
+    /// * `number_of_destination_nodes` - The number of destination nodes.
+    fn load(
+        basename: &Path,
+        number_of_source_nodes: usize,
+        number_of_destination_nodes: usize,
+    ) -> std::io::Result<Self> {
+        let invalid = |message: &'static str| Error::new(ErrorKind::InvalidData, message);
+
+        let bvgraph = BVGraph::with_basename(basename)
+            .offsets_mode::<LoadMmap>()
+            .mode::<LoadMmap>()
+            .load()
+            .map_err(|_| invalid("Could not load BVGraph"))?;
+        if bvgraph.num_nodes() != number_of_source_nodes + number_of_destination_nodes {
+            return Err(invalid("The graph does not match the corpus."));
+        }
+
+        // The weights are searched through the in-memory reader factory, so the
+        // memory-mapped bitstream is copied.
+        let weights = Weights::<MmapReaderFactory>::load(with_extension(basename, "weights"))?;
+        if weights.num_nodes() != number_of_source_nodes {
+            return Err(invalid("The weights do not match the corpus."));
+        }
+        let (num_nodes, num_weights) = (weights.num_nodes(), weights.num_weights());
+        let (reader_factory, offsets) = weights.into_inner();
+        let srcs_to_dsts_weights = Weights::new(
+            CursorReaderFactory::new(reader_factory.data().to_vec()),
+            offsets,
+            num_nodes,
+            num_weights,
+        );
+
+        Ok(Self {
+            graph: LoadedGraph {
+                bvgraph,
+                basename: basename.to_path_buf(),
+                _directory: None,
+            },
+            srcs_to_dsts_weights,
+            number_of_source_nodes,
+            number_of_destination_nodes,
+        })
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, BiWebgraph>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K> + AsRef<str>,
+    K: Key<NG, NG::G> + ?Sized,
+    NG::G: Into<char>,
+{
+    /// Writes the corpus to the files with the provided basename.
+    ///
+    /// # Arguments
+    /// * `basename` - The basename of the files to write, such as `dir/animals`.
+    ///
+    /// # Raises
+    /// * If any of the files cannot be created or written.
+    ///
+    /// # Implementation details
+    /// The graph is written in the webgraph format, in the files with the `.graph`,
+    /// `.properties` and `.ef` extensions, so that it can be memory-mapped when loaded.
+    /// The weights are written with `Weights::write_to` in the file with the `.weights`
+    /// extension. The ngrams, the keys, the removed keys and the average key length are
+    /// written in the file with the `.corpus` extension, which starts with the magic bytes
+    /// `NGCORPUS`, followed by the arity, the number of keys, the number of ngrams, the
+    /// number of removed keys, the bits of the average key length and the padding, which is
+    /// zero for no padding, one for the automatic padding, or two followed by the code point
+    /// of the padding gram as `u32`. Then follow the code points of the grams of each ngram
+    /// as `u32`, the ids of the removed keys, and the length in bytes and the UTF-8 bytes of
+    /// each key. All integers are little-endian `u64` unless stated otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let animals: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(animals);
+    /// let corpus: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+    ///     Corpus::try_from(corpus).unwrap();
+    ///
+    /// let directory = tempfile::tempdir().unwrap();
+    /// let basename = directory.path().join("animals");
+    /// corpus.store(&basename).unwrap();
+    ///
+    /// let loaded: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+    ///     Corpus::load(&basename).unwrap();
+    ///
+    /// assert_eq!(loaded.number_of_keys(), 699);
+    /// assert_eq!(loaded.key_from_id(20), "Alligator");
+    /// ```
+    pub fn store(&self, basename: impl AsRef<Path>) -> std::io::Result<()> {
+        let basename = basename.as_ref();
+        self.graph.store(basename)?;
+
+        let mut writer = BufWriter::new(File::create(with_extension(basename, "corpus"))?);
+        writer.write_all(&CORPUS_MAGIC)?;
+        for value in [
+            NG::ARITY,
+            self.number_of_key_ids(),
+            self.number_of_ngrams(),
+            self.number_of_removed_keys,
+        ] {
+            writer.write_all(&(value as u64).to_le_bytes())?;
+        }
+        writer.write_all(&self.average_key_length.to_bits().to_le_bytes())?;
+        match self.padding {
+            PaddingMode::None => writer.write_all(&0_u64.to_le_bytes())?,
+            PaddingMode::Auto => writer.write_all(&1_u64.to_le_bytes())?,
+            PaddingMode::Pad(gram) => {
+                writer.write_all(&2_u64.to_le_bytes())?;
+                writer.write_all(&u32::from(Into::<char>::into(gram)).to_le_bytes())?;
+            }
+        }
+        for ngram in self.ngrams() {
+            for i in 0..NG::ARITY {
+                writer.write_all(&u32::from(Into::<char>::into(ngram[i])).to_le_bytes())?;
+            }
+        }
+        for key_id in (0..self.number_of_key_ids()).filter(|&key_id| self.is_removed(key_id)) {
+            writer.write_all(&(key_id as u64).to_le_bytes())?;
+        }
+        for key_id in 0..self.number_of_key_ids() {
+            let key = self.key_from_id(key_id);
+            let key: &str = key.as_ref();
+            writer.write_all(&(key.len() as u64).to_le_bytes())?;
+            writer.write_all(key.as_bytes())?;
+        }
+        writer.flush()
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, BiWebgraph>
+where
+    NG: Ngram,
+    KS: GrowableKeys<NG>,
+    KS::K: From<String>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    NG::G: TryFrom<char>,
+{
+    /// Loads the corpus written by `store` with the provided basename.
+    ///
+    /// # Arguments
+    /// * `basename` - The basename of the files to load.
+    ///
+    /// # Raises
+    /// * If any of the files cannot be opened or read.
+    /// * If the files were not written by `store`, with the same ngram type.
+    ///
+    /// # Implementation details
+    /// The graph is memory-mapped, so the files must not be modified while the corpus is
+    /// loaded, while the weights, the ngrams and the keys are read into memory. Please refer
+    /// to the documentation of `store` for the format of the files.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat".to_string(), "dog".to_string(), "cow".to_string()]);
+    /// let corpus: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+    ///     Corpus::try_from(corpus).unwrap();
+    ///
+    /// let directory = tempfile::tempdir().unwrap();
+    /// let basename = directory.path().join("pets");
+    /// corpus.store(&basename).unwrap();
+    ///
+    /// let loaded: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+    ///     Corpus::load(&basename).unwrap();
+    ///
+    /// assert!(loaded.ngrams().eq(corpus.ngrams()));
+    /// assert!(loaded.ngram_ids_from_key(1).eq(corpus.ngram_ids_from_key(1)));
+    /// ```
+    pub fn load(basename: impl AsRef<Path>) -> std::io::Result<Self> {
+        let basename = basename.as_ref();
+        let invalid = |message: &'static str| Error::new(ErrorKind::InvalidData, message);
+
+        let mut reader = BufReader::new(File::open(with_extension(basename, "corpus"))?);
+        let mut magic = [0; CORPUS_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != CORPUS_MAGIC {
+            return Err(invalid("The file is not a corpus file."));
+        }
+        if read_u64(&mut reader)? != NG::ARITY {
+            return Err(invalid(
+                "The arity of the corpus does not match the ngram type.",
+            ));
+        }
+        let number_of_keys = read_u64(&mut reader)?;
+        let number_of_ngrams = read_u64(&mut reader)?;
+        let number_of_removed_keys = read_u64(&mut reader)?;
+        let mut bits = [0; 8];
+        reader.read_exact(&mut bits)?;
+        let average_key_length = f64::from_bits(u64::from_le_bytes(bits));
+        let read_gram = |reader: &mut BufReader<File>| -> std::io::Result<NG::G> {
+            let mut code_point = [0; 4];
+            reader.read_exact(&mut code_point)?;
+            char::from_u32(u32::from_le_bytes(code_point))
+                .and_then(|character| NG::G::try_from(character).ok())
+                .ok_or_else(|| invalid("The gram does not match the gram type."))
+        };
+        let padding = match read_u64(&mut reader)? {
+            0 => PaddingMode::None,
+            1 => PaddingMode::Auto,
+            2 => PaddingMode::Pad(read_gram(&mut reader)?),
+            _ => return Err(invalid("The padding of the corpus is not valid.")),
+        };
+
+        let mut ngrams = Vec::new();
+        for _ in 0..number_of_ngrams {
+            let mut ngram = NG::default();
+            for i in 0..NG::ARITY {
+                ngram[i] = read_gram(&mut reader)?;
+            }
+            ngrams.push(ngram);
+        }
+        if ngrams.windows(2).any(|window| window[0] >= window[1]) {
+            return Err(invalid("The ngrams must be sorted and unique."));
+        }
+        let mut ngram_builder =
+            <<<NG as Ngram>::SortedStorage as SortedNgramStorage<NG>>::Builder>::new_storage_builder(
+                ngrams.len(),
+                ngrams.last().copied().unwrap_or_default(),
+            );
+        for ngram in ngrams {
+            // SAFETY: the ngrams are sorted, unique and bounded by the last one.
+            unsafe { ngram_builder.push_unchecked(ngram) };
+        }
+
+        let mut tombstones = BitVec::new(0);
+        if number_of_removed_keys > 0 {
+            tombstones = BitVec::new(number_of_keys);
+            for _ in 0..number_of_removed_keys {
+                let key_id = read_u64(&mut reader)?;
+                if key_id >= number_of_keys || tombstones.get(key_id) {
+                    return Err(invalid("The removed keys must be distinct key ids."));
+                }
+                tombstones.set(key_id, true);
+            }
+        }
+
+        let mut keys = KS::default();
+        for _ in 0..number_of_keys {
+            let mut key = vec![0; read_u64(&mut reader)?];
+            reader.read_exact(&mut key)?;
+            let key = String::from_utf8(key).map_err(|_| invalid("The key is not UTF-8."))?;
+            keys.push(key.into());
+        }
+
+        let graph = BiWebgraph::load(basename, number_of_keys, number_of_ngrams)?;
+
+        let mut corpus = Self::new(keys, ngram_builder.build(), average_key_length, graph);
+        corpus.tombstones = tombstones;
+        corpus.number_of_removed_keys = number_of_removed_keys;
+        corpus.padding = padding;
+        Ok(corpus)
+    }
+}
+
 impl WeightedBipartiteGraph for BiWebgraph {
     #[inline(always)]
     /// Returns the number of source nodes.
@@ -337,6 +673,37 @@ impl WeightedBipartiteGraph for BiWebgraph {
             .successors(dst_id + self.number_of_source_nodes())
     }
 
+    type SrcsFromDsts<'a> = SrcsFromDstIds<'a, Self>;
+
+    #[inline(always)]
+    /// Returns the source nodes of each destination node.
+    ///
+    /// # Implementation details
+    /// The successors of the destination nodes are decoded one node at a time,
+    /// as the random access to the compressed graph is needed to return them as
+    /// independent iterators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::convert::TryFrom;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let webgraph_corpus: Corpus<&[&str; 699], TriGram<char>, str, BiWebgraph> =
+    ///     Corpus::try_from(corpus.clone()).unwrap();
+    ///
+    /// let srcs = corpus.graph().srcs_from_dsts();
+    /// let webgraph_srcs = webgraph_corpus.graph().srcs_from_dsts();
+    ///
+    /// for (srcs, webgraph_srcs) in srcs.zip(webgraph_srcs) {
+    ///     assert!(srcs.eq(webgraph_srcs));
+    /// }
+    /// ```
+    fn srcs_from_dsts(&self) -> Self::SrcsFromDsts<'_> {
+        SrcsFromDstIds::new(self)
+    }
+
     type Dsts<'a> = Offset<<BVGraph<DecoderFactoryType> as RandomAccessLabeling>::Labels<'a>>;
 
     #[inline(always)]
@@ -485,3 +852,74 @@ impl WeightedBipartiteGraph for BiWebgraph {
         self.graph.bvgraph.offset_deg_iter().map(|(_, deg)| deg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_store_and_load_round_trip() {
+        let animals: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+        let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(animals);
+        let mut corpus: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::try_from(corpus).unwrap();
+        corpus.remove_key(3);
+
+        let directory = tempfile::tempdir().unwrap();
+        let basename = directory.path().join("animals.v1");
+        corpus.store(&basename).unwrap();
+        let loaded: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::load(&basename).unwrap();
+
+        assert_eq!(loaded.number_of_keys(), corpus.number_of_keys());
+        assert_eq!(loaded.average_key_length(), corpus.average_key_length());
+        assert!(loaded.is_removed(3));
+        assert!(loaded.ngrams().eq(corpus.ngrams()));
+        assert!(loaded.graph().weights().eq(corpus.graph().weights()));
+        for key_id in 0..corpus.number_of_key_ids() {
+            assert_eq!(loaded.key_from_id(key_id), corpus.key_from_id(key_id));
+            assert!(loaded
+                .ngram_ids_from_key(key_id)
+                .eq(corpus.ngram_ids_from_key(key_id)));
+        }
+        for ngram_id in 0..corpus.number_of_ngrams() {
+            assert!(loaded
+                .key_ids_from_ngram_id(ngram_id)
+                .eq(corpus.key_ids_from_ngram_id(ngram_id)));
+        }
+
+        // A loaded corpus can be stored again, even onto its own files.
+        loaded.store(&basename).unwrap();
+        let reloaded: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::load(&basename).unwrap();
+        assert!(reloaded.ngrams().eq(corpus.ngrams()));
+        assert_eq!(reloaded.padding(), PaddingMode::Auto);
+
+        // The padding of the corpus is stored as well.
+        let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from_with_padding(
+            vec!["cat".to_string(), "dog".to_string()],
+            PaddingMode::Pad('$'),
+        );
+        let corpus: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::try_from(corpus).unwrap();
+        corpus.store(&basename).unwrap();
+        let loaded: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::load(&basename).unwrap();
+        assert_eq!(loaded.padding(), PaddingMode::Pad('$'));
+        assert!(loaded.ngrams().eq(corpus.ngrams()));
+    }
+
+    #[test]
+    fn test_load_rejects_a_different_arity() {
+        let corpus: Corpus<Vec<String>, TriGram<char>> =
+            Corpus::from(vec!["cat".to_string(), "dog".to_string()]);
+        let corpus: Corpus<Vec<String>, TriGram<char>, str, BiWebgraph> =
+            Corpus::try_from(corpus).unwrap();
+
+        let directory = tempfile::tempdir().unwrap();
+        let basename = directory.path().join("pets");
+        corpus.store(&basename).unwrap();
+
+        assert!(Corpus::<Vec<String>, BiGram<char>, str, BiWebgraph>::load(&basename).is_err());
+    }
+}
@@ -1,15 +1,19 @@
 //! Submodule providing a bitfield bipartite graph which provides a structure
 //! storing a bipartite graph into two CSR-like structures composed of bitfields.
 
+use std::cmp::Ordering;
 use std::iter::Chain;
 use std::iter::Map;
 use std::iter::Zip;
 
 use mem_dbg::{MemDbg, MemSize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use sux::bits::BitFieldVec;
 use sux::dict::elias_fano::EliasFanoIterator;
 use sux::dict::EliasFano;
+use sux::prelude::BitFieldSliceMut;
 use sux::prelude::BitFieldVecIterator;
 use sux::rank_sel::SelectFixed2;
 use sux::traits::BitFieldSliceCore;
@@ -17,11 +21,16 @@ use sux::traits::IndexedDict;
 use sux::traits::Pred;
 use webgraph::traits::RandomAccessLabeling;
 
+use crate::weights::elias_fano_from_sorted;
 use crate::weights::Weights;
 use crate::WeightedBipartiteGraph;
 
 #[derive(MemSize, MemDbg, Debug, Clone)]
 /// A bipartite graph stored in two CSR-like structures composed of bitfields.
+///
+/// # Implementation details
+/// None of the structures of the graph are modified once it is built, so that the graph
+/// is `Send` and `Sync`, and its edges can be read concurrently by multiple threads.
 pub struct WeightedBitFieldBipartiteGraph {
     /// Vector containing the number of times a given gram appears in a given key.
     /// This is a descriptor of an edge from a Key to a Gram.
@@ -32,18 +41,18 @@ pub struct WeightedBitFieldBipartiteGraph {
     /// vector is the total number of edges in the bipartite graph from keys to grams.
     /// We use this vector alongside the `cooccurrences` vector to find the weighted edges
     /// of a given key. The destinations, i.e. the grams, are found in the `grams` vector.
-    srcs_offsets: EliasFano<SelectFixed2>,
+    pub(crate) srcs_offsets: EliasFano<SelectFixed2>,
     /// Vector contain the comulative inbound degree from a given gram to keys.
     /// This is a vector with the same length as the grams vector PLUS ONE, and the value at
     /// index `i` is the sum of the inbound degrees before index `i`. The last element of this
     /// vector is the total number of edges in the bipartite graph from grams to keys.
     /// These edges are NOT weighted, as the weights are stored in the `cooccurrences` vector and
     /// solely refer to the edges from keys to grams.
-    dsts_offsets: EliasFano<SelectFixed2>,
+    pub(crate) dsts_offsets: EliasFano<SelectFixed2>,
     /// Vector containing the destinations of the edges from keys to grams.
-    srcs_to_dsts: BitFieldVec,
+    pub(crate) srcs_to_dsts: BitFieldVec,
     /// Vector containing the sources of the edges from grams to keys.
-    dsts_to_srcs: BitFieldVec,
+    pub(crate) dsts_to_srcs: BitFieldVec,
 }
 
 impl WeightedBitFieldBipartiteGraph {
@@ -119,6 +128,37 @@ impl WeightedBitFieldBipartiteGraph {
     }
 }
 
+/// Iterator over the srcs of each dst of a `WeightedBitFieldBipartiteGraph`.
+///
+/// # Implementation details
+/// The offsets of the dsts are read sequentially, so that each offset is decoded
+/// once, instead of the two lookups of each call to `srcs_from_dst`.
+pub struct SrcsFromDsts<'a> {
+    /// The srcs of the edges from dsts to srcs.
+    srcs: &'a BitFieldVec,
+    /// The offset of the srcs of the next dst.
+    start: usize,
+    /// The offsets of the end of the srcs of the remaining dsts.
+    ends: EliasFanoIterator<'a, SelectFixed2, BitFieldVec>,
+}
+
+impl<'a> Iterator for SrcsFromDsts<'a> {
+    type Item = BitFieldVecIterator<'a, usize, Vec<usize>>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.ends.next()?;
+        let srcs = self.srcs.iter_range(self.start, end);
+        self.start = end;
+        Some(srcs)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ends.size_hint()
+    }
+}
+
 impl WeightedBipartiteGraph for WeightedBitFieldBipartiteGraph {
     #[inline(always)]
     fn number_of_source_nodes(&self) -> usize {
@@ -158,6 +198,17 @@ impl WeightedBipartiteGraph for WeightedBitFieldBipartiteGraph {
         self.srcs_to_dsts.iter_range(start, end)
     }
 
+    type SrcsFromDsts<'a> = SrcsFromDsts<'a>;
+
+    #[inline(always)]
+    fn srcs_from_dsts(&self) -> Self::SrcsFromDsts<'_> {
+        SrcsFromDsts {
+            srcs: &self.srcs_to_dsts,
+            start: self.dsts_offsets.get(0),
+            ends: self.dsts_offsets.into_iter_from(1),
+        }
+    }
+
     type Dsts<'a> = BitFieldVecIterator<'a, usize, Vec<usize>>;
 
     #[inline(always)]
@@ -167,6 +218,23 @@ impl WeightedBipartiteGraph for WeightedBitFieldBipartiteGraph {
         self.dsts_to_srcs.iter_range(start, end)
     }
 
+    #[inline(always)]
+    fn dst_position_from_src(&self, src_id: usize, dst_id: usize) -> Option<usize> {
+        let start = self.srcs_offsets.get(src_id);
+        let end = self.srcs_offsets.get(src_id + 1);
+        let (mut low, mut high) = (start, end);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let candidate = sux::prelude::BitFieldSlice::get(&self.dsts_to_srcs, middle);
+            match candidate.cmp(&dst_id) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(middle - start),
+            }
+        }
+        None
+    }
+
     type WeightsSrc<'a> = crate::weights::Succ<
         <crate::weights::CursorReaderFactory as crate::weights::ReaderFactory>::Reader<'a>,
     >;
@@ -220,3 +288,109 @@ impl WeightedBipartiteGraph for WeightedBitFieldBipartiteGraph {
             )
     }
 }
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Plain representation of a `WeightedBitFieldBipartiteGraph`, used to serialize it and
+/// to write it to a corpus file.
+///
+/// # Implementation details
+/// The Elias-Fano offsets are stored as the plain sorted values, and the bitfields as
+/// their bit width and values, so that the succinct structures are rebuilt on load.
+pub(crate) struct RawWeightedBitFieldBipartiteGraph {
+    /// The weights of the edges from keys to grams.
+    pub(crate) srcs_to_dsts_weights: Weights,
+    /// The comulative outbound degree from a given key to grams.
+    pub(crate) srcs_offsets: Vec<usize>,
+    /// The comulative inbound degree from a given gram to keys.
+    pub(crate) dsts_offsets: Vec<usize>,
+    /// The bit width of the destinations of the edges from keys to grams.
+    pub(crate) srcs_to_dsts_bit_width: usize,
+    /// The destinations of the edges from keys to grams.
+    pub(crate) srcs_to_dsts: Vec<usize>,
+    /// The bit width of the sources of the edges from grams to keys.
+    pub(crate) dsts_to_srcs_bit_width: usize,
+    /// The sources of the edges from grams to keys.
+    pub(crate) dsts_to_srcs: Vec<usize>,
+}
+
+/// Returns a bitfield vector with the provided bit width and values.
+///
+/// # Arguments
+/// * `bit_width` - The number of bits used to store each value.
+/// * `values` - The values to store.
+///
+/// # Raises
+/// * If the bit width is larger than 64 or any of the values does not fit in it.
+fn bit_field_vec_from_values(
+    bit_width: usize,
+    values: &[usize],
+) -> Result<BitFieldVec, &'static str> {
+    if bit_width > 64 || (bit_width < 64 && values.iter().any(|&value| value >> bit_width != 0)) {
+        return Err("The values of the bitfield do not fit in its bit width.");
+    }
+    let mut bit_field_vec = BitFieldVec::new(bit_width, values.len());
+    for (index, &value) in values.iter().enumerate() {
+        bit_field_vec.set(index, value);
+    }
+    Ok(bit_field_vec)
+}
+
+impl From<&WeightedBitFieldBipartiteGraph> for RawWeightedBitFieldBipartiteGraph {
+    fn from(graph: &WeightedBitFieldBipartiteGraph) -> Self {
+        RawWeightedBitFieldBipartiteGraph {
+            srcs_to_dsts_weights: graph.srcs_to_dsts_weights.clone(),
+            srcs_offsets: graph.srcs_offsets.into_iter_from(0).collect(),
+            dsts_offsets: graph.dsts_offsets.into_iter_from(0).collect(),
+            srcs_to_dsts_bit_width: graph.srcs_to_dsts.bit_width(),
+            srcs_to_dsts: graph
+                .srcs_to_dsts
+                .iter_range(0, graph.srcs_to_dsts.len())
+                .collect(),
+            dsts_to_srcs_bit_width: graph.dsts_to_srcs.bit_width(),
+            dsts_to_srcs: graph
+                .dsts_to_srcs
+                .iter_range(0, graph.dsts_to_srcs.len())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<RawWeightedBitFieldBipartiteGraph> for WeightedBitFieldBipartiteGraph {
+    type Error = &'static str;
+
+    fn try_from(graph: RawWeightedBitFieldBipartiteGraph) -> Result<Self, Self::Error> {
+        let number_of_edges = graph.srcs_to_dsts_weights.num_weights();
+        if graph.srcs_offsets.len() != graph.srcs_to_dsts_weights.num_nodes() + 1
+            || graph.srcs_to_dsts.len() != number_of_edges
+            || graph.dsts_to_srcs.len() != number_of_edges
+            || graph.srcs_offsets.last().copied() != Some(number_of_edges)
+            || graph.dsts_offsets.last().copied() != Some(number_of_edges)
+        {
+            return Err("The number of nodes or edges of the graph is not consistent.");
+        }
+        Ok(WeightedBitFieldBipartiteGraph::new(
+            graph.srcs_to_dsts_weights,
+            elias_fano_from_sorted(&graph.srcs_offsets)?,
+            elias_fano_from_sorted(&graph.dsts_offsets)?,
+            bit_field_vec_from_values(graph.srcs_to_dsts_bit_width, &graph.srcs_to_dsts)?,
+            bit_field_vec_from_values(graph.dsts_to_srcs_bit_width, &graph.dsts_to_srcs)?,
+        ))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for WeightedBitFieldBipartiteGraph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawWeightedBitFieldBipartiteGraph::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for WeightedBitFieldBipartiteGraph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let graph = RawWeightedBitFieldBipartiteGraph::deserialize(deserializer)?;
+        WeightedBitFieldBipartiteGraph::try_from(graph).map_err(D::Error::custom)
+    }
+}
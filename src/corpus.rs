@@ -1,20 +1,44 @@
 //! Submodule providing the Corpus data structure.
-// #[cfg(feature = "serde")]
-// use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use std::{cmp::Reverse, iter::Map};
+use std::{cmp::Reverse, collections::HashMap, iter::Map};
 
+use fxhash::FxBuildHasher;
 use mem_dbg::{MemDbg, MemSize};
+use sux::bits::BitVec;
 
-use crate::{bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, traits::*};
+use crate::{
+    bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, overflow_graph::OverflowGraph,
+    traits::*, weights::WeightsBuilder, PaddingMode,
+};
 
 #[derive(MemSize, MemDbg)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "KS: Serialize, NG: Serialize, NG::G: Serialize, G: Serialize",
+        deserialize = "KS: Deserialize<'de>, NG: Deserialize<'de>, NG::G: Deserialize<'de>, \
+                       G: Deserialize<'de>"
+    ))
+)]
 /// Rasterized corpus.
 ///
 /// # Implementation details
 /// This corpus is represented as a sparse graph, using a CSR format. The
 /// links between keys and grams are weighted by the number of times a given
 /// gram appears in a given key: we call this vector the `cooccurrences`.
+///
+/// With the `serde` feature, the corpus can be serialized and deserialized with
+/// any serde format, so that it does not need to be rebuilt from the keys. The
+/// succinct data structures are stored as their plain values, and are rebuilt
+/// when the corpus is deserialized.
+///
+/// The corpus has no interior mutability and is searched through shared references,
+/// so that it is `Send` and `Sync` whenever its keys and its graph are. It can therefore
+/// be shared behind an `Arc` by the threads of a web server, each running its searches
+/// concurrently, while inserting or removing keys requires an exclusive reference.
 pub struct Corpus<
     KS: Keys<NG>,
     NG: Ngram,
@@ -24,15 +48,111 @@ pub struct Corpus<
     /// Vector of unique keys in the corpus.
     pub(crate) keys: KS,
     /// Vector of unique ngrams in the corpus.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_ngrams::<NG, _>",
+            deserialize_with = "deserialize_ngrams::<NG, _>"
+        )
+    )]
     pub(crate) ngrams: NG::SortedStorage,
     /// Graph describing the weighted bipapartite graph from keys to grams.
     pub(crate) graph: G,
     /// Average key length.
     pub(crate) average_key_length: f64,
+    /// Keys inserted after the construction of the corpus, not yet in the graph.
+    pub(crate) overflow: OverflowGraph<NG>,
+    /// Bits set to one mark the ids of the removed keys, empty if no key was ever removed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_tombstones",
+            deserialize_with = "deserialize_tombstones"
+        )
+    )]
+    pub(crate) tombstones: BitVec,
+    /// The number of removed keys.
+    pub(crate) number_of_removed_keys: usize,
+    /// The padding of the keys and of the queries.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) padding: PaddingMode<NG::G>,
     /// Phantom type to store the type of the keys.
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: std::marker::PhantomData<K>,
 }
 
+#[cfg(feature = "serde")]
+/// Serializes the sorted ngrams of a corpus as a sequence of ngrams.
+///
+/// # Arguments
+/// * `ngrams` - The sorted ngrams to serialize.
+/// * `serializer` - The serializer to use.
+fn serialize_ngrams<NG, S>(ngrams: &NG::SortedStorage, serializer: S) -> Result<S::Ok, S::Error>
+where
+    NG: Ngram + Serialize,
+    S: Serializer,
+{
+    serializer.collect_seq(ngrams.iter())
+}
+
+#[cfg(feature = "serde")]
+/// Deserializes the sorted ngrams of a corpus, rebuilding their sorted storage.
+///
+/// # Arguments
+/// * `deserializer` - The deserializer to use.
+///
+/// # Raises
+/// * If the ngrams are not sorted or not unique.
+fn deserialize_ngrams<'de, NG, D>(deserializer: D) -> Result<NG::SortedStorage, D::Error>
+where
+    NG: Ngram + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let ngrams: Vec<NG> = Vec::deserialize(deserializer)?;
+    if ngrams.windows(2).any(|window| window[0] >= window[1]) {
+        return Err(D::Error::custom("The ngrams must be sorted and unique."));
+    }
+    let mut ngram_builder =
+        <<<NG as Ngram>::SortedStorage as SortedNgramStorage<NG>>::Builder>::new_storage_builder(
+            ngrams.len(),
+            ngrams.last().copied().unwrap_or_default(),
+        );
+    for ngram in ngrams {
+        // SAFETY: the ngrams are sorted, unique and bounded by the last one.
+        unsafe { ngram_builder.push_unchecked(ngram) };
+    }
+    Ok(ngram_builder.build())
+}
+
+#[cfg(feature = "serde")]
+/// Serializes the tombstones of a corpus as the sorted ids of the removed keys.
+///
+/// # Arguments
+/// * `tombstones` - The tombstones to serialize.
+/// * `serializer` - The serializer to use.
+fn serialize_tombstones<S: Serializer>(
+    tombstones: &BitVec,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq((0..tombstones.len()).filter(|&key_id| tombstones.get(key_id)))
+}
+
+#[cfg(feature = "serde")]
+/// Deserializes the tombstones of a corpus from the sorted ids of the removed keys.
+///
+/// # Arguments
+/// * `deserializer` - The deserializer to use.
+fn deserialize_tombstones<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BitVec, D::Error> {
+    let removed_key_ids: Vec<usize> = Vec::deserialize(deserializer)?;
+    let mut tombstones = BitVec::new(removed_key_ids.iter().max().map_or(0, |&key_id| key_id + 1));
+    for key_id in removed_key_ids {
+        tombstones.set(key_id, true);
+    }
+    Ok(tombstones)
+}
+
 impl<KS, NG, K, G> Clone for Corpus<KS, NG, K, G>
 where
     KS: Keys<NG> + Clone,
@@ -46,6 +166,10 @@ where
             ngrams: self.ngrams.clone(),
             graph: self.graph.clone(),
             average_key_length: self.average_key_length,
+            overflow: self.overflow.clone(),
+            tombstones: self.tombstones.clone(),
+            number_of_removed_keys: self.number_of_removed_keys,
+            padding: self.padding,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -76,20 +200,27 @@ where
     ///
     /// # Arguments
     /// * `keys` - The keys of the corpus.
-    /// * `ngrams` - The ngrams of the corpus.
-    /// * `average_key_length` - The average key length.
+    /// * `ngrams` - The sorted ngrams of the corpus.
+    /// * `average_key_length` - The average number of ngrams of the keys.
     /// * `graph` - The weighted bipartite graph.
-    pub(crate) fn new(
-        keys: KS,
-        ngrams: NG::SortedStorage,
-        average_key_length: f64,
-        graph: G,
-    ) -> Self {
+    ///
+    /// # Implementation details
+    /// This is the constructor to use to build a corpus around a custom graph, which must
+    /// follow the conventions documented in the `WeightedBipartiteGraph` trait: the source
+    /// with id `i` is the key with id `i`, and the destination with id `j` is the `j`-th
+    /// ngram of the sorted ngrams. The ngrams of each key must be counted as described by
+    /// its `Key` implementation, so that the queries share them. None of these properties
+    /// is checked, and the average key length is clamped to at least one.
+    pub fn new(keys: KS, ngrams: NG::SortedStorage, average_key_length: f64, graph: G) -> Self {
         Corpus {
             keys,
             ngrams,
             graph,
             average_key_length: average_key_length.max(1.0),
+            overflow: OverflowGraph::default(),
+            tombstones: BitVec::new(0),
+            number_of_removed_keys: 0,
+            padding: PaddingMode::Auto,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -108,12 +239,218 @@ where
     pub fn graph(&self) -> &G {
         &self.graph
     }
+
+    #[inline(always)]
+    /// Returns the padding of the keys and of the queries of the corpus.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let padded: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+    /// assert_eq!(padded.padding(), PaddingMode::Auto);
+    ///
+    /// let unpadded: Corpus<[&str; 2], TriGram<char>> =
+    ///     Corpus::from_with_padding(["cat", "dog"], PaddingMode::None);
+    /// assert_eq!(unpadded.padding(), PaddingMode::None);
+    /// ```
+    pub fn padding(&self) -> PaddingMode<NG::G> {
+        self.padding
+    }
+
+    #[inline(always)]
+    /// Returns the counts of the ngrams of the provided key, padded as the keys of the corpus.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted, such as a query.
+    pub(crate) fn key_counts(&self, key: &K) -> HashMap<NG, usize, FxBuildHasher> {
+        self.padding.counts(key)
+    }
 }
 
-/// Iterator over the ngram ids and their co-occurrences.
-pub type NgramIdsAndCooccurrences<'a, G> = std::iter::Zip<
+/// Iterator over the ngrams of a corpus paired with their ids.
+struct NgramsWithIds<I> {
+    /// The ngrams, sorted by ngram id.
+    ngrams: I,
+    /// The id of the next ngram.
+    ngram_id: usize,
+    /// The number of ngrams in the corpus.
+    number_of_ngrams: usize,
+}
+
+impl<NG, I: Iterator<Item = NG>> Iterator for NgramsWithIds<I> {
+    type Item = (usize, NG);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let ngram = self.ngrams.next()?;
+        let ngram_id = self.ngram_id;
+        self.ngram_id += 1;
+        Some((ngram_id, ngram))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.number_of_ngrams - self.ngram_id;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<NG, I: Iterator<Item = NG>> ExactSizeIterator for NgramsWithIds<I> {}
+
+/// Iterator over the ids of the keys containing an ngram, skipping the removed keys.
+pub struct KeyIdsFromNgramId<'a, G: WeightedBipartiteGraph + 'a> {
+    /// The ids of the keys of the compressed graph containing the ngram, if the ngram
+    /// is in its vocabulary.
+    key_ids: Option<G::Srcs<'a>>,
+    /// The ids of the overflow keys containing the ngram, which follow the other ones.
+    overflow_key_ids: std::slice::Iter<'a, usize>,
+    /// The tombstones of the removed keys.
+    tombstones: &'a BitVec,
+}
+
+impl<'a, G: WeightedBipartiteGraph + 'a> Iterator for KeyIdsFromNgramId<'a, G> {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let tombstones = self.tombstones;
+        let is_kept = |key_id: &usize| *key_id >= tombstones.len() || !tombstones.get(*key_id);
+        if let Some(key_ids) = self.key_ids.as_mut() {
+            if let Some(key_id) = key_ids.find(is_kept) {
+                return Some(key_id);
+            }
+            self.key_ids = None;
+        }
+        self.overflow_key_ids.by_ref().copied().find(is_kept)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let number_of_key_ids =
+            self.key_ids.as_ref().map_or(0, |key_ids| key_ids.len()) + self.overflow_key_ids.len();
+        if self.tombstones.len() == 0 {
+            (number_of_key_ids, Some(number_of_key_ids))
+        } else {
+            (0, Some(number_of_key_ids))
+        }
+    }
+}
+
+/// Iterator over the ids of the keys containing an ngram, skipping the removed keys, whose
+/// number is counted when the iterator is created.
+pub struct CountedKeyIdsFromNgramId<'a, G: WeightedBipartiteGraph + 'a> {
+    /// The ids of the keys containing the ngram.
+    key_ids: KeyIdsFromNgramId<'a, G>,
+    /// The number of ids yet to be returned.
+    remaining: usize,
+}
+
+impl<'a, G: WeightedBipartiteGraph + 'a> Iterator for CountedKeyIdsFromNgramId<'a, G> {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let key_id = self.key_ids.next()?;
+        self.remaining -= 1;
+        Some(key_id)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, G: WeightedBipartiteGraph + 'a> ExactSizeIterator for CountedKeyIdsFromNgramId<'a, G> {}
+
+/// Iterator over items in key id order, such as the keys of a corpus, skipping the removed keys.
+pub struct SkipRemoved<'a, I> {
+    /// The items paired with their key ids.
+    items: std::iter::Enumerate<I>,
+    /// The tombstones of the removed keys.
+    tombstones: &'a BitVec,
+    /// The number of items of the keys that were not removed yet to be returned.
+    remaining: usize,
+}
+
+impl<'a, I: Iterator> Iterator for SkipRemoved<'a, I> {
+    type Item = I::Item;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let tombstones = self.tombstones;
+        let (_, item) = self
+            .items
+            .by_ref()
+            .find(|&(key_id, _)| key_id >= tombstones.len() || !tombstones.get(key_id))?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, I: Iterator> ExactSizeIterator for SkipRemoved<'a, I> {}
+
+#[derive(Debug, Clone)]
+/// Iterator over the ngrams of a key, stored either in the compressed graph or in the
+/// overflow graph of the keys inserted after the construction of the corpus.
+pub enum GraphOrOverflow<A, B> {
+    /// The iterator over a key of the compressed graph.
+    Graph(A),
+    /// The iterator over a key of the overflow graph.
+    Overflow(B),
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for GraphOrOverflow<A, B> {
+    type Item = A::Item;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            GraphOrOverflow::Graph(iterator) => iterator.next(),
+            GraphOrOverflow::Overflow(iterator) => iterator.next(),
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            GraphOrOverflow::Graph(iterator) => iterator.size_hint(),
+            GraphOrOverflow::Overflow(iterator) => iterator.size_hint(),
+        }
+    }
+}
+
+impl<A: ExactSizeIterator, B: ExactSizeIterator<Item = A::Item>> ExactSizeIterator
+    for GraphOrOverflow<A, B>
+{
+}
+
+/// Iterator over the ngram ids of a key.
+pub type NgramIdsFromKey<'a, G> = GraphOrOverflow<
     <G as WeightedBipartiteGraph>::Dsts<'a>,
+    Map<std::slice::Iter<'a, (usize, usize)>, fn(&(usize, usize)) -> usize>,
+>;
+
+/// Iterator over the ngram co-occurrences of a key.
+pub type NgramCooccurrencesFromKey<'a, G> = GraphOrOverflow<
     Map<<G as WeightedBipartiteGraph>::WeightsSrc<'a>, fn(usize) -> usize>,
+    Map<std::slice::Iter<'a, (usize, usize)>, fn(&(usize, usize)) -> usize>,
+>;
+
+/// Iterator over the ngram ids and their co-occurrences.
+pub type NgramIdsAndCooccurrences<'a, G> = GraphOrOverflow<
+    std::iter::Zip<
+        <G as WeightedBipartiteGraph>::Dsts<'a>,
+        Map<<G as WeightedBipartiteGraph>::WeightsSrc<'a>, fn(usize) -> usize>,
+    >,
+    std::iter::Copied<std::slice::Iter<'a, (usize, usize)>>,
 >;
 
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
@@ -126,6 +463,10 @@ where
     #[inline(always)]
     /// Returns the number of keys in the corpus.
     ///
+    /// # Implementation details
+    /// The removed keys are not counted, see `number_of_key_ids` for the
+    /// number of ids, including the ones of the removed keys.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -138,9 +479,58 @@ where
     /// assert_eq!(number_of_keys, 699);
     /// ```
     pub fn number_of_keys(&self) -> usize {
+        self.keys.len() - self.number_of_removed_keys
+    }
+
+    #[inline(always)]
+    /// Returns the number of key ids in the corpus, including the ids of the removed keys.
+    ///
+    /// # Implementation details
+    /// The key ids of the corpus are the ones from zero to this number, excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// corpus.remove_key(0);
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 1);
+    /// assert_eq!(corpus.number_of_key_ids(), 2);
+    /// ```
+    pub fn number_of_key_ids(&self) -> usize {
         self.keys.len()
     }
 
+    #[inline(always)]
+    /// Returns whether the key with the provided id was removed.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key.
+    pub fn is_removed(&self, key_id: usize) -> bool {
+        key_id < self.tombstones.len() && self.tombstones.get(key_id)
+    }
+
+    #[inline(always)]
+    /// Returns the position among the overflow keys of the key with the provided id, if
+    /// it was inserted after the construction of the corpus and not yet compacted.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key.
+    pub(crate) fn overflow_key(&self, key_id: usize) -> Option<usize> {
+        if self.overflow.is_empty() {
+            return None;
+        }
+        key_id.checked_sub(self.graph.number_of_source_nodes())
+    }
+
+    #[inline(always)]
+    /// Returns the number of removed keys, which are still stored until the tombstones are purged.
+    pub fn number_of_removed_keys(&self) -> usize {
+        self.number_of_removed_keys
+    }
+
     #[inline(always)]
     /// Returns the number of ngrams in the corpus.
     ///
@@ -180,6 +570,62 @@ where
         self.keys.get_ref(key_id)
     }
 
+    #[inline(always)]
+    /// Returns an iterator over the ids of the keys of the corpus, skipping the removed keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "dog", "cow"]);
+    /// corpus.remove_key(1);
+    ///
+    /// let key_ids = corpus.iter_key_ids();
+    /// assert_eq!(key_ids.len(), 2);
+    /// assert_eq!(key_ids.collect::<Vec<usize>>(), vec![0, 2]);
+    /// ```
+    pub fn iter_key_ids(&self) -> SkipRemoved<'_, std::ops::Range<usize>> {
+        SkipRemoved {
+            items: (0..self.number_of_key_ids()).enumerate(),
+            tombstones: &self.tombstones,
+            remaining: self.number_of_keys(),
+        }
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the keys of the corpus, in key id order and skipping the
+    /// removed keys.
+    ///
+    /// # Implementation details
+    /// The keys are read with the iterator of the key storage, which for the compressed
+    /// storages, such as the `RearCodedList`, is faster than reading each key by its id.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "dog", "cow"]);
+    /// assert_eq!(corpus.keys().collect::<Vec<_>>(), vec![&"cat", &"dog", &"cow"]);
+    ///
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat".to_string(), "dog".to_string()]);
+    /// assert_eq!(corpus.keys().len(), 2);
+    /// assert_eq!(corpus.keys().last().unwrap(), "dog");
+    /// ```
+    pub fn keys<'a>(&'a self) -> SkipRemoved<'a, KS::IterKeys<'a>>
+    where
+        KS::K: 'a,
+    {
+        SkipRemoved {
+            items: self.keys.iter().enumerate(),
+            tombstones: &self.tombstones,
+            remaining: self.number_of_keys(),
+        }
+    }
+
     #[inline(always)]
     /// Returns the ngram curresponding to a given ngram id.
     ///
@@ -203,8 +649,17 @@ where
     ///     assert_eq!(Some(ngram_id), ngram_id_from_ngram);
     /// }
     /// ```
+    ///
+    /// # Implementation details
+    /// The ngrams only found in the keys inserted since the corpus was last compacted
+    /// have the ids following the ones of the vocabulary, from `number_of_ngrams` on.
     pub fn ngram_from_id(&self, ngram_id: usize) -> NG {
-        unsafe { self.ngrams.get_unchecked(ngram_id) }
+        if ngram_id < self.ngrams.len() {
+            unsafe { self.ngrams.get_unchecked(ngram_id) }
+        } else {
+            // The ngrams only appearing in the overflow keys follow the vocabulary.
+            self.overflow.ngram(ngram_id - self.ngrams.len())
+        }
     }
 
     #[inline(always)]
@@ -231,8 +686,16 @@ where
     ///     assert_eq!(Some(ngram_id), ngram_id_from_ngram);
     /// }
     /// ```
+    ///
+    /// # Implementation details
+    /// The ngrams missing from the vocabulary are then looked up among the ones of the
+    /// keys inserted since the corpus was last compacted, as in `ngram_from_id`.
     pub fn ngram_id_from_ngram(&self, ngram: NG) -> Option<usize> {
-        self.ngrams.index_of(ngram)
+        self.ngrams.index_of(ngram).or_else(|| {
+            self.overflow
+                .ngram_position(ngram)
+                .map(|position| self.ngrams.len() + position)
+        })
     }
 
     #[inline(always)]
@@ -253,7 +716,10 @@ where
     /// assert_eq!(animals.number_of_ngrams_from_key_id(20), 11);
     /// ```
     pub fn number_of_ngrams_from_key_id(&self, key_id: usize) -> usize {
-        self.graph.src_degree(key_id)
+        match self.overflow_key(key_id) {
+            Some(overflow_key) => self.overflow.ngram_ids_and_counts(overflow_key).len(),
+            None => self.graph.src_degree(key_id),
+        }
     }
 
     #[inline(always)]
@@ -273,8 +739,22 @@ where
     /// assert_eq!(animals.number_of_keys_from_ngram_id(1), 78);
     /// assert_eq!(animals.number_of_keys_from_ngram_id(20), 4);
     /// ```
+    ///
+    /// # Implementation details
+    /// The removed keys are not counted, which requires a scan of the keys of the
+    /// ngram once any key has been removed. The overflow keys containing the ngram
+    /// are counted along with the keys of the compressed graph.
     pub fn number_of_keys_from_ngram_id(&self, ngram_id: usize) -> usize {
-        self.graph.dst_degree(ngram_id)
+        if self.number_of_removed_keys == 0 {
+            let number_of_graph_keys = if ngram_id < self.number_of_ngrams() {
+                self.graph.dst_degree(ngram_id)
+            } else {
+                0
+            };
+            number_of_graph_keys + self.overflow.key_ids_from_ngram_id(ngram_id).len()
+        } else {
+            self.key_ids_from_ngram_id(ngram_id).count()
+        }
     }
 
     #[inline(always)]
@@ -294,8 +774,17 @@ where
     /// assert_eq!(animals.key_ids_from_ngram_id(1).count(), 78);
     /// assert_eq!(animals.key_ids_from_ngram_id(20).count(), 4);
     /// ```
-    pub fn key_ids_from_ngram_id(&self, ngram_id: usize) -> G::Srcs<'_> {
-        self.graph.srcs_from_dst(ngram_id)
+    ///
+    /// # Implementation details
+    /// The ids of the removed keys are skipped, and the ids of the overflow keys follow
+    /// the ids of the keys of the compressed graph.
+    pub fn key_ids_from_ngram_id(&self, ngram_id: usize) -> KeyIdsFromNgramId<'_, G> {
+        KeyIdsFromNgramId {
+            key_ids: (ngram_id < self.number_of_ngrams())
+                .then(|| self.graph.srcs_from_dst(ngram_id)),
+            overflow_key_ids: self.overflow.key_ids_from_ngram_id(ngram_id).iter(),
+            tombstones: &self.tombstones,
+        }
     }
 
     #[inline(always)]
@@ -315,8 +804,18 @@ where
     /// assert_eq!(animals.ngram_ids_from_key(1).count(), 12);
     /// assert_eq!(animals.ngram_ids_from_key(20).count(), 11);
     /// ```
-    pub fn ngram_ids_from_key(&self, key_id: usize) -> G::Dsts<'_> {
-        self.graph.dsts_from_src(key_id)
+    pub fn ngram_ids_from_key(&self, key_id: usize) -> NgramIdsFromKey<'_, G> {
+        match self.overflow_key(key_id) {
+            Some(overflow_key) => {
+                let ngram_ids: Map<_, fn(&(usize, usize)) -> usize> = self
+                    .overflow
+                    .ngram_ids_and_counts(overflow_key)
+                    .iter()
+                    .map(|(ngram_id, _)| *ngram_id);
+                GraphOrOverflow::Overflow(ngram_ids)
+            }
+            None => GraphOrOverflow::Graph(self.graph.dsts_from_src(key_id)),
+        }
     }
 
     #[inline(always)]
@@ -341,11 +840,67 @@ where
     /// assert!(animals.ngram_cooccurrences_from_key(1).all(|x| x > 0));
     /// assert!(animals.ngram_cooccurrences_from_key(20).all(|x| x > 0));
     /// ```
-    pub fn ngram_cooccurrences_from_key(
-        &self,
-        key_id: usize,
-    ) -> Map<G::WeightsSrc<'_>, fn(usize) -> usize> {
-        self.graph.weights_from_src(key_id).map(|x| x + 1)
+    pub fn ngram_cooccurrences_from_key(&self, key_id: usize) -> NgramCooccurrencesFromKey<'_, G> {
+        match self.overflow_key(key_id) {
+            Some(overflow_key) => {
+                let cooccurrences: Map<_, fn(&(usize, usize)) -> usize> = self
+                    .overflow
+                    .ngram_ids_and_counts(overflow_key)
+                    .iter()
+                    .map(|(_, cooccurrence)| *cooccurrence);
+                GraphOrOverflow::Overflow(cooccurrences)
+            }
+            None => {
+                let cooccurrences: Map<_, fn(usize) -> usize> =
+                    self.graph.weights_from_src(key_id).map(|x| x + 1);
+                GraphOrOverflow::Graph(cooccurrences)
+            }
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the co-occurrence of a given ngram in a given key, if the key contains it.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key to get the co-occurrence from.
+    /// * `ngram_id` - The id of the ngram to get the co-occurrence of.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["banana", "cat"]);
+    ///
+    /// let ana = corpus.ngram_id_from_ngram(['a', 'n', 'a']).unwrap();
+    /// let cat = corpus.ngram_id_from_ngram(['c', 'a', 't']).unwrap();
+    ///
+    /// assert_eq!(corpus.cooccurrence(0, ana), Some(2));
+    /// assert_eq!(corpus.cooccurrence(0, cat), None);
+    /// assert_eq!(corpus.cooccurrence(1, cat), Some(1));
+    /// ```
+    ///
+    /// # Implementation details
+    /// The sorted ngram ids of the key are searched for the ngram, and the co-occurrence
+    /// is then decoded at the same position among the co-occurrences of the key. Since the
+    /// co-occurrences are stored with a variable-length coding, the ones preceding it are
+    /// decoded as well, but unlike `ngram_ids_and_cooccurrences_from_key` no ngram id nor
+    /// co-occurrence following it is.
+    pub fn cooccurrence(&self, key_id: usize, ngram_id: usize) -> Option<usize> {
+        if let Some(overflow_key) = self.overflow_key(key_id) {
+            let ngram_ids_and_counts = self.overflow.ngram_ids_and_counts(overflow_key);
+            return ngram_ids_and_counts
+                .binary_search_by_key(&ngram_id, |(other_ngram_id, _)| *other_ngram_id)
+                .ok()
+                .map(|position| ngram_ids_and_counts[position].1);
+        }
+        if ngram_id >= self.number_of_ngrams() {
+            return None;
+        }
+        self.graph
+            .dst_position_from_src(key_id, ngram_id)
+            .and_then(|position| self.graph.weights_from_src(key_id).nth(position))
+            .map(|weight| weight + 1)
     }
 
     #[inline(always)]
@@ -387,8 +942,19 @@ where
         &self,
         key_id: usize,
     ) -> NgramIdsAndCooccurrences<'_, G> {
-        self.ngram_ids_from_key(key_id)
-            .zip(self.ngram_cooccurrences_from_key(key_id))
+        match self.overflow_key(key_id) {
+            Some(overflow_key) => GraphOrOverflow::Overflow(
+                self.overflow
+                    .ngram_ids_and_counts(overflow_key)
+                    .iter()
+                    .copied(),
+            ),
+            None => {
+                let cooccurrences: Map<_, fn(usize) -> usize> =
+                    self.graph.weights_from_src(key_id).map(|x| x + 1);
+                GraphOrOverflow::Graph(self.graph.dsts_from_src(key_id).zip(cooccurrences))
+            }
+        }
     }
 
     #[inline(always)]
@@ -476,6 +1042,11 @@ where
     /// # Returns
     /// An iterator over the keys associated to the ngram.
     ///
+    /// # Implementation details
+    /// The removed keys are skipped. Their number is counted when the iterator is
+    /// created, which requires a scan of the keys of the ngram once any key has been
+    /// removed, so that the iterator knows its exact length.
+    ///
     /// # Examples
     /// We check that the keys returned by the keys_from_ngram_id method are the
     /// exactly same keys returned keys_from_ngram method.
@@ -494,13 +1065,24 @@ where
     ///         assert_eq!(left, right);
     ///     }
     /// }
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "catfish", "dog"]);
+    /// let ngram_id = corpus.ngram_id_from_ngram(['c', 'a', 't']).unwrap();
+    /// assert_eq!(corpus.keys_from_ngram_id(ngram_id).len(), 2);
+    ///
+    /// corpus.remove_key(0);
+    /// assert_eq!(corpus.keys_from_ngram_id(ngram_id).len(), 1);
     /// ```
     pub fn keys_from_ngram_id(
         &self,
         ngram_id: usize,
     ) -> impl ExactSizeIterator<Item = <KS as Keys<NG>>::KeyRef<'_>> + '_ {
-        self.key_ids_from_ngram_id(ngram_id)
-            .map(move |key_id| self.key_from_id(key_id))
+        CountedKeyIdsFromNgramId {
+            key_ids: self.key_ids_from_ngram_id(ngram_id),
+            remaining: self.number_of_keys_from_ngram_id(ngram_id),
+        }
+        .map(move |key_id| self.key_from_id(key_id))
     }
 
     #[inline(always)]
@@ -587,6 +1169,250 @@ where
             .map(move |ngram_id| self.keys_from_ngram_id(ngram_id))
     }
 
+    #[inline(always)]
+    /// Returns whether the provided ngram is in the vocabulary of the corpus.
+    ///
+    /// # Arguments
+    /// * `ngram` - The ngram to look up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert!(animals.contains_ngram(['c', 'a', 't']));
+    /// assert!(!animals.contains_ngram(['X', 'X', 'X']));
+    /// ```
+    pub fn contains_ngram(&self, ngram: NG) -> bool {
+        self.ngram_id_from_ngram(ngram).is_some()
+    }
+
+    #[inline(always)]
+    /// Returns the number of keys containing the provided ngram.
+    ///
+    /// # Arguments
+    /// * `ngram` - The ngram to get the frequency of.
+    ///
+    /// # Implementation details
+    /// The ngrams that are not in the vocabulary have frequency zero, and the
+    /// removed keys are not counted, as in `number_of_keys_from_ngram_id`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.ngram_frequency(['X', 'X', 'X']), 0);
+    ///
+    /// for (ngram, frequency) in animals.ngram_frequencies() {
+    ///     assert_eq!(animals.ngram_frequency(ngram), frequency);
+    /// }
+    /// ```
+    pub fn ngram_frequency(&self, ngram: NG) -> usize {
+        self.number_of_keys_from_ngram(ngram).unwrap_or(0)
+    }
+
+    /// Returns the number of occurrences of the provided ngram across all keys.
+    ///
+    /// # Arguments
+    /// * `ngram` - The ngram to count the occurrences of.
+    ///
+    /// # Implementation details
+    /// The co-occurrences are stored by key, so the co-occurrences of each key containing
+    /// the ngram are scanned up to the ngram, in time linear in the number of edges of
+    /// these keys. The ngrams that are not in the vocabulary have no occurrences, and
+    /// the removed keys are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.total_ngram_occurrences(['X', 'X', 'X']), 0);
+    ///
+    /// let total_occurrences: usize = animals
+    ///     .ngrams()
+    ///     .map(|ngram| animals.total_ngram_occurrences(ngram))
+    ///     .sum();
+    /// assert_eq!(total_occurrences, animals.cooccurrences().sum::<usize>());
+    ///
+    /// for ngram in animals.ngrams() {
+    ///     assert!(animals.total_ngram_occurrences(ngram) >= animals.ngram_frequency(ngram));
+    /// }
+    /// ```
+    pub fn total_ngram_occurrences(&self, ngram: NG) -> usize {
+        self.ngram_id_from_ngram(ngram).map_or(0, |ngram_id| {
+            self.key_ids_from_ngram_id(ngram_id)
+                .filter_map(|key_id| {
+                    self.ngram_ids_and_cooccurrences_from_key(key_id)
+                        .find(|&(other_ngram_id, _)| other_ngram_id == ngram_id)
+                        .map(|(_, cooccurrence)| cooccurrence)
+                })
+                .sum()
+        })
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the ngrams in the corpus, sorted by ngram id.
+    ///
+    /// # Implementation details
+    /// The iterator walks the sorted storage of the ngrams directly, without allocating,
+    /// so that the ngrams are yielded in strictly increasing order. This is the dual of
+    /// the iterator over the keys, and is useful to export the vocabulary of the corpus.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.ngrams().len(), animals.number_of_ngrams());
+    ///
+    /// for (ngram_id, ngram) in animals.ngrams().enumerate() {
+    ///     assert_eq!(animals.ngram_from_id(ngram_id), ngram);
+    /// }
+    /// ```
+    pub fn ngrams(&self) -> impl ExactSizeIterator<Item = NG> + '_ {
+        self.iter_ngrams_with_ids().map(|(_, ngram)| ngram)
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the ngram ids and the ngrams in the corpus.
+    ///
+    /// # Implementation details
+    /// The ngrams are stored sorted, so that the ngram ids follow the order of the
+    /// ngrams: the iterator yields the ngrams in ascending order, along with their
+    /// ids from zero to `number_of_ngrams`, excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.iter_ngrams_with_ids().len(), animals.number_of_ngrams());
+    ///
+    /// for (ngram_id, ngram) in animals.iter_ngrams_with_ids() {
+    ///     assert_eq!(animals.ngram_id_from_ngram(ngram), Some(ngram_id));
+    /// }
+    /// ```
+    pub fn iter_ngrams_with_ids(&self) -> impl ExactSizeIterator<Item = (usize, NG)> + '_ {
+        NgramsWithIds {
+            ngrams: self.ngrams.iter(),
+            ngram_id: 0,
+            number_of_ngrams: self.number_of_ngrams(),
+        }
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the ngrams in the corpus, each with its posting list.
+    ///
+    /// # Implementation details
+    /// The ngrams are yielded in ascending order, i.e. by ngram id, and the posting list
+    /// of each ngram holds the ids of the keys containing it in ascending order, as
+    /// returned by `key_ids_from_ngram_id`, so the removed keys are skipped. The srcs of
+    /// the ngrams are walked sequentially with `srcs_from_dsts`, which avoids the offset
+    /// lookups of calling `key_ids_from_ngram_id` for each ngram id. This is the layout
+    /// of an inverted index, and can be used to export the corpus to one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.iter_ngram_postings().count(), animals.number_of_ngrams());
+    ///
+    /// for (ngram_id, (ngram, key_ids)) in animals.iter_ngram_postings().enumerate() {
+    ///     assert_eq!(animals.ngram_from_id(ngram_id), ngram);
+    ///     assert!(key_ids.eq(animals.key_ids_from_ngram_id(ngram_id)));
+    /// }
+    /// ```
+    pub fn iter_ngram_postings(&self) -> impl Iterator<Item = (NG, KeyIdsFromNgramId<'_, G>)> + '_ {
+        self.ngrams
+            .iter()
+            .zip(self.graph.srcs_from_dsts())
+            .enumerate()
+            .map(|(ngram_id, (ngram, key_ids))| {
+                (
+                    ngram,
+                    KeyIdsFromNgramId {
+                        key_ids: Some(key_ids),
+                        overflow_key_ids: self.overflow.key_ids_from_ngram_id(ngram_id).iter(),
+                        tombstones: &self.tombstones,
+                    },
+                )
+            })
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the document frequencies of the ngrams, sorted by ngram id.
+    ///
+    /// # Implementation details
+    /// The document frequency of an ngram is the number of keys it appears in,
+    /// i.e. the degree of the ngram in the bipartite graph plus the number of overflow
+    /// keys containing it. The removed keys are not counted, which requires a scan of
+    /// the keys of each ngram once any key has been removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// for (ngram_id, frequency) in animals.document_frequencies().enumerate() {
+    ///     assert_eq!(animals.number_of_keys_from_ngram_id(ngram_id), frequency);
+    /// }
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "catfish", "dog"]);
+    /// let ngram_id = corpus.ngram_id_from_ngram(['c', 'a', 't']).unwrap();
+    /// corpus.remove_key(1);
+    /// assert_eq!(corpus.document_frequencies().nth(ngram_id), Some(1));
+    /// ```
+    pub fn document_frequencies(&self) -> impl ExactSizeIterator<Item = usize> + '_ {
+        (0..self.number_of_ngrams())
+            .map(move |ngram_id| self.number_of_keys_from_ngram_id(ngram_id))
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the ngrams and their document frequencies, sorted by ngram id.
+    ///
+    /// # Examples
+    /// The sum of the document frequencies is the number of distinct (key, ngram) pairs,
+    /// which is at most the total number of ngram occurrences.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// let total_frequency: usize = animals
+    ///     .ngram_frequencies()
+    ///     .map(|(_, frequency)| frequency)
+    ///     .sum();
+    ///
+    /// assert_eq!(total_frequency, animals.graph().number_of_edges());
+    /// assert!(total_frequency <= animals.cooccurrences().sum::<usize>());
+    ///
+    /// for (ngram, frequency) in animals.ngram_frequencies() {
+    ///     assert_eq!(animals.number_of_keys_from_ngram(ngram), Some(frequency));
+    /// }
+    /// ```
+    pub fn ngram_frequencies(&self) -> impl Iterator<Item = (NG, usize)> + '_ {
+        self.ngrams().zip(self.document_frequencies())
+    }
+
     #[inline(always)]
     /// Returns the top k most common ngrams in the corpus.
     ///
@@ -615,12 +1441,7 @@ where
     /// ```
     pub fn top_k_ngrams(&self, k: usize) -> Vec<(usize, NG)> {
         let mut heap = std::collections::BinaryHeap::with_capacity(k);
-        for (degree, ngram) in self
-            .graph
-            .degrees()
-            .skip(self.number_of_keys())
-            .zip(self.ngrams.iter())
-        {
+        for (degree, ngram) in self.document_frequencies().zip(self.ngrams()) {
             if heap.len() < k {
                 heap.push(Reverse((degree, ngram)));
             } else if heap.peek().unwrap().0 .0 < degree {
@@ -633,4 +1454,498 @@ where
             .map(|Reverse(x)| x)
             .collect()
     }
+
+    #[inline(always)]
+    /// Returns an iterator over the edges of the bipartite graph, as triples of key id,
+    /// ngram id and co-occurrence.
+    ///
+    /// # Implementation details
+    /// The edges are yielded in ascending order of key id, and within a key in ascending
+    /// order of ngram id, walking the adjacency and the weights of each key in turn, so
+    /// that the edge list is never materialized. The weights are the co-occurrences, as
+    /// returned by `cooccurrences`, and not the stored weights, which are decreased by one.
+    /// The edges of the removed keys are skipped, while the edges of the overflow keys
+    /// follow the ones of the compressed graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// assert_eq!(animals.iter_edges().count(), animals.graph().number_of_edges());
+    ///
+    /// for (key_id, ngram_id, cooccurrence) in animals.iter_edges() {
+    ///     assert_eq!(animals.cooccurrence(key_id, ngram_id), Some(cooccurrence));
+    /// }
+    /// ```
+    pub fn iter_edges(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.iter_key_ids().flat_map(move |key_id| {
+            self.ngram_ids_and_cooccurrences_from_key(key_id)
+                .map(move |(ngram_id, cooccurrence)| (key_id, ngram_id, cooccurrence))
+        })
+    }
+
+    /// Writes the edges of the bipartite graph to the provided writer, as tab-separated
+    /// lines of key id, ngram id and co-occurrence.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer to write the edges to.
+    ///
+    /// # Raises
+    /// * If the writer fails.
+    ///
+    /// # Implementation details
+    /// The edges are the ones of `iter_edges`, with no header line. The key ids and the
+    /// ngram ids are two separate ranges starting from zero, so that tools expecting a
+    /// single set of node ids require the ngram ids to be shifted, for instance by the
+    /// number of key ids. The writer is not buffered, so that a `BufWriter` should be
+    /// provided when writing to a file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], BiGram<char>> = Corpus::from(["aa", "ab"]);
+    ///
+    /// let mut edgelist = Vec::new();
+    /// corpus.write_edgelist(&mut edgelist).unwrap();
+    /// let edgelist = String::from_utf8(edgelist).unwrap();
+    ///
+    /// assert_eq!(edgelist.lines().count(), corpus.graph().number_of_edges());
+    /// for (line, (key_id, ngram_id, cooccurrence)) in edgelist.lines().zip(corpus.iter_edges()) {
+    ///     assert_eq!(line, format!("{key_id}\t{ngram_id}\t{cooccurrence}"));
+    /// }
+    /// ```
+    pub fn write_edgelist<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for (key_id, ngram_id, cooccurrence) in self.iter_edges() {
+            writeln!(writer, "{key_id}\t{ngram_id}\t{cooccurrence}")?;
+        }
+        writer.flush()
+    }
+}
+
+/// Iterator over the edges incident to a key, see `Corpus::key_edges`.
+pub struct KeyEdges<'a> {
+    /// The global ids of the edges left to iterate.
+    edge_ids: std::ops::Range<usize>,
+    /// The ngram ids of the edges.
+    ngram_ids: <WeightedBitFieldBipartiteGraph as WeightedBipartiteGraph>::Dsts<'a>,
+    /// The weights of the edges.
+    weights: <WeightedBitFieldBipartiteGraph as WeightedBipartiteGraph>::WeightsSrc<'a>,
+}
+
+impl<'a> Iterator for KeyEdges<'a> {
+    type Item = (usize, usize, usize);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge_id = self.edge_ids.next()?;
+        let ngram_id = self.ngram_ids.next().unwrap();
+        let weight = self.weights.next().unwrap() + 1;
+        Some((edge_id, ngram_id, weight))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edge_ids.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for KeyEdges<'a> {}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    #[inline(always)]
+    /// Returns the edges incident to a given key, as triples of edge id, ngram id and co-occurrence.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key to get the edges from.
+    ///
+    /// # Implementation details
+    /// The edge id is the global index of the edge in the graph, i.e. the comulative
+    /// outbound degree of the key plus the position of the edge within the key adjacency.
+    /// Therefore, the edge ids of a key are contiguous and can be mapped back to the key
+    /// with the `src_id_from_edge_id` method of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    ///
+    /// for key_id in [0, 1, 20] {
+    ///     let start = animals.graph().src_comulative_outbound_degree(key_id);
+    ///     let edges = animals.key_edges(key_id);
+    ///     assert_eq!(edges.len(), animals.number_of_ngrams_from_key_id(key_id));
+    ///
+    ///     let expected = animals.ngram_ids_and_cooccurrences_from_key(key_id);
+    ///
+    ///     for (position, (edge, (ngram_id, cooccurrence))) in edges.zip(expected).enumerate() {
+    ///         assert_eq!(edge, (start + position, ngram_id, cooccurrence));
+    ///         assert_eq!(animals.graph().src_id_from_edge_id(edge.0), key_id);
+    ///     }
+    /// }
+    /// ```
+    pub fn key_edges(&self, key_id: usize) -> KeyEdges<'_> {
+        let start = self.graph.src_comulative_outbound_degree(key_id);
+        let end = self.graph.src_comulative_outbound_degree(key_id + 1);
+        KeyEdges {
+            edge_ids: start..end,
+            ngram_ids: self.graph.dsts_from_src(key_id),
+            weights: self.graph.weights_from_src(key_id),
+        }
+    }
+
+    /// Adds the co-occurrences of the provided corpus to the co-occurrences of this corpus.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to add the co-occurrences from.
+    ///
+    /// # Raises
+    /// * If the two corpora have a different number of keys.
+    /// * If the two corpora have different ngrams.
+    /// * If any key is linked to different ngrams in the two corpora.
+    /// * If either corpus has overflow keys, which must be compacted first.
+    ///
+    /// # Implementation details
+    /// The two corpora must have been built over the same keys, in the same order, so
+    /// that their bipartite graphs have the same edges. Only the weights of the edges
+    /// are rebuilt, summing the co-occurrences of each edge, while the keys, the ngrams
+    /// and the offsets of the graph are left untouched. The keys themselves are not
+    /// compared, as only their ngrams determine the structure of the graph. This is
+    /// meant to accumulate the evidence of several snapshots of the same keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dodo"]);
+    /// let snapshot: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dodo"]);
+    ///
+    /// corpus.add_weights_from(&snapshot).unwrap();
+    ///
+    /// assert!(corpus
+    ///     .cooccurrences()
+    ///     .zip(snapshot.cooccurrences())
+    ///     .all(|(summed, original)| summed == 2 * original));
+    ///
+    /// let other: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+    /// assert_eq!(
+    ///     corpus.add_weights_from(&other),
+    ///     Err("The two corpora must have the same ngrams.")
+    /// );
+    /// ```
+    pub fn add_weights_from(&mut self, other: &Self) -> Result<(), &'static str> {
+        if !self.overflow.is_empty() || !other.overflow.is_empty() {
+            return Err("The overflow keys of the two corpora must be compacted first.");
+        }
+        if self.graph.number_of_source_nodes() != other.graph.number_of_source_nodes() {
+            return Err("The two corpora must have the same number of keys.");
+        }
+        if self.number_of_ngrams() != other.number_of_ngrams() || !self.ngrams().eq(other.ngrams())
+        {
+            return Err("The two corpora must have the same ngrams.");
+        }
+        if self.graph.number_of_edges() != other.graph.number_of_edges()
+            || (0..self.graph.number_of_source_nodes()).any(|key_id| {
+                !self
+                    .graph
+                    .dsts_from_src(key_id)
+                    .eq(other.graph.dsts_from_src(key_id))
+            })
+        {
+            return Err("The keys of the two corpora must be linked to the same ngrams.");
+        }
+
+        let mut weights_builder = WeightsBuilder::new();
+        for key_id in 0..self.graph.number_of_source_nodes() {
+            // The weights are stored as the co-occurrences minus one, so
+            // the sum of two stored weights is one less than the stored sum.
+            weights_builder
+                .push(
+                    self.graph
+                        .weights_from_src(key_id)
+                        .zip(other.graph.weights_from_src(key_id))
+                        .map(|(weight, other_weight)| weight + other_weight + 1),
+                )
+                .unwrap();
+        }
+        self.graph.srcs_to_dsts_weights = weights_builder.build();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_ngrams_are_strictly_increasing() {
+        // The ngrams of the different arities and grams are stored in different storages.
+        let trigrams: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        assert_eq!(trigrams.ngrams().len(), trigrams.number_of_ngrams());
+        let ngrams: Vec<TriGram<char>> = trigrams.ngrams().collect();
+        assert!(ngrams.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let bigrams: Corpus<[&str; 699], BiGram<u8>> = Corpus::from(ANIMALS);
+        assert_eq!(bigrams.ngrams().len(), bigrams.number_of_ngrams());
+        let ngrams: Vec<BiGram<u8>> = bigrams.ngrams().collect();
+        assert!(ngrams.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let pentagrams: Corpus<[&str; 699], PentaGram<char>> = Corpus::from(ANIMALS);
+        assert_eq!(pentagrams.ngrams().len(), pentagrams.number_of_ngrams());
+        let ngrams: Vec<PentaGram<char>> = pentagrams.ngrams().collect();
+        assert!(ngrams.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_add_weights_from_itself_doubles_cooccurrences() {
+        let original: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        let mut corpus = original.clone();
+
+        corpus.add_weights_from(&original).unwrap();
+
+        assert_eq!(corpus.number_of_keys(), original.number_of_keys());
+        assert_eq!(corpus.number_of_ngrams(), original.number_of_ngrams());
+        assert_eq!(
+            corpus.cooccurrences().count(),
+            original.cooccurrences().count()
+        );
+        for key_id in 0..original.number_of_keys() {
+            for ((ngram_id, cooccurrence), (original_ngram_id, original_cooccurrence)) in corpus
+                .ngram_ids_and_cooccurrences_from_key(key_id)
+                .zip(original.ngram_ids_and_cooccurrences_from_key(key_id))
+            {
+                assert_eq!(ngram_id, original_ngram_id);
+                assert_eq!(cooccurrence, 2 * original_cooccurrence);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_weights_from_misaligned_corpora() {
+        let mut corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+
+        let different_ngrams: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cow", "dog"]);
+        assert_eq!(
+            corpus.add_weights_from(&different_ngrams),
+            Err("The two corpora must have the same ngrams.")
+        );
+
+        // Same ngrams, but linked to different keys.
+        let swapped: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["dog", "cat"]);
+        assert_eq!(
+            corpus.add_weights_from(&swapped),
+            Err("The keys of the two corpora must be linked to the same ngrams.")
+        );
+
+        // A failed merge leaves the weights untouched.
+        assert!(corpus.cooccurrences().all(|cooccurrence| cooccurrence == 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_preserves_search_results() {
+        let keys: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+        let mut corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+        corpus.remove_key(1);
+
+        let serialized = serde_json::to_string(&corpus).unwrap();
+        let deserialized: Corpus<Vec<String>, TriGram<char>> =
+            serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.number_of_keys(), corpus.number_of_keys());
+        assert_eq!(deserialized.number_of_ngrams(), corpus.number_of_ngrams());
+        assert!(deserialized.ngrams().eq(corpus.ngrams()));
+        assert!(deserialized.is_removed(1));
+
+        for query in ["Cat", "Dog", "Giraffe", "Elephant", "catfish"] {
+            let expected: SearchResults<&String, f32> =
+                corpus.ngram_search(query, NgramSearchConfig::default());
+            let results: SearchResults<&String, f32> =
+                deserialized.ngram_search(query, NgramSearchConfig::default());
+
+            assert_eq!(results.len(), expected.len());
+            for (result, expected) in results.iter().zip(expected.iter()) {
+                assert_eq!(result.key(), expected.key());
+                assert_eq!(result.score(), expected.score());
+            }
+        }
+    }
+
+    #[test]
+    fn test_cooccurrence_matches_cooccurrences_from_key() {
+        let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+
+        for key_id in 0..corpus.number_of_keys() {
+            let ngram_ids_and_cooccurrences: Vec<(usize, usize)> = corpus
+                .ngram_ids_and_cooccurrences_from_key(key_id)
+                .collect();
+            for &(ngram_id, cooccurrence) in &ngram_ids_and_cooccurrences {
+                assert_eq!(corpus.cooccurrence(key_id, ngram_id), Some(cooccurrence));
+            }
+            // The other ngrams are found only if they are among the ngrams of the key.
+            for ngram_id in [0, 1, 100, corpus.number_of_ngrams() - 1] {
+                let expected = ngram_ids_and_cooccurrences
+                    .iter()
+                    .find(|(other_ngram_id, _)| *other_ngram_id == ngram_id)
+                    .map(|(_, cooccurrence)| *cooccurrence);
+                assert_eq!(corpus.cooccurrence(key_id, ngram_id), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_ngrams_with_ids_is_sorted() {
+        let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        let ngrams_with_ids: Vec<(usize, [char; 3])> = corpus.iter_ngrams_with_ids().collect();
+
+        assert_eq!(ngrams_with_ids.len(), corpus.number_of_ngrams());
+        assert!(ngrams_with_ids
+            .iter()
+            .map(|(ngram_id, _)| *ngram_id)
+            .eq(0..corpus.number_of_ngrams()));
+        assert!(ngrams_with_ids
+            .windows(2)
+            .all(|window| window[0].1 < window[1].1));
+        for (ngram_id, ngram) in ngrams_with_ids {
+            assert_eq!(corpus.ngram_from_id(ngram_id), ngram);
+        }
+    }
+
+    #[test]
+    fn test_keys_skip_the_removed_keys() {
+        let keys: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+        let mut corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+        let array: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+
+        assert!(corpus
+            .keys()
+            .map(String::as_str)
+            .eq(ANIMALS.iter().copied()));
+        assert!(array.keys().eq(ANIMALS.iter()));
+        assert!(array.iter_key_ids().eq(0..ANIMALS.len()));
+
+        corpus.remove_key(0);
+        corpus.remove_key(10);
+
+        assert_eq!(corpus.keys().len(), ANIMALS.len() - 2);
+        assert_eq!(corpus.iter_key_ids().len(), ANIMALS.len() - 2);
+        assert!(corpus
+            .iter_key_ids()
+            .eq((1..ANIMALS.len()).filter(|&key_id| key_id != 10)));
+        assert!(corpus
+            .keys()
+            .zip(corpus.iter_key_ids())
+            .all(|(key, key_id)| key == corpus.key_from_id(key_id)));
+    }
+
+    #[test]
+    fn test_iter_ngram_postings_skips_removed_keys() {
+        let mut corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        corpus.remove_key(1);
+        corpus.remove_key(20);
+
+        let mut number_of_postings = 0;
+        for (ngram_id, (ngram, key_ids)) in corpus.iter_ngram_postings().enumerate() {
+            assert_eq!(corpus.ngram_id_from_ngram(ngram), Some(ngram_id));
+            let key_ids: Vec<usize> = key_ids.collect();
+            assert!(!key_ids.contains(&1) && !key_ids.contains(&20));
+            assert!(key_ids.windows(2).all(|window| window[0] < window[1]));
+            assert!(key_ids
+                .iter()
+                .copied()
+                .eq(corpus.key_ids_from_ngram_id(ngram_id)));
+            number_of_postings += 1;
+        }
+        assert_eq!(number_of_postings, corpus.number_of_ngrams());
+    }
+
+    #[test]
+    fn test_iter_edges_skips_removed_keys() {
+        // The unigrams "a", "b" and "c" have ids 0, 1 and 2.
+        let mut corpus: Corpus<[&str; 3], UniGram<char>> = Corpus::from(["aab", "b", "ca"]);
+        assert_eq!(
+            corpus.iter_edges().collect::<Vec<_>>(),
+            vec![(0, 0, 2), (0, 1, 1), (1, 1, 1), (2, 0, 1), (2, 2, 1)]
+        );
+
+        corpus.remove_key(1);
+
+        let edges: Vec<(usize, usize, usize)> = corpus.iter_edges().collect();
+        assert_eq!(edges, vec![(0, 0, 2), (0, 1, 1), (2, 0, 1), (2, 2, 1)]);
+
+        let mut edgelist = Vec::new();
+        corpus.write_edgelist(&mut edgelist).unwrap();
+        assert_eq!(
+            String::from_utf8(edgelist).unwrap(),
+            "0\t0\t2\n0\t1\t1\n2\t0\t1\n2\t2\t1\n"
+        );
+    }
+
+    #[test]
+    fn test_concurrent_searches_of_a_shared_corpus() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<crate::weights::CursorReaderFactory>();
+        assert_send_sync::<crate::weights::Weights>();
+        assert_send_sync::<crate::bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph>();
+        assert_send_sync::<Corpus<Vec<String>, TriGram<char>>>();
+        assert_send_sync::<Corpus<Vec<String>, TriGram<char>, Lowercase<str>>>();
+
+        let keys: Vec<String> = ANIMALS.iter().map(|key| key.to_string()).collect();
+        let corpus: std::sync::Arc<Corpus<Vec<String>, TriGram<char>>> =
+            std::sync::Arc::new(Corpus::from(keys));
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap();
+        let queries = ["Cat", "Catt", "Sea lion", "Doggo", "Hippopotamus", "Lion"];
+        let expected: Vec<Vec<(String, f32)>> = queries
+            .iter()
+            .map(|query| {
+                corpus
+                    .ngram_search(query, config)
+                    .into_iter()
+                    .map(|result| (result.key().to_string(), result.score()))
+                    .collect()
+            })
+            .collect();
+        assert!(expected.iter().all(|results| !results.is_empty()));
+
+        let handles: Vec<_> = (0..8)
+            .map(|thread_id| {
+                let corpus = std::sync::Arc::clone(&corpus);
+                std::thread::spawn(move || {
+                    // Each thread searches the queries starting from a different one.
+                    (0..queries.len())
+                        .map(|offset| {
+                            let query_id = (thread_id + offset) % queries.len();
+                            let results = corpus
+                                .ngram_search(queries[query_id], config)
+                                .into_iter()
+                                .map(|result| (result.key().to_string(), result.score()))
+                                .collect::<Vec<(String, f32)>>();
+                            (query_id, results)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            for (query_id, results) in handle.join().unwrap() {
+                assert_eq!(results, expected[query_id]);
+            }
+        }
+    }
 }
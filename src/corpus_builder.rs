@@ -0,0 +1,170 @@
+//! Submodule providing a fluent builder of corpora, mirroring the legacy `CorpusBuilder`.
+//!
+//! # Implementation details
+//! The type of a corpus is determined by its keys, by its ngram, an array of `ARITY`
+//! grams, and by the key type normalizing the keys and the queries, such as `Lowercase`.
+//! The builder tracks the arity, the gram and the key type in its type parameters, so
+//! that each of its methods returns a builder with the updated type, and `build` returns
+//! the same concrete corpus that would be written with the type parameters. Since the
+//! arity is part of the type of the ngram, it is provided as a const generic parameter,
+//! as in `arity::<2>()`, and not as a runtime value.
+use std::marker::PhantomData;
+
+use crate::prelude::*;
+
+/// A fluent builder of corpora.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let keys: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+///
+/// // Equivalent to `Corpus<Vec<String>, BiGram<char>, Lowercase<str>>`.
+/// let corpus = CorpusBuilder::new().arity::<2>().case_insensitive().build(keys);
+///
+/// let results: SearchResults<&String, f32> =
+///     corpus.ngram_search("catt", NgramSearchConfig::default());
+///
+/// assert_eq!(results[0].key().as_str(), "Cat");
+/// ```
+pub struct CorpusBuilder<const ARITY: usize = 3, G = char, K: ?Sized = str> {
+    /// The configuration of the construction of the corpus.
+    config: CorpusConfig,
+    /// Whether to build the corpus in parallel.
+    parallel: bool,
+    /// The gram and the key type of the corpus.
+    _phantom: PhantomData<(G, fn(&K))>,
+}
+
+impl Default for CorpusBuilder {
+    #[inline(always)]
+    /// Returns a builder of corpora of trigrams of characters.
+    fn default() -> Self {
+        Self {
+            config: CorpusConfig::default(),
+            parallel: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl CorpusBuilder {
+    #[inline(always)]
+    /// Returns a builder of corpora of trigrams of characters, with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<const ARITY: usize, G, K: ?Sized> CorpusBuilder<ARITY, G, K> {
+    #[inline(always)]
+    /// Returns the builder with the provided type parameters and the same settings.
+    fn retype<const A: usize, H, J: ?Sized>(self) -> CorpusBuilder<A, H, J> {
+        CorpusBuilder {
+            config: self.config,
+            parallel: self.parallel,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    /// Set the arity of the ngrams of the corpus.
+    ///
+    /// # Implementation details
+    /// The ngram of the corpus is an array of `A` grams, such as `BiGram` for an arity of
+    /// two. The corpus can only be built for the arities with an ngram implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], BiGram<char>> =
+    ///     CorpusBuilder::new().arity::<2>().build(["cat", "dog"]);
+    ///
+    /// assert!(corpus.ngrams().all(|ngram| ngram.len() == 2));
+    /// ```
+    pub fn arity<const A: usize>(self) -> CorpusBuilder<A, G, K> {
+        self.retype()
+    }
+
+    #[inline(always)]
+    /// Set the type of the grams of the corpus, such as `u8` or `ASCIIChar`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<u8>> =
+    ///     CorpusBuilder::new().gram_type::<u8>().build(["cat", "dog"]);
+    ///
+    /// assert!(corpus.ngram_id_from_ngram(*b"cat").is_some());
+    /// ```
+    pub fn gram_type<H>(self) -> CorpusBuilder<ARITY, H, K> {
+        self.retype()
+    }
+
+    #[inline(always)]
+    /// Set the keys and the queries of the corpus to be lowercased, as the legacy
+    /// `case_insensitive` option did.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>, Lowercase<str>> =
+    ///     CorpusBuilder::new().case_insensitive().build(["Cat", "Dog"]);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("CAT", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// ```
+    pub fn case_insensitive(self) -> CorpusBuilder<ARITY, G, Lowercase<K>> {
+        self.retype()
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline(always)]
+    /// Set the corpus to be built in parallel, as with `Corpus::par_from`.
+    pub fn parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    #[inline(always)]
+    /// Set the configuration of the construction of the corpus.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration of the construction of the corpus.
+    pub fn with_config(mut self, config: CorpusConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the corpus of the provided keys.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    ///
+    /// # Implementation details
+    /// The corpus is built with `Corpus::from_with_config`, or with
+    /// `Corpus::par_from_with_config` when the parallel construction is requested.
+    pub fn build<KS>(self, keys: KS) -> Corpus<KS, [G; ARITY], K>
+    where
+        [G; ARITY]: Ngram<G = G>,
+        KS: Keys<[G; ARITY]>,
+        for<'a> KS::KeyRef<'a>: AsRef<K>,
+        K: Key<[G; ARITY], G>,
+    {
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            return Corpus::par_from_with_config(keys, self.config);
+        }
+        Corpus::from_with_config(keys, self.config)
+    }
+}
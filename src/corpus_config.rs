@@ -0,0 +1,109 @@
+//! Submodule providing the configuration of the construction of a corpus.
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// Struct providing a corpus construction configuration.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let config = CorpusConfig::default()
+///     .set_max_key_grams(100)
+///     .set_max_ngram_document_frequency(0.5)
+///     .unwrap();
+///
+/// assert_eq!(config.max_key_grams(), Some(100));
+/// assert_eq!(config.max_ngram_document_frequency(), 0.5);
+/// ```
+pub struct CorpusConfig {
+    /// The maximum number of grams to consider for each key, if any.
+    max_key_grams: Option<usize>,
+    /// The maximum fraction of the keys an ngram may appear in to be kept in the graph.
+    max_ngram_document_frequency: f64,
+}
+
+impl Default for CorpusConfig {
+    #[inline(always)]
+    /// Returns the default corpus configuration, which keeps all of the grams and ngrams.
+    fn default() -> Self {
+        Self {
+            max_key_grams: None,
+            max_ngram_document_frequency: 1.0,
+        }
+    }
+}
+
+impl CorpusConfig {
+    #[inline(always)]
+    /// Returns the maximum number of grams to consider for each key, if any.
+    pub fn max_key_grams(&self) -> Option<usize> {
+        self.max_key_grams
+    }
+
+    #[inline(always)]
+    /// Set the maximum number of grams to consider for each key.
+    ///
+    /// # Arguments
+    /// * `max_key_grams` - The maximum number of grams to consider for each key.
+    ///
+    /// # Implementation details
+    /// The keys with more grams are truncated, as described in the documentation of
+    /// `Corpus::from_with_max_key_grams`. The maximum must be at least the arity of the
+    /// ngram, which is checked when the corpus is built.
+    pub fn set_max_key_grams(mut self, max_key_grams: usize) -> Self {
+        self.max_key_grams = Some(max_key_grams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the maximum fraction of the keys an ngram may appear in to be kept in the graph.
+    pub fn max_ngram_document_frequency(&self) -> f64 {
+        self.max_ngram_document_frequency
+    }
+
+    #[inline(always)]
+    /// Set the maximum fraction of the keys an ngram may appear in to be kept in the graph.
+    ///
+    /// # Arguments
+    /// * `max_ngram_document_frequency` - The maximum fraction, between zero excluded and one.
+    ///
+    /// # Implementation details
+    /// The ngrams appearing in more than this fraction of the keys, such as the padding
+    /// ngrams shared by most keys, are excluded from the graph entirely, as if they did
+    /// not appear in the keys. Since they are the ngrams with the most keys, this reduces
+    /// the number of candidates visited by the searches. Note that the excluded ngrams do
+    /// NOT count toward the length of the keys, including the average key length, while
+    /// they still count toward the length of the queries, so the similarity scores of all
+    /// of the keys containing them decrease. The default value of one excludes no ngram.
+    ///
+    /// # Raises
+    /// * If the fraction is NaN, not positive or greater than one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let config = CorpusConfig::default();
+    /// assert_eq!(config.max_ngram_document_frequency(), 1.0);
+    ///
+    /// assert!(config.set_max_ngram_document_frequency(0.0).is_err());
+    /// assert!(config.set_max_ngram_document_frequency(1.5).is_err());
+    /// assert!(config.set_max_ngram_document_frequency(f64::NAN).is_err());
+    /// assert!(config.set_max_ngram_document_frequency(0.1).is_ok());
+    /// ```
+    pub fn set_max_ngram_document_frequency(
+        mut self,
+        max_ngram_document_frequency: f64,
+    ) -> Result<Self, &'static str> {
+        if max_ngram_document_frequency.is_nan() {
+            return Err("The maximum ngram document frequency must not be NaN");
+        }
+        if max_ngram_document_frequency <= 0.0 || max_ngram_document_frequency > 1.0 {
+            return Err("The maximum ngram document frequency must be in the interval (0.0, 1.0]");
+        }
+        self.max_ngram_document_frequency = max_ngram_document_frequency;
+        Ok(self)
+    }
+}
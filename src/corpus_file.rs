@@ -0,0 +1,805 @@
+//! Submodule providing a single-file format to save and open a corpus.
+//!
+//! # Implementation details
+//! The format of a corpus serialized with the `serde` feature is chosen by the user, and
+//! the files written by the `BiWebgraph` corpus do not record the version of the crate
+//! that wrote them, so that a change of their layout is only detected, if at all, as
+//! corrupted data. A corpus file instead starts with the magic bytes `NGRAMMAT` and with
+//! a version byte, and a file of a different version is rejected with a descriptive
+//! error when opened. Then follow the sections of the corpus, in this order, each one
+//! introduced by a four-byte tag and by its length in bytes, and followed by the FNV-1a
+//! hash of its content as a little-endian `u64`, so that a corrupted section is detected
+//! even when it is consistent with the other ones:
+//!
+//! * `HEAD`: the arity of the ngrams, the number of keys, the number of keys in the
+//!   compressed graph, the number of ngrams, the bits of the average key length and the
+//!   padding, which is zero for no padding, one for the automatic padding, or two
+//!   followed by the code point of the padding gram as `u32`.
+//! * `KEYS`: the length in bytes and the UTF-8 bytes of each key.
+//! * `TOMB`: the ids of the removed keys.
+//! * `NGRM`: the code points of the grams of each ngram, as `u32`.
+//! * `OFFS`: the comulative outbound degree of the keys and the comulative inbound
+//!   degree of the ngrams in the compressed graph.
+//! * `EDGE`: the ngram ids of the edges from keys to ngrams, and the key ids of the
+//!   edges from ngrams to keys.
+//! * `WGHT`: the number of nodes and of weights, the coding of the offsets as a byte,
+//!   which is zero for Elias-Fano and one for gap coding, the bit offset of the weights
+//!   of each key and the length and bytes of the bitstream. The offsets are written in
+//!   full whatever their coding, and are always loaded into Elias-Fano offsets.
+//!
+//! The sequences of ids and offsets are packed in little-endian `u64` words, with the
+//! bit width of their largest value, and are preceded by the bit width and by the number
+//! of values. All other integers are little-endian `u64` unless stated otherwise.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+use sux::bits::BitVec;
+use sux::prelude::IntoIteratorFrom;
+
+use crate::bit_field_bipartite_graph::{
+    RawWeightedBitFieldBipartiteGraph, WeightedBitFieldBipartiteGraph,
+};
+use crate::padding_mode::PaddingMode;
+use crate::weights::{elias_fano_from_sorted, CursorReaderFactory, Weights};
+use crate::{traits::*, Corpus};
+
+/// The magic bytes at the start of a corpus file.
+const MAGIC: [u8; 8] = *b"NGRAMMAT";
+
+/// The version of the format of the corpus files written and read by this crate.
+pub const CORPUS_FILE_VERSION: u8 = 1;
+
+/// Returns an error of invalid data with the provided message.
+///
+/// # Arguments
+/// * `message` - The message of the error.
+fn invalid(message: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Writes the provided value as a little-endian `u64`.
+///
+/// # Arguments
+/// * `writer` - The writer to write to.
+/// * `value` - The value to write.
+fn write_u64(writer: &mut impl Write, value: usize) -> std::io::Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+/// Reads a little-endian `u64` from the provided reader.
+///
+/// # Arguments
+/// * `reader` - The reader to read from.
+fn read_u64(reader: &mut impl Read) -> std::io::Result<usize> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| invalid("The value does not fit a usize."))
+}
+
+/// Returns the FNV-1a hash of the provided bytes, used as the checksum of the sections.
+///
+/// # Arguments
+/// * `bytes` - The bytes to hash.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Returns the number of bits needed to store the provided value.
+///
+/// # Arguments
+/// * `value` - The value to store.
+fn bit_width(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()) as usize
+}
+
+/// Writes the provided values packed with the provided bit width.
+///
+/// # Arguments
+/// * `writer` - The writer to write to.
+/// * `bit_width` - The number of bits used to store each value, at most 64.
+/// * `values` - The values to write, which must fit in the bit width.
+///
+/// # Implementation details
+/// The values are stored from the least significant bit of each word, and a value may
+/// span two consecutive words.
+fn write_packed(
+    writer: &mut impl Write,
+    bit_width: usize,
+    values: &[usize],
+) -> std::io::Result<()> {
+    write_u64(writer, bit_width)?;
+    write_u64(writer, values.len())?;
+    if bit_width == 0 {
+        return Ok(());
+    }
+    let mut word = 0_u64;
+    let mut filled = 0;
+    for &value in values {
+        let value = value as u64;
+        word |= value << filled;
+        if filled + bit_width >= 64 {
+            writer.write_all(&word.to_le_bytes())?;
+            let written = 64 - filled;
+            word = if written == 64 { 0 } else { value >> written };
+            filled = filled + bit_width - 64;
+        } else {
+            filled += bit_width;
+        }
+    }
+    if filled > 0 {
+        writer.write_all(&word.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reads the values written by `write_packed`, returning their bit width and values.
+///
+/// # Arguments
+/// * `reader` - The reader to read from.
+/// * `number_of_values` - The expected number of values.
+///
+/// # Raises
+/// * If the number of values is not the expected one, or the bit width is larger than 64.
+/// * If the values are truncated.
+fn read_packed(
+    reader: &mut impl Read,
+    number_of_values: usize,
+) -> std::io::Result<(usize, Vec<usize>)> {
+    let bit_width = read_u64(reader)?;
+    if bit_width > 64 {
+        return Err(invalid(
+            "The bit width of the packed values is larger than 64.",
+        ));
+    }
+    if read_u64(reader)? != number_of_values {
+        return Err(invalid(
+            "The number of packed values does not match the corpus.",
+        ));
+    }
+    if bit_width == 0 {
+        return Ok((bit_width, vec![0; number_of_values]));
+    }
+
+    let number_of_words = number_of_values
+        .checked_mul(bit_width)
+        .ok_or_else(|| invalid("The packed values are too many."))?
+        .div_ceil(64);
+    let words = (0..number_of_words)
+        .map(|_| {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        })
+        .collect::<std::io::Result<Vec<u64>>>()?;
+
+    let mask = if bit_width == 64 {
+        u64::MAX
+    } else {
+        (1 << bit_width) - 1
+    };
+    let values = (0..number_of_values)
+        .map(|index| {
+            let position = index * bit_width;
+            let (word, offset) = (position / 64, position % 64);
+            let mut value = words[word] >> offset;
+            if offset + bit_width > 64 {
+                value |= words[word + 1] << (64 - offset);
+            }
+            (value & mask) as usize
+        })
+        .collect();
+    Ok((bit_width, values))
+}
+
+/// Writes a section of a corpus file.
+///
+/// # Arguments
+/// * `writer` - The writer to write to.
+/// * `tag` - The tag of the section.
+/// * `payload` - The content of the section.
+fn write_section(writer: &mut impl Write, tag: &[u8; 4], payload: &[u8]) -> std::io::Result<()> {
+    writer.write_all(tag)?;
+    write_u64(writer, payload.len())?;
+    writer.write_all(payload)?;
+    writer.write_all(&checksum(payload).to_le_bytes())
+}
+
+/// Reads the content of the section of a corpus file with the provided tag.
+///
+/// # Arguments
+/// * `reader` - The reader to read from.
+/// * `tag` - The expected tag of the section.
+///
+/// # Raises
+/// * If the next section does not have the expected tag, or it is truncated.
+/// * If the content of the section does not match its checksum.
+fn read_section(reader: &mut impl Read, tag: &[u8; 4]) -> std::io::Result<Vec<u8>> {
+    let mut found = [0; 4];
+    reader.read_exact(&mut found)?;
+    if &found != tag {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Expected the section {} of the corpus file, found {}.",
+                String::from_utf8_lossy(tag),
+                String::from_utf8_lossy(&found)
+            ),
+        ));
+    }
+    let length = read_u64(reader)?;
+    let mut payload = Vec::new();
+    reader
+        .by_ref()
+        .take(length as u64)
+        .read_to_end(&mut payload)?;
+    if payload.len() != length {
+        return Err(invalid("The corpus file is truncated."));
+    }
+    if read_u64(reader)? as u64 != checksum(&payload) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "The section {} of the corpus file does not match its checksum.",
+                String::from_utf8_lossy(tag)
+            ),
+        ));
+    }
+    Ok(payload)
+}
+
+/// Returns the version of the format of the corpus file at the provided path.
+///
+/// # Arguments
+/// * `path` - The path of the file to read.
+///
+/// # Raises
+/// * If the file cannot be opened or read.
+/// * If the file is not a corpus file.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+/// use ngrammatic::corpus_file::{corpus_file_version, CORPUS_FILE_VERSION};
+///
+/// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+///
+/// let file = tempfile::NamedTempFile::new().unwrap();
+/// corpus.save(file.path()).unwrap();
+///
+/// assert_eq!(corpus_file_version(file.path()).unwrap(), CORPUS_FILE_VERSION);
+/// ```
+pub fn corpus_file_version(path: impl AsRef<Path>) -> std::io::Result<u8> {
+    read_header(&mut BufReader::new(File::open(path)?))
+}
+
+/// Reads the magic bytes and the version of a corpus file, returning the version.
+///
+/// # Arguments
+/// * `reader` - The reader to read from.
+///
+/// # Raises
+/// * If the file is not a corpus file.
+fn read_header(reader: &mut impl Read) -> std::io::Result<u8> {
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid("The file is not a corpus file."));
+    }
+    let mut version = [0; 1];
+    reader.read_exact(&mut version)?;
+    Ok(version[0])
+}
+
+/// Checks that the content of a section was read entirely.
+///
+/// # Arguments
+/// * `payload` - The unread content of the section.
+fn finish_section(payload: &[u8]) -> std::io::Result<()> {
+    if payload.is_empty() {
+        Ok(())
+    } else {
+        Err(invalid("A section of the corpus file has trailing bytes."))
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K> + AsRef<str>,
+    K: Key<NG, NG::G> + ?Sized,
+    NG::G: Into<char>,
+{
+    /// Writes the corpus to a single file at the provided path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to write.
+    ///
+    /// # Raises
+    /// * If the file cannot be created or written.
+    ///
+    /// # Implementation details
+    /// The file is written in the version `CORPUS_FILE_VERSION` of the format, which is
+    /// recorded in the file so that `open` rejects it when it is read by an incompatible
+    /// version of the crate. The keys inserted after the construction of the corpus are
+    /// written along with the other keys, and are inserted again when the corpus is opened. Please refer to the documentation of the
+    /// `corpus_file` module for the format of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Vec<String> = ANIMALS.iter().map(|animal| animal.to_string()).collect();
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(animals);
+    ///
+    /// let file = tempfile::NamedTempFile::new().unwrap();
+    /// corpus.save(file.path()).unwrap();
+    ///
+    /// let opened: Corpus<Vec<String>, TriGram<char>> = Corpus::open(file.path()).unwrap();
+    ///
+    /// assert_eq!(opened.number_of_keys(), 699);
+    /// assert_eq!(opened.key_from_id(20), "Alligator");
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[CORPUS_FILE_VERSION])?;
+
+        let mut head = Vec::new();
+        for value in [
+            NG::ARITY,
+            self.number_of_key_ids(),
+            self.graph.number_of_source_nodes(),
+            self.number_of_ngrams(),
+        ] {
+            write_u64(&mut head, value)?;
+        }
+        head.write_all(&self.average_key_length.to_bits().to_le_bytes())?;
+        match self.padding {
+            PaddingMode::None => write_u64(&mut head, 0)?,
+            PaddingMode::Auto => write_u64(&mut head, 1)?,
+            PaddingMode::Pad(gram) => {
+                write_u64(&mut head, 2)?;
+                head.write_all(&u32::from(Into::<char>::into(gram)).to_le_bytes())?;
+            }
+        }
+        write_section(&mut writer, b"HEAD", &head)?;
+
+        let mut keys = Vec::new();
+        for key_id in 0..self.number_of_key_ids() {
+            let key = self.key_from_id(key_id);
+            let key: &str = key.as_ref();
+            write_u64(&mut keys, key.len())?;
+            keys.write_all(key.as_bytes())?;
+        }
+        write_section(&mut writer, b"KEYS", &keys)?;
+
+        let removed_keys: Vec<usize> = (0..self.number_of_key_ids())
+            .filter(|&key_id| self.is_removed(key_id))
+            .collect();
+        let mut tombstones = Vec::new();
+        write_u64(&mut tombstones, removed_keys.len())?;
+        write_packed(
+            &mut tombstones,
+            bit_width(removed_keys.last().copied().unwrap_or(0)),
+            &removed_keys,
+        )?;
+        write_section(&mut writer, b"TOMB", &tombstones)?;
+
+        let mut ngrams = Vec::new();
+        for ngram in self.ngrams() {
+            for i in 0..NG::ARITY {
+                ngrams.write_all(&u32::from(Into::<char>::into(ngram[i])).to_le_bytes())?;
+            }
+        }
+        write_section(&mut writer, b"NGRM", &ngrams)?;
+
+        let graph = RawWeightedBitFieldBipartiteGraph::from(&self.graph);
+        let number_of_edges = graph.srcs_to_dsts.len();
+        let mut offsets = Vec::new();
+        write_packed(
+            &mut offsets,
+            bit_width(number_of_edges),
+            &graph.srcs_offsets,
+        )?;
+        write_packed(
+            &mut offsets,
+            bit_width(number_of_edges),
+            &graph.dsts_offsets,
+        )?;
+        write_section(&mut writer, b"OFFS", &offsets)?;
+
+        let mut edges = Vec::new();
+        write_u64(&mut edges, number_of_edges)?;
+        write_packed(
+            &mut edges,
+            graph.srcs_to_dsts_bit_width,
+            &graph.srcs_to_dsts,
+        )?;
+        write_packed(
+            &mut edges,
+            graph.dsts_to_srcs_bit_width,
+            &graph.dsts_to_srcs,
+        )?;
+        write_section(&mut writer, b"EDGE", &edges)?;
+
+        let weights = &graph.srcs_to_dsts_weights;
+        let weights_offsets: Vec<usize> = weights.offsets.into_iter_from(0).collect();
+        let data = &weights.reader_factory.data;
+        let mut weights_section = Vec::new();
+        write_u64(&mut weights_section, weights.num_nodes())?;
+        write_u64(&mut weights_section, weights.num_weights())?;
+        // The offsets of the weights of the corpus are always Elias-Fano.
+        weights_section.write_all(&[0])?;
+        write_packed(
+            &mut weights_section,
+            bit_width(weights_offsets.last().copied().unwrap_or(0)),
+            &weights_offsets,
+        )?;
+        write_u64(&mut weights_section, data.len())?;
+        weights_section.write_all(data)?;
+        write_section(&mut writer, b"WGHT", &weights_section)?;
+
+        writer.flush()
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: GrowableKeys<NG>,
+    KS::K: From<String>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    NG::G: TryFrom<char>,
+{
+    /// Opens the corpus written by `save` at the provided path.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to open.
+    ///
+    /// # Raises
+    /// * If the file cannot be opened or read.
+    /// * If the file is not a corpus file, or it was written with a different version of
+    ///   the format or with a different ngram type.
+    /// * If the file is truncated, its sections do not match their checksums, or they are
+    ///   not consistent with each other.
+    ///
+    /// # Implementation details
+    /// The whole file is read into memory, and the succinct structures of the graph are
+    /// rebuilt from the values stored in the file. Please refer to the documentation of
+    /// the `corpus_file` module for the format of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat".to_string(), "dog".to_string(), "cow".to_string()]);
+    ///
+    /// let file = tempfile::NamedTempFile::new().unwrap();
+    /// corpus.save(file.path()).unwrap();
+    ///
+    /// let opened: Corpus<Vec<String>, TriGram<char>> = Corpus::open(file.path()).unwrap();
+    ///
+    /// assert!(opened.ngrams().eq(corpus.ngrams()));
+    /// assert!(opened.ngram_ids_from_key(1).eq(corpus.ngram_ids_from_key(1)));
+    ///
+    /// // A file that is not a corpus file is rejected.
+    /// std::fs::write(file.path(), b"not a corpus").unwrap();
+    /// assert!(Corpus::<Vec<String>, TriGram<char>>::open(file.path()).is_err());
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let version = read_header(&mut reader)?;
+        if version != CORPUS_FILE_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The corpus file has format version {}, while this version of the \
+                     crate reads version {}.",
+                    version, CORPUS_FILE_VERSION
+                ),
+            ));
+        }
+
+        let read_gram = |reader: &mut &[u8]| -> std::io::Result<NG::G> {
+            let mut code_point = [0; 4];
+            reader.read_exact(&mut code_point)?;
+            char::from_u32(u32::from_le_bytes(code_point))
+                .and_then(|character| NG::G::try_from(character).ok())
+                .ok_or_else(|| invalid("The gram does not match the gram type."))
+        };
+
+        let section = read_section(&mut reader, b"HEAD")?;
+        let mut head = section.as_slice();
+        if read_u64(&mut head)? != NG::ARITY {
+            return Err(invalid(
+                "The arity of the corpus does not match the ngram type.",
+            ));
+        }
+        let number_of_keys = read_u64(&mut head)?;
+        let number_of_graph_keys = read_u64(&mut head)?;
+        let number_of_ngrams = read_u64(&mut head)?;
+        let mut bits = [0; 8];
+        head.read_exact(&mut bits)?;
+        let average_key_length = f64::from_bits(u64::from_le_bytes(bits));
+        let padding = match read_u64(&mut head)? {
+            0 => PaddingMode::None,
+            1 => PaddingMode::Auto,
+            2 => PaddingMode::Pad(read_gram(&mut head)?),
+            _ => return Err(invalid("The padding of the corpus is not valid.")),
+        };
+        finish_section(head)?;
+        if number_of_graph_keys > number_of_keys {
+            return Err(invalid(
+                "The keys of the graph are more than the keys of the corpus.",
+            ));
+        }
+
+        let section = read_section(&mut reader, b"KEYS")?;
+        let mut section_keys = section.as_slice();
+        let mut keys = KS::default();
+        for _ in 0..number_of_keys {
+            let length = read_u64(&mut section_keys)?;
+            let key = section_keys
+                .get(..length)
+                .ok_or_else(|| invalid("The corpus file is truncated."))?;
+            let key = std::str::from_utf8(key).map_err(|_| invalid("The key is not UTF-8."))?;
+            keys.push(key.to_string().into());
+            section_keys = &section_keys[length..];
+        }
+        finish_section(section_keys)?;
+
+        let section = read_section(&mut reader, b"TOMB")?;
+        let mut section_tombstones = section.as_slice();
+        let number_of_removed_keys = read_u64(&mut section_tombstones)?;
+        let (_, removed_keys) = read_packed(&mut section_tombstones, number_of_removed_keys)?;
+        finish_section(section_tombstones)?;
+        if removed_keys.windows(2).any(|window| window[0] >= window[1])
+            || removed_keys
+                .last()
+                .is_some_and(|&key_id| key_id >= number_of_keys)
+        {
+            return Err(invalid("The removed keys must be distinct key ids."));
+        }
+        let mut tombstones = BitVec::new(0);
+        if number_of_removed_keys > 0 {
+            tombstones = BitVec::new(number_of_keys);
+            for key_id in removed_keys {
+                tombstones.set(key_id, true);
+            }
+        }
+
+        let section = read_section(&mut reader, b"NGRM")?;
+        let mut section_ngrams = section.as_slice();
+        let mut ngrams = Vec::new();
+        for _ in 0..number_of_ngrams {
+            let mut ngram = NG::default();
+            for i in 0..NG::ARITY {
+                ngram[i] = read_gram(&mut section_ngrams)?;
+            }
+            ngrams.push(ngram);
+        }
+        finish_section(section_ngrams)?;
+        if ngrams.windows(2).any(|window| window[0] >= window[1]) {
+            return Err(invalid("The ngrams must be sorted and unique."));
+        }
+        let mut ngram_builder =
+            <<<NG as Ngram>::SortedStorage as SortedNgramStorage<NG>>::Builder>::new_storage_builder(
+                ngrams.len(),
+                ngrams.last().copied().unwrap_or_default(),
+            );
+        for ngram in ngrams {
+            // SAFETY: the ngrams are sorted, unique and bounded by the last one.
+            unsafe { ngram_builder.push_unchecked(ngram) };
+        }
+
+        let section = read_section(&mut reader, b"OFFS")?;
+        let mut section_offsets = section.as_slice();
+        let (_, srcs_offsets) = read_packed(&mut section_offsets, number_of_graph_keys + 1)?;
+        let (_, dsts_offsets) = read_packed(&mut section_offsets, number_of_ngrams + 1)?;
+        finish_section(section_offsets)?;
+
+        let section = read_section(&mut reader, b"EDGE")?;
+        let mut section_edges = section.as_slice();
+        let number_of_edges = read_u64(&mut section_edges)?;
+        let (srcs_to_dsts_bit_width, srcs_to_dsts) =
+            read_packed(&mut section_edges, number_of_edges)?;
+        let (dsts_to_srcs_bit_width, dsts_to_srcs) =
+            read_packed(&mut section_edges, number_of_edges)?;
+        finish_section(section_edges)?;
+        if srcs_to_dsts
+            .iter()
+            .any(|&ngram_id| ngram_id >= number_of_ngrams)
+            || dsts_to_srcs
+                .iter()
+                .any(|&key_id| key_id >= number_of_graph_keys)
+        {
+            return Err(invalid(
+                "The edges must be between keys and ngrams of the corpus.",
+            ));
+        }
+
+        let section = read_section(&mut reader, b"WGHT")?;
+        let mut section_weights = section.as_slice();
+        let num_nodes = read_u64(&mut section_weights)?;
+        let num_weights = read_u64(&mut section_weights)?;
+        let mut offset_coding = [0; 1];
+        section_weights.read_exact(&mut offset_coding)?;
+        if offset_coding[0] > 1 {
+            return Err(invalid("The coding of the offsets is not valid."));
+        }
+        let (_, weights_offsets) = read_packed(&mut section_weights, num_nodes)?;
+        let length = read_u64(&mut section_weights)?;
+        let data = section_weights
+            .get(..length)
+            .ok_or_else(|| invalid("The corpus file is truncated."))?
+            .to_vec();
+        finish_section(&section_weights[length..])?;
+        if num_nodes != number_of_graph_keys {
+            return Err(invalid("The weights do not match the corpus."));
+        }
+        if weights_offsets
+            .last()
+            .is_some_and(|&offset| offset > data.len() * 8)
+        {
+            return Err(invalid("The offsets must be within the bitstream."));
+        }
+        let srcs_to_dsts_weights = Weights::new(
+            CursorReaderFactory::new(data),
+            elias_fano_from_sorted(&weights_offsets).map_err(invalid)?,
+            num_nodes,
+            num_weights,
+        );
+
+        let graph = WeightedBitFieldBipartiteGraph::try_from(RawWeightedBitFieldBipartiteGraph {
+            srcs_to_dsts_weights,
+            srcs_offsets,
+            dsts_offsets,
+            srcs_to_dsts_bit_width,
+            srcs_to_dsts,
+            dsts_to_srcs_bit_width,
+            dsts_to_srcs,
+        })
+        .map_err(invalid)?;
+
+        let mut corpus = Self::new(keys, ngram_builder.build(), average_key_length, graph);
+        corpus.tombstones = tombstones;
+        corpus.number_of_removed_keys = number_of_removed_keys;
+        corpus.padding = padding;
+        // The keys inserted after the construction of the saved corpus are inserted again.
+        for key_id in number_of_graph_keys..number_of_keys {
+            let ngram_counts = {
+                let key = corpus.key_from_id(key_id);
+                corpus.key_counts(key.as_ref())
+            };
+            corpus.overflow.push(key_id, ngram_counts, &corpus.ngrams);
+        }
+        Ok(corpus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_packed_values_round_trip() {
+        for bit_width in [0, 1, 7, 31, 63, 64] {
+            let mask = if bit_width == 64 {
+                usize::MAX
+            } else {
+                (1 << bit_width) - 1
+            };
+            let values: Vec<usize> = (0..100_usize)
+                .map(|index| (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) as usize & mask)
+                .collect();
+            let mut bytes = Vec::new();
+            write_packed(&mut bytes, bit_width, &values).unwrap();
+            let mut reader = bytes.as_slice();
+            assert_eq!(
+                read_packed(&mut reader, values.len()).unwrap(),
+                (bit_width, values)
+            );
+            assert!(reader.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_corpus_file_round_trip() {
+        let keys: Vec<String> = ANIMALS[..600].iter().map(|key| key.to_string()).collect();
+        let mut corpus: Corpus<Vec<String>, TriGram<char>, Lowercase<str>> =
+            Corpus::from_with_padding(keys, PaddingMode::Pad('$'));
+        corpus.remove_key(3);
+        corpus.remove_key(42);
+        for key in &ANIMALS[600..] {
+            corpus.insert(key.to_string());
+        }
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        corpus.save(file.path()).unwrap();
+        let opened: Corpus<Vec<String>, TriGram<char>, Lowercase<str>> =
+            Corpus::open(file.path()).unwrap();
+
+        assert_eq!(opened.number_of_key_ids(), corpus.number_of_key_ids());
+        assert_eq!(opened.number_of_keys(), corpus.number_of_keys());
+        assert_eq!(
+            opened.number_of_overflow_keys(),
+            corpus.number_of_overflow_keys()
+        );
+        assert_eq!(opened.padding(), corpus.padding());
+        assert!(opened.is_removed(3) && opened.is_removed(42));
+        assert!(opened.ngrams().eq(corpus.ngrams()));
+        assert!(opened.cooccurrences().eq(corpus.cooccurrences()));
+        for key_id in 0..corpus.number_of_key_ids() {
+            assert_eq!(opened.key_from_id(key_id), corpus.key_from_id(key_id));
+        }
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap();
+        for query in ["cat", "zebra", "yak", "sea lion"] {
+            assert_eq!(
+                opened.ngram_search(query, config),
+                corpus.ngram_search(query, config)
+            );
+        }
+    }
+
+    #[test]
+    fn test_corpus_file_rejects_other_versions() {
+        // A corpus of borrowed keys can be opened as a corpus of owned keys.
+        let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        let file = tempfile::NamedTempFile::new().unwrap();
+        corpus.save(file.path()).unwrap();
+        let opened: Corpus<Vec<String>, TriGram<char>> = Corpus::open(file.path()).unwrap();
+        assert!(opened.ngrams().eq(corpus.ngrams()));
+
+        let mut bytes = std::fs::read(file.path()).unwrap();
+        bytes[MAGIC.len()] = CORPUS_FILE_VERSION + 1;
+        std::fs::write(file.path(), &bytes).unwrap();
+        let error = Corpus::<Vec<String>, TriGram<char>>::open(file.path()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("version"));
+
+        // A file written for another ngram type is rejected as well.
+        bytes[MAGIC.len()] = CORPUS_FILE_VERSION;
+        std::fs::write(file.path(), &bytes).unwrap();
+        assert_eq!(
+            Corpus::<Vec<String>, BiGram<char>>::open(file.path())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_corpus_file_detects_corrupted_sections() {
+        let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        let file = tempfile::NamedTempFile::new().unwrap();
+        corpus.save(file.path()).unwrap();
+        assert_eq!(
+            corpus_file_version(file.path()).unwrap(),
+            CORPUS_FILE_VERSION
+        );
+
+        // A flipped bit in the last section is detected by its checksum.
+        let mut corrupted = std::fs::read(file.path()).unwrap();
+        let last = corrupted.len() - 9;
+        corrupted[last] ^= 1;
+        std::fs::write(file.path(), &corrupted).unwrap();
+        let error = Corpus::<Vec<String>, TriGram<char>>::open(file.path()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("checksum"));
+    }
+}
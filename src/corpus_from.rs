@@ -1,14 +1,22 @@
 //! Submodule implementing the `From` trait for the `Corpus` struct.
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
 use std::io::Cursor;
+#[cfg(feature = "mmap")]
+use std::io::{self, BufRead};
 
 use fxhash::FxBuildHasher;
+
 use sux::prelude::*;
 use sux::traits::bit_field_slice::BitFieldSliceApply;
 
+use crate::math;
+#[cfg(feature = "mmap")]
+use crate::string_pool::StringPool;
 use crate::weights::WeightsBuilder;
 use crate::{
     bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, traits::*, AdaptativeVector,
+    CorpusConfig, PaddingMode, ReusableCorpusBuilder,
 };
 
 use crate::Corpus;
@@ -24,78 +32,376 @@ where
     ///
     /// # Arguments
     /// * `keys` - The keys to digest.
-    pub(crate) fn parse_keys(
+    /// * `max_key_grams` - The maximum number of grams to consider for each key, if any.
+    /// * `padding` - The padding of the grams of the keys.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    ///
+    /// # Implementation details
+    /// The buffers of the builder are cleared before being populated, so that their
+    /// capacity from any previous build is retained. Once this method returns, the
+    /// `ngrams` buffer contains the unsorted unique ngrams of the keys, while the
+    /// `key_to_ngrams` buffer contains the ngrams of each key, in key order.
+    pub(crate) fn parse_keys<S: BuildHasher + Default>(
         keys: &KS,
-    ) -> (Vec<NG>, WeightsBuilder, f64, AdaptativeVector, Vec<NG>) {
-        // Sorted vector of ngrams.
-        let mut ngrams: HashSet<NG, FxBuildHasher> = HashSet::with_capacity_and_hasher(
-            (keys.len() as f32).sqrt() as usize,
-            FxBuildHasher::default(),
-        );
+        max_key_grams: Option<usize>,
+        padding: PaddingMode<NG::G>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> (WeightsBuilder, f64, AdaptativeVector) {
+        Self::parse_keys_with_progress(keys, max_key_grams, padding, builder, |_, _| {})
+    }
 
-        let mut cooccurrences_builder = WeightsBuilder::<Cursor<Vec<u8>>>::new();
-        let mut number_of_edges: usize = 0;
-        let mut total_key_length: f64 = 0.0;
-        let mut key_offsets = AdaptativeVector::with_capacity(keys.len() + 1, keys.len());
-        key_offsets.push(0_u8);
-        let mut key_to_ngrams: Vec<NG> = Vec::with_capacity(keys.len());
+    /// Runs the keys digestion as `parse_keys`, reporting its progress.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to digest.
+    /// * `max_key_grams` - The maximum number of grams to consider for each key, if any.
+    /// * `padding` - The padding of the grams of the keys.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    /// * `progress` - A function called with the number of digested keys and the total
+    /// number of keys, each time another hundredth of the keys is digested.
+    pub(crate) fn parse_keys_with_progress<S: BuildHasher + Default>(
+        keys: &KS,
+        max_key_grams: Option<usize>,
+        padding: PaddingMode<NG::G>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> (WeightsBuilder, f64, AdaptativeVector) {
+        let number_of_keys = keys.len();
+        let mut parser = KeysParser::new(number_of_keys, builder);
+        let mut milestones = Milestones::new(number_of_keys);
 
-        log::debug!("Building ngrams from keys.");
+        debug!("Building ngrams from keys.");
 
         for key in keys.iter() {
             // First, we get the reference to the inner key.
             let key: &K = key.as_ref();
+            parser.digest(key, max_key_grams, padding, builder);
+            if let Some(done) = milestones.advance() {
+                progress(done, number_of_keys);
+            }
+        }
+
+        parser.finish(builder)
+    }
+
+    /// Runs the keys digestion as `parse_keys`, applying the provided configuration.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to digest.
+    /// * `config` - The configuration of the construction of the corpus.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    ///
+    /// # Implementation details
+    /// The document frequencies of the ngrams are only known once all of the keys are
+    /// digested, so when some ngram exceeds the maximum document frequency the keys are
+    /// digested a second time, skipping the excluded ngrams.
+    ///
+    /// # Panics
+    /// * If the maximum number of grams per key is smaller than the arity of the ngram.
+    pub(crate) fn parse_keys_with_config<S: BuildHasher + Default>(
+        keys: &KS,
+        config: &CorpusConfig,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> (WeightsBuilder, f64, AdaptativeVector) {
+        let max_key_grams = config.max_key_grams();
+        assert!(
+            max_key_grams.map_or(true, |max_key_grams| max_key_grams >= NG::ARITY),
+            "The maximum number of grams per key must be at least the arity of the ngram."
+        );
+        let parsed = Self::parse_keys(keys, max_key_grams, PaddingMode::Auto, builder);
+        if config.max_ngram_document_frequency() >= 1.0 {
+            return parsed;
+        }
+
+        // Each ngram appears once in the ngrams of each key containing it.
+        let mut document_frequencies: HashMap<NG, usize, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(builder.ngrams.len(), FxBuildHasher::default());
+        for &ngram in builder.key_to_ngrams.iter() {
+            *document_frequencies.entry(ngram).or_insert(0) += 1;
+        }
+        let max_document_frequency = config.max_ngram_document_frequency() * keys.len() as f64;
+        let excluded_ngrams: HashSet<NG, FxBuildHasher> = document_frequencies
+            .into_iter()
+            .filter(|&(_, document_frequency)| document_frequency as f64 > max_document_frequency)
+            .map(|(ngram, _)| ngram)
+            .collect();
+        if excluded_ngrams.is_empty() {
+            return parsed;
+        }
+
+        debug!(
+            "Digesting the keys again excluding {} frequent ngrams.",
+            excluded_ngrams.len()
+        );
+
+        let mut parser = KeysParser::new(keys.len(), builder);
+        for key in keys.iter() {
+            let key: &K = key.as_ref();
+            parser.digest_excluding(
+                key,
+                max_key_grams,
+                PaddingMode::Auto,
+                &excluded_ngrams,
+                builder,
+            );
+        }
 
-            // We create a hashmap to store the ngrams of the key and their counts.
-            let ngram_counts = key.counts();
-
-            // Before digesting the hashmap, we convert it to a vector of tuples and we sort if
-            // by ngram. This is done so that when we remap the ngrams to the overall sorted array,
-            // we can also update the key to gram edges vector inplace without having to sort every
-            // set of ngrams associated to a document as we are sure that, once replaced, any ngram
-            // will already be in an ordering that is consistent with the overall ordering of ngrams.
-            // This way we do not need to sort things such as the associated co-occurrences.
-            let mut ngram_counts: Vec<(NG, usize)> = ngram_counts.into_iter().collect();
-
-            // We sort the ngrams by ngram.
-            ngram_counts.sort_unstable_by(|(ngram_a, _), (ngram_b, _)| ngram_a.cmp(ngram_b));
-
-            cooccurrences_builder
-                .push(ngram_counts.iter().map(|(_, count)| count - 1))
-                .unwrap();
-            number_of_edges += ngram_counts.len();
-
-            // Then, we digest the sorted array of tuples.
-            for (ngram, count) in ngram_counts {
-                // We check that the provided count is greater or equal to one.
-                assert!(
-                    count > 0,
-                    "The count of an ngram must be greater than zero."
+        parser.finish(builder)
+    }
+}
+
+/// Counter of the processed items of a phase of the construction of a corpus, reaching
+/// a milestone each time another hundredth of the items is processed.
+pub(crate) struct Milestones {
+    /// The number of processed items.
+    done: usize,
+    /// The number of processed items at the next milestone.
+    next: usize,
+    /// The number of items between two milestones.
+    step: usize,
+    /// The total number of items.
+    total: usize,
+}
+
+impl Milestones {
+    /// Creates a new counter of the provided number of items.
+    ///
+    /// # Arguments
+    /// * `total` - The total number of items.
+    pub(crate) fn new(total: usize) -> Self {
+        let step = total.div_ceil(100).max(1);
+        Self {
+            done: 0,
+            next: step.min(total),
+            step,
+            total,
+        }
+    }
+
+    #[inline(always)]
+    /// Counts another processed item, returning the number of processed items when a
+    /// milestone is reached.
+    ///
+    /// # Implementation details
+    /// The last item is always a milestone, so that the completion of the phase is
+    /// reported, while otherwise only a comparison is made per item.
+    pub(crate) fn advance(&mut self) -> Option<usize> {
+        self.done += 1;
+        if self.done != self.next {
+            return None;
+        }
+        self.next = (self.next + self.step).min(self.total);
+        Some(self.done)
+    }
+}
+
+/// The state of the digestion of the keys of a corpus, which are parsed one at a time.
+pub(crate) struct KeysParser {
+    /// The builder of the cooccurrences of the ngrams of each key.
+    cooccurrences_builder: WeightsBuilder,
+    /// The number of edges from the digested keys to their ngrams.
+    number_of_edges: usize,
+    /// The total number of ngrams in the digested keys, including repetitions.
+    total_key_length: f64,
+    /// The offsets of the edges of each digested key, starting with zero.
+    key_offsets: AdaptativeVector,
+}
+
+impl KeysParser {
+    /// Creates a new parser, clearing the buffers of the provided builder.
+    ///
+    /// # Arguments
+    /// * `number_of_keys` - The expected number of keys, used to reserve the buffers.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    pub(crate) fn new<NG: Ngram, S: BuildHasher + Default>(
+        number_of_keys: usize,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> Self {
+        builder.clear();
+
+        // Set of the ngrams.
+        builder
+            .unique_ngrams
+            .reserve(math::sqrtf(number_of_keys as f32) as usize);
+        builder.key_to_ngrams.reserve(number_of_keys);
+
+        let mut key_offsets = AdaptativeVector::with_capacity(number_of_keys + 1, number_of_keys);
+        key_offsets.push(0_u8);
+
+        Self {
+            cooccurrences_builder: WeightsBuilder::<Cursor<Vec<u8>>>::new(),
+            number_of_edges: 0,
+            total_key_length: 0.0,
+            key_offsets,
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the number of keys digested so far.
+    pub(crate) fn number_of_keys(&self) -> usize {
+        self.key_offsets.len() - 1
+    }
+
+    /// Digests the provided key, storing its ngrams and their cooccurrences.
+    ///
+    /// # Arguments
+    /// * `key` - The key to digest.
+    /// * `max_key_grams` - The maximum number of grams to consider for the key, if any.
+    /// * `padding` - The padding of the grams of the key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    pub(crate) fn digest<NG, K, S>(
+        &mut self,
+        key: &K,
+        max_key_grams: Option<usize>,
+        padding: PaddingMode<NG::G>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) where
+        NG: Ngram,
+        K: Key<NG, NG::G> + ?Sized,
+        S: BuildHasher + Default,
+    {
+        self.count_ngrams(key, max_key_grams, padding, builder);
+        self.digest_ngram_counts(builder);
+    }
+
+    /// Digests the provided key, skipping the provided excluded ngrams.
+    ///
+    /// # Arguments
+    /// * `key` - The key to digest.
+    /// * `max_key_grams` - The maximum number of grams to consider for the key, if any.
+    /// * `padding` - The padding of the grams of the key.
+    /// * `excluded_ngrams` - The ngrams that are not digested, as if they were not in the key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    pub(crate) fn digest_excluding<NG, K, S>(
+        &mut self,
+        key: &K,
+        max_key_grams: Option<usize>,
+        padding: PaddingMode<NG::G>,
+        excluded_ngrams: &HashSet<NG, FxBuildHasher>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) where
+        NG: Ngram,
+        K: Key<NG, NG::G> + ?Sized,
+        S: BuildHasher + Default,
+    {
+        self.count_ngrams(key, max_key_grams, padding, builder);
+        builder
+            .ngram_counts
+            .retain(|(ngram, _)| !excluded_ngrams.contains(ngram));
+        self.digest_ngram_counts(builder);
+    }
+
+    /// Stores the ngrams of the provided key and their counts in the `ngram_counts` buffer,
+    /// sorted by ngram.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted.
+    /// * `max_key_grams` - The maximum number of grams to consider for the key, if any.
+    /// * `padding` - The padding of the grams of the key.
+    /// * `builder` - The reusable buffers, whose `ngram_counts` buffer is overwritten.
+    fn count_ngrams<NG, K, S>(
+        &self,
+        key: &K,
+        max_key_grams: Option<usize>,
+        padding: PaddingMode<NG::G>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) where
+        NG: Ngram,
+        K: Key<NG, NG::G> + ?Sized,
+        S: BuildHasher + Default,
+    {
+        let key_id = self.number_of_keys();
+        let ngram_counts = &mut builder.ngram_counts;
+
+        // We create a hashmap to store the ngrams of the key and their counts.
+        // If the key has more grams than the provided maximum, we truncate it.
+        let key_ngram_counts = match max_key_grams {
+            Some(max_key_grams) if key.grams().nth(max_key_grams).is_some() => {
+                warn!(
+                    "The key with id {} has more than {} grams and will be truncated.",
+                    key_id, max_key_grams
                 );
-                // We insert the ngram in the sorted btreeset.
-                ngrams.insert(ngram);
-                total_key_length += count as f64;
-                // And finally we store the index of the ngram in the key_to_ngrams vector.
-                key_to_ngrams.push(ngram);
+                padding.truncated_counts_with_hasher::<NG, K, S>(key, max_key_grams)
             }
-            // We store the number of edges from the current key in the key_offsets vector.
-            key_offsets.push(number_of_edges);
+            _ => padding.counts_with_hasher::<NG, K, S>(key),
+        };
+
+        // Before digesting the hashmap, we convert it to a vector of tuples and we sort if
+        // by ngram. This is done so that when we remap the ngrams to the overall sorted array,
+        // we can also update the key to gram edges vector inplace without having to sort every
+        // set of ngrams associated to a document as we are sure that, once replaced, any ngram
+        // will already be in an ordering that is consistent with the overall ordering of ngrams.
+        // This way we do not need to sort things such as the associated co-occurrences.
+        ngram_counts.clear();
+        ngram_counts.extend(key_ngram_counts);
+
+        // We sort the ngrams by ngram.
+        ngram_counts.sort_unstable_by(|(ngram_a, _), (ngram_b, _)| ngram_a.cmp(ngram_b));
+    }
+
+    /// Digests the ngrams and the counts of a key, as stored in the `ngram_counts` buffer.
+    ///
+    /// # Arguments
+    /// * `builder` - The reusable buffers, whose `ngram_counts` buffer contains the ngrams
+    /// of the key sorted by ngram, with their counts.
+    pub(crate) fn digest_ngram_counts<NG: Ngram, S: BuildHasher>(
+        &mut self,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) {
+        let ReusableCorpusBuilder {
+            unique_ngrams,
+            key_to_ngrams,
+            ngram_counts,
+            ..
+        } = builder;
+
+        self.cooccurrences_builder
+            .push(ngram_counts.iter().map(|(_, count)| count - 1))
+            .unwrap();
+        self.number_of_edges += ngram_counts.len();
+
+        // Then, we digest the sorted array of tuples.
+        for &(ngram, count) in ngram_counts.iter() {
+            // We check that the provided count is greater or equal to one.
+            assert!(
+                count > 0,
+                "The count of an ngram must be greater than zero."
+            );
+            // We insert the ngram in the sorted btreeset.
+            unique_ngrams.insert(ngram);
+            self.total_key_length += count as f64;
+            // And finally we store the index of the ngram in the key_to_ngrams vector.
+            key_to_ngrams.push(ngram);
         }
+        // We store the number of edges from the current key in the key_offsets vector.
+        self.key_offsets.push(self.number_of_edges);
+    }
 
+    /// Completes the digestion, returning the cooccurrences builder, the average key
+    /// length and the key offsets.
+    ///
+    /// # Arguments
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    ///
+    /// # Panics
+    /// * If the digested keys do not contain any ngram.
+    pub(crate) fn finish<NG: Ngram, S: BuildHasher>(
+        self,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> (WeightsBuilder, f64, AdaptativeVector) {
         assert!(
-            !ngrams.is_empty(),
+            !builder.unique_ngrams.is_empty(),
             "The corpus must contain at least one ngram."
         );
 
-        // We convert the ngram set into a vector.
-        let ngrams: Vec<NG> = ngrams.into_iter().collect();
+        // We move the ngram set into the vector, retaining the capacity of both.
+        builder.ngrams.extend(builder.unique_ngrams.drain());
 
+        let number_of_keys = self.number_of_keys();
         (
-            ngrams,
-            cooccurrences_builder,
-            total_key_length / keys.len() as f64,
-            key_offsets,
-            key_to_ngrams,
+            self.cooccurrences_builder,
+            self.total_key_length / number_of_keys as f64,
+            self.key_offsets,
         )
     }
 }
@@ -108,19 +414,247 @@ where
     K: Key<NG, NG::G> + ?Sized,
 {
     fn from(keys: KS) -> Self {
+        Self::from_keys(keys, None, &mut ReusableCorpusBuilder::<NG>::default())
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Creates a new corpus from a set of keys, truncating any key with more than
+    /// `max_key_grams` grams.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `max_key_grams` - The maximum number of grams to consider for each key.
+    ///
+    /// # Implementation details
+    /// This protects the construction from pathological keys, such as a whole document
+    /// that slipped into a column of names, which may otherwise explode the number of
+    /// ngrams and the memory requirements. The truncation is lossy: the ngrams appearing
+    /// after the first `max_key_grams` grams of a key, including its right padding, are
+    /// not indexed. A warning is logged for each truncated key.
+    ///
+    /// # Panics
+    /// * If `max_key_grams` is smaller than the arity of the ngram.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let huge_key = (0..10_000)
+    ///     .map(|i| i.to_string())
+    ///     .collect::<Vec<String>>()
+    ///     .join(" ");
+    /// let keys = vec!["cat".to_string(), "dog".to_string(), huge_key];
+    ///
+    /// let full: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys.clone());
+    /// let truncated: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::from_with_max_key_grams(keys, 100);
+    ///
+    /// // The small keys are indexed fully.
+    /// assert_eq!(
+    ///     truncated.number_of_ngrams_from_key_id(0),
+    ///     full.number_of_ngrams_from_key_id(0)
+    /// );
+    /// assert_eq!(
+    ///     truncated.number_of_ngrams_from_key_id(1),
+    ///     full.number_of_ngrams_from_key_id(1)
+    /// );
+    ///
+    /// // While the huge key is truncated.
+    /// assert!(truncated.number_of_ngrams_from_key_id(2) <= 100);
+    /// assert!(truncated.ngram_cooccurrences_from_key(2).sum::<usize>() <= 100);
+    /// assert!(full.number_of_ngrams_from_key_id(2) > 100);
+    /// ```
+    pub fn from_with_max_key_grams(keys: KS, max_key_grams: usize) -> Self {
+        assert!(
+            max_key_grams >= NG::ARITY,
+            "The maximum number of grams per key must be at least the arity of the ngram."
+        );
+        Self::from_keys(
+            keys,
+            Some(max_key_grams),
+            &mut ReusableCorpusBuilder::<NG>::default(),
+        )
+    }
+
+    /// Creates a new corpus from a set of keys, applying the provided configuration.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `config` - The configuration of the construction of the corpus.
+    ///
+    /// # Implementation details
+    /// Please refer to the documentation of `CorpusConfig` for the available options.
+    /// When the maximum ngram document frequency excludes some ngram, the keys are
+    /// digested twice, so the construction takes longer in exchange of faster searches.
+    ///
+    /// # Panics
+    /// * If the maximum number of grams per key is smaller than the arity of the ngram.
+    /// * If all of the ngrams of the keys are excluded.
+    ///
+    /// # Examples
+    /// The padding ngrams at the start of the keys are shared by many keys, and a low
+    /// enough maximum document frequency excludes them from the graph.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let config = CorpusConfig::default()
+    ///     .set_max_ngram_document_frequency(0.05)
+    ///     .unwrap();
+    ///
+    /// let full: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+    /// let pruned: Corpus<[&str; 699], TriGram<char>> = Corpus::from_with_config(ANIMALS, config);
+    ///
+    /// assert!(pruned.number_of_ngrams() < full.number_of_ngrams());
+    /// assert!(pruned.graph().number_of_edges() < full.graph().number_of_edges());
+    /// assert!(pruned
+    ///     .document_frequencies()
+    ///     .all(|document_frequency| document_frequency as f64 <= 0.05 * 699.0));
+    /// ```
+    pub fn from_with_config(keys: KS, config: CorpusConfig) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys_with_config(&keys, &config, builder);
+        Self::from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        )
+    }
+
+    /// Creates a new corpus from a set of keys, padding their grams as requested.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `padding` - The padding of the grams of the keys and of the queries.
+    ///
+    /// # Implementation details
+    /// The padding is stored in the corpus, so that the queries of all of the searches
+    /// are padded as the keys are. Without padding, the first and last grams of a key
+    /// appear in fewer ngrams than the others, which suits fixed-width codes, and the
+    /// keys shorter than the arity of the ngram have no ngrams at all, so they are never
+    /// returned by the searches.
+    ///
+    /// # Panics
+    /// * If the keys do not contain any ngram.
+    ///
+    /// # Examples
+    /// Without padding, the codes sharing their prefix are not favoured over the codes
+    /// sharing the same number of inner ngrams.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let codes = ["AB1234", "XB1235", "AB9999"];
+    ///
+    /// let padded: Corpus<[&str; 3], TriGram<char>> = Corpus::from(codes);
+    /// let unpadded: Corpus<[&str; 3], TriGram<char>> =
+    ///     Corpus::from_with_padding(codes, PaddingMode::None);
+    ///
+    /// assert!(padded.number_of_ngrams() > unpadded.number_of_ngrams());
+    /// assert!(unpadded.ngrams().all(|ngram| !ngram.contains(&'\0')));
+    /// assert_eq!(unpadded.number_of_ngrams_from_key_id(0), 4);
+    ///
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    /// let results: SearchResults<&&str, f32> = unpadded.ngram_search("AB1234", config);
+    /// assert_eq!(results[0].key(), &"AB1234");
+    /// assert_eq!(results[0].score(), 1.0);
+    /// ```
+    pub fn from_with_padding(keys: KS, padding: PaddingMode<NG::G>) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys(&keys, None, padding, builder);
+        let mut corpus = Self::from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        );
+        corpus.padding = padding;
+        corpus
+    }
+
+    /// Creates a new corpus from a set of keys.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `max_key_grams` - The maximum number of grams to consider for each key, if any.
+    /// * `builder` - The reusable buffers to use while building the corpus.
+    pub(crate) fn from_keys<S: BuildHasher + Default>(
+        keys: KS,
+        max_key_grams: Option<usize>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> Self {
         // We start by parsing the keys to extract the ngrams, the cooccurrences, the key offsets,
         // and the maximal cooccurrence.
-        let (mut ngrams, cooccurrences_builder, average_key_length, key_offsets, key_to_ngrams) =
-            Self::parse_keys(&keys);
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys(&keys, max_key_grams, PaddingMode::Auto, builder);
+        Self::from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        )
+    }
+
+    /// Creates a new corpus from a set of keys that were already parsed.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `cooccurrences_builder` - The builder of the cooccurrences of the keys.
+    /// * `average_key_length` - The average number of ngrams in the keys.
+    /// * `key_offsets` - The offsets of the edges of each key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams were stored.
+    pub(crate) fn from_parsed_keys<S: BuildHasher + Default>(
+        keys: KS,
+        cooccurrences_builder: WeightsBuilder,
+        average_key_length: f64,
+        key_offsets: AdaptativeVector,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> Self {
+        let (ngrams, graph) =
+            Self::graph_from_parsed_keys(keys.len(), cooccurrences_builder, key_offsets, builder);
+        Corpus::new(keys, ngrams, average_key_length, graph)
+    }
+
+    /// Builds the sorted ngrams and the graph of a set of keys that were already parsed.
+    ///
+    /// # Arguments
+    /// * `number_of_keys` - The number of parsed keys.
+    /// * `cooccurrences_builder` - The builder of the cooccurrences of the keys.
+    /// * `key_offsets` - The offsets of the edges of each key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams were stored.
+    pub(crate) fn graph_from_parsed_keys<S: BuildHasher + Default>(
+        number_of_keys: usize,
+        cooccurrences_builder: WeightsBuilder,
+        key_offsets: AdaptativeVector,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> (NG::SortedStorage, WeightedBitFieldBipartiteGraph) {
+        let ngrams = &mut builder.ngrams;
+        let key_to_ngrams = &builder.key_to_ngrams;
 
         let cooccurrences = cooccurrences_builder.build();
 
         // We sort the ngrams.
-        log::debug!("Sorting ngrams.");
+        debug!("Sorting ngrams.");
         ngrams.sort_unstable();
 
         // We can now start to compress several of the vectors into BitFieldVecs.
-        log::debug!("Compressing key offsets into Elias-Fano.");
+        debug!("Compressing key offsets into Elias-Fano.");
         let key_offsets = unsafe { key_offsets.into_elias_fano() };
 
         // We now create the various required bitvectors, knowing all of their characteristics
@@ -136,7 +670,7 @@ where
         // number of edges from keys to ngrams, which we already have at this time (i.e. the length
         // of the cooccurrences vector).
         let mut ngram_degrees = BitFieldVec::new(
-            (keys.len() + 1).next_power_of_two().ilog2() as usize,
+            (number_of_keys + 1).next_power_of_two().ilog2() as usize,
             ngrams.len() + 1,
         );
 
@@ -150,9 +684,9 @@ where
             key_to_ngrams.len(),
         );
 
-        log::debug!("Building the key to ngram edges and computing ngram degrees.");
+        debug!("Building the key to ngram edges and computing ngram degrees.");
 
-        let mut keys_iter = key_to_ngrams.into_iter();
+        let mut keys_iter = key_to_ngrams.iter().copied();
 
         unsafe {
             key_to_ngram_edges.apply_inplace_unchecked(|_| {
@@ -171,19 +705,19 @@ where
 
         // We create the ngrams vector. Since we are using a btreeset, we already have the
         // ngrams sorted, so we can simply convert the btreeset into a vector.
-        log::debug!(
+        debug!(
             "Storing ngrams into {}.",
             std::any::type_name::<NG::SortedStorage>()
         );
         let mut ngram_builder = <<<NG as Ngram>::SortedStorage as SortedNgramStorage<NG>>::Builder>::new_storage_builder(ngrams.len(), *ngrams.last().unwrap());
 
-        for ngram in ngrams {
+        for ngram in ngrams.iter().copied() {
             unsafe { ngram_builder.push_unchecked(ngram) };
         }
 
         let ngrams: NG::SortedStorage = ngram_builder.build();
 
-        log::debug!("Computing ngrams degrees comulative sum.");
+        debug!("Computing ngrams degrees comulative sum.");
 
         // Now that we have fully populated the ngram_degrees vector, we need to compute the comulative
         // sum of the inbound degrees of the ngrams.
@@ -207,11 +741,11 @@ where
         // We build the ngram_offsets vector.
         let ngram_offsets = ngram_offsets_builder.build().convert_to().unwrap();
 
-        log::debug!("Building edges from gram to key.");
+        debug!("Building edges from gram to key.");
         // Finally, we can allocate and populate the gram_to_key_edges vector. This vector has the same length
         // as the cooccurrences vector.
         let mut gram_to_key_edges = BitFieldVec::new(
-            (keys.len() + 1).next_power_of_two().ilog2() as usize,
+            (number_of_keys + 1).next_power_of_two().ilog2() as usize,
             cooccurrences.num_weights(),
         );
 
@@ -256,10 +790,8 @@ where
             }
         }
 
-        Corpus::new(
-            keys,
+        (
             ngrams,
-            average_key_length,
             WeightedBitFieldBipartiteGraph::new(
                 cooccurrences,
                 key_offsets,
@@ -270,3 +802,343 @@ where
         )
     }
 }
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: GrowableKeys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Creates a new corpus from an iterator of keys, digesting each key as it is read.
+    ///
+    /// # Arguments
+    /// * `keys` - The iterator of keys, such as the lines of a file.
+    ///
+    /// # Implementation details
+    /// Each key is pushed into the growable key storage and its ngrams are digested right
+    /// away, so that the keys are never collected into a separate container before the
+    /// construction, as happens when calling `from` on the collected lines of a file.
+    /// The resulting corpus is the same one built by `from` from the collected keys.
+    ///
+    /// # Panics
+    /// * If the keys do not contain any ngram.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let lines = "cat\ndog\ncatfish".lines().map(|line| line.to_string());
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from_iterator(lines);
+    ///
+    /// let collected: Vec<String> = vec!["cat".into(), "dog".into(), "catfish".into()];
+    /// let expected: Corpus<Vec<String>, TriGram<char>> = Corpus::from(collected);
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 3);
+    /// assert_eq!(corpus.number_of_ngrams(), expected.number_of_ngrams());
+    /// assert_eq!(corpus.key_from_id(2), "catfish");
+    /// ```
+    pub fn from_iterator<I>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = KS::K>,
+    {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let keys = keys.into_iter();
+        let mut parser = KeysParser::new(keys.size_hint().0, builder);
+        let mut storage = KS::default();
+
+        debug!("Building ngrams from the streamed keys.");
+
+        for key in keys {
+            storage.push(key);
+            let key = storage.get_ref(storage.len() - 1);
+            let key: &K = key.as_ref();
+            parser.digest(key, None, PaddingMode::Auto, builder);
+        }
+
+        let (cooccurrences_builder, average_key_length, key_offsets) = parser.finish(builder);
+        Self::from_parsed_keys(
+            storage,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        )
+    }
+}
+
+/// Collects an iterator of strings into a case-insensitive corpus of bigrams.
+///
+/// # Implementation details
+/// The keys are lowercased and split into bigrams of characters, which suit the short
+/// keys such as names or titles that are most commonly collected into a corpus, and
+/// they are digested as they are read by the `from_iterator` method. To choose another
+/// arity or normalization, annotate the corpus with the desired types and build it with
+/// `Corpus::from` or `Corpus::from_iterator` instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<Vec<String>, BiGram<char>, Lowercase<str>> =
+///     ["Cat", "Dog", "Catfish"].iter().map(|key| key.to_string()).collect();
+///
+/// let results: SearchResults<&String, f32> =
+///     corpus.ngram_search("CAT", NgramSearchConfig::default());
+/// assert_eq!(results[0].key(), "Cat");
+/// assert_eq!(results[0].score(), 1.0);
+///
+/// // Another arity and normalization are chosen explicitly.
+/// let keys = vec!["Cat".to_string(), "Dog".to_string()];
+/// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+/// assert_eq!(corpus.number_of_keys(), 2);
+/// ```
+impl FromIterator<String> for Corpus<Vec<String>, BiGram<char>, Lowercase<str>> {
+    fn from_iter<I: IntoIterator<Item = String>>(keys: I) -> Self {
+        Self::from_iterator(keys)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<NG, K> Corpus<StringPool, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    StringPool: Keys<NG>,
+    for<'a> <StringPool as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Creates a new corpus from the lines of a reader, digesting each line as it is read.
+    ///
+    /// # Arguments
+    /// * `reader` - The reader whose lines are the keys, such as a buffered file.
+    ///
+    /// # Raises
+    /// * The first error returned by the reader, including invalid UTF-8 in a line.
+    /// * An error of kind `InvalidData` if the lines do not contain any ngram, as when
+    /// the reader is empty.
+    ///
+    /// # Implementation details
+    /// Each line is read in a reused buffer and appended to a `StringPool`, and its
+    /// ngrams are digested right away as in the `from_iterator` method, so that at no
+    /// point the lines are also held as separate strings. As in the `lines` method of
+    /// `BufRead`, the trailing newline, or carriage return and newline, of each line is
+    /// removed. The resulting corpus is the same one built by `from` from the lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let reader = std::io::Cursor::new("cat\r\ndog\ncatfish");
+    /// let corpus: Corpus<StringPool, TriGram<char>> = Corpus::from_reader(reader).unwrap();
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 3);
+    /// assert_eq!(corpus.key_from_id(0), "cat");
+    /// assert_eq!(corpus.key_from_id(2), "catfish");
+    /// ```
+    pub fn from_reader<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let mut parser = KeysParser::new(0, builder);
+        let mut keys = StringPool::default();
+        let mut line = String::new();
+
+        debug!("Building ngrams from the lines of the reader.");
+
+        while reader.read_line(&mut line)? != 0 {
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            keys.push_str(&line);
+            line.clear();
+            let key = keys.get(keys.len() - 1);
+            let key: &K = AsRef::<K>::as_ref(&key);
+            parser.digest(key, None, PaddingMode::Auto, builder);
+        }
+
+        if builder.unique_ngrams.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The lines of the reader do not contain any ngram.",
+            ));
+        }
+
+        let (cooccurrences_builder, average_key_length, key_offsets) = parser.finish(builder);
+        Ok(Self::from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        ))
+    }
+}
+
+impl<S, NG, K> Corpus<Vec<S>, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    Vec<S>: Keys<NG>,
+    for<'a> <Vec<S> as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Creates a new corpus from an iterator of fallible keys, stopping at the first error.
+    ///
+    /// # Arguments
+    /// * `keys` - The iterator of fallible keys, such as the lines of a file.
+    ///
+    /// # Raises
+    /// * The first error yielded by the iterator. The keys following the error are not consumed.
+    ///
+    /// # Examples
+    /// When reading the keys from a file, each line is a `Result`, and a bad line
+    /// makes the construction return its error instead of panicking.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let lines: Vec<Result<String, &str>> = vec![
+    ///     Ok("cat".to_string()),
+    ///     Ok("dog".to_string()),
+    ///     Err("invalid UTF-8 on line 3"),
+    ///     Ok("bird".to_string()),
+    /// ];
+    ///
+    /// let mut consumed = 0;
+    /// let corpus: Result<Corpus<Vec<String>, TriGram<char>>, &str> =
+    ///     Corpus::try_from_results(lines.into_iter().inspect(|_| consumed += 1));
+    ///
+    /// assert_eq!(corpus.err(), Some("invalid UTF-8 on line 3"));
+    /// assert_eq!(consumed, 3);
+    ///
+    /// let lines: Vec<Result<String, &str>> = vec![Ok("cat".to_string()), Ok("dog".to_string())];
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::try_from_results(lines).unwrap();
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 2);
+    /// ```
+    pub fn try_from_results<E, I>(keys: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<S, E>>,
+    {
+        let keys = keys.into_iter().collect::<Result<Vec<S>, E>>()?;
+        Ok(Self::from(keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_from_iterator_matches_from() {
+        let streamed: Corpus<Vec<String>, TriGram<char>> =
+            Corpus::from_iterator(ANIMALS.iter().map(|animal| animal.to_string()));
+        let collected: Corpus<Vec<String>, TriGram<char>> = Corpus::from(
+            ANIMALS
+                .iter()
+                .map(|animal| animal.to_string())
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(streamed.number_of_keys(), collected.number_of_keys());
+        assert_eq!(
+            streamed.average_key_length(),
+            collected.average_key_length()
+        );
+        assert!(streamed.ngrams().eq(collected.ngrams()));
+        assert!(streamed.cooccurrences().eq(collected.cooccurrences()));
+        for key_id in 0..streamed.number_of_keys() {
+            assert_eq!(streamed.key_from_id(key_id), collected.key_from_id(key_id));
+            assert!(streamed
+                .ngram_ids_from_key(key_id)
+                .eq(collected.ngram_ids_from_key(key_id)));
+        }
+        for ngram_id in 0..streamed.number_of_ngrams() {
+            assert!(streamed
+                .key_ids_from_ngram_id(ngram_id)
+                .eq(collected.key_ids_from_ngram_id(ngram_id)));
+        }
+    }
+
+    #[test]
+    fn test_collect_and_extend_a_corpus() {
+        let (initial, inserted) = ANIMALS.split_at(600);
+        let mut corpus: Corpus<Vec<String>, BiGram<char>, Lowercase<str>> =
+            initial.iter().map(|animal| animal.to_string()).collect();
+        let expected: Corpus<Vec<String>, BiGram<char>, Lowercase<str>> =
+            Corpus::from_iterator(initial.iter().map(|animal| animal.to_string()));
+
+        assert_eq!(corpus.number_of_keys(), initial.len());
+        assert!(corpus.ngrams().eq(expected.ngrams()));
+        assert!(corpus.cooccurrences().eq(expected.cooccurrences()));
+
+        let config = NgramSearchConfig::default();
+        let results: SearchResults<&String, f32> = corpus.ngram_search("sea LION", config);
+        assert_eq!(results[0].key(), "Sea Lion");
+        assert_eq!(results[0].score(), 1.0);
+
+        corpus.extend(inserted.iter().map(|animal| animal.to_string()));
+        assert_eq!(corpus.number_of_keys(), ANIMALS.len());
+        assert_eq!(corpus.number_of_overflow_keys(), inserted.len());
+        let results: SearchResults<&String, f32> = corpus.ngram_search(inserted[0], config);
+        assert_eq!(results[0].key(), inserted[0]);
+        assert_eq!(results[0].key_id(), initial.len());
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_from_reader_rejects_readers_without_ngrams() {
+        let corpus: std::io::Result<Corpus<StringPool, TriGram<char>>> =
+            Corpus::from_reader(std::io::Cursor::new(""));
+        assert_eq!(
+            corpus.err().map(|error| error.kind()),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+
+        let corpus: Corpus<StringPool, TriGram<char>> =
+            Corpus::from_reader(std::io::Cursor::new("cat\n")).unwrap();
+        assert_eq!(corpus.number_of_keys(), 1);
+        assert_eq!(corpus.key_from_id(0), "cat");
+    }
+
+    /// Returns the number of edges visited when retrieving the candidates of the query.
+    fn number_of_visited_edges(corpus: &Corpus<[&str; 699], TriGram<char>>, query: &str) -> usize {
+        Key::<TriGram<char>, char>::counts(query)
+            .into_keys()
+            .map(|ngram| corpus.ngram_frequency(ngram))
+            .sum()
+    }
+
+    #[test]
+    fn test_max_ngram_document_frequency_preserves_distinctive_terms() {
+        let config = CorpusConfig::default()
+            .set_max_ngram_document_frequency(0.02)
+            .unwrap();
+        let full: Corpus<[&str; 699], TriGram<char>> = Corpus::from(ANIMALS);
+        let pruned: Corpus<[&str; 699], TriGram<char>> = Corpus::from_with_config(ANIMALS, config);
+        let search_config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        for (query, expected) in [
+            ("Aardvark", "Aardvark"),
+            ("Axolotl", "Axolotl"),
+            ("Chinchila", "Chinchilla"),
+            ("Hippopotamus", "Hippopotamus"),
+            ("Narwal", "Narwhal"),
+            ("Platypos", "Platypus"),
+            ("Wolverine", "Wolverine"),
+        ] {
+            // The frequent ngrams are the ones with the longest posting lists, so
+            // the candidates of the search are retrieved visiting fewer edges.
+            assert!(
+                number_of_visited_edges(&pruned, query) < number_of_visited_edges(&full, query)
+            );
+            let results: SearchResults<&&str, f32> = pruned.ngram_search(query, search_config);
+            assert_eq!(results[0].key(), &expected);
+        }
+    }
+}
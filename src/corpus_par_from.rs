@@ -1,12 +1,32 @@
 //! Submodule implementing the `From` trait for the `Corpus` struct.
+use std::hash::BuildHasher;
+
 use rayon::prelude::*;
 use sux::prelude::*;
 use sux::traits::bit_field_slice::AtomicHelper;
 
-use crate::{bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, traits::*};
+use crate::{
+    bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, corpus_from::Milestones, traits::*,
+    weights::WeightsBuilder, AdaptativeVector, CorpusConfig, PaddingMode, ReusableCorpusBuilder,
+};
 
 use crate::Corpus;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The phases of the parallel construction of a corpus, reported in this order to the
+/// progress callback of the `par_from_with_progress` method.
+pub enum BuildPhase {
+    /// The keys are split into their ngrams, sequentially, and the progress is in keys.
+    ParsingKeys,
+    /// The unique ngrams are sorted, and the progress is in ngrams.
+    SortingNgrams,
+    /// The ngrams are stored and the offsets of their edges are computed, and the
+    /// progress is in ngrams.
+    BuildingOffsets,
+    /// The edges from the ngrams to the keys are built, and the progress is in keys.
+    BuildingEdges,
+}
+
 impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
 where
     NG: Ngram + Send + Sync,
@@ -25,7 +45,8 @@ where
     /// for arrays, such as UniGrams, BiGrams, TriGrams, and so on. This is
     /// solely done for the sake of better readability. The Ngrams are implemented
     /// up to the cardidality of 8, which is the maximum number of `u8`-based grams that can
-    /// be stored in a single u64.
+    /// be stored in a single u64, and the `u8`-based and `ASCIIChar`-based Ngrams up to
+    /// the cardinality of 16, which fit in the 128 bits of a u128.
     ///
     /// ```rust
     /// use ngrammatic::prelude::*;
@@ -129,19 +150,272 @@ where
     ///     Corpus::par_from(animals.clone());
     /// ```
     pub fn par_from(keys: KS) -> Self {
+        Self::par_from_keys(keys, None, &mut ReusableCorpusBuilder::<NG>::default())
+    }
+
+    /// Creates a new corpus from a set of keys, in parallel within the provided thread pool.
+    ///
+    /// # Arguments
+    /// * `pool` - The thread pool to build the corpus in.
+    /// * `keys` - The keys to create the corpus from.
+    ///
+    /// # Implementation details
+    /// The parallel work is executed via `ThreadPool::install`, so that it does not
+    /// occupy the threads of the global rayon pool.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let pool = rayon::ThreadPoolBuilder::new()
+    ///     .num_threads(2)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::par_from_in(&pool, ANIMALS);
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 699);
+    /// ```
+    pub fn par_from_in(pool: &rayon::ThreadPool, keys: KS) -> Self
+    where
+        KS: Send,
+        NG::SortedStorage: Send,
+        K: Send,
+    {
+        pool.install(|| Self::par_from(keys))
+    }
+
+    /// Creates a new corpus from a set of keys, in parallel, reporting the progress of
+    /// the construction to the provided callback.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `progress` - A function called with the phase of the construction, the number of
+    /// processed items and the total number of items of the phase.
+    ///
+    /// # Implementation details
+    /// The phases are reported in the order of the `BuildPhase` variants. The phases
+    /// iterating over the keys call the callback each time another hundredth of the keys
+    /// is processed, and always once the last key is, while the sorting and the offsets
+    /// are reported when they start and when they are completed. The callback is only
+    /// called from the thread calling this method, so that it does not need to be `Sync`,
+    /// and the resulting corpus is the same one built by `par_from`, which uses a no-op
+    /// callback that is optimized away.
+    ///
+    /// # Examples
+    /// In a command line tool, the callback can drive a progress bar, such as the ones
+    /// of the `indicatif` crate.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut reports: Vec<(BuildPhase, usize, usize)> = Vec::new();
+    /// let corpus: Corpus<[&str; 699], TriGram<char>> =
+    ///     Corpus::par_from_with_progress(ANIMALS, |phase, done, total| {
+    ///         reports.push((phase, done, total));
+    ///     });
+    ///
+    /// assert_eq!(reports[0].0, BuildPhase::ParsingKeys);
+    /// assert!(reports.contains(&(BuildPhase::ParsingKeys, 699, 699)));
+    /// assert_eq!(reports.last(), Some(&(BuildPhase::BuildingEdges, 699, 699)));
+    /// assert_eq!(corpus.number_of_keys(), 699);
+    /// ```
+    pub fn par_from_with_progress(
+        keys: KS,
+        mut progress: impl FnMut(BuildPhase, usize, usize),
+    ) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys_with_progress(
+                &keys,
+                None,
+                PaddingMode::Auto,
+                builder,
+                |done, total| progress(BuildPhase::ParsingKeys, done, total),
+            );
+        Self::par_from_parsed_keys_with_progress(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+            progress,
+        )
+    }
+
+    /// Creates a new corpus from a set of keys in parallel, truncating any key with more
+    /// than `max_key_grams` grams.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `max_key_grams` - The maximum number of grams to consider for each key.
+    ///
+    /// # Implementation details
+    /// This is the concurrent version of the `from_with_max_key_grams` method, please
+    /// refer to its documentation for the details. Note that the truncation is lossy.
+    ///
+    /// # Panics
+    /// * If `max_key_grams` is smaller than the arity of the ngram.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let huge_key = "abcdefghijklmnopqrstuvwxyz".repeat(100);
+    /// let keys = vec!["cat".to_string(), huge_key];
+    ///
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::par_from_with_max_key_grams(keys, 10);
+    ///
+    /// assert_eq!(corpus.number_of_ngrams_from_key_id(0), 5);
+    /// assert!(corpus.number_of_ngrams_from_key_id(1) <= 10);
+    /// ```
+    pub fn par_from_with_max_key_grams(keys: KS, max_key_grams: usize) -> Self {
+        assert!(
+            max_key_grams >= NG::ARITY,
+            "The maximum number of grams per key must be at least the arity of the ngram."
+        );
+        Self::par_from_keys(
+            keys,
+            Some(max_key_grams),
+            &mut ReusableCorpusBuilder::<NG>::default(),
+        )
+    }
+
+    /// Creates a new corpus from a set of keys in parallel, applying the provided
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `config` - The configuration of the construction of the corpus.
+    ///
+    /// # Implementation details
+    /// This is the concurrent version of the `from_with_config` method, please refer to
+    /// its documentation for the details. The resulting corpus is the same one.
+    ///
+    /// # Panics
+    /// * If the maximum number of grams per key is smaller than the arity of the ngram.
+    /// * If all of the ngrams of the keys are excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let config = CorpusConfig::default()
+    ///     .set_max_ngram_document_frequency(0.05)
+    ///     .unwrap();
+    ///
+    /// let corpus: Corpus<[&str; 699], TriGram<char>> = Corpus::from_with_config(ANIMALS, config);
+    /// let par_corpus: Corpus<[&str; 699], TriGram<char>> =
+    ///     Corpus::par_from_with_config(ANIMALS, config);
+    ///
+    /// assert!(par_corpus.ngrams().eq(corpus.ngrams()));
+    /// assert!(par_corpus.cooccurrences().eq(corpus.cooccurrences()));
+    /// ```
+    pub fn par_from_with_config(keys: KS, config: CorpusConfig) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys_with_config(&keys, &config, builder);
+        Self::par_from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        )
+    }
+
+    /// Creates a new corpus from a set of keys, in parallel.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `max_key_grams` - The maximum number of grams to consider for each key, if any.
+    /// * `builder` - The reusable buffers to use while building the corpus.
+    pub(crate) fn par_from_keys<S: BuildHasher + Default>(
+        keys: KS,
+        max_key_grams: Option<usize>,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> Self {
         // We start by parsing the keys to extract the ngrams, the cooccurrences, the key offsets,
         // and the maximal cooccurrence.
-        let (mut ngrams, cooccurrences_builder, average_key_length, key_offsets, key_to_ngrams) =
-            Self::parse_keys(&keys);
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_keys(&keys, max_key_grams, PaddingMode::Auto, builder);
+        Self::par_from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        )
+    }
+
+    /// Creates a new corpus from a set of keys that were already parsed, in parallel.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `cooccurrences_builder` - The builder of the cooccurrences of the keys.
+    /// * `average_key_length` - The average number of ngrams in the keys.
+    /// * `key_offsets` - The offsets of the edges of each key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams were stored.
+    pub(crate) fn par_from_parsed_keys<S: BuildHasher + Default>(
+        keys: KS,
+        cooccurrences_builder: WeightsBuilder,
+        average_key_length: f64,
+        key_offsets: AdaptativeVector,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+    ) -> Self {
+        Self::par_from_parsed_keys_with_progress(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+            |_, _, _| {},
+        )
+    }
+
+    /// Creates a new corpus from a set of keys that were already parsed, in parallel,
+    /// reporting its progress.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    /// * `cooccurrences_builder` - The builder of the cooccurrences of the keys.
+    /// * `average_key_length` - The average number of ngrams in the keys.
+    /// * `key_offsets` - The offsets of the edges of each key.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams were stored.
+    /// * `progress` - A function called with the phase of the construction, the number of
+    /// processed items and the total number of items of the phase.
+    pub(crate) fn par_from_parsed_keys_with_progress<S: BuildHasher + Default>(
+        keys: KS,
+        cooccurrences_builder: WeightsBuilder,
+        average_key_length: f64,
+        key_offsets: AdaptativeVector,
+        builder: &mut ReusableCorpusBuilder<NG, S>,
+        mut progress: impl FnMut(BuildPhase, usize, usize),
+    ) -> Self {
+        let ngrams = &mut builder.ngrams;
+        let key_to_ngrams = &builder.key_to_ngrams;
 
         let cooccurrences = cooccurrences_builder.par_build();
 
         // We sort the ngrams in parallel.
-        log::debug!("Sorting ngrams.");
+        debug!("Sorting ngrams.");
+        progress(BuildPhase::SortingNgrams, 0, ngrams.len());
         ngrams.par_sort_unstable();
+        let ngrams: &Vec<NG> = ngrams;
+        let number_of_ngrams = ngrams.len();
+        progress(
+            BuildPhase::SortingNgrams,
+            number_of_ngrams,
+            number_of_ngrams,
+        );
+        progress(BuildPhase::BuildingOffsets, 0, number_of_ngrams);
 
         // We can now start to compress several of the vectors into BitFieldVecs.
-        log::debug!("Compressing key offsets into Elias-Fano.");
+        debug!("Compressing key offsets into Elias-Fano.");
         let key_offsets = unsafe { key_offsets.par_into_elias_fano() };
 
         // We now create the various required bitvectors, knowing all of their characteristics
@@ -171,11 +445,11 @@ where
             key_to_ngrams.len(),
         );
 
-        log::debug!("Building the key to ngram edges.");
+        debug!("Building the key to ngram edges.");
         key_to_ngrams
-            .into_par_iter()
+            .par_iter()
             .enumerate()
-            .for_each(|(edge_id, ngram)| {
+            .for_each(|(edge_id, &ngram)| {
                 // We find the index of the ngram in the ngrams vector.
                 // We can always unwrap since we know that the ngram is in the ngrams vector.
                 let ngram_index = unsafe { ngrams.index_of_unchecked(ngram) };
@@ -195,22 +469,22 @@ where
 
         // We create the ngrams vector. Since we are using a btreeset, we already have the
         // ngrams sorted, so we can simply convert the btreeset into a vector.
-        log::debug!(
+        debug!(
             "Storing ngrams into {}.",
             std::any::type_name::<NG::SortedStorage>()
         );
         let ngram_builder = <<<NG as Ngram>::SortedStorage as SortedNgramStorage<NG>>::ConcurrentBuilder>::new_storage_builder(ngrams.len(), *ngrams.last().unwrap());
 
         ngrams
-            .into_par_iter()
+            .par_iter()
             .enumerate()
-            .for_each(|(index, ngram)| unsafe {
+            .for_each(|(index, &ngram)| unsafe {
                 ngram_builder.set_unchecked(ngram, index);
             });
 
         let ngrams: NG::SortedStorage = ngram_builder.build();
 
-        log::debug!("Computing ngrams degrees.");
+        debug!("Computing ngrams degrees.");
 
         // We iterate on the key_to_ngrams vector. For each ngram we encounter, we find the index of the ngram
         // in the ngram vector by employing a binary search, since we know that the ngrams are sorted.
@@ -222,7 +496,7 @@ where
             }
         }
 
-        log::debug!("Computing ngrams degrees comulative sum.");
+        debug!("Computing ngrams degrees comulative sum.");
 
         // Now that we have fully populated the ngram_degrees vector, we need to compute the comulative
         // sum of the inbound degrees of the ngrams.
@@ -245,8 +519,13 @@ where
 
         // We build the ngram_offsets vector.
         let ngram_offsets = ngram_offsets_builder.build().convert_to().unwrap();
+        progress(
+            BuildPhase::BuildingOffsets,
+            number_of_ngrams,
+            number_of_ngrams,
+        );
 
-        log::debug!("Building edges from gram to key.");
+        debug!("Building edges from gram to key.");
         // Finally, we can allocate and populate the gram_to_key_edges vector. This vector has the same length
         // as the cooccurrences vector.
         let mut gram_to_key_edges = BitFieldVec::new(
@@ -261,6 +540,7 @@ where
         // For each ngram, by using the ngram_degrees, we can find the position of the key in the gram_to_key_edges vector.
 
         let mut ngram_iterator = key_to_ngram_edges.iter();
+        let mut milestones = Milestones::new(keys.len());
 
         for (key_id, (key_offset_start, key_offset_end)) in key_offsets
             .into_iter_from(0)
@@ -292,6 +572,9 @@ where
                 //We increment the inbound degree of the key.
                 unsafe { ngram_degrees.set_unchecked(ngram_id, ngram_degree + 1) };
             }
+            if let Some(done) = milestones.advance() {
+                progress(BuildPhase::BuildingEdges, done, keys.len());
+            }
         }
 
         Corpus::new(
@@ -308,3 +591,44 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_par_from_with_progress_reports_every_phase() {
+        let mut reports: Vec<(BuildPhase, usize, usize)> = Vec::new();
+        let corpus: Corpus<&[&str; 699], TriGram<char>> =
+            Corpus::par_from_with_progress(&ANIMALS, |phase, done, total| {
+                reports.push((phase, done, total));
+            });
+        let expected: Corpus<&[&str; 699], TriGram<char>> = Corpus::par_from(&ANIMALS);
+        assert!(corpus.ngrams().eq(expected.ngrams()));
+        assert!(corpus.cooccurrences().eq(expected.cooccurrences()));
+
+        // The phases are reported in order, with an increasing progress.
+        assert!(
+            reports
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0
+                    || (pair[0].0 == pair[1].0 && pair[0].1 < pair[1].1))
+        );
+        let number_of_ngrams = corpus.number_of_ngrams();
+        for (phase, total) in [
+            (BuildPhase::ParsingKeys, ANIMALS.len()),
+            (BuildPhase::SortingNgrams, number_of_ngrams),
+            (BuildPhase::BuildingOffsets, number_of_ngrams),
+            (BuildPhase::BuildingEdges, ANIMALS.len()),
+        ] {
+            let phase_reports: Vec<(BuildPhase, usize, usize)> = reports
+                .iter()
+                .copied()
+                .filter(|report| report.0 == phase)
+                .collect();
+            assert!(phase_reports.iter().all(|report| report.2 == total));
+            assert_eq!(phase_reports.last().unwrap().1, total);
+            assert!(phase_reports.len() <= 101);
+        }
+    }
+}
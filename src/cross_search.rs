@@ -0,0 +1,270 @@
+//! Submodule providing the search of the keys of a corpus in another corpus.
+//!
+//! # Implementation details
+//! Entity resolution between two datasets requires, for each key of the first corpus,
+//! its best matches among the keys of the second corpus. Each key of the first corpus
+//! is searched as a query in the second corpus, which must share the ngram and the key
+//! type of the first one, so that the queries are normalized and split into ngrams as
+//! the keys they are compared with.
+use crate::prelude::*;
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Calls the provided callback with the best matches in the other corpus of each key.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to search the keys in.
+    /// * `config` - The configuration for the searches.
+    /// * `callback` - A function called with the id of each key and its best matches.
+    ///
+    /// # Implementation details
+    /// The keys are searched in the order of their ids, as in the `ngram_search_with_warp`
+    /// method, and the removed keys are skipped. The results are handed over to the
+    /// callback as soon as they are computed, so that only the results of one key are
+    /// held in memory at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let left: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["Cat", "Doggo"]);
+    /// let right: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let mut best_matches = Vec::new();
+    /// left.cross_search_for_each(&right, NgramSearchConfig::default(), |key_id, results| {
+    ///     best_matches.push((key_id, results.into_keys()));
+    /// });
+    ///
+    /// assert_eq!(best_matches.len(), 2);
+    /// assert_eq!(best_matches[0].0, 0);
+    /// assert_eq!(best_matches[0].1[0], &"Cat");
+    /// ```
+    pub fn cross_search_for_each<'b, KS2, G2, W: Copy, F: Float>(
+        &self,
+        other: &'b Corpus<KS2, NG, K, G2>,
+        config: NgramSearchConfig<W, F>,
+        mut callback: impl FnMut(usize, SearchResults<KS2::KeyRef<'b>, F>),
+    ) where
+        KS2: Keys<NG>,
+        for<'a> KS2::KeyRef<'a>: AsRef<K>,
+        G2: WeightedBipartiteGraph,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        for key_id in 0..self.number_of_key_ids() {
+            if self.is_removed(key_id) {
+                continue;
+            }
+            callback(
+                key_id,
+                other.ngram_search_with_warp(self.key_from_id(key_id), config),
+            );
+        }
+    }
+
+    /// Returns the best matches in the other corpus of each key.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to search the keys in.
+    /// * `config` - The configuration for the searches.
+    ///
+    /// # Implementation details
+    /// The results are indexed by key id, and the ones of the removed keys are empty.
+    /// All of the results are held in memory, which takes up to the number of keys times
+    /// the maximum number of results times the size of a key reference and of a score,
+    /// so that for a large corpus the `cross_search_for_each` method is to be preferred.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let left: Corpus<[&str; 3], TriGram<char>, Lowercase<str>> =
+    ///     Corpus::from(["cat", "sea lion", "xyz"]);
+    /// let right: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: Vec<SearchResults<&&str, f32>> =
+    ///     left.cross_search(&right, NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// assert_eq!(results[0][0].key(), &"Cat");
+    /// assert_eq!(results[1][0].key(), &"Sea Lion");
+    /// assert!(results[2].is_empty());
+    /// ```
+    pub fn cross_search<'b, KS2, G2, W: Copy, F: Float>(
+        &self,
+        other: &'b Corpus<KS2, NG, K, G2>,
+        config: NgramSearchConfig<W, F>,
+    ) -> Vec<SearchResults<KS2::KeyRef<'b>, F>>
+    where
+        KS2: Keys<NG>,
+        for<'a> KS2::KeyRef<'a>: AsRef<K>,
+        G2: WeightedBipartiteGraph,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        (0..self.number_of_key_ids())
+            .map(|key_id| {
+                if self.is_removed(key_id) {
+                    SearchResults::default()
+                } else {
+                    other.ngram_search_with_warp(self.key_from_id(key_id), config)
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Calls the provided callback in parallel with the best matches in the other corpus
+    /// of each key.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to search the keys in.
+    /// * `config` - The configuration for the searches.
+    /// * `callback` - A function called with the id of each key and its best matches.
+    ///
+    /// # Implementation details
+    /// The keys are distributed among the threads, each searching its keys sequentially,
+    /// so that the callback is called concurrently and in no particular order.
+    pub fn par_cross_search_for_each<'b, KS2, G2, W: Copy, F: Float>(
+        &self,
+        other: &'b Corpus<KS2, NG, K, G2>,
+        config: NgramSearchConfig<W, F>,
+        callback: impl Fn(usize, SearchResults<KS2::KeyRef<'b>, F>) + Send + Sync,
+    ) where
+        Self: Sync,
+        Corpus<KS2, NG, K, G2>: Sync,
+        KS2: Keys<NG>,
+        for<'a> KS2::KeyRef<'a>: AsRef<K>,
+        G2: WeightedBipartiteGraph,
+        W: Send + Sync,
+        F: Send + Sync,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        use rayon::prelude::*;
+
+        (0..self.number_of_key_ids())
+            .into_par_iter()
+            .filter(|&key_id| !self.is_removed(key_id))
+            .for_each(|key_id| {
+                callback(
+                    key_id,
+                    other.ngram_search_with_warp(self.key_from_id(key_id), config),
+                );
+            });
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Returns the best matches in the other corpus of each key, searching the keys
+    /// in parallel.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to search the keys in.
+    /// * `config` - The configuration for the searches.
+    ///
+    /// # Implementation details
+    /// The results are the same ones of the `cross_search` method, with the same memory
+    /// requirements, while the keys are distributed among the threads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let left: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let right: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: Vec<SearchResults<&&str, f32>> =
+    ///     left.par_cross_search(&right, NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results, left.cross_search(&right, NgramSearchConfig::default()));
+    /// ```
+    pub fn par_cross_search<'b, KS2, G2, W: Copy, F: Float>(
+        &self,
+        other: &'b Corpus<KS2, NG, K, G2>,
+        config: NgramSearchConfig<W, F>,
+    ) -> Vec<SearchResults<KS2::KeyRef<'b>, F>>
+    where
+        Self: Sync,
+        Corpus<KS2, NG, K, G2>: Sync,
+        KS2: Keys<NG>,
+        for<'a> KS2::KeyRef<'a>: AsRef<K> + Send,
+        G2: WeightedBipartiteGraph,
+        W: Send + Sync,
+        F: Send + Sync,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        use rayon::prelude::*;
+
+        (0..self.number_of_key_ids())
+            .into_par_iter()
+            .map(|key_id| {
+                if self.is_removed(key_id) {
+                    SearchResults::default()
+                } else {
+                    other.ngram_search_with_warp(self.key_from_id(key_id), config)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_cross_search_agrees_with_the_searches_of_each_key() {
+        let mut left: Corpus<Vec<&str>, TriGram<char>> =
+            Corpus::from(vec!["Cat", "Catt", "Sea lion", "Doggo", "Hippopotamus"]);
+        left.remove_key(1);
+        let right: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap();
+
+        let results = left.cross_search(&right, config);
+        assert_eq!(results.len(), 5);
+        assert!(results[1].is_empty());
+
+        let mut streamed = Vec::new();
+        left.cross_search_for_each(&right, config, |key_id, key_results| {
+            streamed.push((key_id, key_results));
+        });
+        assert_eq!(
+            streamed
+                .iter()
+                .map(|(key_id, _)| *key_id)
+                .collect::<Vec<usize>>(),
+            vec![0, 2, 3, 4]
+        );
+
+        for (key_id, key_results) in streamed {
+            let expected = right.ngram_search(left.key_from_id(key_id), config);
+            assert!(!expected.is_empty());
+            assert_eq!(results[key_id], expected);
+            assert_eq!(key_results, expected);
+        }
+
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(left.par_cross_search(&right, config), results);
+
+            let streamed = std::sync::Mutex::new(Vec::new());
+            left.par_cross_search_for_each(&right, config, |key_id, key_results| {
+                streamed.lock().unwrap().push((key_id, key_results));
+            });
+            let mut streamed = streamed.into_inner().unwrap();
+            streamed.sort_unstable_by_key(|(key_id, _)| *key_id);
+            assert_eq!(streamed.len(), 4);
+            for (key_id, key_results) in streamed {
+                assert_eq!(key_results, results[key_id]);
+            }
+        }
+    }
+}
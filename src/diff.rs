@@ -0,0 +1,161 @@
+//! Submodule implementing the diff method for the `Corpus` struct, which compares
+//! the keys and the ngrams of two corpora, such as two snapshots of the same index.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::prelude::*;
+
+/// A struct containing the keys and the ngrams added and removed between two corpora.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusDiff<KR, NG> {
+    /// The keys of the other corpus that are not in this corpus.
+    pub added_keys: Vec<KR>,
+    /// The keys of this corpus that are not in the other corpus.
+    pub removed_keys: Vec<KR>,
+    /// The ngrams of the other corpus that are not in this corpus, in ascending order.
+    pub added_ngrams: Vec<NG>,
+    /// The ngrams of this corpus that are not in the other corpus, in ascending order.
+    pub removed_ngrams: Vec<NG>,
+}
+
+impl<KR, NG> CorpusDiff<KR, NG> {
+    #[inline(always)]
+    /// Returns whether the two corpora have the same keys and the same ngrams.
+    pub fn is_empty(&self) -> bool {
+        self.added_keys.is_empty()
+            && self.removed_keys.is_empty()
+            && self.added_ngrams.is_empty()
+            && self.removed_ngrams.is_empty()
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the value of the provided key, which is used to compare the keys.
+    ///
+    /// # Arguments
+    /// * `key` - The reference to the key.
+    fn key_value<'b, KR: AsRef<K>>(key: &'b KR) -> &'b <K as Key<NG, NG::G>>::Ref {
+        let key: &K = key.as_ref();
+        key.as_ref()
+    }
+
+    /// Returns the keys of the corpus, excluding the removed ones.
+    fn live_keys(&self) -> Vec<KS::KeyRef<'_>> {
+        self.iter_key_ids()
+            .map(|key_id| self.key_from_id(key_id))
+            .collect()
+    }
+
+    /// Returns the keys and the ngrams added and removed in the other corpus.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to compare with, such as a newer build of this corpus.
+    ///
+    /// # Implementation details
+    /// The keys are compared by value, and the removed keys of both of the corpora are
+    /// ignored. The ngrams are compared by value as well, so that the ngram ids of the
+    /// two corpora, which depend on their whole vocabularies, need not match. The keys
+    /// are reported in the order of their ids, and the ngrams in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let old: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// let new: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "cow"]);
+    ///
+    /// let diff = old.diff(&new);
+    ///
+    /// assert_eq!(diff.added_keys, vec![&"cow"]);
+    /// assert_eq!(diff.removed_keys, vec![&"dog"]);
+    /// assert!(diff.added_ngrams.contains(&['c', 'o', 'w']));
+    /// assert!(diff.removed_ngrams.contains(&['d', 'o', 'g']));
+    /// assert!(old.diff(&old).is_empty());
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Self) -> CorpusDiff<KS::KeyRef<'a>, NG>
+    where
+        <K as Key<NG, NG::G>>::Ref: Hash + Eq,
+    {
+        let keys = self.live_keys();
+        let other_keys = other.live_keys();
+        let key_values: HashSet<&<K as Key<NG, NG::G>>::Ref> =
+            keys.iter().map(Self::key_value).collect();
+        let other_key_values: HashSet<&<K as Key<NG, NG::G>>::Ref> =
+            other_keys.iter().map(Self::key_value).collect();
+
+        let added_keys = other_keys
+            .iter()
+            .filter(|key| !key_values.contains(Self::key_value(*key)))
+            .cloned()
+            .collect();
+        let removed_keys = keys
+            .iter()
+            .filter(|key| !other_key_values.contains(Self::key_value(*key)))
+            .cloned()
+            .collect();
+
+        let ngrams: HashSet<NG> = self.ngrams().collect();
+        let other_ngrams: HashSet<NG> = other.ngrams().collect();
+
+        CorpusDiff {
+            added_keys,
+            removed_keys,
+            added_ngrams: other
+                .ngrams()
+                .filter(|ngram| !ngrams.contains(ngram))
+                .collect(),
+            removed_ngrams: self
+                .ngrams()
+                .filter(|ngram| !other_ngrams.contains(ngram))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_with_an_extra_key() {
+        let keys: Vec<&str> = ANIMALS.iter().copied().take(100).collect();
+        let mut extended = keys.clone();
+        extended.push("Zebrafish");
+
+        let corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(keys);
+        let extended_corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(extended);
+
+        let diff = corpus.diff(&extended_corpus);
+        assert_eq!(diff.added_keys, vec![&"Zebrafish"]);
+        assert!(diff.removed_keys.is_empty());
+        assert!(!diff.added_ngrams.is_empty());
+        assert!(diff.removed_ngrams.is_empty());
+        assert_eq!(
+            corpus.number_of_ngrams() + diff.added_ngrams.len(),
+            extended_corpus.number_of_ngrams()
+        );
+        for ngram in &diff.added_ngrams {
+            assert!(corpus.ngram_id_from_ngram(*ngram).is_none());
+            assert!(extended_corpus.ngram_id_from_ngram(*ngram).is_some());
+        }
+
+        // The diff in the opposite direction swaps the added and the removed items.
+        let reverse = extended_corpus.diff(&corpus);
+        assert_eq!(reverse.removed_keys, diff.added_keys);
+        assert_eq!(reverse.removed_ngrams, diff.added_ngrams);
+        assert!(reverse.added_keys.is_empty());
+        assert!(reverse.added_ngrams.is_empty());
+
+        assert!(corpus.diff(&corpus).is_empty());
+    }
+}
@@ -0,0 +1,784 @@
+//! Submodule providing the kernel computing the dot product of two sparse count vectors.
+//!
+//! # Implementation details
+//! A sparse count vector is stored as the sorted ids of its non-zero entries and their
+//! counts, such as the ngram ids and co-occurrences of a key. The dot product only
+//! involves the ids appearing in both vectors, so the kernel intersects the two sorted
+//! lists of ids and accumulates the products of the counts of the shared ids.
+//!
+//! The scalar kernel merges the two lists one id at a time, with a data-dependent branch
+//! at each step that the processor often mispredicts. The SIMD kernel instead compares
+//! blocks of eight ids of each list at once: each id of the right block is broadcast and
+//! compared with the whole left block, and the block with the smaller last id is then
+//! replaced by the following one. Since the ids of each list are unique, each id of the
+//! right block matches at most one lane. When the last id of a block precedes the first
+//! id of the other block, none of its ids can be shared, and the kernel gallops over the
+//! list of the former block with an exponential search instead of comparing the blocks.
+//! The blocks do not pay off for short lists, so
+//! `dot_product` only selects the SIMD kernel when the right list is at least
+//! `SIMD_DEGREE_THRESHOLD` long. Both kernels use integer arithmetic, so that they return
+//! exactly the same dot product. The SIMD kernels are only available under the `simd`
+//! feature, without which `dot_product` always selects the scalar kernel.
+//!
+//! The same kernels also compute the bounded overlap of two vectors, which sums the
+//! minimum of the counts of the shared ids instead of their product. They are used by
+//! `Corpus::similarity_search`, which scores the candidate keys by the cosine similarity
+//! or by the bounded overlap of their ngram counts with the ngram counts of the query.
+//! Under the `simd` feature, the `intersection` kernel, returning both the number of shared
+//! ids and their bounded overlap, is also used to score the candidates of the ngram search.
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+#[cfg(feature = "simd")]
+use wide::i32x8;
+
+use crate::math;
+use crate::prelude::*;
+use crate::SearchResultsHeap;
+
+#[cfg(feature = "simd")]
+/// The number of ids compared at once by the SIMD kernel.
+const LANES: usize = 8;
+
+/// The minimum length of the right list of ids for which the SIMD kernel is selected.
+pub const SIMD_DEGREE_THRESHOLD: usize = 32;
+
+/// Returns the dot product of two sparse count vectors, merging their ids one at a time.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::dot_product::dot_product_scalar;
+///
+/// let dot = dot_product_scalar(&[1, 3, 5], &[2, 1, 4], &[3, 4, 5], &[7, 1, 2]);
+///
+/// assert_eq!(dot, 1 * 7 + 4 * 2);
+/// ```
+pub fn dot_product_scalar(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> u64 {
+    assert_eq!(left_ids.len(), left_counts.len());
+    assert_eq!(right_ids.len(), right_counts.len());
+    merge_accumulate(left_ids, left_counts, right_ids, right_counts, product).1
+}
+
+#[inline(always)]
+/// Returns the product of the counts of a shared id.
+fn product(left_count: u32, right_count: u32) -> u64 {
+    left_count as u64 * right_count as u64
+}
+
+#[inline(always)]
+/// Returns the minimum of the counts of a shared id.
+fn minimum(left_count: u32, right_count: u32) -> u64 {
+    left_count.min(right_count) as u64
+}
+
+#[inline(always)]
+/// Returns the number of shared ids and the sum of their combined counts, merging the ids
+/// one at a time.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+/// * `combine` - The function combining the two counts of a shared id.
+fn merge_accumulate(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+    combine: fn(u32, u32) -> u64,
+) -> (usize, u64) {
+    let mut shared = 0;
+    let mut accumulator = 0;
+    let mut left = 0;
+    let mut right = 0;
+
+    while left < left_ids.len() && right < right_ids.len() {
+        match left_ids[left].cmp(&right_ids[right]) {
+            Ordering::Less => {
+                left += 1;
+            }
+            Ordering::Greater => {
+                right += 1;
+            }
+            Ordering::Equal => {
+                shared += 1;
+                accumulator += combine(left_counts[left], right_counts[right]);
+                left += 1;
+                right += 1;
+            }
+        }
+    }
+
+    (shared, accumulator)
+}
+
+#[cfg(feature = "simd")]
+/// Returns the dot product of two sparse count vectors, comparing blocks of ids with SIMD.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Implementation details
+/// The blocks are compared until either list has fewer than eight ids left, and the
+/// remaining ids are merged with the scalar kernel.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::dot_product::{dot_product_scalar, dot_product_simd};
+///
+/// let left_ids: Vec<u32> = (0..100).map(|id| id * 2).collect();
+/// let right_ids: Vec<u32> = (0..100).map(|id| id * 3).collect();
+/// let left_counts: Vec<u32> = (0..100).map(|count| count % 7 + 1).collect();
+/// let right_counts: Vec<u32> = (0..100).map(|count| count % 5 + 1).collect();
+///
+/// assert_eq!(
+///     dot_product_simd(&left_ids, &left_counts, &right_ids, &right_counts),
+///     dot_product_scalar(&left_ids, &left_counts, &right_ids, &right_counts)
+/// );
+/// ```
+pub fn dot_product_simd(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> u64 {
+    assert_eq!(left_ids.len(), left_counts.len());
+    assert_eq!(right_ids.len(), right_counts.len());
+    simd_accumulate(left_ids, left_counts, right_ids, right_counts, product).1
+}
+
+#[cfg(feature = "simd")]
+#[inline(always)]
+/// Returns the index of the first id not smaller than the target, searching from the start.
+///
+/// # Arguments
+/// * `ids` - The sorted ids to search.
+/// * `start` - The index to start the search from.
+/// * `target` - The id to search for.
+///
+/// # Implementation details
+/// The steps from the start double until an id not smaller than the target is found, and
+/// the last step is then binary searched, so that skipping `n` ids costs `O(log n)`.
+fn gallop(ids: &[u32], start: usize, target: u32) -> usize {
+    let mut low = start;
+    let mut step = 1;
+    while low + step < ids.len() && ids[low + step] < target {
+        low += step;
+        step *= 2;
+    }
+    let high = (low + step + 1).min(ids.len());
+    low + ids[low..high].partition_point(|&id| id < target)
+}
+
+#[cfg(feature = "simd")]
+#[inline(always)]
+/// Returns the number of shared ids and the sum of their combined counts, comparing blocks
+/// of ids with SIMD.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+/// * `combine` - The function combining the two counts of a shared id.
+fn simd_accumulate(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+    combine: fn(u32, u32) -> u64,
+) -> (usize, u64) {
+    let mut shared = 0;
+    let mut accumulator = 0;
+    let mut left = 0;
+    let mut right = 0;
+
+    while left + LANES <= left_ids.len() && right + LANES <= right_ids.len() {
+        // When a block ends before the other one starts, we gallop over its list.
+        if left_ids[left + LANES - 1] < right_ids[right] {
+            left = gallop(left_ids, left, right_ids[right]);
+            continue;
+        }
+        if right_ids[right + LANES - 1] < left_ids[left] {
+            right = gallop(right_ids, right, left_ids[left]);
+            continue;
+        }
+
+        // The ids are reinterpreted as signed integers, which preserves their equality.
+        let left_block = i32x8::new(std::array::from_fn(|lane| left_ids[left + lane] as i32));
+
+        for offset in 0..LANES {
+            let right_id = i32x8::splat(right_ids[right + offset] as i32);
+            let mask = left_block.cmp_eq(right_id).move_mask();
+            if mask != 0 {
+                let lane = mask.trailing_zeros() as usize;
+                shared += 1;
+                accumulator += combine(left_counts[left + lane], right_counts[right + offset]);
+            }
+        }
+
+        let left_last = left_ids[left + LANES - 1];
+        let right_last = right_ids[right + LANES - 1];
+        if left_last <= right_last {
+            left += LANES;
+        }
+        if right_last <= left_last {
+            right += LANES;
+        }
+    }
+
+    let (remaining_shared, remaining_accumulator) = merge_accumulate(
+        &left_ids[left..],
+        &left_counts[left..],
+        &right_ids[right..],
+        &right_counts[right..],
+        combine,
+    );
+
+    (
+        shared + remaining_shared,
+        accumulator + remaining_accumulator,
+    )
+}
+
+#[inline(always)]
+/// Returns the dot product of two sparse count vectors, selecting the fastest kernel.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Implementation details
+/// Under the `simd` feature, the SIMD kernel is selected when the right list has at least
+/// `SIMD_DEGREE_THRESHOLD` ids, and the scalar kernel otherwise.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+pub fn dot_product(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> u64 {
+    #[cfg(feature = "simd")]
+    if right_ids.len() >= SIMD_DEGREE_THRESHOLD {
+        return dot_product_simd(left_ids, left_counts, right_ids, right_counts);
+    }
+    dot_product_scalar(left_ids, left_counts, right_ids, right_counts)
+}
+
+#[inline(always)]
+/// Returns the bounded overlap of two sparse count vectors, selecting the fastest kernel.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Implementation details
+/// The bounded overlap is the sum, over the shared ids, of the minimum of the two counts,
+/// so that an id repeated many times in one vector contributes at most its count in the
+/// other one. The kernel is selected as in `dot_product`.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::dot_product::bounded_overlap;
+///
+/// let overlap = bounded_overlap(&[1, 3, 5], &[2, 1, 4], &[3, 4, 5], &[7, 1, 2]);
+///
+/// assert_eq!(overlap, 1 + 2);
+/// ```
+pub fn bounded_overlap(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> u64 {
+    intersection(left_ids, left_counts, right_ids, right_counts).1
+}
+
+/// Returns the number of shared ids of two sparse count vectors and their bounded overlap,
+/// merging their ids one at a time.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::dot_product::intersection_scalar;
+///
+/// let (shared, overlap) = intersection_scalar(&[1, 3, 5], &[2, 1, 4], &[3, 4, 5], &[7, 1, 2]);
+///
+/// assert_eq!(shared, 2);
+/// assert_eq!(overlap, 1 + 2);
+/// ```
+pub fn intersection_scalar(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> (usize, u64) {
+    assert_eq!(left_ids.len(), left_counts.len());
+    assert_eq!(right_ids.len(), right_counts.len());
+    merge_accumulate(left_ids, left_counts, right_ids, right_counts, minimum)
+}
+
+#[cfg(feature = "simd")]
+/// Returns the number of shared ids of two sparse count vectors and their bounded overlap,
+/// comparing blocks of ids with SIMD and galloping over the blocks without shared ids.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::dot_product::{intersection_scalar, intersection_simd};
+///
+/// let left_ids: Vec<u32> = (0..100).map(|id| id * 2).collect();
+/// let right_ids: Vec<u32> = (0..100).map(|id| id * 3 + 150).collect();
+/// let left_counts: Vec<u32> = (0..100).map(|count| count % 7 + 1).collect();
+/// let right_counts: Vec<u32> = (0..100).map(|count| count % 5 + 1).collect();
+///
+/// assert_eq!(
+///     intersection_simd(&left_ids, &left_counts, &right_ids, &right_counts),
+///     intersection_scalar(&left_ids, &left_counts, &right_ids, &right_counts)
+/// );
+/// ```
+pub fn intersection_simd(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> (usize, u64) {
+    assert_eq!(left_ids.len(), left_counts.len());
+    assert_eq!(right_ids.len(), right_counts.len());
+    simd_accumulate(left_ids, left_counts, right_ids, right_counts, minimum)
+}
+
+#[inline(always)]
+/// Returns the number of shared ids of two sparse count vectors and their bounded overlap,
+/// selecting the fastest kernel.
+///
+/// # Arguments
+/// * `left_ids` - The sorted and unique ids of the first vector.
+/// * `left_counts` - The counts of the first vector, in the same order of `left_ids`.
+/// * `right_ids` - The sorted and unique ids of the second vector.
+/// * `right_counts` - The counts of the second vector, in the same order of `right_ids`.
+///
+/// # Implementation details
+/// The kernel is selected as in `dot_product`.
+///
+/// # Panics
+/// * If the ids and the counts of a vector have different lengths.
+pub fn intersection(
+    left_ids: &[u32],
+    left_counts: &[u32],
+    right_ids: &[u32],
+    right_counts: &[u32],
+) -> (usize, u64) {
+    #[cfg(feature = "simd")]
+    if right_ids.len() >= SIMD_DEGREE_THRESHOLD {
+        return intersection_simd(left_ids, left_counts, right_ids, right_counts);
+    }
+    intersection_scalar(left_ids, left_counts, right_ids, right_counts)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The similarity used by `similarity_search` to score the candidate keys.
+pub enum Similarity {
+    #[default]
+    /// The cosine similarity of the ngram counts, whose numerator is the dot product
+    /// of the counts of the query and of the key.
+    Cosine,
+    /// The bounded overlap of the ngram counts, whose numerator caps the contribution of
+    /// each shared ngram to the minimum of its counts in the query and in the key.
+    ///
+    /// # Implementation details
+    /// The overlap is divided by the geometric mean of the total number of ngrams of the
+    /// query and of the key, so that the score is in the range 0.0 to 1.0. Differently
+    /// from the cosine similarity, a key repeating the same ngram many times, such as
+    /// "aaaaaaaa", cannot dominate the scores of a query repeating it fewer times.
+    BoundedOverlap,
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the best matches by the cosine similarity of their ngram counts.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// This is the same as calling `similarity_search` with `Similarity::Cosine`.
+    ///
+    /// # Panics
+    /// * If the number of ngrams in the corpus, or an ngram count, does not fit in an `u32`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.cosine_search("Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert!((results[0].score() - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn cosine_search<W: Copy, F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        Warp<W>: NgramSimilarity + One,
+    {
+        self.similarity_search(key, config, Similarity::Cosine)
+    }
+
+    /// Returns the best matches by the provided similarity of their ngram counts.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - The similarity to score the candidate keys with.
+    ///
+    /// # Implementation details
+    /// The score of a candidate key combines its ngram counts with the ngram counts of
+    /// the query, as defined by the similarity, and divides the result by the product of
+    /// their norms. The ngrams of the query that do not appear in the corpus only
+    /// contribute to the norm of the query. The ngram ids of the query are collected
+    /// once, while the ngram ids of each candidate are collected into buffers reused
+    /// across the candidates, and the counts are combined with the SIMD kernel, under the
+    /// `simd` feature, when the candidate has at least `SIMD_DEGREE_THRESHOLD` distinct
+    /// ngrams, as is common for long keys such as taxon names, and with the scalar kernel
+    /// otherwise.
+    ///
+    /// The warp factor of the configuration is applied to the similarity `s` as it is to
+    /// the ratio of shared ngrams in `ngram_search`, so that the score is `1 - (1 - s)^warp`.
+    /// A warp of one leaves the similarity unchanged, while greater warps raise the scores
+    /// of the partial matches without changing the ranking.
+    ///
+    /// # Panics
+    /// * If the number of ngrams in the corpus, or an ngram count, does not fit in an `u32`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use ngrammatic::dot_product::Similarity;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let config = NgramSearchConfig::default();
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.similarity_search("Cat", config, Similarity::BoundedOverlap);
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert!((results[0].score() - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn similarity_search<W: Copy, F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        config: NgramSearchConfig<W, F>,
+        similarity: Similarity,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        Warp<W>: NgramSimilarity + One,
+    {
+        let warp: Warp<W> = config.warp();
+        let config: SearchConfig<F> = config.into();
+        // The contribution of an ngram with the provided count to the squared norm.
+        let norm_term = |count: usize| -> u64 {
+            match similarity {
+                Similarity::Cosine => (count * count) as u64,
+                Similarity::BoundedOverlap => count as u64,
+            }
+        };
+        let kernel = match similarity {
+            Similarity::Cosine => dot_product,
+            Similarity::BoundedOverlap => bounded_overlap,
+        };
+
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        let counts = self.key_counts(key);
+        let query_squared_norm: u64 = counts.values().map(|&count| norm_term(count)).sum();
+        let query_hashmap = self.ngram_ids_from_ngram_counts(counts);
+        let (query_ids, query_counts): (Vec<u32>, Vec<u32>) = query_hashmap
+            .ngram_ids_and_counts()
+            .map(|(ngram_id, count)| {
+                (
+                    u32::try_from(ngram_id).expect("The ngram id must fit in an u32."),
+                    u32::try_from(count).expect("The ngram count must fit in an u32."),
+                )
+            })
+            .unzip();
+        let candidate: RefCell<(Vec<u32>, Vec<u32>)> = RefCell::default();
+        let mut heap = SearchResultsHeap::new(config.maximum_number_of_results());
+
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            |_, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                let mut candidate = candidate.borrow_mut();
+                let (candidate_ids, candidate_counts) = &mut *candidate;
+                candidate_ids.clear();
+                candidate_counts.clear();
+                let mut candidate_squared_norm: u64 = 0;
+                for (ngram_id, count) in ngrams {
+                    candidate_ids
+                        .push(u32::try_from(ngram_id).expect("The ngram id must fit in an u32."));
+                    candidate_counts
+                        .push(u32::try_from(count).expect("The ngram count must fit in an u32."));
+                    candidate_squared_norm += norm_term(count);
+                }
+                let numerator = kernel(&query_ids, &query_counts, candidate_ids, candidate_counts);
+                let similarity = numerator as f64
+                    / math::sqrt(query_squared_norm as f64 * candidate_squared_norm as f64);
+                F::from_f64(if warp.is_one() {
+                    similarity
+                } else {
+                    1.0 - warp.pow((1.0 - similarity).max(0.0))
+                })
+            },
+            |_| true,
+            |key_id, score| {
+                heap.push(key_id, score);
+            },
+        );
+
+        // Sort highest similarity to lowest
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    /// Returns the sorted ids of the multiples of `step` below `limit`, with their counts.
+    fn multiples(step: u32, limit: u32) -> (Vec<u32>, Vec<u32>) {
+        let ids: Vec<u32> = (0..limit).filter(|id| id % step == 0).collect();
+        let counts = ids.iter().map(|id| id % 11 + 1).collect();
+        (ids, counts)
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_and_scalar_dot_products_are_identical() {
+        for (left_step, left_limit) in [(1, 0), (1, 7), (2, 100), (3, 1_000), (5, 64)] {
+            for (right_step, right_limit) in [(1, 9), (1, 300), (4, 500), (7, 2_000), (2, 16)] {
+                let (left_ids, left_counts) = multiples(left_step, left_limit);
+                let (right_ids, right_counts) = multiples(right_step, right_limit);
+
+                let scalar = dot_product_scalar(&left_ids, &left_counts, &right_ids, &right_counts);
+                let simd = dot_product_simd(&left_ids, &left_counts, &right_ids, &right_counts);
+                assert_eq!(scalar, simd);
+                assert_eq!(
+                    simd,
+                    dot_product_simd(&right_ids, &right_counts, &left_ids, &left_counts)
+                );
+                assert_eq!(
+                    scalar,
+                    dot_product(&left_ids, &left_counts, &right_ids, &right_counts)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_and_scalar_intersections_are_identical() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashSet;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..1_000 {
+            // The random gaps and offsets produce both overlapping and disjoint blocks,
+            // so that the galloping is exercised on either list.
+            let random_vector = |rng: &mut StdRng| -> (Vec<u32>, Vec<u32>) {
+                let length = rng.gen_range(0..300);
+                let maximum_gap = rng.gen_range(1..50);
+                let mut id = rng.gen_range(0..1_000);
+                (0..length)
+                    .map(|_| {
+                        id += rng.gen_range(1..=maximum_gap);
+                        (id, rng.gen_range(1..10))
+                    })
+                    .unzip()
+            };
+            let (left_ids, left_counts) = random_vector(&mut rng);
+            let (right_ids, right_counts) = random_vector(&mut rng);
+
+            let scalar = intersection_scalar(&left_ids, &left_counts, &right_ids, &right_counts);
+            let simd = intersection_simd(&left_ids, &left_counts, &right_ids, &right_counts);
+            assert_eq!(scalar, simd);
+            assert_eq!(
+                simd,
+                intersection_simd(&right_ids, &right_counts, &left_ids, &left_counts)
+            );
+
+            let left_set: HashSet<u32> = left_ids.iter().copied().collect();
+            let shared = right_ids.iter().filter(|id| left_set.contains(id)).count();
+            assert_eq!(simd.0, shared);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_dot_product_with_ids_beyond_i32() {
+        let left_ids = (0..20)
+            .map(|id| u32::MAX - 40 + 2 * id)
+            .collect::<Vec<u32>>();
+        let right_ids = (0..20).map(|id| u32::MAX - 40 + id).collect::<Vec<u32>>();
+        let counts = vec![3; 20];
+
+        assert_eq!(
+            dot_product_simd(&left_ids, &counts, &right_ids, &counts),
+            dot_product_scalar(&left_ids, &counts, &right_ids, &counts)
+        );
+        assert_eq!(
+            dot_product_scalar(&left_ids, &counts, &right_ids, &counts),
+            10 * 9
+        );
+    }
+
+    #[test]
+    fn test_cosine_search_scores_are_in_unit_range() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(50);
+        let results: SearchResults<&&str, f32> = corpus.cosine_search("Catfish", config);
+
+        assert_eq!(results[0].key(), &"Catfish");
+        assert!(results
+            .iter()
+            .all(|result| result.score() >= 0.0 && result.score() <= 1.0 + 1e-6));
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].score() >= pair[1].score()));
+
+        // The warp raises the scores of the partial matches without changing their order.
+        let unwarped: SearchResults<&&str, f32> =
+            corpus.cosine_search("Catfish", config.set_warp(1).unwrap());
+        assert_eq!(unwarped[0].key(), results[0].key());
+        for unwarped in unwarped.iter() {
+            if let Some(warped) = results.iter().find(|warped| warped.key() == unwarped.key()) {
+                assert!(warped.score() >= unwarped.score() - 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounded_overlap_matches_scalar_reference() {
+        for (left_step, right_step) in [(1, 1), (2, 3), (3, 7)] {
+            let (left_ids, left_counts) = multiples(left_step, 1_000);
+            let (right_ids, right_counts) = multiples(right_step, 1_000);
+            let expected =
+                merge_accumulate(&left_ids, &left_counts, &right_ids, &right_counts, minimum).1;
+            assert_eq!(
+                bounded_overlap(&left_ids, &left_counts, &right_ids, &right_counts),
+                expected
+            );
+            assert_eq!(
+                bounded_overlap(
+                    &left_ids[..10],
+                    &left_counts[..10],
+                    &right_ids[..10],
+                    &right_counts[..10]
+                ),
+                merge_accumulate(
+                    &left_ids[..10],
+                    &left_counts[..10],
+                    &right_ids[..10],
+                    &right_counts[..10],
+                    minimum
+                )
+                .1
+            );
+        }
+    }
+
+    #[test]
+    fn test_bounded_overlap_limits_repeated_ngrams() {
+        let repeated = "a".repeat(30);
+        let corpus: Corpus<Vec<&str>, TriGram<char>> =
+            Corpus::from(vec![repeated.as_str(), "aaaaaaab"]);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        // With the cosine similarity, the long run of a single character wins.
+        let cosine: SearchResults<&&str, f32> = corpus.cosine_search("aaaaaa", config);
+        assert_eq!(cosine[0].key(), &repeated.as_str());
+
+        // While the bounded overlap prefers the more balanced candidate.
+        let bounded: SearchResults<&&str, f32> =
+            corpus.similarity_search("aaaaaa", config, Similarity::BoundedOverlap);
+        assert_eq!(bounded[0].key(), &"aaaaaaab");
+        assert!(bounded[0].score() > bounded[1].score());
+        assert!(bounded[1].score() < cosine[0].score());
+    }
+}
@@ -0,0 +1,199 @@
+//! Submodule providing helpers to evaluate the quality of search results on labeled data.
+//!
+//! # Implementation details
+//! All of the metrics use binary relevance: a search result is relevant when its key
+//! appears in the provided set of relevant keys. Only the first `k` search results are
+//! considered, which are expected to be sorted by decreasing score as returned by the
+//! search methods of the corpus.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ngrammatic::prelude::*;
+//! use ngrammatic::eval::*;
+//! use std::collections::HashSet;
+//!
+//! let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+//!
+//! let results: SearchResults<&&str, f32> =
+//!     corpus.ngram_search("Cat", NgramSearchConfig::default());
+//!
+//! let relevant: HashSet<&&str> = [&"Cat"].into_iter().collect();
+//!
+//! assert_eq!(precision_at_k(&results, &relevant, 1), 1.0);
+//! assert_eq!(recall_at_k(&results, &relevant, 1), 1.0);
+//! assert_eq!(ndcg_at_k(&results, &relevant, 1), 1.0);
+//! ```
+
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+use crate::math;
+use crate::{Float, SearchResult};
+
+#[inline(always)]
+/// Returns the number of relevant keys among the first `k` search results.
+///
+/// # Arguments
+/// * `results` - The search results, sorted by decreasing score.
+/// * `relevant` - The set of relevant keys.
+/// * `k` - The number of search results to consider.
+fn number_of_relevant_results<K, F, S>(
+    results: &[SearchResult<K, F>],
+    relevant: &HashSet<K, S>,
+    k: usize,
+) -> usize
+where
+    K: Eq + Hash,
+    F: Float,
+    S: BuildHasher,
+{
+    results
+        .iter()
+        .take(k)
+        .filter(|result| relevant.contains(result.key_ref()))
+        .count()
+}
+
+/// Returns the precision of the first `k` search results.
+///
+/// # Arguments
+/// * `results` - The search results, sorted by decreasing score.
+/// * `relevant` - The set of relevant keys.
+/// * `k` - The number of search results to consider.
+///
+/// # Implementation details
+/// The precision is the fraction of the first `k` search results that are relevant.
+/// When fewer than `k` results are provided, the missing results count as non-relevant.
+///
+/// # Panics
+/// * If `k` is zero.
+pub fn precision_at_k<K, F, S>(
+    results: &[SearchResult<K, F>],
+    relevant: &HashSet<K, S>,
+    k: usize,
+) -> f64
+where
+    K: Eq + Hash,
+    F: Float,
+    S: BuildHasher,
+{
+    assert!(k > 0, "The number of results to consider must be positive.");
+    number_of_relevant_results(results, relevant, k) as f64 / k as f64
+}
+
+/// Returns the recall of the first `k` search results.
+///
+/// # Arguments
+/// * `results` - The search results, sorted by decreasing score.
+/// * `relevant` - The set of relevant keys.
+/// * `k` - The number of search results to consider.
+///
+/// # Implementation details
+/// The recall is the fraction of the relevant keys that appear in the first `k`
+/// search results. When the set of relevant keys is empty, the recall is zero.
+///
+/// # Panics
+/// * If `k` is zero.
+pub fn recall_at_k<K, F, S>(
+    results: &[SearchResult<K, F>],
+    relevant: &HashSet<K, S>,
+    k: usize,
+) -> f64
+where
+    K: Eq + Hash,
+    F: Float,
+    S: BuildHasher,
+{
+    assert!(k > 0, "The number of results to consider must be positive.");
+    if relevant.is_empty() {
+        return 0.0;
+    }
+    number_of_relevant_results(results, relevant, k) as f64 / relevant.len() as f64
+}
+
+/// Returns the normalized discounted cumulative gain of the first `k` search results.
+///
+/// # Arguments
+/// * `results` - The search results, sorted by decreasing score.
+/// * `relevant` - The set of relevant keys.
+/// * `k` - The number of search results to consider.
+///
+/// # Implementation details
+/// The relevant result at the zero-based position `i` contributes a gain of
+/// `1 / log2(i + 2)`. The sum of the gains is normalized by the gain of the ideal
+/// ranking, where all of the relevant keys appear first. When the set of relevant
+/// keys is empty, the NDCG is zero.
+///
+/// # Panics
+/// * If `k` is zero.
+pub fn ndcg_at_k<K, F, S>(results: &[SearchResult<K, F>], relevant: &HashSet<K, S>, k: usize) -> f64
+where
+    K: Eq + Hash,
+    F: Float,
+    S: BuildHasher,
+{
+    assert!(k > 0, "The number of results to consider must be positive.");
+    if relevant.is_empty() {
+        return 0.0;
+    }
+
+    let gain = |position: usize| 1.0 / math::log2(position as f64 + 2.0);
+
+    let discounted_cumulative_gain: f64 = results
+        .iter()
+        .take(k)
+        .enumerate()
+        .filter(|(_, result)| relevant.contains(result.key_ref()))
+        .map(|(position, _)| gain(position))
+        .sum();
+
+    let ideal_discounted_cumulative_gain: f64 = (0..k.min(relevant.len())).map(gain).sum();
+
+    discounted_cumulative_gain / ideal_discounted_cumulative_gain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the search results and relevant keys used in the tests.
+    fn results_and_relevant() -> (Vec<SearchResult<&'static str, f32>>, HashSet<&'static str>) {
+        let results = vec![
+            SearchResult::new("a", 0.9),
+            SearchResult::new("b", 0.8),
+            SearchResult::new("c", 0.7),
+            SearchResult::new("d", 0.6),
+        ];
+        let relevant = ["a", "c", "e"].into_iter().collect();
+        (results, relevant)
+    }
+
+    #[test]
+    fn test_precision_and_recall_at_k() {
+        let (results, relevant) = results_and_relevant();
+
+        assert_eq!(precision_at_k(&results, &relevant, 1), 1.0);
+        assert_eq!(precision_at_k(&results, &relevant, 2), 0.5);
+        assert_eq!(precision_at_k(&results, &relevant, 4), 0.5);
+        // Missing results count as non-relevant.
+        assert_eq!(precision_at_k(&results, &relevant, 8), 0.25);
+
+        assert_eq!(recall_at_k(&results, &relevant, 2), 1.0 / 3.0);
+        assert_eq!(recall_at_k(&results, &relevant, 4), 2.0 / 3.0);
+        assert_eq!(recall_at_k(&results, &HashSet::new(), 4), 0.0);
+    }
+
+    #[test]
+    fn test_ndcg_at_k() {
+        let (results, relevant) = results_and_relevant();
+
+        // The only result is relevant, which is the ideal ranking.
+        assert_eq!(ndcg_at_k(&results, &relevant, 1), 1.0);
+        // DCG = 1, IDCG = 1 + 1 / log2(3).
+        assert!((ndcg_at_k(&results, &relevant, 2) - 0.613147).abs() < 1e-6);
+        // DCG = 1 + 1 / log2(4), IDCG = 1 + 1 / log2(3) + 1 / log2(4).
+        assert!((ndcg_at_k(&results, &relevant, 4) - 0.703918).abs() < 1e-6);
+        assert_eq!(ndcg_at_k(&results, &HashSet::new(), 4), 0.0);
+    }
+}
@@ -0,0 +1,269 @@
+//! Submodule providing a reader factory fetching the weights bitstream with HTTP range requests.
+//!
+//! # Implementation details
+//! This factory is meant for deployments where a prebuilt corpus lives in an object
+//! storage and only the byte ranges needed by a search should be downloaded. The
+//! bitstream is split into blocks of a fixed size, and each block is fetched with a
+//! single HTTP range request the first time it is needed. Fetched blocks are cached
+//! for the lifetime of the factory, so that repeated searches touching the same keys
+//! do not issue further requests. Note that reading from the factory is latency-bound:
+//! larger blocks mean fewer round trips at the cost of downloading more unneeded bytes.
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+use dsi_bitstream::prelude::*;
+use fxhash::FxBuildHasher;
+
+use crate::weights::ReaderFactory;
+
+/// The default size in bytes of the blocks fetched by the factory.
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+/// A factory of readers fetching the weights bitstream with HTTP range requests.
+pub struct HttpReaderFactory {
+    /// The URL serving the bitstream.
+    url: String,
+    /// The length in bytes of the bitstream.
+    length: u64,
+    /// The size in bytes of the blocks fetched with each request.
+    block_size: usize,
+    /// The blocks fetched so far, indexed by block id.
+    cache: Mutex<HashMap<u64, Arc<[u8]>, FxBuildHasher>>,
+}
+
+impl HttpReaderFactory {
+    /// Creates a new `HttpReaderFactory` reading from the given URL.
+    ///
+    /// # Arguments
+    /// * `url` - The URL serving the bitstream, which must support range requests.
+    /// * `length` - The length in bytes of the bitstream.
+    pub fn new(url: impl Into<String>, length: u64) -> Self {
+        Self {
+            url: url.into(),
+            length,
+            block_size: DEFAULT_BLOCK_SIZE,
+            cache: Mutex::new(HashMap::with_hasher(FxBuildHasher::default())),
+        }
+    }
+
+    /// Sets the size in bytes of the blocks fetched with each request.
+    ///
+    /// # Arguments
+    /// * `block_size` - The size in bytes of the blocks.
+    ///
+    /// # Raises
+    /// * If the block size is not a positive multiple of four, i.e. of the size of the bitstream words.
+    pub fn set_block_size(mut self, block_size: usize) -> Result<Self, &'static str> {
+        if block_size == 0 || block_size % 4 != 0 {
+            return Err("The block size must be a positive multiple of four.");
+        }
+        self.block_size = block_size;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns the size in bytes of the blocks fetched with each request.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    #[inline(always)]
+    /// Returns the number of blocks fetched and cached so far.
+    pub fn number_of_cached_blocks(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    /// Returns the block with the provided id, fetching it if it is not cached.
+    ///
+    /// # Arguments
+    /// * `block_id` - The id of the block to return.
+    fn block(&self, block_id: u64) -> std::io::Result<Arc<[u8]>> {
+        if let Some(block) = self.cache.lock().unwrap().get(&block_id) {
+            return Ok(block.clone());
+        }
+
+        let start = block_id * self.block_size as u64;
+        let end = (start + self.block_size as u64).min(self.length);
+
+        let response = ureq::get(&self.url)
+            .set("Range", &format!("bytes={}-{}", start, end - 1))
+            .call()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+
+        let mut bytes = Vec::with_capacity((end - start) as usize);
+        response.into_reader().read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 != end - start {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "The server returned a range of unexpected length.",
+            ));
+        }
+
+        let block: Arc<[u8]> = bytes.into();
+        self.cache.lock().unwrap().insert(block_id, block.clone());
+        Ok(block)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A seekable reader over the bitstream served by an `HttpReaderFactory`.
+pub struct HttpRangeReader<'a> {
+    /// The factory fetching and caching the blocks.
+    factory: &'a HttpReaderFactory,
+    /// The current position in bytes.
+    position: u64,
+}
+
+impl<'a> Read for HttpRangeReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.factory.length || buf.is_empty() {
+            return Ok(0);
+        }
+        let block_size = self.factory.block_size as u64;
+        let block = self.factory.block(self.position / block_size)?;
+        let offset = (self.position % block_size) as usize;
+        let number_of_bytes = buf.len().min(block.len() - offset);
+        buf[..number_of_bytes].copy_from_slice(&block[offset..offset + number_of_bytes]);
+        self.position += number_of_bytes as u64;
+        Ok(number_of_bytes)
+    }
+}
+
+impl<'a> Seek for HttpRangeReader<'a> {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        let position = match position {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.factory.length.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position.",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl ReaderFactory for HttpReaderFactory {
+    type Reader<'a> = BufBitReader<LittleEndian, WordAdapter<u32, HttpRangeReader<'a>>>;
+
+    fn get_reader(&self, offset: usize) -> Self::Reader<'_> {
+        let mut res =
+            BufBitReader::<LittleEndian, _>::new(WordAdapter::<u32, _>::new(HttpRangeReader {
+                factory: self,
+                position: 0,
+            }));
+        res.set_bit_pos(offset as u64).unwrap();
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use webgraph::prelude::*;
+
+    use super::*;
+    use crate::weights::{Weights, WeightsBuilder};
+
+    /// Serves the provided bytes on a local port, answering range requests.
+    ///
+    /// Returns the URL of the server and the counter of the requests served.
+    fn serve(data: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/weights", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(bytes) = line.to_lowercase().strip_prefix("range: bytes=") {
+                        let (start, end) = bytes.split_once('-').unwrap();
+                        range = Some((
+                            start.parse::<usize>().unwrap(),
+                            end.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+                let (start, end) = range.unwrap();
+                let body = &data[start..=end];
+                counter.fetch_add(1, Ordering::SeqCst);
+                write!(
+                    stream,
+                    concat!(
+                        "HTTP/1.1 206 Partial Content\r\n",
+                        "Content-Length: {}\r\n",
+                        "Content-Range: bytes {}-{}/{}\r\n",
+                        "Connection: close\r\n\r\n"
+                    ),
+                    body.len(),
+                    start,
+                    end,
+                    data.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    #[test]
+    fn test_http_reader_factory() {
+        let weights = (0..200)
+            .map(|node| {
+                (0..node % 17)
+                    .map(|i| (i * node) % 5)
+                    .collect::<Vec<usize>>()
+            })
+            .collect::<Vec<Vec<usize>>>();
+
+        let mut builder = WeightsBuilder::new();
+        for node_weights in &weights {
+            builder.push(node_weights.iter().copied()).unwrap();
+        }
+        let local = builder.build();
+        let (num_nodes, num_weights) = (local.num_nodes(), local.num_weights());
+        let (reader_factory, offsets) = local.clone().into_inner();
+        let data = reader_factory.into_inner();
+        let length = data.len() as u64;
+
+        let (url, requests) = serve(data);
+        let factory = HttpReaderFactory::new(url, length)
+            .set_block_size(64)
+            .unwrap();
+        let remote = Weights::new(factory, offsets, num_nodes, num_weights);
+
+        for (node_id, node_weights) in weights.iter().enumerate() {
+            assert_eq!(&remote.labels(node_id).collect::<Vec<_>>(), node_weights);
+            assert_eq!(
+                local.labels(node_id).collect::<Vec<_>>(),
+                remote.labels(node_id).collect::<Vec<_>>()
+            );
+        }
+
+        // Each block is fetched at most once, as later reads hit the cache.
+        let (factory, _) = remote.into_inner();
+        assert_eq!(
+            requests.load(Ordering::SeqCst),
+            factory.number_of_cached_blocks()
+        );
+        assert!(factory.number_of_cached_blocks() as u64 <= length.div_ceil(64));
+    }
+}
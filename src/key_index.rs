@@ -0,0 +1,174 @@
+//! Submodule providing the lookup of the id of a key from its value.
+//!
+//! # Implementation details
+//! The keys are not stored sorted, so looking up a key requires either scanning all of the
+//! keys, or building a key index mapping each key to its id. In both cases the keys are
+//! compared after the normalizations applied by the key type, such as the lowercasing, so
+//! that with a `Lowercase<str>` key type the lookup of "CAT" finds the key "cat".
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The ids of the keys of a corpus by their normalized value, used by the key lookup.
+///
+/// # Implementation details
+/// The index stores the grams of each key, as returned by the key type, including the
+/// padding. The keys with the same grams are indistinguishable, and are mapped to the
+/// lowest of their ids. Only the keys that were not removed when the index was built are
+/// stored, so that after inserting, removing or purging keys `key_id_with_index` only
+/// agrees with `key_id` once the index is built again.
+pub struct KeyIndex<G> {
+    /// The id of the keys by their grams.
+    key_ids: HashMap<Vec<G>, usize>,
+}
+
+impl<G> KeyIndex<G> {
+    #[inline(always)]
+    /// Returns the number of distinct normalized keys in the index.
+    pub fn len(&self) -> usize {
+        self.key_ids.len()
+    }
+
+    #[inline(always)]
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.key_ids.is_empty()
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns whether the key with the provided id has the grams of the provided key.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key.
+    /// * `key` - The key to compare with.
+    fn key_id_matches(&self, key_id: usize, key: &K) -> bool {
+        let stored_key = self.key_from_id(key_id);
+        AsRef::<K>::as_ref(&stored_key).grams().eq(key.grams())
+    }
+
+    /// Returns the id of the provided key, if it is in the corpus.
+    ///
+    /// # Arguments
+    /// * `key` - The key to look up.
+    ///
+    /// # Implementation details
+    /// The keys are scanned in the order of their ids, so the lookup takes time linear in
+    /// the number of keys. When looking up many keys, the `key_id_with_index` method
+    /// avoids the scan. The keys are compared after the normalizations of the key type,
+    /// and when several keys match the lowest id is returned. The removed keys are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>, Lowercase<str>> =
+    ///     Corpus::from(["cat", "dog", "cow"]);
+    ///
+    /// assert_eq!(corpus.key_id("dog"), Some(1));
+    /// assert_eq!(corpus.key_id("CAT"), Some(0));
+    /// assert_eq!(corpus.key_id("bird"), None);
+    /// ```
+    pub fn key_id<KR: AsRef<K>>(&self, key: KR) -> Option<usize> {
+        let key: &K = key.as_ref();
+        self.iter_key_ids()
+            .find(|&key_id| self.key_id_matches(key_id, key))
+    }
+
+    /// Returns the key index of the keys of the corpus.
+    ///
+    /// # Implementation details
+    /// The removed keys are not indexed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>, Lowercase<str>> =
+    ///     Corpus::from(["Cat", "dog", "cat"]);
+    /// let index = corpus.build_key_index();
+    ///
+    /// assert_eq!(index.len(), 2);
+    /// ```
+    pub fn build_key_index(&self) -> KeyIndex<NG::G> {
+        let mut key_ids = HashMap::with_capacity(self.number_of_keys());
+        for key_id in self.iter_key_ids() {
+            let key = self.key_from_id(key_id);
+            let grams: Vec<NG::G> = AsRef::<K>::as_ref(&key).grams().collect();
+            key_ids.entry(grams).or_insert(key_id);
+        }
+        KeyIndex { key_ids }
+    }
+
+    /// Returns the id of the provided key, if it is in the corpus, using the provided index.
+    ///
+    /// # Arguments
+    /// * `index` - The key index built from this corpus with `build_key_index`.
+    /// * `key` - The key to look up.
+    ///
+    /// # Implementation details
+    /// The key is normalized as the keys of the corpus, and then looked up in the index,
+    /// so the results are the same ones of the `key_id` method.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>, Lowercase<str>> =
+    ///     Corpus::from(["cat", "dog", "cow"]);
+    /// let index = corpus.build_key_index();
+    ///
+    /// assert_eq!(corpus.key_id_with_index(&index, "Cow"), Some(2));
+    /// assert_eq!(corpus.key_id_with_index(&index, "bird"), None);
+    /// ```
+    pub fn key_id_with_index<KR: AsRef<K>>(
+        &self,
+        index: &KeyIndex<NG::G>,
+        key: KR,
+    ) -> Option<usize> {
+        let grams: Vec<NG::G> = key.as_ref().grams().collect();
+        index.key_ids.get(&grams).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_id_with_and_without_index() {
+        let mut corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> =
+            Corpus::from(&ANIMALS);
+        corpus.remove_key(0);
+        let index = corpus.build_key_index();
+
+        for (key_id, animal) in ANIMALS.iter().enumerate().skip(1) {
+            let uppercase = animal.to_uppercase();
+            let expected = corpus.key_id(*animal);
+            assert!(matches!(expected, Some(found) if found <= key_id));
+            assert_eq!(corpus.key_id(uppercase.as_str()), expected);
+            assert_eq!(corpus.key_id_with_index(&index, *animal), expected);
+            assert_eq!(
+                corpus.key_id_with_index(&index, uppercase.as_str()),
+                expected
+            );
+        }
+
+        assert_eq!(corpus.key_id(ANIMALS[0]), None);
+        assert_eq!(corpus.key_id_with_index(&index, ANIMALS[0]), None);
+        assert_eq!(corpus.key_id("Unicorn"), None);
+        assert_eq!(corpus.key_id_with_index(&index, "Unicorn"), None);
+    }
+}
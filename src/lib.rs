@@ -1,12 +1,18 @@
 #![doc = include_str!("../README.md")]
 #![deny(missing_docs)]
 
+#[macro_use]
+mod logging;
 pub mod traits;
 pub use traits::*;
+pub mod search_options;
 pub mod search_result;
 pub use search_result::*;
 pub mod corpus;
 pub use corpus::*;
+pub use corpus_config::CorpusConfig;
+pub use reusable_corpus_builder::ReusableCorpusBuilder;
+mod math;
 mod ngram_similarity;
 pub use ngram_similarity::*;
 pub mod adaptative_vector;
@@ -14,34 +20,87 @@ pub mod search;
 pub use adaptative_vector::*;
 pub mod animals;
 pub mod bit_field_bipartite_graph;
+pub mod corpus_builder;
+pub mod corpus_config;
 pub mod corpus_from;
+pub mod cross_search;
+pub mod diff;
+pub mod dot_product;
+pub mod eval;
+pub mod key_index;
 pub mod lender_bit_field_bipartite_graph;
+pub mod merge;
+pub mod metric;
+pub mod minhash;
 pub mod ngram_search;
+pub mod overflow_graph;
+pub mod padding_mode;
+pub mod prefix;
+pub mod prune;
+pub mod query_cache;
 pub mod report;
+pub mod rerank;
+pub mod reusable_corpus_builder;
+pub mod string_pool;
 pub mod tfidf;
+pub mod tombstones;
 pub mod weights;
 
 #[cfg(feature = "rayon")]
 pub mod corpus_par_from;
 
-// #[cfg(feature = "webgraph")]
+#[cfg(feature = "mmap")]
 pub mod bi_webgraph;
 
+#[cfg(feature = "mmap")]
+pub mod corpus_file;
+
+#[cfg(feature = "mmap")]
+pub mod mmap_keys;
+
+#[cfg(feature = "mmap")]
+pub mod mmap_reader_factory;
+
 #[cfg(feature = "rayon")]
 pub mod par_search;
 
+#[cfg(feature = "http")]
+pub mod http_reader_factory;
+
+#[cfg(feature = "phonetic")]
+pub mod phonetic;
+
 /// Re-export of the most commonly used traits and structs.
 pub mod prelude {
     pub use crate::adaptative_vector::*;
     pub use crate::corpus::*;
+    pub use crate::corpus_builder::*;
+    pub use crate::corpus_config::*;
+    #[cfg(feature = "rayon")]
+    pub use crate::corpus_par_from::*;
+    pub use crate::key_index::*;
     pub use crate::ngram_similarity::*;
     pub use crate::search_result::*;
     pub use crate::traits::*;
-    // #[cfg(feature = "webgraph")]
+
     pub use crate::animals::*;
+    #[cfg(feature = "mmap")]
     pub use crate::bi_webgraph::*;
+    pub use crate::metric::*;
+    pub use crate::minhash::*;
+    #[cfg(feature = "mmap")]
+    pub use crate::mmap_keys::*;
     pub use crate::ngram_search::*;
+    pub use crate::padding_mode::*;
+    #[cfg(feature = "phonetic")]
+    pub use crate::phonetic::*;
+    pub use crate::prefix::*;
+    pub use crate::query_cache::*;
+    pub use crate::rerank::*;
+    pub use crate::reusable_corpus_builder::*;
     pub use crate::search::*;
+    pub use crate::search_options::*;
+    pub use crate::string_pool::*;
     pub use crate::tfidf::*;
     pub use sux::dict::rear_coded_list::{RearCodedList, RearCodedListBuilder};
 
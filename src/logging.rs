@@ -0,0 +1,26 @@
+//! Submodule providing the logging macros used while building the corpus.
+//!
+//! # Implementation details
+//! The macros forward their messages to the `log` crate when the `log` feature is
+//! enabled, and otherwise only type-check their arguments, so that the crate can be
+//! built without the `log` dependency and without warnings about unused values.
+
+/// Logs a debug message, when the `log` feature is enabled.
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Logs a warning, when the `log` feature is enabled.
+macro_rules! warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
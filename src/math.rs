@@ -0,0 +1,84 @@
+//! Submodule providing the floating point functions used by the scores.
+//!
+//! # Implementation details
+//! The functions are the ones of the standard library, unless the `libm` feature is
+//! enabled, in which case they are the ones of the `libm` crate. The `libm` functions do
+//! not depend on the platform, so that the scores are the same ones on all of the
+//! targets, at the cost of being slower on some of them.
+
+#[cfg(feature = "libm")]
+pub(crate) use libm::{fabs, fabsf, log, log2, pow, powf, sqrt, sqrtf};
+
+#[cfg(feature = "libm")]
+#[inline(always)]
+/// Returns the value raised to the provided integer power.
+///
+/// # Arguments
+/// * `value` - The base.
+/// * `n` - The integer exponent.
+pub(crate) fn powi(value: f64, n: i32) -> f64 {
+    pow(value, f64::from(n))
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the absolute value of the provided value.
+pub(crate) fn fabsf(value: f32) -> f32 {
+    value.abs()
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the absolute value of the provided value.
+pub(crate) fn fabs(value: f64) -> f64 {
+    value.abs()
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the square root of the provided value.
+pub(crate) fn sqrtf(value: f32) -> f32 {
+    value.sqrt()
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the square root of the provided value.
+pub(crate) fn sqrt(value: f64) -> f64 {
+    value.sqrt()
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the value raised to the provided power.
+pub(crate) fn powf(value: f32, n: f32) -> f32 {
+    value.powf(n)
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the value raised to the provided power.
+pub(crate) fn pow(value: f64, n: f64) -> f64 {
+    value.powf(n)
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the value raised to the provided integer power.
+pub(crate) fn powi(value: f64, n: i32) -> f64 {
+    value.powi(n)
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the natural logarithm of the provided value.
+pub(crate) fn log(value: f64) -> f64 {
+    value.ln()
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline(always)]
+/// Returns the base two logarithm of the provided value.
+pub(crate) fn log2(value: f64) -> f64 {
+    value.log2()
+}
@@ -0,0 +1,244 @@
+//! Submodule implementing the merge of two corpora, such as the corpora of two shards.
+//!
+//! # Implementation details
+//! The ngram ids of a corpus depend on its whole vocabulary, so the ids of the two corpora
+//! cannot be concatenated. The ngrams and the cooccurrences of each key are instead read
+//! back from the graphs of the two corpora, and digested as if the keys were parsed again,
+//! without normalizing them a second time. The merged vocabulary is then sorted, and the
+//! graph is rebuilt remapping the ngrams of each key to their merged ids.
+use std::hash::BuildHasher;
+
+use sux::bits::BitVec;
+
+use crate::{
+    bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, corpus_from::KeysParser, traits::*,
+    weights::WeightsBuilder, AdaptativeVector, Corpus, ReusableCorpusBuilder,
+};
+
+impl<S, NG, K> Corpus<Vec<S>, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    Vec<S>: Keys<NG>,
+    for<'a> <Vec<S> as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Digests the ngrams of the keys of the provided corpora, in the order of the corpora.
+    ///
+    /// # Arguments
+    /// * `corpora` - The corpora whose keys are digested.
+    /// * `builder` - The reusable buffers where the ngrams and key to ngrams are stored.
+    ///
+    /// # Implementation details
+    /// The keys in the graph are digested from their ngrams and cooccurrences, which are
+    /// sorted by ngram as their ids are, while the overflow keys are parsed again.
+    ///
+    /// # Panics
+    /// * If the two corpora pad their keys differently.
+    pub(crate) fn parse_merged_keys<H: BuildHasher + Default>(
+        corpora: [&Self; 2],
+        builder: &mut ReusableCorpusBuilder<NG, H>,
+    ) -> (WeightsBuilder, f64, AdaptativeVector) {
+        assert_eq!(
+            corpora[0].padding, corpora[1].padding,
+            "The merged corpora must pad their keys in the same way."
+        );
+        let number_of_keys = corpora
+            .iter()
+            .map(|corpus| corpus.number_of_key_ids())
+            .sum();
+        let mut parser = KeysParser::new(number_of_keys, builder);
+
+        debug!("Digesting the ngrams of the keys of the merged corpora.");
+
+        for corpus in corpora {
+            let number_of_graph_keys =
+                corpus.number_of_key_ids() - corpus.number_of_overflow_keys();
+            for key_id in 0..corpus.number_of_key_ids() {
+                if key_id < number_of_graph_keys {
+                    builder.ngram_counts.clear();
+                    builder
+                        .ngram_counts
+                        .extend(corpus.ngrams_and_cooccurrences_from_key(key_id));
+                    parser.digest_ngram_counts(builder);
+                } else {
+                    let key = corpus.key_from_id(key_id);
+                    parser.digest(AsRef::<K>::as_ref(&key), None, corpus.padding, builder);
+                }
+            }
+        }
+
+        parser.finish(builder)
+    }
+
+    /// Returns the tombstones of the merged corpora, and the number of removed keys.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus whose keys follow the keys of this corpus.
+    pub(crate) fn merged_tombstones(&self, other: &Self) -> (BitVec, usize) {
+        let number_of_removed_keys = self.number_of_removed_keys + other.number_of_removed_keys;
+        if number_of_removed_keys == 0 {
+            return (BitVec::new(0), 0);
+        }
+        let offset = self.number_of_key_ids();
+        let mut tombstones = BitVec::new(offset + other.number_of_key_ids());
+        for key_id in (0..offset).filter(|&key_id| self.is_removed(key_id)) {
+            tombstones.set(key_id, true);
+        }
+        for key_id in (0..other.number_of_key_ids()).filter(|&key_id| other.is_removed(key_id)) {
+            tombstones.set(offset + key_id, true);
+        }
+        (tombstones, number_of_removed_keys)
+    }
+
+    /// Returns the corpus with the keys of this corpus followed by the keys of the other.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to merge, with the same ngram and key types.
+    ///
+    /// # Implementation details
+    /// The keys of this corpus keep their ids, while the key with id `key_id` in the other
+    /// corpus receives the id `key_id` plus the number of key ids of this corpus. The keys
+    /// are not parsed again, as their ngrams are read from the graphs, so any truncation
+    /// applied when building the two corpora is preserved. The overflow keys are folded into
+    /// the graph, and the removed keys stay removed. The merged corpus is the same one built
+    /// by `from` from the concatenated keys, at the cost of building the graph, but without
+    /// the cost of parsing the keys.
+    ///
+    /// # Panics
+    /// * If the two corpora pad their keys differently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let left: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// let right: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cow", "catfish"]);
+    ///
+    /// let merged = left.merge(right);
+    /// let expected: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "dog", "cow", "catfish"]);
+    ///
+    /// assert_eq!(merged.number_of_keys(), 4);
+    /// assert_eq!(merged.key_from_id(2), &"cow");
+    /// assert!(merged.ngrams().eq(expected.ngrams()));
+    /// assert!(merged.ngram_ids_from_key(3).eq(expected.ngram_ids_from_key(3)));
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_merged_keys([&self, &other], builder);
+        let (tombstones, number_of_removed_keys) = self.merged_tombstones(&other);
+        let padding = self.padding;
+
+        let mut keys = self.keys;
+        keys.extend(other.keys);
+
+        let mut corpus = Self::from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        );
+        corpus.tombstones = tombstones;
+        corpus.number_of_removed_keys = number_of_removed_keys;
+        corpus.padding = padding;
+        corpus
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<S, NG, K> Corpus<Vec<S>, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram + Send + Sync,
+    Vec<S>: Keys<NG>,
+    for<'a> <Vec<S> as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Returns the corpus with the keys of this corpus followed by the keys of the other,
+    /// building the merged graph in parallel.
+    ///
+    /// # Arguments
+    /// * `other` - The corpus to merge, with the same ngram and key types.
+    ///
+    /// # Implementation details
+    /// This is the concurrent version of the `merge` method, please refer to its
+    /// documentation for the details. The resulting corpus is the same one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let left: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// let right: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cow", "catfish"]);
+    ///
+    /// let merged = left.par_merge(right);
+    ///
+    /// assert_eq!(merged.number_of_keys(), 4);
+    /// assert_eq!(merged.key_from_id(3), &"catfish");
+    /// ```
+    pub fn par_merge(self, other: Self) -> Self {
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let (cooccurrences_builder, average_key_length, key_offsets) =
+            Self::parse_merged_keys([&self, &other], builder);
+        let (tombstones, number_of_removed_keys) = self.merged_tombstones(&other);
+        let padding = self.padding;
+
+        let mut keys = self.keys;
+        keys.extend(other.keys);
+
+        let mut corpus = Self::par_from_parsed_keys(
+            keys,
+            cooccurrences_builder,
+            average_key_length,
+            key_offsets,
+            builder,
+        );
+        corpus.tombstones = tombstones;
+        corpus.number_of_removed_keys = number_of_removed_keys;
+        corpus.padding = padding;
+        corpus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    /// Asserts that the two corpora have the same keys, ngrams and graph.
+    fn assert_same_corpus(
+        corpus: &Corpus<Vec<&str>, TriGram<char>>,
+        expected: &Corpus<Vec<&str>, TriGram<char>>,
+    ) {
+        assert_eq!(corpus.number_of_keys(), expected.number_of_keys());
+        assert_eq!(corpus.average_key_length(), expected.average_key_length());
+        assert!(corpus.ngrams().eq(expected.ngrams()));
+        assert!(corpus.cooccurrences().eq(expected.cooccurrences()));
+        for key_id in 0..expected.number_of_keys() {
+            assert_eq!(corpus.key_from_id(key_id), expected.key_from_id(key_id));
+            assert!(corpus
+                .ngram_ids_from_key(key_id)
+                .eq(expected.ngram_ids_from_key(key_id)));
+        }
+        for ngram_id in 0..expected.number_of_ngrams() {
+            assert!(corpus
+                .key_ids_from_ngram_id(ngram_id)
+                .eq(expected.key_ids_from_ngram_id(ngram_id)));
+        }
+    }
+
+    #[test]
+    fn test_merging_two_halves_equals_building_from_the_whole() {
+        let (left, right) = ANIMALS.split_at(ANIMALS.len() / 2);
+        let expected: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+
+        let left: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(left.to_vec());
+        let right: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(right.to_vec());
+
+        assert_same_corpus(&left.clone().merge(right.clone()), &expected);
+        #[cfg(feature = "rayon")]
+        assert_same_corpus(&left.par_merge(right), &expected);
+    }
+}
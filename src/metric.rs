@@ -0,0 +1,879 @@
+//! Submodule providing configurable similarity metrics for the corpus search.
+//!
+//! # Implementation details
+//! A metric scores a candidate key given the ngrams it shares with the query, and the
+//! total number of ngrams of the query and of the key. Since the corpus stores only the
+//! ngrams and their co-occurrences, the metric can be swapped at search time without
+//! rebuilding the corpus, whether it was built with `From` or with `par_from`. Note that
+//! the default `ngram_search` method scores the keys with the `WarpMetric` with a warp
+//! factor of two.
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use fxhash::FxBuildHasher;
+
+use crate::math;
+use crate::prelude::*;
+use crate::SearchResultsHeap;
+
+/// Trait defining a similarity metric between a query and a key.
+pub trait Metric<F: Float> {
+    /// Returns the similarity score between a query and a key.
+    ///
+    /// # Arguments
+    /// * `shared` - The counts in the query and in the key of each shared ngram.
+    /// * `query_len` - The total number of ngrams in the query, including the unknown ones.
+    /// * `key_len` - The total number of ngrams in the key.
+    ///
+    /// # Implementation details
+    /// The implementations must return zero, and not NaN, when no ngram is shared.
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F;
+}
+
+#[inline(always)]
+/// Returns the number of shared ngrams, counting each ngram as many times as it appears in both.
+///
+/// # Arguments
+/// * `shared` - The counts in the query and in the key of each shared ngram.
+fn number_of_shared_ngrams(shared: &[(usize, usize)]) -> usize {
+    shared
+        .iter()
+        .map(|(query_count, key_count)| query_count.min(key_count))
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An ngram visited by `merge_ngram_counts`.
+pub(crate) enum MergedNgram {
+    /// An ngram only found in the query, with its id and its count in the query.
+    Query(usize, usize),
+    /// An ngram only found in the key, with its id and its cooccurrence in the key.
+    Key(usize, usize),
+    /// An ngram found in both, with its id, its count in the query and its cooccurrence
+    /// in the key.
+    Shared(usize, usize, usize),
+}
+
+/// Visits the union of the ngrams of a query and of a key, sorted by ngram id.
+///
+/// # Arguments
+/// * `query` - The sorted ngram ids and counts of the query.
+/// * `key` - The sorted ngram ids and cooccurrences of the key.
+/// * `visit` - The function called on each ngram of the union.
+///
+/// # Implementation details
+/// This is the sorted merge shared by the scoring functions that need more than the
+/// number of shared ngrams, which accumulate their sums in the closure, so that no
+/// allocation is made per scored key.
+pub(crate) fn merge_ngram_counts<Q, I>(mut query: Q, mut key: I, mut visit: impl FnMut(MergedNgram))
+where
+    Q: Iterator<Item = (usize, usize)>,
+    I: Iterator<Item = (usize, usize)>,
+{
+    let mut query_next = query.next();
+    let mut key_next = key.next();
+
+    loop {
+        match (query_next, key_next) {
+            (Some((query_id, query_count)), Some((key_id, cooccurrence))) => {
+                match query_id.cmp(&key_id) {
+                    Ordering::Less => {
+                        visit(MergedNgram::Query(query_id, query_count));
+                        query_next = query.next();
+                    }
+                    Ordering::Greater => {
+                        visit(MergedNgram::Key(key_id, cooccurrence));
+                        key_next = key.next();
+                    }
+                    Ordering::Equal => {
+                        visit(MergedNgram::Shared(query_id, query_count, cooccurrence));
+                        query_next = query.next();
+                        key_next = key.next();
+                    }
+                }
+            }
+            (Some((query_id, query_count)), None) => {
+                visit(MergedNgram::Query(query_id, query_count));
+                query_next = query.next();
+            }
+            (None, Some((key_id, cooccurrence))) => {
+                visit(MergedNgram::Key(key_id, cooccurrence));
+                key_next = key.next();
+            }
+            (None, None) => break,
+        }
+    }
+}
+
+/// Returns the score of a candidate key with the provided metric.
+///
+/// # Arguments
+/// * `metric` - The metric to score the candidate key with.
+/// * `query` - The ngram ids and counts of the query.
+/// * `ngrams` - The sorted ngram ids and cooccurrences of the candidate key.
+/// * `shared` - The buffer receiving the counts of the shared ngrams, which is cleared
+///   first, so that it can be reused across the candidate keys.
+pub(crate) fn metric_similarity<F, M, I>(
+    metric: &M,
+    query: &QueryHashmap,
+    ngrams: I,
+    shared: &mut Vec<(usize, usize)>,
+) -> F
+where
+    F: Float,
+    M: Metric<F>,
+    I: Iterator<Item = (usize, usize)>,
+{
+    shared.clear();
+    let mut key_len = 0;
+    merge_ngram_counts(
+        query.ngram_ids_and_counts(),
+        ngrams,
+        |merged| match merged {
+            MergedNgram::Query(..) => {}
+            MergedNgram::Key(_, cooccurrence) => key_len += cooccurrence,
+            MergedNgram::Shared(_, query_count, cooccurrence) => {
+                key_len += cooccurrence;
+                shared.push((query_count, cooccurrence));
+            }
+        },
+    );
+
+    metric.score(shared, query.total_count(), key_len)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// The warped ngram similarity, as used by the `ngram_search` method.
+///
+/// # Implementation details
+/// With `s` the number of shared ngrams and `a` the number of ngrams in the union of
+/// the query and the key, the score is `(a^w - (a - s)^w) / a^w`, which is equal to
+/// `s / a` for a warp factor `w` of one. Warp factors greater than one increase the
+/// similarity of shorter pairs of strings.
+pub struct WarpMetric<W> {
+    /// The warp factor.
+    warp: Warp<W>,
+}
+
+impl<W> WarpMetric<W>
+where
+    W: Copy + TryInto<Warp<W>, Error = &'static str>,
+{
+    /// Creates a new warp metric with the provided warp factor.
+    ///
+    /// # Arguments
+    /// * `warp` - The warp factor, in the range 1 to 3.
+    ///
+    /// # Raises
+    /// * If the warp factor is not in the range 1 to 3.
+    pub fn new(warp: W) -> Result<Self, &'static str> {
+        Ok(Self {
+            warp: warp.try_into()?,
+        })
+    }
+}
+
+impl<W: Copy> WarpMetric<W> {
+    #[inline(always)]
+    /// Returns the warp factor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let metric = WarpMetric::new(3.0).unwrap();
+    ///
+    /// assert_eq!(metric.warp(), Warp::try_from(3.0).unwrap());
+    /// assert_eq!(WarpMetric::default().warp(), Warp::try_from(2).unwrap());
+    /// ```
+    pub fn warp(&self) -> Warp<W> {
+        self.warp
+    }
+}
+
+impl<W> From<Warp<W>> for WarpMetric<W> {
+    #[inline(always)]
+    /// Returns the warp metric with the provided warp factor.
+    fn from(warp: Warp<W>) -> Self {
+        Self { warp }
+    }
+}
+
+impl Default for WarpMetric<i32> {
+    #[inline(always)]
+    /// Returns the warp metric with a warp factor of two.
+    fn default() -> Self {
+        Self {
+            warp: Warp::try_from(2).unwrap(),
+        }
+    }
+}
+
+impl<W, F: Float> Metric<F> for WarpMetric<W>
+where
+    Warp<W>: NgramSimilarity + One + Copy,
+{
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F {
+        let sharegrams = number_of_shared_ngrams(shared);
+        let allgrams = query_len + key_len - sharegrams;
+        if sharegrams == 0 || allgrams == 0 {
+            return F::ZERO;
+        }
+        F::from_f64(if self.warp.is_one() {
+            sharegrams as f64 / allgrams as f64
+        } else {
+            let exponentiated_allgrams = self.warp.pow(allgrams as f64);
+            (exponentiated_allgrams - self.warp.pow((allgrams - sharegrams) as f64))
+                / exponentiated_allgrams
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The Jaccard similarity between the multisets of ngrams of the query and of the key.
+///
+/// # Implementation details
+/// The score is the number of shared ngrams divided by the number of ngrams in the
+/// union of the query and the key, and it is equal to the `WarpMetric` with a warp
+/// factor of one.
+pub struct JaccardMetric;
+
+impl<F: Float> Metric<F> for JaccardMetric {
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F {
+        let sharegrams = number_of_shared_ngrams(shared);
+        let allgrams = query_len + key_len - sharegrams;
+        if sharegrams == 0 || allgrams == 0 {
+            return F::ZERO;
+        }
+        F::from_f64(sharegrams as f64 / allgrams as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The cosine similarity between the multisets of ngrams of the query and of the key.
+///
+/// # Implementation details
+/// The score is the number of shared ngrams divided by the geometric mean of the number
+/// of ngrams in the query and in the key, also known as the Otsuka-Ochiai coefficient.
+/// Differently from the Jaccard similarity, it penalizes less a key of which the query
+/// is a substring.
+pub struct CosineMetric;
+
+impl<F: Float> Metric<F> for CosineMetric {
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F {
+        let sharegrams = number_of_shared_ngrams(shared);
+        if sharegrams == 0 {
+            return F::ZERO;
+        }
+        F::from_f64(sharegrams as f64 / math::sqrt((query_len as f64) * (key_len as f64)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The Dice coefficient between the multisets of ngrams of the query and of the key.
+///
+/// # Implementation details
+/// The score is twice the number of shared ngrams divided by the sum of the number of
+/// ngrams in the query and in the key, also known as the Sørensen-Dice coefficient. It is
+/// never smaller than the Jaccard similarity, as it counts the shared ngrams only once in
+/// the denominator.
+///
+/// # Examples
+/// The trigrams of "night" and "nacht" share the two padding trigrams at the end and the
+/// one at the start, out of the seven trigrams of each of the two words.
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<[&str; 1], TriGram<char>> = Corpus::from(["nacht"]);
+/// let config = NgramSearchConfig::default()
+///     .set_minimum_similarity_score(0.0)
+///     .unwrap();
+///
+/// let jaccard: SearchResults<&&str, f64> =
+///     corpus.ngram_search_with_metric("night", config, JaccardMetric);
+/// let dice: SearchResults<&&str, f64> =
+///     corpus.ngram_search_with_metric("night", config, DiceMetric);
+/// let overlap: SearchResults<&&str, f64> =
+///     corpus.ngram_search_with_metric("night", config, OverlapCoefficientMetric);
+///
+/// assert_eq!(jaccard[0].score(), 3.0 / 11.0);
+/// assert_eq!(dice[0].score(), 6.0 / 14.0);
+/// assert_eq!(overlap[0].score(), 3.0 / 7.0);
+/// ```
+pub struct DiceMetric;
+
+impl<F: Float> Metric<F> for DiceMetric {
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F {
+        let sharegrams = number_of_shared_ngrams(shared);
+        if sharegrams == 0 {
+            return F::ZERO;
+        }
+        F::from_f64((2 * sharegrams) as f64 / (query_len + key_len) as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The overlap coefficient between the multisets of ngrams of the query and of the key.
+///
+/// # Implementation details
+/// The score is the number of shared ngrams divided by the number of ngrams of the
+/// shorter between the query and the key, also known as the Szymkiewicz-Simpson
+/// coefficient. It is one whenever the ngrams of the query are all contained in the key,
+/// or vice versa, which makes it suited to match abbreviated records. Differently from
+/// the `OverlapMetric`, which is the raw number of shared ngrams, it is normalized.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+///
+/// let score: f32 = OverlapCoefficientMetric.score(&[(1, 1), (1, 1)], 2, 8);
+/// assert_eq!(score, 1.0);
+///
+/// let score: f32 = corpus.query_self_score("catfish", OverlapCoefficientMetric);
+/// assert_eq!(score, 1.0);
+/// ```
+pub struct OverlapCoefficientMetric;
+
+impl<F: Float> Metric<F> for OverlapCoefficientMetric {
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], query_len: usize, key_len: usize) -> F {
+        let sharegrams = number_of_shared_ngrams(shared);
+        let shortest = query_len.min(key_len);
+        if sharegrams == 0 || shortest == 0 {
+            return F::ZERO;
+        }
+        F::from_f64(sharegrams as f64 / shortest as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The raw overlap between the ngrams of the query and of the key.
+///
+/// # Implementation details
+/// The score is the number of distinct ngrams shared by the query and the key, and it
+/// is not normalized, so it should be compared with the self-score of the query returned
+/// by the `query_self_score` method.
+pub struct OverlapMetric;
+
+impl<F: Float> Metric<F> for OverlapMetric {
+    #[inline(always)]
+    fn score(&self, shared: &[(usize, usize)], _query_len: usize, _key_len: usize) -> F {
+        F::from_f64(shared.len() as f64)
+    }
+}
+
+/// Returns the similarity between the ngrams of two strings, scored with the provided metric.
+///
+/// # Arguments
+/// * `a` - The first string.
+/// * `b` - The second string.
+/// * `metric` - The metric to score the strings with.
+///
+/// # Implementation details
+/// The ngrams of both strings are counted with the `Key::counts` method of `str`, so
+/// they are normalized and padded as the keys of a corpus built with the same ngram type,
+/// and no corpus is built. The first string is scored as the query and the second one
+/// as the key, which makes a difference only for the asymmetric metrics.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let warp = WarpMetric::new(3).unwrap();
+/// let identical: f64 = ngram_similarity::<TriGram<char>, _>("bookkeeper", "bookkeeper", warp);
+/// assert_eq!(identical, 1.0);
+///
+/// let different: f64 = ngram_similarity::<TriGram<char>, _>("abcdefghij", "klmnopqrstuv", warp);
+/// assert_eq!(different, 0.0);
+///
+/// let jaccard: f64 = ngram_similarity::<TriGram<char>, _>("nacht", "night", JaccardMetric);
+/// assert_eq!(jaccard, 3.0 / 11.0);
+/// ```
+pub fn ngram_similarity<NG, F>(a: &str, b: &str, metric: impl Metric<F>) -> F
+where
+    NG: Ngram,
+    F: Float,
+    str: Key<NG, NG::G>,
+{
+    let left: HashMap<NG, usize, FxBuildHasher> = a.counts();
+    let right: HashMap<NG, usize, FxBuildHasher> = b.counts();
+    let shared: Vec<(usize, usize)> = left
+        .iter()
+        .filter_map(|(ngram, left_count)| {
+            right
+                .get(ngram)
+                .map(|right_count| (*left_count, *right_count))
+        })
+        .collect();
+    metric.score(&shared, left.values().sum(), right.values().sum())
+}
+
+#[inline(always)]
+/// Returns the similarity between the ngrams of two strings, with a warp factor of three.
+///
+/// # Arguments
+/// * `a` - The first string.
+/// * `b` - The second string.
+///
+/// # Implementation details
+/// The warp factor of three is the default one of the `similarity_to` method of the
+/// previous versions of the crate. Use the `ngram_similarity` function to score the
+/// strings with a different metric.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let score: f32 = similarity::<TriGram<char>, f32>("bookkeeper", "bookkeeper");
+/// assert_eq!(score, 1.0);
+///
+/// let score: f32 = similarity::<TriGram<char>, f32>("bookkeeper", "bookkeeping");
+/// assert!(score > 0.0 && score < 1.0);
+/// ```
+pub fn similarity<NG, F>(a: &str, b: &str) -> F
+where
+    NG: Ngram,
+    F: Float,
+    str: Key<NG, NG::G>,
+{
+    ngram_similarity::<NG, F>(a, b, WarpMetric::new(3).unwrap())
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the best matches scored with the provided metric.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search. Its warp factor is ignored, as the
+    /// scoring is entirely defined by the metric.
+    /// * `metric` - The metric to score the candidate keys with.
+    ///
+    /// # Examples
+    /// The same corpus can be searched with different metrics. The default
+    /// `WarpMetric` yields the same scores of the `ngram_search` method.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let config = NgramSearchConfig::default();
+    ///
+    /// let warped: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_metric("Cat", config, WarpMetric::default());
+    /// let expected: SearchResults<&&str, f32> = corpus.ngram_search("Cat", config);
+    /// assert_eq!(warped[0].key(), expected[0].key());
+    /// assert_eq!(warped[0].score(), expected[0].score());
+    ///
+    /// let jaccard: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_metric("Cat", config, JaccardMetric);
+    /// let cosine: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_metric("Cat", config, CosineMetric);
+    ///
+    /// assert_eq!(jaccard[0].key(), &"Cat");
+    /// assert_eq!(cosine[0].key(), &"Cat");
+    /// ```
+    pub fn ngram_search_with_metric<KR, W: Copy, F: Float, M: Metric<F>>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        metric: M,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        let shared = RefCell::new(Vec::new());
+        self.search(
+            key,
+            config.into(),
+            |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                metric_similarity(&metric, query, ngrams, &mut shared.borrow_mut())
+            },
+        )
+    }
+
+    /// Returns the similarity score of the provided query with itself.
+    ///
+    /// # Arguments
+    /// * `query` - The query to score.
+    /// * `metric` - The metric to score the query with.
+    ///
+    /// # Implementation details
+    /// The query shares all of its ngrams with itself, including the ones that do not
+    /// appear in the corpus. The self-score is the maximum score a key may reach, so it
+    /// can be used to normalize the scores of the unnormalized metrics such as the
+    /// `OverlapMetric`, while it is always one for the normalized ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+    ///
+    /// let cosine: f32 = corpus.query_self_score("catfish", CosineMetric);
+    /// assert_eq!(cosine, 1.0);
+    ///
+    /// let overlap: f32 = corpus.query_self_score("catfish", OverlapMetric);
+    /// assert_eq!(overlap, 9.0);
+    /// ```
+    pub fn query_self_score<KR, F: Float, M: Metric<F>>(&self, query: KR, metric: M) -> F
+    where
+        KR: AsRef<K>,
+    {
+        self.self_score(query.as_ref(), &metric)
+    }
+
+    /// Returns the similarity score of the provided query with itself.
+    ///
+    /// # Arguments
+    /// * `query` - The query to score.
+    /// * `metric` - The metric to score the query with.
+    fn self_score<F: Float, M: Metric<F>>(&self, query: &K, metric: &M) -> F {
+        let shared: Vec<(usize, usize)> = self
+            .key_counts(query)
+            .into_values()
+            .map(|count| (count, count))
+            .collect();
+        let query_len = shared.iter().map(|(count, _)| count).sum();
+        metric.score(&shared, query_len, query_len)
+    }
+
+    /// Returns the best matches scored with the provided metric, and the self-score of the query.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search. Its warp factor is ignored, as the
+    /// scoring is entirely defined by the metric.
+    /// * `metric` - The metric to score the candidate keys with.
+    ///
+    /// # Implementation details
+    /// The self-score is computed as in the `query_self_score` method, and the scores of
+    /// the results may be divided by it to normalize them into the range 0.0 to 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let (results, self_score): (SearchResults<&&str, f32>, f32) =
+    ///     corpus.ngram_search_with_self_score("cat", config, OverlapMetric);
+    ///
+    /// assert_eq!(results[0].key(), &"cat");
+    /// assert_eq!(results[0].score() / self_score, 1.0);
+    /// assert!(results[1].score() / self_score < 1.0);
+    /// ```
+    pub fn ngram_search_with_self_score<KR, W: Copy, F: Float, M: Metric<F>>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        metric: M,
+    ) -> (SearchResults<KS::KeyRef<'_>, F>, F)
+    where
+        KR: AsRef<K>,
+    {
+        let self_score = self.self_score(key.as_ref(), &metric);
+        (
+            self.ngram_search_with_metric(key, config, metric),
+            self_score,
+        )
+    }
+
+    /// Returns the best matches according to the weighted Jaccard similarity.
+    ///
+    /// # Arguments
+    /// * `query` - The key to search for in the corpus
+    /// * `threshold` - The minimum similarity value for a result to be included in the
+    /// output. This value should be in the range 0.0 to 1.0.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Implementation details
+    /// The ngrams of the query and of each key are treated as multisets, where the
+    /// multiplicity of an ngram in a key is its cooccurrence count. The score is the sum
+    /// of the minimum multiplicities divided by the sum of the maximum multiplicities,
+    /// which is the same score of the `JaccardMetric`. Only the keys sharing at least one
+    /// ngram with the query are scored, no matter how common the shared ngrams are, and
+    /// only the `limit` best ones are retained. A query without any ngram of the corpus,
+    /// such as an empty string, has no similarity with any key and returns no results.
+    ///
+    /// # Panics
+    /// * If the threshold is negative or NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.jaccard_search("Cat", 0.3, 5);
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert_eq!(results[0].score(), 1.0);
+    /// assert!(results.len() <= 5);
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.jaccard_search("", 0.0, 5);
+    /// assert!(results.is_empty());
+    /// ```
+    pub fn jaccard_search<F: Float>(
+        &self,
+        query: impl AsRef<K>,
+        threshold: F,
+        limit: usize,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(query.as_ref()));
+
+        // An empty query, whose only ngrams are made of padding, or more generally
+        // a query without any ngram in the corpus cannot share ngrams with any key.
+        if query_hashmap.ngram_ids().next().is_none() || limit == 0 {
+            return SearchResults::default();
+        }
+
+        let config: SearchConfig<F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(threshold)
+            .unwrap()
+            .set_maximum_number_of_results(limit)
+            .set_max_ngram_degree(MaxNgramDegree::None)
+            .into();
+        let mut heap = SearchResultsHeap::new(limit);
+
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                let mut minimum_sum = 0;
+                let mut key_len = 0;
+                merge_ngram_counts(
+                    query.ngram_ids_and_counts(),
+                    ngrams,
+                    |merged| match merged {
+                        MergedNgram::Query(..) => {}
+                        MergedNgram::Key(_, cooccurrence) => key_len += cooccurrence,
+                        MergedNgram::Shared(_, query_count, cooccurrence) => {
+                            key_len += cooccurrence;
+                            minimum_sum += query_count.min(cooccurrence);
+                        }
+                    },
+                );
+
+                // The sum of the maximum multiplicities, including the ngrams
+                // found only in the query or only in the key.
+                let maximum_sum = query.total_count() + key_len - minimum_sum;
+                F::from_f64(minimum_sum as f64 / maximum_sum as f64)
+            },
+            |_| true,
+            |key_id, score| {
+                heap.push(key_id, score);
+            },
+        );
+
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ngram_counts_visits_the_union() {
+        let query = [(1, 2), (3, 1), (7, 1)];
+        let key = [(0, 4), (3, 2), (5, 1), (7, 3), (9, 1)];
+        let mut merged = Vec::new();
+        merge_ngram_counts(query.into_iter(), key.into_iter(), |ngram| {
+            merged.push(ngram)
+        });
+        assert_eq!(
+            merged,
+            vec![
+                MergedNgram::Key(0, 4),
+                MergedNgram::Query(1, 2),
+                MergedNgram::Shared(3, 1, 2),
+                MergedNgram::Key(5, 1),
+                MergedNgram::Shared(7, 1, 3),
+                MergedNgram::Key(9, 1),
+            ]
+        );
+
+        let mut merged = Vec::new();
+        merge_ngram_counts(query.into_iter(), std::iter::empty(), |ngram| {
+            merged.push(ngram)
+        });
+        assert_eq!(merged.len(), query.len());
+    }
+
+    #[test]
+    fn test_metrics_without_shared_ngrams_are_zero() {
+        for (query_len, key_len) in [(0, 0), (0, 5), (5, 0), (5, 5)] {
+            let warp: f64 = WarpMetric::default().score(&[], query_len, key_len);
+            let jaccard: f64 = JaccardMetric.score(&[], query_len, key_len);
+            let cosine: f64 = CosineMetric.score(&[], query_len, key_len);
+            let dice: f64 = DiceMetric.score(&[], query_len, key_len);
+            let overlap: f64 = OverlapCoefficientMetric.score(&[], query_len, key_len);
+            assert_eq!(warp, 0.0);
+            assert_eq!(jaccard, 0.0);
+            assert_eq!(cosine, 0.0);
+            assert_eq!(dice, 0.0);
+            assert_eq!(overlap, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_metrics_on_identical_and_substring_keys() {
+        // Identical multisets of ngrams have similarity one.
+        let shared = [(1, 1), (2, 2), (1, 1)];
+        let warp: f64 = WarpMetric::default().score(&shared, 4, 4);
+        let jaccard: f64 = JaccardMetric.score(&shared, 4, 4);
+        let cosine: f64 = CosineMetric.score(&shared, 4, 4);
+        let dice: f64 = DiceMetric.score(&shared, 4, 4);
+        let overlap: f64 = OverlapCoefficientMetric.score(&shared, 4, 4);
+        assert_eq!(warp, 1.0);
+        assert_eq!(jaccard, 1.0);
+        assert_eq!(cosine, 1.0);
+        assert_eq!(dice, 1.0);
+        assert_eq!(overlap, 1.0);
+
+        // A query whose ngrams are all contained in the key.
+        let shared = [(1, 1), (1, 1)];
+        let jaccard: f64 = JaccardMetric.score(&shared, 2, 8);
+        let cosine: f64 = CosineMetric.score(&shared, 2, 8);
+        let dice: f64 = DiceMetric.score(&shared, 2, 8);
+        let overlap: f64 = OverlapCoefficientMetric.score(&shared, 2, 8);
+        assert_eq!(jaccard, 0.25);
+        assert_eq!(cosine, 0.5);
+        assert_eq!(dice, 0.4);
+        assert_eq!(overlap, 1.0);
+        let warp_one: f64 = WarpMetric::new(1).unwrap().score(&shared, 2, 8);
+        assert_eq!(warp_one, jaccard);
+    }
+
+    #[test]
+    fn test_metric_search_on_substring_keys() {
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for results in [
+            corpus.ngram_search_with_metric("cat", config, WarpMetric::default()),
+            corpus.ngram_search_with_metric("cat", config, JaccardMetric),
+            corpus.ngram_search_with_metric("cat", config, CosineMetric),
+            corpus.ngram_search_with_metric("cat", config, DiceMetric),
+        ] {
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].key(), &"cat");
+            assert_eq!(results[0].score(), 1.0);
+            assert_eq!(results[1].key(), &"cattle");
+            assert!(results[1].score() > 0.0 && results[1].score() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_overlap_self_score_is_the_number_of_distinct_query_ngrams() {
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "aaaa", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for query in ["cat", "aaaa", "zebra"] {
+            let distinct_ngrams = Key::<TriGram<char>, char>::counts(query).len();
+            let self_score: f32 = corpus.query_self_score(query, OverlapMetric);
+            assert_eq!(self_score, distinct_ngrams as f32, "Query {:?}", query);
+
+            let cosine: f32 = corpus.query_self_score(query, CosineMetric);
+            assert_eq!(cosine, 1.0);
+        }
+
+        // The repeated trigram "aaa" is counted once.
+        let self_score: f32 = corpus.query_self_score("aaaa", OverlapMetric);
+        assert_eq!(self_score, 5.0);
+
+        let (results, self_score) =
+            corpus.ngram_search_with_self_score("aaaa", config, OverlapMetric);
+        assert_eq!(results[0].key(), &"aaaa");
+        assert_eq!(results[0].score(), self_score);
+    }
+
+    #[test]
+    fn test_jaccard_search_matches_jaccard_metric() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.2_f32)
+            .unwrap()
+            .set_maximum_number_of_results(20)
+            .set_max_ngram_degree(MaxNgramDegree::None);
+
+        for query in ["cat", "dog", "hippopotamus", "zzzz"] {
+            let expected: SearchResults<&&str, f32> =
+                corpus.ngram_search_with_metric(query, config, JaccardMetric);
+            let results: SearchResults<&&str, f32> = corpus.jaccard_search(query, 0.2, 20);
+
+            assert_eq!(results.len(), expected.len());
+            for (result, expected) in results.iter().zip(expected.iter()) {
+                assert_eq!(result.score(), expected.score());
+            }
+        }
+
+        assert!(corpus.jaccard_search("", 0.0_f32, 20).is_empty());
+        assert!(corpus.jaccard_search("cat", 0.0_f32, 0).is_empty());
+    }
+
+    #[test]
+    fn test_ngram_similarity_matches_corpus_search() {
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for (a, b) in [
+            ("bookkeeper", "bookkeeper"),
+            ("bookkeeper", "bookkeeping"),
+            ("nacht", "night"),
+            ("abcdefghij", "klmnopqrstuv"),
+        ] {
+            let corpus: Corpus<[&str; 1], TriGram<char>> = Corpus::from([b]);
+            let expected: f32 = corpus
+                .ngram_search(a, config)
+                .iter()
+                .map(|result| result.score())
+                .next()
+                .unwrap_or(0.0);
+            let score: f32 = ngram_similarity::<TriGram<char>, _>(a, b, WarpMetric::default());
+            assert_eq!(score, expected, "Pair {:?}", (a, b));
+
+            // The warp metric of the warp factor three is symmetric.
+            let left: f32 = similarity::<TriGram<char>, f32>(a, b);
+            let right: f32 = similarity::<TriGram<char>, f32>(b, a);
+            assert_eq!(left, right, "Pair {:?}", (a, b));
+        }
+
+        let identical: f64 = similarity::<TriGram<char>, _>("bookkeeper", "bookkeeper");
+        assert_eq!(identical, 1.0);
+        let different: f64 = similarity::<TriGram<char>, _>("abcdefghij", "klmnopqrstuv");
+        assert_eq!(different, 0.0);
+    }
+}
@@ -0,0 +1,331 @@
+//! Submodule providing an approximate search of the corpus based on MinHash sketches.
+//!
+//! # Implementation details
+//! A MinHash signature summarizes the set of ngram ids of a key with the minimum value
+//! of each of `k` hash functions over the set. The probability that two signatures
+//! agree on a given hash function is exactly the Jaccard similarity of the two sets, so
+//! the fraction of agreeing hash functions is an unbiased estimate of it. Comparing two
+//! signatures costs `k` integer comparisons, no matter how many ngrams the keys have,
+//! and does not require decoding the compressed graph of the corpus.
+//!
+//! The number of hash functions trades accuracy for size: the standard error of the
+//! estimate is `sqrt(J * (1 - J) / k)`, at most `1 / (2 * sqrt(k))`, while the index
+//! takes `8 * k` bytes per key. For instance, 128 hash functions give an error of at
+//! most about 0.044 with one kilobyte per key, and quadrupling the number of hash
+//! functions halves the error. Note that the signatures ignore the co-occurrences of
+//! the ngrams, so the estimated similarity is the one between the sets of ngrams.
+use crate::prelude::*;
+use crate::SearchResultsHeap;
+
+#[inline(always)]
+/// Returns the hash of the provided value with the provided seed.
+///
+/// # Arguments
+/// * `value` - The value to hash.
+/// * `seed` - The seed selecting the hash function.
+///
+/// # Implementation details
+/// The hash is the finalizer of the SplitMix64 generator, which is a bijection
+/// on 64-bit integers and therefore a permutation of the ngram ids.
+fn hash(value: u64, seed: u64) -> u64 {
+    let mut z = (value ^ seed).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Precomputed MinHash signatures of the keys of a corpus, used by the approximate search.
+///
+/// # Implementation details
+/// The signatures of all of the keys are stored contiguously, so that the signature of
+/// the key with id `key_id` starts at `key_id * number_of_hashes`. The search checks that
+/// the index has no more signatures than the corpus has key ids, but it cannot tell apart
+/// the signatures of a corpus of the same size, whose similarities would be meaningless.
+pub struct MinHashIndex {
+    /// The seeds of the hash functions.
+    seeds: Vec<u64>,
+    /// The signatures of the keys, indexed by key id.
+    signatures: Vec<u64>,
+}
+
+impl MinHashIndex {
+    /// Returns a new index with the provided number of hash functions and no keys.
+    ///
+    /// # Arguments
+    /// * `number_of_hashes` - The number of hash functions.
+    fn with_number_of_hashes(number_of_hashes: usize) -> Self {
+        Self {
+            seeds: (0..number_of_hashes as u64).map(|i| hash(i, 0)).collect(),
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Returns the signature of the provided set of ngram ids.
+    ///
+    /// # Arguments
+    /// * `ngram_ids` - The ngram ids, without repetitions.
+    fn signature(&self, ngram_ids: impl Iterator<Item = usize>) -> Vec<u64> {
+        let mut signature = vec![u64::MAX; self.number_of_hashes()];
+        for ngram_id in ngram_ids {
+            for (minimum, &seed) in signature.iter_mut().zip(self.seeds.iter()) {
+                *minimum = (*minimum).min(hash(ngram_id as u64, seed));
+            }
+        }
+        signature
+    }
+
+    #[inline(always)]
+    /// Returns the number of hash functions of the index.
+    pub fn number_of_hashes(&self) -> usize {
+        self.seeds.len()
+    }
+
+    #[inline(always)]
+    /// Returns the number of keys in the index.
+    pub fn number_of_keys(&self) -> usize {
+        self.signatures.len() / self.number_of_hashes()
+    }
+
+    #[inline(always)]
+    /// Returns the signature of the provided key.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key.
+    pub fn signature_from_key(&self, key_id: usize) -> &[u64] {
+        let number_of_hashes = self.number_of_hashes();
+        &self.signatures[key_id * number_of_hashes..(key_id + 1) * number_of_hashes]
+    }
+
+    #[inline(always)]
+    /// Returns the fraction of hash functions on which the provided signature agrees
+    /// with the signature of the provided key.
+    ///
+    /// # Arguments
+    /// * `signature` - The signature to compare.
+    /// * `key_id` - The id of the key.
+    fn estimate_jaccard_with_signature(&self, signature: &[u64], key_id: usize) -> f64 {
+        let number_of_agreements = signature
+            .iter()
+            .zip(self.signature_from_key(key_id))
+            .filter(|(left, right)| left == right)
+            .count();
+        number_of_agreements as f64 / self.number_of_hashes() as f64
+    }
+
+    #[inline(always)]
+    /// Returns the estimated Jaccard similarity between the sets of ngrams of two keys.
+    ///
+    /// # Arguments
+    /// * `first_key_id` - The id of the first key.
+    /// * `second_key_id` - The id of the second key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cats", "dog"]);
+    /// let index = corpus.build_minhash(128).unwrap();
+    ///
+    /// assert_eq!(index.estimate_jaccard(0, 0), 1.0);
+    /// assert!(index.estimate_jaccard(0, 1) > index.estimate_jaccard(0, 2));
+    /// ```
+    pub fn estimate_jaccard(&self, first_key_id: usize, second_key_id: usize) -> f64 {
+        self.estimate_jaccard_with_signature(self.signature_from_key(first_key_id), second_key_id)
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the MinHash signatures of the keys of the corpus.
+    ///
+    /// # Arguments
+    /// * `number_of_hashes` - The number of hash functions of the signatures.
+    ///
+    /// # Raises
+    /// * If the number of hash functions is zero.
+    ///
+    /// # Implementation details
+    /// Building the index requires a pass over all of the ngrams of the corpus, and
+    /// `number_of_hashes` hash evaluations for each of them. The keys inserted in the
+    /// corpus and not yet compacted are part of the index, but compacting the corpus
+    /// changes the ids of their ngrams, so the index must then be built again. See the
+    /// documentation of the `minhash` module for the tradeoff between accuracy and size.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let index = corpus.build_minhash(64).unwrap();
+    ///
+    /// assert_eq!(index.number_of_hashes(), 64);
+    /// assert_eq!(index.number_of_keys(), corpus.number_of_keys());
+    /// assert_eq!(
+    ///     corpus.build_minhash(0),
+    ///     Err("The number of hash functions must be positive.")
+    /// );
+    /// ```
+    pub fn build_minhash(&self, number_of_hashes: usize) -> Result<MinHashIndex, &'static str> {
+        if number_of_hashes == 0 {
+            return Err("The number of hash functions must be positive.");
+        }
+        let mut index = MinHashIndex::with_number_of_hashes(number_of_hashes);
+        let number_of_keys = self.number_of_key_ids();
+        index.signatures.reserve(number_of_keys * number_of_hashes);
+        for key_id in 0..number_of_keys {
+            let signature = index.signature(self.ngram_ids_from_key(key_id));
+            index.signatures.extend(signature);
+        }
+        Ok(index)
+    }
+
+    /// Returns the best matches according to the Jaccard similarity estimated with MinHash.
+    ///
+    /// # Arguments
+    /// * `query` - The key to search for in the corpus.
+    /// * `index` - The signatures built with `build_minhash` on this corpus.
+    /// * `threshold` - The minimum estimated similarity for a result to be included in
+    ///   the output. This value should be in the range 0.0 to 1.0.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Implementation details
+    /// The signature of the query is computed from its set of ngrams, where the ngrams
+    /// missing from the corpus receive ids that no key has, and is compared with the
+    /// signature of every key of the index. The keys with an estimated similarity of
+    /// zero are never returned, while the removed keys are skipped. A query without any
+    /// ngram of the corpus has no similarity with any key and returns no results.
+    ///
+    /// # Panics
+    /// * If the index was built from a corpus with more keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let index = corpus.build_minhash(128).unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.search_minhash("Cat", &index, 0.3, 5);
+    ///
+    /// assert_eq!(results[0].score(), 1.0);
+    /// assert!(results.iter().any(|result| result.key() == &"Cat"));
+    /// assert!(results.len() <= 5);
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.search_minhash("", &index, 0.0, 5);
+    /// assert!(results.is_empty());
+    /// ```
+    pub fn search_minhash<F: Float>(
+        &self,
+        query: impl AsRef<K>,
+        index: &MinHashIndex,
+        threshold: F,
+        limit: usize,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        assert!(
+            index.number_of_keys() <= self.number_of_key_ids(),
+            "The index was built from a corpus with more keys."
+        );
+
+        // The ngrams of the keys inserted since the last compaction follow the vocabulary.
+        let number_of_ngrams = self.number_of_ngrams() + self.overflow.number_of_ngrams();
+        let mut number_of_unknown_ngrams = 0;
+        let ngram_ids: Vec<usize> = self
+            .key_counts(query.as_ref())
+            .into_keys()
+            .map(|ngram| {
+                self.ngram_id_from_ngram(ngram).unwrap_or_else(|| {
+                    number_of_unknown_ngrams += 1;
+                    number_of_ngrams + number_of_unknown_ngrams - 1
+                })
+            })
+            .collect();
+
+        if limit == 0
+            || ngram_ids
+                .iter()
+                .all(|&ngram_id| ngram_id >= number_of_ngrams)
+        {
+            return SearchResults::default();
+        }
+
+        let signature = index.signature(ngram_ids.into_iter());
+        let mut heap = SearchResultsHeap::new(limit);
+
+        for key_id in 0..index.number_of_keys() {
+            if self.is_removed(key_id) {
+                continue;
+            }
+            let score = F::from_f64(index.estimate_jaccard_with_signature(&signature, key_id));
+            if !score.is_zero() && score >= threshold {
+                heap.push(key_id, score);
+            }
+        }
+
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the exact Jaccard similarity between the sets of ngrams of two keys.
+    fn exact_jaccard(
+        corpus: &Corpus<&[&str; 699], TriGram<char>>,
+        first_key_id: usize,
+        second_key_id: usize,
+    ) -> f64 {
+        let first: Vec<usize> = corpus.ngram_ids_from_key(first_key_id).collect();
+        let second: Vec<usize> = corpus.ngram_ids_from_key(second_key_id).collect();
+        let intersection = first
+            .iter()
+            .filter(|ngram_id| second.binary_search(ngram_id).is_ok())
+            .count();
+        intersection as f64 / (first.len() + second.len() - intersection) as f64
+    }
+
+    #[test]
+    fn test_estimated_jaccard_is_close_to_exact() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let index = corpus.build_minhash(256).unwrap();
+        let cat = ANIMALS.iter().position(|animal| *animal == "Cat").unwrap();
+
+        for key_id in 0..100 {
+            assert_eq!(index.estimate_jaccard(key_id, key_id), 1.0);
+            for other_key_id in [key_id + 1, cat] {
+                let exact = exact_jaccard(&corpus, key_id, other_key_id);
+                let estimate = index.estimate_jaccard(key_id, other_key_id);
+                assert!(
+                    (estimate - exact).abs() <= 0.15,
+                    "The estimate {} between {} and {} is far from the exact {}.",
+                    estimate,
+                    ANIMALS[key_id],
+                    ANIMALS[other_key_id],
+                    exact
+                );
+            }
+        }
+
+        let results: SearchResults<&&str, f32> = corpus.search_minhash("Cat", &index, 0.0, 20);
+        assert!(!results.is_empty());
+        for result in &results {
+            let key_id = ANIMALS
+                .iter()
+                .position(|animal| animal == result.key())
+                .unwrap();
+            let exact = exact_jaccard(&corpus, cat, key_id);
+            assert!((result.score() as f64 - exact).abs() <= 0.15);
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Submodule providing a container of string keys backed by a memory-mapped file.
+//!
+//! # Implementation details
+//! The `StringPool` keeps all of the keys in memory, which for dictionaries with
+//! hundreds of millions of keys may not be possible. The `MmapKeys` instead memory-map
+//! a string table written with `MmapKeys::write`, so that the operating system only
+//! loads the pages of the keys that are read, such as the ones of the search results.
+//! Together with the memory-mapped weights of the `MmapReaderFactory` and the
+//! `BiWebgraph`, this allows the corpus to be larger than the available memory.
+//!
+//! The file is made of a header with the magic bytes `NGKEYTAB`, followed by the number
+//! of keys and the length in bytes of the strings, then by the offset where each key
+//! ends, which also encodes its length, and finally by the concatenation of the keys.
+//! All integers are stored as little-endian `u64`.
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{Key, Keys, Ngram};
+
+/// The magic bytes at the start of a string table file.
+const MAGIC: [u8; 8] = *b"NGKEYTAB";
+
+/// The length in bytes of the header of a string table file.
+const HEADER_LENGTH: usize = MAGIC.len() + 2 * core::mem::size_of::<u64>();
+
+#[derive(Debug)]
+/// A container of string keys, stored in a memory-mapped file.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let file = tempfile::NamedTempFile::new().unwrap();
+/// MmapKeys::write(file.path(), ["cat", "", "dog"]).unwrap();
+///
+/// let keys = MmapKeys::load(file.path()).unwrap();
+/// assert_eq!(keys.len(), 3);
+/// assert_eq!(keys.get(0), "cat");
+/// assert_eq!(keys.get(1), "");
+/// assert_eq!(keys.get(2), "dog");
+/// assert_eq!(keys.iter().collect::<Vec<&str>>(), vec!["cat", "", "dog"]);
+/// ```
+pub struct MmapKeys {
+    /// The memory-mapped file.
+    mmap: Mmap,
+    /// The number of keys.
+    number_of_keys: usize,
+    /// The position in bytes of the strings in the file.
+    start: usize,
+}
+
+impl MmapKeys {
+    /// Writes the provided keys to the provided path, so that they can be loaded with `load`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to write.
+    /// * `keys` - The keys to write.
+    ///
+    /// # Raises
+    /// * If the file cannot be created or written.
+    ///
+    /// # Implementation details
+    /// The keys are iterated three times, to measure them, to write their offsets and to
+    /// write their bytes, so that they never need to be collected in memory.
+    pub fn write<I>(path: impl AsRef<Path>, keys: I) -> std::io::Result<()>
+    where
+        I: IntoIterator + Clone,
+        I::Item: AsRef<str>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let (number_of_keys, length) = keys
+            .clone()
+            .into_iter()
+            .fold((0_usize, 0_usize), |(number_of_keys, length), key| {
+                (number_of_keys + 1, length + key.as_ref().len())
+            });
+
+        writer.write_all(&MAGIC)?;
+        for value in [number_of_keys, length] {
+            writer.write_all(&(value as u64).to_le_bytes())?;
+        }
+        let mut end = 0_u64;
+        for key in keys.clone() {
+            end += key.as_ref().len() as u64;
+            writer.write_all(&end.to_le_bytes())?;
+        }
+        for key in keys {
+            writer.write_all(key.as_ref().as_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Loads the keys written with `write` at the provided path, memory-mapping the file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to load.
+    ///
+    /// # Raises
+    /// * If the file cannot be opened or memory-mapped.
+    /// * If the file is not a string table, it is truncated, or its keys are not UTF-8.
+    ///
+    /// # Implementation details
+    /// The offsets and the keys are validated once when the file is loaded, which reads
+    /// the whole file, but the pages may then be evicted by the operating system as
+    /// they are never modified. The file must not be modified while the keys are loaded.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the file is only read, and it must not be modified while mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |message: &'static str| Error::new(ErrorKind::InvalidData, message);
+        let read_u64 = |position: usize| -> std::io::Result<usize> {
+            mmap.get(position..position + 8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+                .ok_or_else(|| invalid("The keys file is truncated."))
+        };
+
+        if mmap.get(..MAGIC.len()) != Some(MAGIC.as_slice()) {
+            return Err(invalid("The file is not a keys file."));
+        }
+        let number_of_keys = read_u64(MAGIC.len())?;
+        let length = read_u64(MAGIC.len() + 8)?;
+
+        let start = number_of_keys
+            .checked_mul(8)
+            .and_then(|offsets_length| offsets_length.checked_add(HEADER_LENGTH))
+            .filter(|&start| {
+                start
+                    .checked_add(length)
+                    .is_some_and(|end| end == mmap.len())
+            })
+            .ok_or_else(|| invalid("The keys file is truncated."))?;
+
+        let mut previous_end = 0;
+        for key_id in 0..number_of_keys {
+            let end = read_u64(HEADER_LENGTH + key_id * 8)?;
+            if end < previous_end || end > length {
+                return Err(invalid(
+                    "The offsets of the keys must be sorted and in bounds.",
+                ));
+            }
+            if std::str::from_utf8(&mmap[start + previous_end..start + end]).is_err() {
+                return Err(invalid("The keys must be valid UTF-8."));
+            }
+            previous_end = end;
+        }
+        if previous_end != length {
+            return Err(invalid("The offsets of the keys must cover the strings."));
+        }
+
+        Ok(Self {
+            mmap,
+            number_of_keys,
+            start,
+        })
+    }
+
+    #[inline(always)]
+    /// Returns the offset where the key with the provided index ends.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the key.
+    fn end(&self, index: usize) -> usize {
+        let position = HEADER_LENGTH + index * 8;
+        u64::from_le_bytes(self.mmap[position..position + 8].try_into().unwrap()) as usize
+    }
+
+    #[inline(always)]
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.number_of_keys
+    }
+
+    #[inline(always)]
+    /// Returns whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.number_of_keys == 0
+    }
+
+    #[inline(always)]
+    /// Returns the key with the provided index.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the key.
+    ///
+    /// # Panics
+    /// * If the index is out of bounds.
+    pub fn get(&self, index: usize) -> &str {
+        assert!(
+            index < self.number_of_keys,
+            "The key index is out of bounds."
+        );
+        let start = if index == 0 { 0 } else { self.end(index - 1) };
+        let bytes = &self.mmap[self.start + start..self.start + self.end(index)];
+        // SAFETY: the keys were validated as UTF-8 when the file was loaded.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the keys.
+    pub fn iter(&self) -> MmapKeysIter<'_> {
+        MmapKeysIter {
+            keys: self,
+            index: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterator over the keys of a `MmapKeys`.
+pub struct MmapKeysIter<'a> {
+    /// The keys to iterate.
+    keys: &'a MmapKeys,
+    /// The index of the next key.
+    index: usize,
+}
+
+impl<'a> Iterator for MmapKeysIter<'a> {
+    type Item = &'a str;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.keys.len() {
+            return None;
+        }
+        self.index += 1;
+        Some(self.keys.get(self.index - 1))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.keys.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for MmapKeysIter<'a> {}
+
+impl<NG: Ngram> Keys<NG> for MmapKeys
+where
+    str: Key<NG, <NG as Ngram>::G>,
+    String: Key<NG, <NG as Ngram>::G>,
+{
+    type K = String;
+    type KeyRef<'a>
+        = &'a str
+    where
+        Self: 'a;
+    type IterKeys<'a>
+        = MmapKeysIter<'a>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get_ref(&self, index: usize) -> Self::KeyRef<'_> {
+        self.get(index)
+    }
+
+    fn iter(&self) -> Self::IterKeys<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_mmap_keys_search_agrees_with_in_memory_keys() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        MmapKeys::write(file.path(), ANIMALS.iter()).unwrap();
+        let keys = MmapKeys::load(file.path()).unwrap();
+
+        assert_eq!(keys.len(), ANIMALS.len());
+        assert!(keys.iter().eq(ANIMALS.iter().copied()));
+
+        let corpus: Corpus<MmapKeys, TriGram<char>> = Corpus::from(keys);
+        let expected: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        for (key_id, animal) in ANIMALS.iter().enumerate() {
+            assert_eq!(corpus.key_from_id(key_id), *animal);
+        }
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap();
+        for query in ["Cat", "Catt", "Sea lion", "Hippopotamus"] {
+            let results = corpus.ngram_search(query, config);
+            let expected_results = expected.ngram_search(query, config);
+            assert_eq!(results.len(), expected_results.len());
+            for (result, expected_result) in results.iter().zip(expected_results.iter()) {
+                assert_eq!(result.key(), *expected_result.key());
+                assert_eq!(result.score(), expected_result.score());
+            }
+        }
+    }
+
+    #[test]
+    fn test_mmap_keys_rejects_invalid_files() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"not a keys file").unwrap();
+        assert_eq!(
+            MmapKeys::load(file.path()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+
+        // A truncated file is rejected.
+        MmapKeys::write(file.path(), ["cat", "dog"]).unwrap();
+        let bytes = std::fs::read(file.path()).unwrap();
+        std::fs::write(file.path(), &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(
+            MmapKeys::load(file.path()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+
+        // A key that is not valid UTF-8 is rejected.
+        let mut bytes = bytes;
+        let last = bytes.len() - 1;
+        bytes[last] = 0xFF;
+        std::fs::write(file.path(), &bytes).unwrap();
+        assert_eq!(
+            MmapKeys::load(file.path()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
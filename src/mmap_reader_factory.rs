@@ -0,0 +1,214 @@
+//! Submodule providing a reader factory over a memory-mapped weights file.
+//!
+//! # Implementation details
+//! The `CursorReaderFactory` keeps the whole weights bitstream in memory, which for
+//! corpora with millions of keys dominates the memory usage. The `MmapReaderFactory`
+//! instead memory-maps a file written with `Weights::write_to`, so that the operating
+//! system only loads the pages of the bitstream touched by the searches. The file also
+//! stores the offsets of the weights of each node, which are loaded into an Elias-Fano
+//! representation when the file is opened.
+//!
+//! The file is made of a header with the magic bytes `NGWEIGHT`, followed by the number
+//! of nodes, the number of weights and the length in bytes of the bitstream, then by the
+//! bit offset of each node and finally by the bitstream. All integers are stored as
+//! little-endian `u64`.
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+
+use dsi_bitstream::prelude::*;
+use memmap2::Mmap;
+use sux::prelude::*;
+
+use crate::weights::{elias_fano_from_sorted, ReaderFactory, Weights};
+
+/// The magic bytes at the start of a weights file.
+const MAGIC: [u8; 8] = *b"NGWEIGHT";
+
+/// The length in bytes of the header of a weights file.
+const HEADER_LENGTH: usize = MAGIC.len() + 3 * core::mem::size_of::<u64>();
+
+#[derive(Debug)]
+/// A factory of readers over the weights bitstream of a memory-mapped file.
+pub struct MmapReaderFactory {
+    /// The memory-mapped file.
+    mmap: Mmap,
+    /// The position in bytes of the bitstream in the file.
+    start: usize,
+    /// The length in bytes of the bitstream.
+    length: usize,
+}
+
+impl MmapReaderFactory {
+    #[inline(always)]
+    /// Returns the bitstream in the memory-mapped file.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.mmap[self.start..self.start + self.length]
+    }
+}
+
+impl ReaderFactory for MmapReaderFactory {
+    type Reader<'a> = BufBitReader<LittleEndian, WordAdapter<u32, std::io::Cursor<&'a [u8]>>>;
+
+    fn get_reader(&self, offset: usize) -> Self::Reader<'_> {
+        let mut res = BufBitReader::<LittleEndian, _>::new(WordAdapter::<u32, _>::new(
+            std::io::Cursor::new(self.data()),
+        ));
+        res.set_bit_pos(offset as u64).unwrap();
+        res
+    }
+}
+
+impl Weights {
+    /// Writes the weights to the provided path, so that they can be loaded with `load`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to write.
+    ///
+    /// # Raises
+    /// * If the file cannot be created or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::mmap_reader_factory::MmapReaderFactory;
+    /// use ngrammatic::weights::{Weights, WeightsBuilder};
+    /// use webgraph::prelude::*;
+    ///
+    /// let mut builder = WeightsBuilder::new();
+    /// builder.push(vec![1, 0, 3].into_iter()).unwrap();
+    /// builder.push(vec![2].into_iter()).unwrap();
+    /// let weights = builder.build();
+    ///
+    /// let file = tempfile::NamedTempFile::new().unwrap();
+    /// weights.write_to(file.path()).unwrap();
+    ///
+    /// let loaded = Weights::<MmapReaderFactory>::load(file.path()).unwrap();
+    /// assert_eq!(loaded.num_nodes(), 2);
+    /// assert_eq!(loaded.labels(0).collect::<Vec<_>>(), vec![1, 0, 3]);
+    /// assert_eq!(loaded.labels(1).collect::<Vec<_>>(), vec![2]);
+    /// ```
+    pub fn write_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let data = &self.reader_factory.data;
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&MAGIC)?;
+        for value in [self.num_nodes, self.num_weights, data.len()] {
+            writer.write_all(&(value as u64).to_le_bytes())?;
+        }
+        for offset in self.offsets.into_iter_from(0) {
+            writer.write_all(&(offset as u64).to_le_bytes())?;
+        }
+        writer.write_all(data)?;
+        writer.flush()
+    }
+}
+
+impl Weights<MmapReaderFactory> {
+    /// Loads the weights written with `write_to` at the provided path, memory-mapping
+    /// the bitstream.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file to load.
+    ///
+    /// # Raises
+    /// * If the file cannot be opened or memory-mapped.
+    /// * If the file is not a weights file, or it is truncated.
+    ///
+    /// # Implementation details
+    /// The offsets are read from the file into an Elias-Fano representation, while the
+    /// bitstream is accessed through the memory mapping. The file must not be modified
+    /// while the weights are loaded.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the file is only read, and it must not be modified while mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |message: &'static str| Error::new(ErrorKind::InvalidData, message);
+        let read_u64 = |position: usize| -> std::io::Result<usize> {
+            mmap.get(position..position + 8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+                .ok_or_else(|| invalid("The weights file is truncated."))
+        };
+
+        if mmap.get(..MAGIC.len()) != Some(MAGIC.as_slice()) {
+            return Err(invalid("The file is not a weights file."));
+        }
+        let num_nodes = read_u64(MAGIC.len())?;
+        let num_weights = read_u64(MAGIC.len() + 8)?;
+        let length = read_u64(MAGIC.len() + 16)?;
+
+        let start = num_nodes
+            .checked_mul(8)
+            .and_then(|offsets_length| offsets_length.checked_add(HEADER_LENGTH))
+            .filter(|&start| {
+                start
+                    .checked_add(length)
+                    .is_some_and(|end| end <= mmap.len())
+            })
+            .ok_or_else(|| invalid("The weights file is truncated."))?;
+        let offsets = (0..num_nodes)
+            .map(|node_id| read_u64(HEADER_LENGTH + node_id * 8))
+            .collect::<std::io::Result<Vec<usize>>>()?;
+        if offsets.last().is_some_and(|&offset| offset > length * 8) {
+            return Err(invalid("The offsets must be within the bitstream."));
+        }
+        let offsets = elias_fano_from_sorted(&offsets).map_err(invalid)?;
+
+        Ok(Weights::new(
+            MmapReaderFactory {
+                mmap,
+                start,
+                length,
+            },
+            offsets,
+            num_nodes,
+            num_weights,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use webgraph::prelude::*;
+
+    use super::*;
+    use crate::weights::WeightsBuilder;
+
+    #[test]
+    fn test_mmap_reader_factory() {
+        let weights = (0..200)
+            .map(|node| {
+                (0..node % 17)
+                    .map(|i| (i * node) % 5)
+                    .collect::<Vec<usize>>()
+            })
+            .collect::<Vec<Vec<usize>>>();
+
+        let mut builder = WeightsBuilder::new();
+        for node_weights in &weights {
+            builder.push(node_weights.iter().copied()).unwrap();
+        }
+        let local = builder.build();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        local.write_to(file.path()).unwrap();
+        let mapped = Weights::<MmapReaderFactory>::load(file.path()).unwrap();
+
+        assert_eq!(mapped.num_nodes(), local.num_nodes());
+        assert_eq!(mapped.num_weights(), local.num_weights());
+        for (node_id, node_weights) in weights.iter().enumerate() {
+            assert_eq!(&mapped.labels(node_id).collect::<Vec<_>>(), node_weights);
+        }
+        assert!(mapped.weights().eq(local.weights()));
+
+        // A file that is not a weights file is rejected.
+        let other = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(other.path(), b"not a weights file").unwrap();
+        assert_eq!(
+            Weights::<MmapReaderFactory>::load(other.path())
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
@@ -1,6 +1,16 @@
 //! Submodule providing the trigram search implementation.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+use fxhash::FxBuildHasher;
+
+use crate::metric::metric_similarity;
+use crate::ngram_similarity::weighted_ngram_similarity;
 use crate::prelude::*;
+use crate::SearchResultsHeap;
+use sux::bits::BitVec;
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 /// Struct providing an ngram search configuration.
@@ -107,6 +117,154 @@ impl<W: Copy, F: Float> NgramSearchConfig<W, F> {
         self.search_config.max_ngram_degree()
     }
 
+    #[inline(always)]
+    /// Set the penalty applied to the score for each ngram of difference between
+    /// the length of the query and the length of the candidate key.
+    ///
+    /// # Arguments
+    /// * `length_mismatch_penalty` - The penalty, zero to disable it.
+    ///
+    /// # Implementation details
+    /// The score of each candidate is multiplied by `1 / (1 + penalty * difference)`,
+    /// where `difference` is the absolute difference between the number of ngrams
+    /// in the query and in the candidate key. This improves the precision when matching
+    /// short keys, such as codes, where a much longer candidate is usually wrong even
+    /// when it has a high overlap with the query.
+    ///
+    /// # Raises
+    /// * If the penalty is NaN or negative.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default();
+    /// assert_eq!(config.length_mismatch_penalty(), 0.0);
+    /// assert!(config.set_length_mismatch_penalty(-1.0).is_err());
+    /// let config = config.set_length_mismatch_penalty(0.5).unwrap();
+    ///
+    /// assert_eq!(config.length_mismatch_penalty(), 0.5);
+    /// ```
+    pub fn set_length_mismatch_penalty(
+        mut self,
+        length_mismatch_penalty: F,
+    ) -> Result<Self, &'static str> {
+        self.search_config = self
+            .search_config
+            .set_length_mismatch_penalty(length_mismatch_penalty)?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns the length mismatch penalty.
+    pub fn length_mismatch_penalty(&self) -> F {
+        self.search_config.length_mismatch_penalty()
+    }
+
+    #[inline(always)]
+    /// Set whether the ngrams exceeding the maximum degree are used to score the candidates.
+    ///
+    /// # Arguments
+    /// * `score_common_ngrams` - Whether to score the candidates with the common ngrams.
+    ///
+    /// # Implementation details
+    /// The ngrams exceeding the maximum ngram degree never generate candidates, but by
+    /// default they are still used to score the candidates found through the rarer ngrams,
+    /// so that capping the degree does not change their scores. Disabling the scoring of
+    /// the common ngrams counts them as unknown to the corpus, which lowers the scores of
+    /// the candidates sharing them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["cab", "cad", "cam", "cat"]);
+    ///
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap()
+    ///     .set_max_ngram_degree(MaxNgramDegree::Custom(1));
+    /// assert!(config.score_common_ngrams());
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+    /// assert_eq!(results.best_score(), Some(1.0));
+    ///
+    /// let config = config.set_score_common_ngrams(false);
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+    /// assert_eq!(results[0].key(), &"cat");
+    /// assert!(results[0].score() < 1.0);
+    /// ```
+    pub fn set_score_common_ngrams(mut self, score_common_ngrams: bool) -> Self {
+        self.search_config = self
+            .search_config
+            .set_score_common_ngrams(score_common_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns whether the ngrams exceeding the maximum degree are used to score the candidates.
+    pub fn score_common_ngrams(&self) -> bool {
+        self.search_config.score_common_ngrams()
+    }
+
+    #[inline(always)]
+    /// Set the minimum number of distinct ngrams a candidate must share with the query
+    /// to be scored.
+    ///
+    /// # Arguments
+    /// * `minimum_shared_ngrams` - The minimum number of shared ngrams, one by default.
+    ///
+    /// # Implementation details
+    /// The candidates are the keys sharing at least one of the query ngrams not exceeding
+    /// the maximum ngram degree, and scoring a candidate sharing a single ngram with a long
+    /// query is usually wasted work, as its score is low. The candidates sharing fewer of
+    /// these ngrams than the provided minimum are skipped with an integer comparison before
+    /// computing their score. Furthermore, a key sharing at least `m` of the `n` query
+    /// ngrams contains one of the first `n - m + 1` of them, so that the keys containing
+    /// only the last ngrams are never gathered.
+    ///
+    /// The recall loss is bounded: when the `n` query ngrams are distinct and none of them
+    /// exceeds the maximum ngram degree, a skipped candidate shares at most `m - 1` ngrams
+    /// with the query, so that with a warp of one its score is at most `(m - 1) / n`, and
+    /// the results above a minimum similarity score of `(m - 1) / n` are unchanged. Larger
+    /// warps reward the shared ngrams more, and the bound is then `1 - (1 - (m - 1) / n)^warp`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> =
+    ///     Corpus::from(["acanthocephala", "acanthus", "cephalopod"]);
+    ///
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    /// assert_eq!(config.minimum_shared_ngrams(), 1);
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("acanthocephala", config);
+    /// assert_eq!(results.len(), 3);
+    ///
+    /// let config = config.set_minimum_shared_ngrams(6);
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("acanthocephala", config);
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].key(), &"acanthocephala");
+    /// ```
+    pub fn set_minimum_shared_ngrams(mut self, minimum_shared_ngrams: usize) -> Self {
+        self.search_config = self
+            .search_config
+            .set_minimum_shared_ngrams(minimum_shared_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the minimum number of distinct ngrams a candidate must share with the query.
+    pub fn minimum_shared_ngrams(&self) -> usize {
+        self.search_config.minimum_shared_ngrams()
+    }
+
     #[inline(always)]
     /// Set the warp factor to use in the trigram similarity calculation.
     ///
@@ -160,12 +318,27 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.ngram_search("Cat", NgramSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     /// ```
     ///
+    /// The type of the scores can also be selected with the turbofish syntax. With the
+    /// `half` feature, the `f16` and `bf16` types halve the memory of the scores with
+    /// respect to `f32`, at the cost of their precision.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results = corpus.ngram_search::<f64>("Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert_eq!(results[0].score(), 1.0_f64);
+    /// ```
+    ///
     /// Now let's proceed with an example to highlight the importance of normalizing the input.
     /// In this case, always using the default search configurations which set the minimum similarity
     /// score to 0.7 and the maximum number of results to 10, we observe that the search for "catt"
@@ -177,7 +350,7 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.ngram_search("catt", NgramSearchConfig::default());
     ///
     /// assert!(results.is_empty());
@@ -193,7 +366,7 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.ngram_search("catt", NgramSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
@@ -208,7 +381,7 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<ASCIIChar>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.ngram_search("Cat", NgramSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
@@ -224,19 +397,16 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<u8>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.ngram_search("Cat", NgramSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     /// ```
-    pub fn ngram_search<KR, F: Float>(
+    pub fn ngram_search<F: Float>(
         &self,
-        key: KR,
+        key: impl AsRef<K>,
         mut config: NgramSearchConfig<i32, F>,
-    ) -> SearchResults<'_, KS, NG, F>
-    where
-        KR: AsRef<K>,
-    {
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
         config = config.set_warp(2).unwrap();
         self.ngram_search_with_warp(key, config)
     }
@@ -248,6 +418,28 @@ where
     /// * `key` - The key to search for in the corpus
     /// * `config` - The configuration for the search.
     ///
+    /// # Implementation details
+    /// The ngrams of the query are counted with `Key::counts`, and are compared as a
+    /// multiset against the cooccurrences of the ngrams of each key: a shared ngram
+    /// contributes the minimum of its count in the query and of its cooccurrence in the
+    /// key. Differently from a set-based matching, where each distinct ngram counts once,
+    /// a query repeating an ngram, such as "aaaa", prefers the keys repeating it as many
+    /// times over the keys containing it once or many more times.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["aa", "aaaa", "aaaaaaaa"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search_with_warp("aaaa", config);
+    ///
+    /// assert_eq!(results[0].key(), &"aaaa");
+    /// assert_eq!(results[0].score(), 1.0);
+    /// ```
+    ///
     /// # Examples
     /// In this example we use the ANIMALS dataset shipped with the library to search for similar keys,
     /// using the version of the search with a custom warp factor.
@@ -259,7 +451,7 @@ where
     ///
     /// let config = NgramSearchConfig::default().set_warp(2.5).unwrap();
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> = corpus.ngram_search_with_warp("Cat", config);
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search_with_warp("Cat", config);
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     /// ```
@@ -267,108 +459,1723 @@ where
         &self,
         key: KR,
         config: NgramSearchConfig<W, F>,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        self.ngram_search_ngram_counts(self.key_counts(key), config)
+    }
+
+    #[inline(always)]
+    /// Returns the best matches of the provided key, scored with the provided warp factor.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `warp` - The warp factor, in the range 1 to 3.
+    /// * `config` - The configuration for the search, whose warp factor is replaced.
+    ///
+    /// # Raises
+    /// * If the warp factor is not in the range 1 to 3.
+    ///
+    /// # Implementation details
+    /// This is the `similarity_to` method of the legacy API, with the score computed as in
+    /// the `WarpMetric`: with `s` the number of shared ngrams and `a` the number of ngrams
+    /// in the union of the query and the key, the score is `(a^w - (a - s)^w) / a^w`.
+    /// Since it is equal to `1 - (1 - s / a)^w`, the warp factor does not change the
+    /// ranking of the keys, while a warp factor greater than one raises their scores, and
+    /// therefore the number of keys above the minimum similarity score.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.5)
+    ///     .unwrap();
+    ///
+    /// let unwarped: SearchResults<&&str, f32> = corpus.search_warp("Catt", 1, config).unwrap();
+    /// let warped: SearchResults<&&str, f32> = corpus.search_warp("Catt", 3, config).unwrap();
+    ///
+    /// assert_eq!(warped[0].key(), &"Cat");
+    /// assert!(warped[0].score() > unwarped[0].score());
+    /// assert!(warped.len() >= unwarped.len());
+    ///
+    /// assert!(corpus.search_warp("Catt", 4, config).is_err());
+    /// ```
+    pub fn search_warp<KR, W, W2: Copy, F: Float>(
+        &self,
+        key: KR,
+        warp: W,
+        config: NgramSearchConfig<W2, F>,
+    ) -> Result<SearchResults<KS::KeyRef<'_>, F>, &'static str>
     where
         KR: AsRef<K>,
+        W: Copy + TryInto<Warp<W>, Error = &'static str>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        Ok(self.ngram_search_with_warp(key, config.set_warp(warp)?))
+    }
+
+    /// Returns the best matches of the query with the provided ngram counts, scored as
+    /// in the `ngram_search_with_warp` method.
+    ///
+    /// # Arguments
+    /// * `ngram_counts` - The counts of the ngrams of the query.
+    /// * `config` - The configuration for the search.
+    pub(crate) fn ngram_search_ngram_counts<W: Copy, F: Float>(
+        &self,
+        ngram_counts: HashMap<NG, usize, FxBuildHasher>,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
         Warp<W>: NgramSimilarity + Copy,
     {
         let warp: Warp<W> = config.warp();
-        self.search(
-            key,
+        self.search_ngram_counts(
+            ngram_counts,
             config.into(),
             move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
                 warp.ngram_similarity(query, ngrams)
             },
+            |_| true,
         )
     }
-}
 
-#[cfg(feature = "rayon")]
-impl<KS, NG, K, G> Corpus<KS, NG, K, G>
-where
-    NG: Ngram + Send + Sync,
-    <NG as Ngram>::G: Send + Sync,
-    <NG as Ngram>::SortedStorage: Send + Sync,
-    KS: Keys<NG> + Send + Sync,
-    for<'a> KS::KeyRef<'a>: AsRef<K> + Send + Sync,
-    <<KS as Keys<NG>>::K as Key<NG, <NG as Ngram>::G>>::Ref: Send + Sync,
-    K: Key<NG, NG::G> + ?Sized + Send + Sync,
-    G: WeightedBipartiteGraph + Send + Sync,
-{
     #[inline(always)]
-    /// Returns the number of ngrams from a given key.
+    /// Returns the best matches among the keys accepted by the provided filter, scored
+    /// with the warped ngram similarity of the configuration.
     ///
     /// # Arguments
     /// * `key` - The key to search for in the corpus
     /// * `config` - The configuration for the search.
+    /// * `filter` - A function that returns whether a given key id should be scored.
+    pub(crate) fn warped_search_with_filter<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        filter: impl Fn(usize) -> bool,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let warp: Warp<W> = config.warp();
+        self.search_with_filter(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+            filter,
+        )
+    }
+
+    /// Returns the best matches of the query with the provided ngram counts.
+    ///
+    /// # Arguments
+    /// * `ngram_counts` - The counts of the ngrams of the query.
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The results are the ones of the `ngram_search_with_warp` method for a query with
+    /// the provided ngrams, without splitting a key into its grams. The caller is therefore
+    /// responsible for building the ngrams as the corpus built the ngrams of its keys, with
+    /// the same normalization, such as the lowercasing of a `Lowercase` corpus, and with
+    /// the same padding, as returned by `Corpus::padding`. Otherwise, the ngrams of the
+    /// query may not match the ones of the keys even when they come from the same string.
+    /// The ngrams with a count of zero are ignored, and a query without ngrams has no results.
     ///
     /// # Examples
-    /// This is the concurrent version of the `ngram_search` method.
-    /// Please look at the documentation of the `ngram_search` method for the extended
-    /// documentation.
     ///
     /// ```rust
     /// use ngrammatic::prelude::*;
+    /// use std::collections::HashMap;
     ///
-    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default();
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
-    ///     corpus.ngram_par_search("Cat", NgramSearchConfig::default());
+    /// // The ngrams of "Cat", padded as the keys of the corpus.
+    /// let ngram_counts: HashMap<TriGram<char>, usize> = [
+    ///     ['\0', '\0', 'C'],
+    ///     ['\0', 'C', 'a'],
+    ///     ['C', 'a', 't'],
+    ///     ['a', 't', '\0'],
+    ///     ['t', '\0', '\0'],
+    /// ]
+    /// .into_iter()
+    /// .map(|ngram| (ngram, 1))
+    /// .collect();
+    ///
+    /// let results = corpus.search_ngrams(&ngram_counts, config);
     ///
     /// assert_eq!(results[0].key(), &"Cat");
+    /// assert_eq!(results, corpus.ngram_search("Cat", config));
+    /// assert!(corpus.search_ngrams(&HashMap::new(), config).is_empty());
     /// ```
-    pub fn ngram_par_search<KR, F: Float>(
+    pub fn search_ngrams<S: BuildHasher, W: Copy, F: Float>(
         &self,
-        key: KR,
-        mut config: NgramSearchConfig<i32, F>,
-    ) -> SearchResults<'_, KS, NG, F>
+        ngram_counts: &HashMap<NG, usize, S>,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
     where
-        KR: AsRef<K> + Send + Sync,
+        Warp<W>: NgramSimilarity + Copy,
     {
-        config = config.set_warp(2).unwrap();
-        self.ngram_par_search_with_warp(key, config)
+        let ngram_counts: HashMap<NG, usize, FxBuildHasher> = ngram_counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(ngram, count)| (*ngram, *count))
+            .collect();
+        if ngram_counts.is_empty() {
+            return SearchResults::default();
+        }
+        self.ngram_search_ngram_counts(ngram_counts, config)
     }
 
-    #[inline(always)]
-    /// Returns the number of ngrams from a given key.
+    /// Returns the best matches along with the ngrams they share with the query.
     ///
     /// # Arguments
     /// * `key` - The key to search for in the corpus
     /// * `config` - The configuration for the search.
     ///
+    /// # Implementation details
+    /// The results are the same ones of the `ngram_search_with_warp` method. Then, the
+    /// ngrams of each result are intersected with the ngrams of the query, so that the
+    /// cost of the explanation is only paid for the returned results. The shared ngrams
+    /// are sorted by their number of occurrences in the key, from the highest, and then
+    /// in ascending order, so that the strongest matches can be highlighted first.
+    ///
     /// # Examples
-    /// This is the concurrent version of the `ngram_search_with_warp` method.
-    /// Please look at the documentation of the `ngram_search_with_warp` method for the extended
-    /// documentation.
     ///
     /// ```rust
     /// use ngrammatic::prelude::*;
     ///
-    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
-    ///
-    /// let config = NgramSearchConfig::default().set_warp(2.5).unwrap();
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> = corpus.ngram_par_search_with_warp("Cat", config);
+    /// let results: SearchResults<&&str, f32, Vec<TriGram<char>>> =
+    ///     corpus.search_with_explanation("Cat", NgramSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
+    /// assert!(results[0].shared_ngrams().contains(&['C', 'a', 't']));
     /// ```
-    pub fn ngram_par_search_with_warp<KR, W, F: Float>(
+    pub fn search_with_explanation<KR, W: Copy, F: Float>(
         &self,
         key: KR,
         config: NgramSearchConfig<W, F>,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F, Vec<NG>>
     where
-        KR: AsRef<K> + Send + Sync,
-        W: Copy + TryInto<Warp<W>, Error = &'static str>,
-        Warp<W>: NgramSimilarity + Copy + Send + Sync,
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
     {
-        let warp: Warp<W> = config.warp();
-        self.par_search(
-            key,
-            config.into(),
+        let query_counts = self.key_counts(key.as_ref());
+        self.ngram_search_with_warp(key, config)
+            .into_iter()
+            .map(|result| {
+                let result_key: &K = result.key_ref().as_ref();
+                let mut shared_ngrams: Vec<(NG, usize)> = self
+                    .key_counts(result_key)
+                    .into_iter()
+                    .filter(|(ngram, _)| query_counts.contains_key(ngram))
+                    .collect();
+                shared_ngrams.sort_unstable_by(|(left, left_count), (right, right_count)| {
+                    right_count.cmp(left_count).then(left.cmp(right))
+                });
+                result.explained(shared_ngrams.into_iter().map(|(ngram, _)| ngram).collect())
+            })
+            .collect()
+    }
+
+    /// Returns the `k` best matches, however low their scores are.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `k` - The maximum number of results to return.
+    ///
+    /// # Implementation details
+    /// The keys are scored as in the `ngram_search` method, without a minimum similarity
+    /// score, while only the best `k` results are kept in the heap. The keys that do not
+    /// share any ngram with the query are never scored, as their score would be zero.
+    /// The keys with the same score are ranked by their id, so that the results are
+    /// reproducible across runs, including which keys are kept when the ties exceed `k`.
+    ///
+    /// # Examples
+    /// In this example, we look for a "did you mean" suggestion for a misspelled query.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.knn("Elephnt", 3);
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// assert_eq!(results[0].key(), &"Elephant");
+    /// ```
+    pub fn knn<F: Float>(&self, key: impl AsRef<K>, k: usize) -> SearchResults<KS::KeyRef<'_>, F> {
+        let config: NgramSearchConfig<i32, F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(F::ZERO)
+            .unwrap()
+            .set_maximum_number_of_results(k);
+        let warp = config.warp();
+        let config: SearchConfig<F> = config.into();
+        let key: &K = key.as_ref();
+        let mut heap = SearchResultsHeap::new(k);
+
+        self.for_each_scored_key(
+            &self.ngram_ids_from_ngram_counts(self.key_counts(key)),
+            config,
             move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
                 warp.ngram_similarity(query, ngrams)
             },
-        )
+            |_| true,
+            |key_id, score| {
+                if score > F::ZERO {
+                    heap.push(key_id, score);
+                }
+            },
+        );
+
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    /// Returns the `limit` keys most similar to the key with the provided id.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key whose neighbors are returned.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Implementation details
+    /// The stored ngrams of the key and their cooccurrences are used directly as the
+    /// query, so that the key is neither retrieved nor split into ngrams again, which
+    /// is useful when clustering the keys of a corpus. The other keys are then scored as
+    /// in the `knn` method, and the key itself is never returned, while the keys equal
+    /// to it are. The neighbors of a removed key can still be computed, as its ngrams
+    /// are stored until the tombstones are purged.
+    ///
+    /// # Panics
+    /// * If the key id is not smaller than `number_of_key_ids`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let key_id = ANIMALS.iter().position(|animal| *animal == "Elephant").unwrap();
+    ///
+    /// let neighbors: SearchResults<&&str, f32> = corpus.neighbors(key_id, 5);
+    ///
+    /// assert_eq!(neighbors.len(), 5);
+    /// assert!(neighbors.iter().all(|result| result.key_id() != key_id));
+    ///
+    /// // The same neighbors are found by searching the key, which is its own best match.
+    /// let expected: SearchResults<&&str, f32> =
+    ///     corpus.knn("Elephant", 6).into_iter().skip(1).collect();
+    /// assert_eq!(neighbors, expected);
+    /// ```
+    pub fn neighbors<F: Float>(
+        &self,
+        key_id: usize,
+        limit: usize,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        assert!(
+            key_id < self.number_of_key_ids(),
+            "The key id {} is out of bounds, as there are {} key ids.",
+            key_id,
+            self.number_of_key_ids()
+        );
+        let config: NgramSearchConfig<i32, F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(F::ZERO)
+            .unwrap()
+            .set_maximum_number_of_results(limit);
+        let warp = config.warp();
+        let config: SearchConfig<F> = config.into();
+        let mut heap = SearchResultsHeap::new(limit);
+
+        let query_hashmap = QueryHashmap::from_ngram_ids(
+            self.ngram_ids_and_cooccurrences_from_key(key_id).collect(),
+            0,
+        );
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+            |other_key_id| other_key_id != key_id,
+            |other_key_id, score| {
+                if score > F::ZERO {
+                    heap.push(other_key_id, score);
+                }
+            },
+        );
+
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    /// Returns the keys sharing at least `min_shared` distinct ngrams with the query,
+    /// ranked by the number of shared ngrams and then by their similarity.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `min_shared` - The minimum number of distinct ngrams a key must share with the
+    /// query to be returned, where zero and one are equivalent.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Implementation details
+    /// Whether a key is returned is decided by an integer comparison of its number of
+    /// shared ngrams, as counted by the `set_minimum_shared_ngrams` option of the search
+    /// configuration, and never by its score, so that no threshold on the similarity
+    /// metric has to be tuned. All of the ngrams of the query generate candidates, however
+    /// common they are. The keys are still scored as in the `ngram_search` method, with a
+    /// warp factor of two, to break the ties among the keys sharing as many ngrams, and
+    /// then by their id. The best `limit` keys are kept in a bounded heap, ranked by their
+    /// number of shared ngrams before their score, so that the memory does not grow with
+    /// the number of accepted keys.
+    ///
+    /// This is meant as a coarse first pass, such as the blocking step of record linkage,
+    /// to be followed by a re-ranking stage: the ids of the returned keys can restrict a
+    /// regular search to the block with the `ngram_search_within` method, and the results
+    /// can be sorted again by their score or by an edit distance from the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use sux::bits::BitVec;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// // The block of the keys sharing at least three trigrams with the query.
+    /// let block: SearchResults<&&str, f32, SharedNgramCount> =
+    ///     corpus.search_by_count("Elephant seal", 3, 100);
+    ///
+    /// assert!(block.iter().all(|result| result.shared_ngram_count() >= 3));
+    /// assert!(block
+    ///     .windows(2)
+    ///     .all(|pair| pair[0].shared_ngram_count() >= pair[1].shared_ngram_count()));
+    ///
+    /// // The second stage only scores the keys of the block.
+    /// let mut allowed = BitVec::new(corpus.number_of_key_ids());
+    /// for result in block.iter() {
+    ///     allowed.set(result.key_id(), true);
+    /// }
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.5_f32)
+    ///     .unwrap();
+    /// let results = corpus.ngram_search_within("Elephant seal", config, &allowed);
+    ///
+    /// assert_eq!(results[0].key(), &"Elephant Seal");
+    /// ```
+    pub fn search_by_count<F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        min_shared: usize,
+        limit: usize,
+    ) -> SearchResults<KS::KeyRef<'_>, F, SharedNgramCount> {
+        let config: NgramSearchConfig<i32, F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(F::ZERO)
+            .unwrap()
+            .set_max_ngram_degree(MaxNgramDegree::None)
+            .set_minimum_shared_ngrams(min_shared);
+        let warp = config.warp();
+        let config: SearchConfig<F> = config.into();
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        // The best accepted keys, ranked by their number of shared ngrams and their score.
+        let mut heap: SearchResultsHeap<F, SharedNgramCount> =
+            SearchResultsHeap::prioritized(limit);
+
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(key));
+        let query_ngram_ids: Vec<usize> = query_hashmap.ngram_ids().collect();
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+            |_| true,
+            |key_id, score| {
+                let shared_ngrams = self.number_of_shared_ngram_ids(&query_ngram_ids, key_id);
+                heap.push_with_priority(SharedNgramCount(shared_ngrams), key_id, score);
+            },
+        );
+
+        heap.into_explained_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    /// Writes the ids and scores of the best matches into the provided buffers.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `threshold` - The minimum similarity value for a result to be included in the
+    /// output. This value should be in the range 0.0 to 1.0.
+    /// * `k` - The maximum number of results to write.
+    /// * `out_ids` - The buffer where the ids of the matching keys are written.
+    /// * `out_scores` - The buffer where the scores of the matching keys are written.
+    ///
+    /// # Returns
+    /// The number of results written, sorted from the highest to the lowest score
+    /// in the first positions of both buffers.
+    ///
+    /// # Implementation details
+    /// The keys are scored as in the `ngram_search` method, with a warp factor of two,
+    /// but the results are kept sorted directly in the provided buffers instead of in
+    /// a heap, so that no vector of results is allocated per query. The results with the
+    /// same score are ranked by their key id, from the smallest one, so that the ids and
+    /// their order are the same as the ones of `ngram_search`. This is meant for
+    /// services handing the ids over to another layer, such as an FFI or RPC boundary.
+    /// Note that the tokenization of the query still allocates its ngram counts.
+    ///
+    /// # Panics
+    /// * If either buffer is shorter than `k`.
+    /// * If the threshold is negative or NaN.
+    /// * If a key id does not fit in an `u32`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let mut ids = [0_u32; 10];
+    /// let mut scores = [0.0_f32; 10];
+    /// let number_of_results = corpus.search_into("Cat", 0.7, 10, &mut ids, &mut scores);
+    ///
+    /// assert!(number_of_results > 0);
+    /// assert_eq!(corpus.key_from_id(ids[0] as usize), &"Cat");
+    /// assert_eq!(scores[0], 1.0);
+    /// ```
+    pub fn search_into<F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        threshold: F,
+        k: usize,
+        out_ids: &mut [u32],
+        out_scores: &mut [F],
+    ) -> usize {
+        assert!(
+            out_ids.len() >= k && out_scores.len() >= k,
+            "The output buffers must have a length of at least k."
+        );
+        let config: SearchConfig<F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(threshold)
+            .expect("The threshold must be a non-negative number.")
+            .set_maximum_number_of_results(k)
+            .into();
+        if k == 0 {
+            return 0;
+        }
+
+        let warp: Warp<i32> = Warp::try_from(2).unwrap();
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return 0;
+        }
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(key));
+        let mut number_of_results = 0;
+
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+            |_| true,
+            |key_id, score| {
+                // As in the heap of the `ngram_search` method, a result ranks before
+                // another one when it has a higher score, or the same score and a smaller
+                // key id, so that the ties do not depend on the order the keys are visited.
+                let ranks_before = |other_score: F, other_id: u32| {
+                    score > other_score || (score == other_score && key_id < other_id as usize)
+                };
+                // When the buffers are full, the result must beat the worst one.
+                if number_of_results == k {
+                    if !ranks_before(out_scores[k - 1], out_ids[k - 1]) {
+                        return;
+                    }
+                    number_of_results -= 1;
+                }
+                // We shift the results ranking after the new one to make room for it.
+                let mut position = number_of_results;
+                while position > 0 && ranks_before(out_scores[position - 1], out_ids[position - 1])
+                {
+                    out_ids[position] = out_ids[position - 1];
+                    out_scores[position] = out_scores[position - 1];
+                    position -= 1;
+                }
+                out_ids[position] = u32::try_from(key_id).expect("The key id must fit in an u32.");
+                out_scores[position] = score;
+                number_of_results += 1;
+            },
+        );
+
+        number_of_results
+    }
+
+    /// Returns the best matches, with the scores multiplied by per-key boosts.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `threshold` - The minimum similarity value for a result to be included in the
+    /// output, before applying the boosts. This value should be in the range 0.0 to 1.0.
+    /// * `k` - The maximum number of results to return.
+    /// * `boosts` - The score multipliers of the boosted keys, indexed by key id.
+    ///
+    /// # Implementation details
+    /// The keys are scored as in the `ngram_search` method, with a warp factor of two,
+    /// and the score of each key passing the threshold is multiplied by its boost, or
+    /// by one if the key is not boosted. The best `k` keys are then selected by their
+    /// boosted score, so that promoted keys can rise above keys with a higher similarity
+    /// without changing the corpus. Since the threshold is checked before boosting, a
+    /// boost cannot let in a key that is not similar enough to the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.search_boosted("Cat", 0.7, 10, &HashMap::new());
+    /// let expected: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("Cat", NgramSearchConfig::default());
+    /// assert_eq!(results, expected);
+    /// ```
+    pub fn search_boosted<F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        threshold: F,
+        k: usize,
+        boosts: &HashMap<usize, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        let config: SearchConfig<F> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(threshold)
+            .unwrap()
+            .set_maximum_number_of_results(k)
+            .into();
+        let warp: Warp<i32> = Warp::try_from(2).unwrap();
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(key));
+        let mut heap = SearchResultsHeap::new(k);
+
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+            |_| true,
+            |key_id, score| {
+                let boost = boosts.get(&key_id).copied().unwrap_or(F::ONE);
+                heap.push(key_id, score * boost);
+            },
+        );
+
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    #[inline(always)]
+    /// Returns the best matches among the keys whose id is set in the provided allowlist.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `allowed` - A bit vector with the same length as the number of key ids, where
+    /// the bits set to one mark the key ids that may be scored.
+    ///
+    /// # Implementation details
+    /// The keys reached via the shared ngrams are checked against the allowlist before
+    /// any similarity is computed, so keys outside of the allowlist are never scored.
+    /// This is useful when a prior stage has already narrowed down the candidates, for
+    /// instance by category. The allowlist is indexed by key id, so it must be sized by
+    /// `number_of_key_ids`, which also counts the removed keys.
+    ///
+    /// # Panics
+    /// * If the length of `allowed` is smaller than the number of key ids and a key
+    /// with an id out of its bounds is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use sux::bits::BitVec;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0_f32)
+    ///     .unwrap()
+    ///     .set_maximum_number_of_results(100);
+    ///
+    /// // We only allow the even key ids.
+    /// let mut allowed = BitVec::new(corpus.number_of_key_ids());
+    /// for key_id in (0..corpus.number_of_key_ids()).step_by(2) {
+    ///     allowed.set(key_id, true);
+    /// }
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_within("cat", config, &allowed);
+    ///
+    /// assert!(!results.is_empty());
+    /// for result in results {
+    ///     let key_id = ANIMALS.iter().position(|key| key == result.key()).unwrap();
+    ///     assert_eq!(key_id % 2, 0);
+    /// }
+    /// ```
+    pub fn ngram_search_within<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        allowed: &BitVec,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        self.warped_search_with_filter(key, config, |key_id| allowed.get(key_id))
+    }
+
+    #[inline(always)]
+    /// Returns the best matches, weighting the contribution of each ngram with the provided function.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `weights` - The function returning the weight of an ngram, given its id.
+    ///
+    /// # Implementation details
+    /// Each occurrence of an ngram contributes its weight, instead of one, to both the
+    /// shared and the total ngrams of the ngram similarity. With all weights equal to one,
+    /// the scores are the same of `ngram_search_with_warp`, while by providing as weights
+    /// the inverse document frequencies of the ngrams one obtains an IDF-weighted similarity.
+    /// This allows domain experts to hand-tune the importance of each ngram, for instance
+    /// of specific morphemes. The ngrams of the query that do not appear in the corpus have
+    /// no id and always count as one, and so do the ngrams only appearing in the keys
+    /// inserted since the corpus was last compacted, whose ids are not smaller than
+    /// `number_of_ngrams`. Weights should be non-negative.
+    ///
+    /// # Examples
+    /// The keys "bat" and "cab" share the same number of trigrams with "cat", so with
+    /// uniform weights they are tied. Up-weighting the trigram "at\0" favours "bat".
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cab", "bat"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0_f32)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_weights("cat", config, |_| 1.0);
+    /// assert_eq!(results[0].score(), results[1].score());
+    ///
+    /// let boosted = corpus.ngram_id_from_ngram(['a', 't', '\0']).unwrap();
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_weights("cat", config, |ngram_id| {
+    ///         if ngram_id == boosted {
+    ///             5.0
+    ///         } else {
+    ///             1.0
+    ///         }
+    ///     });
+    /// assert_eq!(results[0].key(), &"bat");
+    /// assert!(results[0].score() > results[1].score());
+    /// ```
+    pub fn ngram_search_with_weights<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        weights: impl Fn(usize) -> F,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy + One + Zero + Three + PartialOrd,
+    {
+        let warp: Warp<W> = config.warp();
+        let number_of_ngrams = self.number_of_ngrams();
+        let weights = move |ngram_id: usize| {
+            if ngram_id < number_of_ngrams {
+                weights(ngram_id)
+            } else {
+                F::ONE
+            }
+        };
+        self.search(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                weighted_ngram_similarity(warp, query, ngrams, &weights)
+            },
+        )
+    }
+
+    #[inline(always)]
+    /// Returns the best matches, weighting the contribution of each ngram with the provided table.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `weights` - The weight of each ngram, indexed by ngram id.
+    ///
+    /// # Raises
+    /// * If the length of the table is not equal to the number of ngrams in the corpus.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cab", "bat"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0_f32)
+    ///     .unwrap();
+    ///
+    /// let mut weights = vec![1.0_f32; corpus.number_of_ngrams()];
+    /// weights[corpus.ngram_id_from_ngram(['\0', 'c', 'a']).unwrap()] = 5.0;
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus
+    ///     .ngram_search_with_weight_table("cat", config, &weights)
+    ///     .unwrap();
+    /// assert_eq!(results[0].key(), &"cab");
+    ///
+    /// assert!(corpus
+    ///     .ngram_search_with_weight_table("cat", config, &weights[1..])
+    ///     .is_err());
+    /// ```
+    pub fn ngram_search_with_weight_table<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        weights: &[F],
+    ) -> Result<SearchResults<KS::KeyRef<'_>, F>, &'static str>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy + One + Zero + Three + PartialOrd,
+    {
+        if weights.len() != self.number_of_ngrams() {
+            return Err("The number of weights must be equal to the number of ngrams.");
+        }
+        Ok(self.ngram_search_with_weights(key, config, |ngram_id| weights[ngram_id]))
+    }
+
+    /// Returns the best matches for the positive key, penalizing those similar to the negative keys.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `negatives` - The keys whose similar matches should be demoted.
+    /// * `negative_weight` - The weight of the penalty of each negative key.
+    /// * `config` - The configuration for the search. Its warp factor is ignored, as the
+    /// candidates are scored with the cosine similarity.
+    ///
+    /// # Implementation details
+    /// The candidates are the keys sharing at least an ngram with the positive key, as
+    /// in `ngram_search_with_warp`. Each candidate is scored with the cosine similarity of
+    /// the `CosineMetric` to the positive key, minus the sum of its cosine similarities to
+    /// each of the negative keys multiplied by `negative_weight`, and the score is then
+    /// clamped to the range from zero to one. A weight of zero yields the same scores of
+    /// `ngram_search_with_metric` with the `CosineMetric`, while a weight of one removes
+    /// the whole similarity to a negative key. The minimum similarity score of the
+    /// configuration is applied to the penalized score.
+    ///
+    /// # Panics
+    /// * If `negative_weight` is NaN or negative.
+    ///
+    /// # Examples
+    /// Without negative keys, "dogfish" and "catfish" are equally similar to "fish".
+    /// Penalizing the keys similar to "dog" demotes "dogfish" below "goldfish", and
+    /// leaves the other keys unchanged.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["fish", "dogfish", "catfish", "goldfish"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0_f32)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_negatives("fish", &[] as &[&str], 0.5, config);
+    /// assert_eq!(
+    ///     results.keys().collect::<Vec<_>>(),
+    ///     vec![&"fish", &"dogfish", &"catfish", &"goldfish"]
+    /// );
+    ///
+    /// let penalized: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_with_negatives("fish", &["dog"], 0.5, config);
+    /// assert_eq!(
+    ///     penalized.keys().collect::<Vec<_>>(),
+    ///     vec![&"fish", &"catfish", &"goldfish", &"dogfish"]
+    /// );
+    /// assert!(penalized[3].score() < results[1].score());
+    /// assert_eq!(penalized[1].score(), results[2].score());
+    /// ```
+    pub fn ngram_search_with_negatives<KR, NR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        negatives: &[NR],
+        negative_weight: F,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        NR: AsRef<K>,
+    {
+        assert!(
+            !negative_weight.is_nan() && negative_weight >= F::ZERO,
+            "The negative weight must be a non-negative number."
+        );
+        let negatives: Vec<QueryHashmap> = negatives
+            .iter()
+            .map(|negative| self.ngram_ids_from_ngram_counts(self.key_counts(negative.as_ref())))
+            .collect();
+        let shared = RefCell::new(Vec::new());
+        self.search(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                let mut shared = shared.borrow_mut();
+                let mut score: F =
+                    metric_similarity(&CosineMetric, query, ngrams.clone(), &mut shared);
+                for negative in &negatives {
+                    score = score
+                        - negative_weight
+                            * metric_similarity::<F, _, _>(
+                                &CosineMetric,
+                                negative,
+                                ngrams.clone(),
+                                &mut shared,
+                            );
+                }
+                if score < F::ZERO {
+                    F::ZERO
+                } else if score > F::ONE {
+                    F::ONE
+                } else {
+                    score
+                }
+            },
+        )
+    }
+
+    /// Returns the requested page of the best matches for the provided key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search. Its maximum number of results is
+    /// overridden to cover the requested page.
+    /// * `page` - The zero-based index of the page to return.
+    /// * `page_size` - The number of results in each page.
+    ///
+    /// # Implementation details
+    /// The search computes the top `(page + 1) * page_size` results with the same bounded
+    /// heap used by `ngram_search_with_warp`, and then returns the slice of results of the
+    /// requested page. Therefore, the cost of the search grows with the depth of the page,
+    /// as for deep pages many results need to be computed and discarded. The last page may
+    /// contain fewer than `page_size` results, and pages past the last one are empty.
+    ///
+    /// # Panics
+    /// * If `page_size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0_f32)
+    ///     .unwrap();
+    ///
+    /// let first_page: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_page("cat", config, 0, 5);
+    /// let second_page: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_page("cat", config, 1, 5);
+    ///
+    /// assert_eq!(first_page.len(), 5);
+    /// assert_eq!(second_page.len(), 5);
+    /// assert_eq!(first_page[0].key(), &"Cat");
+    /// assert!(first_page[4].score() >= second_page[0].score());
+    /// ```
+    pub fn ngram_search_page<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        page: usize,
+        page_size: usize,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        assert!(page_size > 0, "The page size must be positive.");
+        let config = config.set_maximum_number_of_results((page + 1) * page_size);
+        let mut results: Vec<_> = self.ngram_search_with_warp(key, config).into();
+        let start = (page * page_size).min(results.len());
+        results.drain(..start);
+        results.into()
+    }
+
+    #[inline(always)]
+    /// Returns the best matches by scanning every key of the corpus, without using the graph.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// This method scores the query directly against every key, retrieved via `key_from_id`,
+    /// instead of walking the bipartite graph from the query ngrams. It yields the same scores
+    /// as `ngram_search_with_warp`, but its cost is linear in the total length of the keys.
+    /// For corpora of at most a few dozen short keys, this scan avoids the graph traversal and
+    /// the candidate deduplication overheads and is generally as fast or faster, while for
+    /// larger corpora the graph-based search quickly becomes the better choice as it only
+    /// visits the keys sharing at least one ngram with the query. Although every key is
+    /// visited, the `max_ngram_degree` and `minimum_shared_ngrams` options select the same
+    /// candidates as in the graph-based search, so that both return the same results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search_small("cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"cat");
+    /// ```
+    pub fn ngram_search_small<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let warp: Warp<W> = config.warp();
+        self.search_small(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, candidate: &QueryHashmap| {
+                warp.ngram_similarity(query, candidate.ngram_ids_and_counts())
+            },
+        )
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    NG::G: CharLike,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the best matches of the lowercased query, scored as in the `ngram_search`
+    /// method.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus, in any case.
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The grams of the ngrams of the query are lowercased after the query is normalized
+    /// by the key type of the corpus, so that the case of the query can be ignored even
+    /// when the key type of the corpus does not fold it, as `Lowercase<str>` does. Only
+    /// the query is lowercased: the keys of the corpus must be lowercased as well when
+    /// the corpus is built, otherwise the keys with uppercase grams do not match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let keys: Vec<String> = ["Nice", "Car", "mice"]
+    ///     .iter()
+    ///     .map(|key| key.to_lowercase())
+    ///     .collect();
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+    ///
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.2)
+    ///     .unwrap();
+    ///
+    /// // The search is case-sensitive, so the uppercase query does not match.
+    /// assert!(corpus.ngram_search("ICE", config).is_empty());
+    ///
+    /// let results: SearchResults<&String, f32> = corpus.search_case_insensitive("ICE", config);
+    ///
+    /// assert_eq!(results.len(), 2);
+    /// assert!(results.iter().any(|result| result.key().as_str() == "nice"));
+    /// assert!(results.iter().any(|result| result.key().as_str() == "mice"));
+    /// ```
+    pub fn search_case_insensitive<F: Float>(
+        &self,
+        key: impl AsRef<K>,
+        config: NgramSearchConfig<i32, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        let mut ngram_counts: HashMap<NG, usize, FxBuildHasher> =
+            HashMap::with_hasher(FxBuildHasher::default());
+        for (mut ngram, count) in self.key_counts(key.as_ref()) {
+            for i in 0..NG::ARITY {
+                ngram[i] = ngram[i].to_lowercase();
+            }
+            *ngram_counts.entry(ngram).or_insert(0) += count;
+        }
+        self.ngram_search_ngram_counts(ngram_counts, config)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram + Send + Sync,
+    <NG as Ngram>::G: Send + Sync,
+    <NG as Ngram>::SortedStorage: Send + Sync,
+    KS: Keys<NG> + Send + Sync,
+    for<'a> KS::KeyRef<'a>: AsRef<K> + Send + Sync,
+    <<KS as Keys<NG>>::K as Key<NG, <NG as Ngram>::G>>::Ref: Send + Sync,
+    K: Key<NG, NG::G> + ?Sized + Send + Sync,
+    G: WeightedBipartiteGraph + Send + Sync,
+{
+    #[inline(always)]
+    /// Returns the number of ngrams from a given key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Examples
+    /// This is the concurrent version of the `ngram_search` method.
+    /// Please look at the documentation of the `ngram_search` method for the extended
+    /// documentation.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_par_search("Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// ```
+    pub fn ngram_par_search<F: Float>(
+        &self,
+        key: impl AsRef<K> + Send + Sync,
+        mut config: NgramSearchConfig<i32, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        config = config.set_warp(2).unwrap();
+        self.ngram_par_search_with_warp(key, config)
+    }
+
+    #[inline(always)]
+    /// Returns the number of ngrams from a given key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Examples
+    /// This is the concurrent version of the `ngram_search_with_warp` method.
+    /// Please look at the documentation of the `ngram_search_with_warp` method for the extended
+    /// documentation.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
+    ///
+    /// let config = NgramSearchConfig::default().set_warp(2.5).unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_par_search_with_warp("Cat", config);
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// ```
+    pub fn ngram_par_search_with_warp<KR, W, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K> + Send + Sync,
+        W: Copy + TryInto<Warp<W>, Error = &'static str>,
+        Warp<W>: NgramSimilarity + Copy + Send + Sync,
+    {
+        let warp: Warp<W> = config.warp();
+        self.par_search(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                warp.ngram_similarity(query, ngrams)
+            },
+        )
+    }
+
+    #[inline(always)]
+    /// Performs the `ngram_par_search` within the provided thread pool.
+    ///
+    /// # Arguments
+    /// * `pool` - The thread pool to run the search in.
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The parallel work is executed via `ThreadPool::install`, so that it does not
+    /// occupy the threads of the global rayon pool, which may be shared with other
+    /// subsystems.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
+    /// let pool = rayon::ThreadPoolBuilder::new()
+    ///     .num_threads(2)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_par_search_in(&pool, "Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// ```
+    pub fn ngram_par_search_in<F: Float>(
+        &self,
+        pool: &rayon::ThreadPool,
+        key: impl AsRef<K> + Send + Sync,
+        config: NgramSearchConfig<i32, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        pool.install(|| self.ngram_par_search(key, config))
+    }
+
+    #[inline(always)]
+    /// Performs the `ngram_par_search_with_warp` within the provided thread pool.
+    ///
+    /// # Arguments
+    /// * `pool` - The thread pool to run the search in.
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
+    /// let pool = rayon::ThreadPoolBuilder::new()
+    ///     .num_threads(2)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let config = NgramSearchConfig::default().set_warp(2.5).unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_par_search_with_warp_in(&pool, "Cat", config);
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// ```
+    pub fn ngram_par_search_with_warp_in<KR, W, F: Float>(
+        &self,
+        pool: &rayon::ThreadPool,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K> + Send + Sync,
+        W: Copy + Send + TryInto<Warp<W>, Error = &'static str>,
+        Warp<W>: NgramSimilarity + Copy + Send + Sync,
+    {
+        pool.install(|| self.ngram_par_search_with_warp(key, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ngram_search_pages_match_single_search() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let single: SearchResults<&&str, f32> =
+            corpus.ngram_search_with_warp("cat", config.set_maximum_number_of_results(35));
+
+        let mut pages: Vec<SearchResult<&&str, f32>> = Vec::new();
+        for page in 0..5 {
+            let results = corpus.ngram_search_page("cat", config, page, 7);
+            assert!(results.len() <= 7);
+            pages.extend(results);
+        }
+
+        assert_eq!(single.len(), pages.len());
+        // Results with the same score may appear in either order, so we compare the scores.
+        for (single, paged) in single.iter().zip(pages.iter()) {
+            assert_eq!(single.score(), paged.score());
+        }
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn test_f16_search_matches_f32_search() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+
+        for query in ["cat", "dog", "giraffe", "sea lion", "hippopotamus"] {
+            let expected = corpus.ngram_search::<f32>(query, NgramSearchConfig::default());
+            let results = corpus.ngram_search::<half::f16>(query, NgramSearchConfig::default());
+
+            assert_eq!(results.len(), expected.len());
+            // The scores are rounded to the precision of the f16, so the results whose
+            // scores are within the tolerance may appear in either order.
+            for (result, expected) in results.iter().zip(expected.iter()) {
+                assert!((result.score().to_f32() - expected.score()).abs() < 1e-2);
+            }
+            for (key, score) in results.keys().zip(results.scores()) {
+                assert!(expected.keys().zip(expected.scores()).any(
+                    |(expected_key, expected_score)| {
+                        key == expected_key && (score.to_f32() - expected_score).abs() < 1e-2
+                    }
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_into_matches_ngram_search() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+
+        for (query, k) in [("cat", 10), ("dog", 3), ("giraffe", 1), ("zzzz", 5)] {
+            let config = NgramSearchConfig::default()
+                .set_minimum_similarity_score(0.3_f32)
+                .unwrap()
+                .set_maximum_number_of_results(k);
+            let expected: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+
+            // The buffers may be longer than the number of requested results.
+            let mut ids = vec![u32::MAX; k + 2];
+            let mut scores = vec![-1.0_f32; k + 2];
+            let number_of_results = corpus.search_into(query, 0.3, k, &mut ids, &mut scores);
+
+            assert_eq!(number_of_results, expected.len());
+            assert!(ids[number_of_results..].iter().all(|id| *id == u32::MAX));
+            assert!(ids[..number_of_results]
+                .iter()
+                .map(|id| *id as usize)
+                .eq(expected.iter().map(|result| result.key_id())));
+            assert!(scores[..number_of_results]
+                .iter()
+                .copied()
+                .eq(expected.scores()));
+        }
+
+        // The two keys have the same score, and the one with the smallest id is kept,
+        // whichever of the two is visited first.
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["xcat", "catx", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.1_f32)
+            .unwrap();
+        let expected: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+        assert_eq!(expected.len(), 2);
+        assert_eq!(expected[0].score(), expected[1].score());
+        for k in [1, 2] {
+            let mut ids = vec![u32::MAX; k];
+            let mut scores = vec![0.0_f32; k];
+            assert_eq!(corpus.search_into("cat", 0.1, k, &mut ids, &mut scores), k);
+            assert!(ids
+                .iter()
+                .map(|id| *id as usize)
+                .eq(expected.iter().take(k).map(|result| result.key_id())));
+        }
+        assert_eq!(expected[0].key_id(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The threshold must be a non-negative number.")]
+    fn test_search_into_rejects_a_negative_threshold() {
+        let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+        let mut ids = [0; 2];
+        let mut scores = [0.0_f32; 2];
+        corpus.search_into("cat", -0.5, 0, &mut ids, &mut scores);
+    }
+
+    #[test]
+    fn test_search_boosted_promotes_key() {
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cats", "dog"]);
+
+        let unboosted: SearchResults<&&str, f32> =
+            corpus.search_boosted("cat", 0.1, 10, &HashMap::new());
+        assert_eq!(unboosted.len(), 2);
+        assert_eq!(unboosted[0].key(), &"cat");
+        assert_eq!(unboosted[1].key(), &"cats");
+        assert!(unboosted[0].score() > unboosted[1].score());
+
+        // The boost of "cats" is enough to overcome its lower similarity.
+        let boost = 1.5 * unboosted[0].score() / unboosted[1].score();
+        let boosts: HashMap<usize, f32> = [(1, boost)].into_iter().collect();
+        let boosted: SearchResults<&&str, f32> = corpus.search_boosted("cat", 0.1, 10, &boosts);
+        assert_eq!(boosted.len(), 2);
+        assert_eq!(boosted[0].key(), &"cats");
+        assert_eq!(boosted[0].score(), unboosted[1].score() * boost);
+        assert_eq!(boosted[1].key(), &"cat");
+        assert_eq!(boosted[1].score(), unboosted[0].score());
+    }
+
+    #[test]
+    fn test_length_mismatch_penalty_flips_the_ranking() {
+        // Both keys share the ngrams of "abcd" with the query, but only the second one
+        // has as many ngrams as the query.
+        let corpus: Corpus<[&str; 2], UniGram<char>> = Corpus::from(["abcd", "abcdwxyz"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap()
+            .set_max_ngram_degree(MaxNgramDegree::None);
+
+        // Without the penalty, the shorter key has fewer ngrams not shared with the query.
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("abcdefgh", config);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key(), &"abcd");
+        assert_eq!(results[1].key(), &"abcdwxyz");
+        assert!(results[0].score() > results[1].score());
+
+        // With the penalty, the shorter key is demoted while the other one, whose length
+        // matches the one of the query, keeps its score.
+        let penalized: SearchResults<&&str, f32> =
+            corpus.ngram_search("abcdefgh", config.set_length_mismatch_penalty(0.5).unwrap());
+        assert_eq!(penalized.len(), 2);
+        assert_eq!(penalized[0].key(), &"abcdwxyz");
+        assert_eq!(penalized[0].score(), results[1].score());
+        assert_eq!(penalized[1].key(), &"abcd");
+        assert!(penalized[1].score() < results[0].score());
+        assert!(penalized[0].score() > penalized[1].score());
+    }
+
+    #[test]
+    fn test_negative_demotes_only_its_similar_matches() {
+        let corpus: Corpus<[&str; 5], TriGram<char>> =
+            Corpus::from(["fish", "dogfish", "catfish", "goldfish", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let cosine: SearchResults<&&str, f32> =
+            corpus.ngram_search_with_metric("fish", config, CosineMetric);
+        assert_eq!(
+            cosine.keys().collect::<Vec<_>>(),
+            vec![&"fish", &"dogfish", &"catfish", &"goldfish"]
+        );
+
+        // Without a weight, the negative keys do not change the cosine similarities.
+        let unweighted: SearchResults<&&str, f32> =
+            corpus.ngram_search_with_negatives("fish", &["dog"], 0.0, config);
+        assert_eq!(unweighted, cosine);
+
+        // Among the matches, only "dogfish" shares ngrams with "dog", so it is the only
+        // one to be demoted, from the second to the last position.
+        let results: SearchResults<&&str, f32> =
+            corpus.ngram_search_with_negatives("fish", &["dog"], 0.5, config);
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            vec![&"fish", &"catfish", &"goldfish", &"dogfish"]
+        );
+        for result in results.iter() {
+            let expected = cosine
+                .iter()
+                .find(|expected| expected.key_id() == result.key_id())
+                .unwrap();
+            if result.key() == &"dogfish" {
+                assert!(result.score() > 0.0);
+                assert!(result.score() < expected.score());
+            } else {
+                assert_eq!(result.score(), expected.score());
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_ngram_par_search_in_custom_pool() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::par_from(&ANIMALS);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        for query in ["Cat", "Dog", "Giraffe", "Elephant"] {
+            let serial: SearchResults<&&str, f32> =
+                corpus.ngram_search(query, NgramSearchConfig::default());
+            let parallel: SearchResults<&&str, f32> =
+                corpus.ngram_par_search_in(&pool, query, NgramSearchConfig::default());
+
+            assert_eq!(serial, parallel, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_search_with_explanation_sorts_the_shared_ngrams() {
+        let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["banana", "cherry"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("bananas", config);
+        let explained = corpus.search_with_explanation("bananas", config);
+
+        assert_eq!(explained.len(), 1);
+        assert!(results.scores().eq(explained.scores()));
+        assert_eq!(explained[0].key(), &"banana");
+        // The "ana" ngram occurs twice in "banana", and the other ones once.
+        assert_eq!(
+            explained[0].shared_ngrams(),
+            &[
+                ['a', 'n', 'a'],
+                ['\0', '\0', 'b'],
+                ['\0', 'b', 'a'],
+                ['b', 'a', 'n'],
+                ['n', 'a', 'n'],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_knn_breaks_ties_by_key_id() {
+        let corpus: Corpus<[&str; 5], TriGram<char>> =
+            Corpus::from(["cam", "dog", "cad", "cab", "can"]);
+
+        // The keys starting with "ca" have the same score, and "dog" shares no ngram.
+        let results: SearchResults<&&str, f32> = corpus.knn("ca", 10);
+        assert_eq!(results.into_keys(), vec![&"cam", &"cad", &"cab", &"can"]);
+
+        let results: SearchResults<&&str, f32> = corpus.knn("ca", 2);
+        assert_eq!(results.into_keys(), vec![&"cam", &"cad"]);
+        let results: SearchResults<&&str, f32> = corpus.knn("ca", 0);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_by_count_ranks_by_the_number_of_shared_ngrams() {
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+        corpus.insert("Cattle Egret");
+        corpus.remove_key(ANIMALS.iter().position(|key| *key == "Cat").unwrap());
+        let number_of_key_ids = corpus.number_of_key_ids();
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(number_of_key_ids)
+            .set_max_ngram_degree(MaxNgramDegree::None);
+
+        for query in ["Cat", "Cattle", "Sea Lion"] {
+            let query_ngrams = corpus.key_counts(query);
+            let scores: HashMap<usize, f32> = corpus
+                .ngram_search(query, config)
+                .iter()
+                .map(|result| (result.key_id(), result.score()))
+                .collect();
+
+            for min_shared in [0, 1, 3, 5] {
+                let mut expected: Vec<(usize, usize)> = (0..number_of_key_ids)
+                    .filter(|&key_id| !corpus.is_removed(key_id))
+                    .map(|key_id| {
+                        let key_ngrams = corpus.key_counts(*corpus.key_from_id(key_id));
+                        let shared_ngrams = query_ngrams
+                            .keys()
+                            .filter(|ngram| key_ngrams.contains_key(ngram))
+                            .count();
+                        (key_id, shared_ngrams)
+                    })
+                    .filter(|(_, shared_ngrams)| *shared_ngrams >= min_shared.max(1))
+                    .collect();
+                expected.sort_unstable();
+
+                let results: SearchResults<&&str, f32, SharedNgramCount> =
+                    corpus.search_by_count(query, min_shared, number_of_key_ids);
+                let mut found: Vec<(usize, usize)> = results
+                    .iter()
+                    .map(|result| (result.key_id(), result.shared_ngram_count()))
+                    .collect();
+                found.sort_unstable();
+                assert_eq!(found, expected, "{} {}", query, min_shared);
+
+                for result in results.iter() {
+                    assert_eq!(scores[&result.key_id()], result.score());
+                }
+                assert!(results.windows(2).all(|pair| {
+                    (pair[0].shared_ngram_count(), pair[0].score())
+                        >= (pair[1].shared_ngram_count(), pair[1].score())
+                }));
+
+                let limited = corpus.search_by_count::<f32>(query, min_shared, 3);
+                assert_eq!(limited.len(), results.len().min(3));
+                assert!(limited.iter().zip(results.iter()).all(|(left, right)| {
+                    left.key_id() == right.key_id() && left.score() == right.score()
+                }));
+            }
+        }
+
+        // The inserted key is found among the keys sharing the most ngrams.
+        let results = corpus.search_by_count::<f32>("Cattle Egret", 10, 1);
+        assert_eq!(results[0].key(), &"Cattle Egret");
+        assert!(corpus.search_by_count::<f32>("", 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_neighbors_of_a_key_are_the_keys_sharing_its_ngrams() {
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+            Corpus::from(vec!["cat", "bat", "rat", "bird", "cow"]);
+
+        let neighbors: SearchResults<&&str, f32> = corpus.neighbors(0, 10);
+        assert_eq!(neighbors.into_keys(), vec![&"bat", &"rat", &"cow"]);
+        let neighbors: SearchResults<&&str, f32> = corpus.neighbors(0, 1);
+        assert_eq!(neighbors.into_keys(), vec![&"bat"]);
+
+        // The neighbors agree with the search of the key, except for the key itself.
+        for key_id in 0..corpus.number_of_keys() {
+            let neighbors: SearchResults<&&str, f32> = corpus.neighbors(key_id, 10);
+            let expected: SearchResults<&&str, f32> = corpus
+                .knn(*corpus.key_from_id(key_id), 11)
+                .into_iter()
+                .filter(|result| result.key_id() != key_id)
+                .collect();
+            assert_eq!(neighbors, expected);
+        }
+
+        // The overflow keys are both scored and used as queries.
+        corpus.insert("hat");
+        let neighbors: SearchResults<&&str, f32> = corpus.neighbors(0, 10);
+        assert_eq!(neighbors.into_keys(), vec![&"bat", &"rat", &"hat", &"cow"]);
+        let neighbors: SearchResults<&&str, f32> = corpus.neighbors(5, 10);
+        assert_eq!(neighbors.into_keys(), vec![&"cat", &"bat", &"rat"]);
+        assert!(corpus.neighbors::<f32>(3, 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_case_insensitive_matches_lowercase_corpus() {
+        let keys: Vec<String> = ANIMALS.iter().map(|key| key.to_lowercase()).collect();
+        let folded: Corpus<Vec<String>, TriGram<char>> = Corpus::from(keys);
+        let lowercase: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap();
+
+        for query in ["ELEPHANT", "Cat", "dOg", "Komodo Dragon"] {
+            let results = folded.search_case_insensitive(query, config);
+            let expected = lowercase.ngram_search(query, config);
+            assert!(!results.is_empty(), "Query {:?}", query);
+            assert!(results.scores().eq(expected.scores()), "Query {:?}", query);
+            assert!(results
+                .iter()
+                .zip(expected.iter())
+                .all(|(result, expected)| result.key().as_str() == expected.key().to_lowercase()));
+        }
+
+        let results = folded.search_case_insensitive("ELEPHANT", config);
+        assert_eq!(results[0].key().as_str(), "elephant");
+        assert_eq!(results[0].score(), 1.0);
+    }
+
+    #[test]
+    fn test_ngram_search_respects_query_ngram_multiplicities() {
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["aa", "aaaa", "aaaaaaaa"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let results = corpus.ngram_search("aaaa", config);
+        let scores: HashMap<&str, f32> = results
+            .iter()
+            .map(|result| (*result.key(), result.score()))
+            .collect();
+
+        assert_eq!(results[0].key(), &"aaaa");
+        assert_eq!(scores["aaaa"], 1.0);
+        assert!(scores["aa"] < scores["aaaa"]);
+        // With a set-based matching, the longer key would share all of the distinct
+        // ngrams of the query and score one, while the repeated "aaa" ngram tells it apart.
+        assert!(scores["aaaaaaaa"] < scores["aaaa"]);
+    }
+
+    #[test]
+    fn test_search_warp_raises_the_scores_without_changing_the_ranking() {
+        let corpus: Corpus<[&str; 6], TriGram<char>> =
+            Corpus::from(["concatenation", "category", "dog", "catfish", "cats", "cat"]);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        let unwarped = corpus.search_warp("cat", 1, config).unwrap();
+        let warped = corpus.search_warp("cat", 3, config).unwrap();
+        assert_eq!(
+            unwarped.clone().into_keys(),
+            vec![&"cat", &"cats", &"catfish", &"category", &"concatenation"]
+        );
+        assert_eq!(warped.clone().into_keys(), unwarped.clone().into_keys());
+
+        // With a warp factor of three, the score is 1 - (1 - s / a)^3.
+        for (unwarped, warped) in unwarped.iter().zip(warped.iter()) {
+            let expected = 1.0 - (1.0 - unwarped.score()).powi(3);
+            assert!((warped.score() - expected).abs() < 1e-6);
+            assert!(warped.score() >= unwarped.score());
+        }
+
+        let config = config.set_minimum_similarity_score(0.5).unwrap();
+        assert_eq!(corpus.search_warp("cat", 1, config).unwrap().len(), 1);
+        assert_eq!(corpus.search_warp("cat", 3, config).unwrap().len(), 4);
+        assert_eq!(
+            corpus.search_warp("cat", 0, config).unwrap_err(),
+            "Warp factor must be in the range 1 to 3"
+        );
+    }
+
+    #[test]
+    fn test_search_ngrams_matches_the_search_of_the_key() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.2)
+            .unwrap();
+
+        for query in ["cat", "sea lion", "hippopotamus"] {
+            let mut ngram_counts = <str as Key<TriGram<char>, char>>::counts(query);
+            assert_eq!(
+                corpus.search_ngrams(&ngram_counts, config),
+                corpus.ngram_search(query, config)
+            );
+
+            // The ngrams with a count of zero are ignored.
+            ngram_counts.insert(['z', 'z', 'z'], 0);
+            assert_eq!(
+                corpus.search_ngrams(&ngram_counts, config),
+                corpus.ngram_search(query, config)
+            );
+        }
+
+        // The ngrams are not normalized, so that the uppercase ones match no key.
+        let ngram_counts = <str as Key<TriGram<char>, char>>::counts("CAT");
+        assert!(corpus.search_ngrams(&ngram_counts, config).is_empty());
     }
 }
@@ -1,8 +1,13 @@
+#[cfg(feature = "simd")]
+use std::cell::RefCell;
 use std::{
     cmp::Ordering,
     fmt::{Display, Formatter},
 };
 
+#[cfg(feature = "simd")]
+use crate::dot_product::{intersection_simd, SIMD_DEGREE_THRESHOLD};
+use crate::metric::{merge_ngram_counts, MergedNgram};
 use crate::{corpus::Corpus, search::QueryHashmap, traits::*};
 
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
@@ -50,6 +55,47 @@ where
 
         false
     }
+
+    #[inline(always)]
+    /// Returns how many of the provided ngrams appear in the provided key.
+    ///
+    /// # Arguments
+    /// * `sorted_ngram_ids` - The sorted and distinct ngram ids to count.
+    /// * `key_id` - The id of the key to count the ngrams of.
+    ///
+    /// # Implementative details
+    /// As in `contains_any_ngram_ids`, both sorted iterators are walked at once, and only
+    /// integer comparisons are made, so that the count is cheaper than a score.
+    pub(crate) fn number_of_shared_ngram_ids(
+        &self,
+        sorted_ngram_ids: &[usize],
+        key_id: usize,
+    ) -> usize {
+        let mut left_iterator = self.ngram_ids_from_key(key_id);
+        let mut right_iterator = sorted_ngram_ids.iter().copied();
+
+        let mut left_next = left_iterator.next();
+        let mut right_next = right_iterator.next();
+        let mut count = 0;
+
+        while let (Some(left), Some(right)) = (left_next, right_next) {
+            match left.cmp(&right) {
+                Ordering::Less => {
+                    left_next = left_iterator.next();
+                }
+                Ordering::Greater => {
+                    right_next = right_iterator.next();
+                }
+                Ordering::Equal => {
+                    count += 1;
+                    left_next = left_iterator.next();
+                    right_next = right_iterator.next();
+                }
+            }
+        }
+
+        count
+    }
 }
 
 /// Returns the number of shared ngrams between two iterators.
@@ -100,11 +146,100 @@ where
     (count, other_count)
 }
 
+#[cfg(feature = "simd")]
+thread_local! {
+    /// The ids and counts of the candidate, reused across the candidates scored by each
+    /// thread.
+    static SHARED_ITEMS_BUFFERS: RefCell<[Vec<u32>; 2]> = RefCell::default();
+}
+
+#[cfg(feature = "simd")]
+/// Returns the number of shared ngrams between the query and the provided ngrams, and
+/// the total count of the provided ngrams, intersecting them with SIMD.
+///
+/// # Arguments
+/// * `query` - The query hashmap.
+/// * `ngrams` - The iterator of ngrams.
+///
+/// # Implementation details
+/// The ngrams are buffered, and the intersection is computed by `intersection_simd` when
+/// they are at least `SIMD_DEGREE_THRESHOLD`, with the ids and counts of the query that
+/// the query hashmap converted once for the whole search. Both paths return the same
+/// counts of `number_of_shared_items`, and the scalar one is also used when the ids or
+/// the counts of the query do not fit in an `u32`.
+///
+/// # Panics
+/// * If an ngram id, or an ngram count, of the provided ngrams does not fit in an `u32`.
+fn simd_number_of_shared_items<I>(query: &QueryHashmap, ngrams: I) -> (usize, usize)
+where
+    I: Iterator<Item = (usize, usize)>,
+{
+    SHARED_ITEMS_BUFFERS.with(|buffers| {
+        let mut buffers = buffers.borrow_mut();
+        let [ngram_ids, ngram_counts] = &mut *buffers;
+        ngram_ids.clear();
+        ngram_counts.clear();
+        let mut other_count = 0;
+        for (ngram_id, count) in ngrams {
+            ngram_ids.push(u32::try_from(ngram_id).expect("The ngram id must fit in an u32."));
+            ngram_counts.push(u32::try_from(count).expect("The ngram count must fit in an u32."));
+            other_count += count;
+        }
+
+        match query.simd_ngram_ids_and_counts() {
+            Some((query_ids, query_counts)) if ngram_ids.len() >= SIMD_DEGREE_THRESHOLD => {
+                let (_, sharegrams) =
+                    intersection_simd(query_ids, query_counts, ngram_ids, ngram_counts);
+                (sharegrams as usize, other_count)
+            }
+            _ => {
+                let ngrams = ngram_ids
+                    .iter()
+                    .zip(ngram_counts.iter())
+                    .map(|(&ngram_id, &count)| (ngram_id as usize, count as usize));
+                number_of_shared_items(query.ngram_ids_and_counts(), ngrams)
+            }
+        }
+    })
+}
+
 /// Test that number_of_shared_items works correctly.
 #[cfg(test)]
 mod test_number_of_shared_items {
     use super::*;
 
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_number_of_shared_items_matches_scalar() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..1_000 {
+            // We draw sorted and unique ids, with a random density and offset so that
+            // both the blocks and the galloping of the SIMD kernel are exercised.
+            let random_ngrams = |rng: &mut StdRng| -> Vec<(usize, usize)> {
+                let length = rng.gen_range(0..200);
+                let offset = rng.gen_range(0..500);
+                let mut ngram_id = offset;
+                (0..length)
+                    .map(|_| {
+                        ngram_id += rng.gen_range(1..6);
+                        (ngram_id, rng.gen_range(1..4))
+                    })
+                    .collect()
+            };
+            let query_ngrams = random_ngrams(&mut rng);
+            let ngrams = random_ngrams(&mut rng);
+            let query = QueryHashmap::from_ngram_ids(query_ngrams.clone(), 0);
+
+            assert_eq!(
+                simd_number_of_shared_items(&query, ngrams.iter().copied()),
+                number_of_shared_items(query_ngrams.into_iter(), ngrams.into_iter())
+            );
+        }
+    }
+
     #[test]
     fn test_number_of_shared_items() {
         let left = vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)];
@@ -141,7 +276,7 @@ mod test_number_of_shared_items {
 /// Use warp greater than 1.0 to increase the similarity of shorter string pairs.
 /// * `query` - The query hashmap.
 /// * `ngrams` - The iterator of ngrams.
-pub(crate) fn ngram_similarity<I, W, F>(warp: Warp<W>, query: &QueryHashmap, ngrams: I) -> F
+pub(crate) fn warped_ngram_similarity<I, W, F>(warp: Warp<W>, query: &QueryHashmap, ngrams: I) -> F
 where
     I: Iterator<Item = (usize, usize)>,
     F: Float,
@@ -152,6 +287,9 @@ where
         "Warp factor must be in the range 1 to 3"
     );
 
+    #[cfg(feature = "simd")]
+    let (sharegrams, other_count) = simd_number_of_shared_items(query, ngrams);
+    #[cfg(not(feature = "simd"))]
     let (sharegrams, other_count) = number_of_shared_items(query.ngram_ids_and_counts(), ngrams);
 
     debug_assert!(sharegrams <= query.total_count());
@@ -176,14 +314,81 @@ where
     })
 }
 
+#[inline(always)]
+/// Calculate the similarity between two iterators of ngrams, weighting each ngram.
+///
+/// # Arguments
+/// * `warp` - The warp value to use in the trigram similarity calculation.
+/// Use warp greater than 1.0 to increase the similarity of shorter string pairs.
+/// * `query` - The query hashmap.
+/// * `ngrams` - The iterator of ngrams.
+/// * `weight` - The weight of each ngram, given its id.
+///
+/// # Implementation details
+/// This is the same similarity computed by `warped_ngram_similarity`, where each occurrence
+/// of an ngram counts as its weight instead of one. The ngrams of the query that do
+/// not appear in the corpus have no id, and therefore count as one. When all of the
+/// ngrams have zero weight, the similarity is zero.
+pub(crate) fn weighted_ngram_similarity<I, W, F>(
+    warp: Warp<W>,
+    query: &QueryHashmap,
+    ngrams: I,
+    weight: impl Fn(usize) -> F,
+) -> F
+where
+    I: Iterator<Item = (usize, usize)>,
+    F: Float,
+    Warp<W>: NgramSimilarity + One + Zero + Three + PartialOrd,
+{
+    debug_assert!(
+        warp.is_between_one_and_three(),
+        "Warp factor must be in the range 1 to 3"
+    );
+
+    let identified_count: usize = query.ngram_ids_and_counts().map(|(_, count)| count).sum();
+    let mut query_total = (query.total_count() - identified_count) as f64;
+    let mut other_total = 0.0;
+    let mut sharegrams = 0.0;
+
+    merge_ngram_counts(
+        query.ngram_ids_and_counts(),
+        ngrams,
+        |merged| match merged {
+            MergedNgram::Query(query_id, query_count) => {
+                query_total += weight(query_id).to_f64() * query_count as f64;
+            }
+            MergedNgram::Key(ngram_id, ngram_count) => {
+                other_total += weight(ngram_id).to_f64() * ngram_count as f64;
+            }
+            MergedNgram::Shared(ngram_id, query_count, ngram_count) => {
+                let ngram_weight = weight(ngram_id).to_f64();
+                query_total += ngram_weight * query_count as f64;
+                other_total += ngram_weight * ngram_count as f64;
+                sharegrams += ngram_weight * query_count.min(ngram_count) as f64;
+            }
+        },
+    );
+
+    let allgrams = query_total + other_total - sharegrams;
+
+    if allgrams <= 0.0 {
+        return F::ZERO;
+    }
+
+    F::from_f64(if warp.is_one() {
+        sharegrams / allgrams
+    } else {
+        let exponentiated_allgrams = warp.pow(allgrams);
+        (exponentiated_allgrams - warp.pow(allgrams - sharegrams)) / exponentiated_allgrams
+    })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 /// Struct representing a warp factor.
 pub struct Warp<W> {
     value: W,
 }
 
-unsafe impl<W> Send for Warp<W> {}
-
 impl<W: One> One for Warp<W> {
     const ONE: Self = Warp { value: W::ONE };
 
@@ -225,7 +430,7 @@ pub trait NgramSimilarity {
 impl NgramSimilarity for Warp<i32> {
     #[inline(always)]
     fn pow(&self, value: f64) -> f64 {
-        value.powi(self.value)
+        crate::math::powi(value, self.value)
     }
 
     #[inline(always)]
@@ -234,14 +439,14 @@ impl NgramSimilarity for Warp<i32> {
         I: Iterator<Item = (usize, usize)>,
         F: Float,
     {
-        ngram_similarity(self, query, ngrams)
+        warped_ngram_similarity(self, query, ngrams)
     }
 }
 
 impl NgramSimilarity for Warp<f64> {
     #[inline(always)]
     fn pow(&self, value: f64) -> f64 {
-        value.powf(self.value)
+        crate::math::pow(value, self.value)
     }
 
     #[inline(always)]
@@ -250,7 +455,7 @@ impl NgramSimilarity for Warp<f64> {
         I: Iterator<Item = (usize, usize)>,
         F: Float,
     {
-        ngram_similarity(self, query, ngrams)
+        warped_ngram_similarity(self, query, ngrams)
     }
 }
 
@@ -0,0 +1,524 @@
+//! Submodule providing the overflow region of a corpus, where keys are inserted without rebuilding it.
+//!
+//! # Implementation details
+//! The compressed bipartite graph of a corpus cannot be extended in place, so the keys
+//! inserted after construction are stored in a small graph backed by plain vectors.
+//! The overflow keys are appended to the keys of the corpus, and their ids follow the
+//! ids of the keys in the compressed graph. The ngrams of the overflow keys are stored
+//! with the ids of the vocabulary of the corpus, and the ngrams missing from it receive
+//! the ids following the ones of the vocabulary, in order of insertion. The accessors of
+//! the corpus, such as `ngram_ids_and_cooccurrences_from_key`, therefore work on both
+//! regions with the same ids, and the searches score the overflow keys along with the
+//! compressed ones.
+use std::collections::HashMap;
+
+use fxhash::FxBuildHasher;
+use mem_dbg::{MemDbgImpl, MemSize, SizeFlags};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sux::bits::BitVec;
+
+use crate::{bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, traits::*, Corpus};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// A bipartite graph from keys to ngrams, backed by vectors and supporting insertions.
+pub struct OverflowGraph<NG> {
+    /// The ngrams of the overflow keys missing from the vocabulary of the corpus, in order
+    /// of insertion, so that the id of the `i`-th one is the number of ngrams of the
+    /// vocabulary plus `i`.
+    ngrams: Vec<NG>,
+    /// The positions of the ngrams in `ngrams`, sorted by ngram.
+    sorted_ngrams: Vec<usize>,
+    /// The sorted ids of the overflow keys containing each ngram, by ngram id.
+    ngram_to_keys: HashMap<usize, Vec<usize>, FxBuildHasher>,
+    /// The offsets of the ngrams of each overflow key in `key_ngrams`, plus a final one.
+    key_offsets: Vec<usize>,
+    /// The ngram ids of each overflow key and their counts, sorted by ngram id.
+    key_ngrams: Vec<(usize, usize)>,
+}
+
+impl<NG> Default for OverflowGraph<NG> {
+    fn default() -> Self {
+        Self {
+            ngrams: Vec::new(),
+            sorted_ngrams: Vec::new(),
+            ngram_to_keys: HashMap::default(),
+            key_offsets: vec![0],
+            key_ngrams: Vec::new(),
+        }
+    }
+}
+
+impl<NG> MemSize for OverflowGraph<NG> {
+    fn mem_size(&self, flags: SizeFlags) -> usize {
+        /// Returns the size in bytes of the elements of the provided vector.
+        fn vec_size<T>(vector: &Vec<T>, flags: SizeFlags) -> usize {
+            let number_of_elements = if flags.contains(SizeFlags::CAPACITY) {
+                vector.capacity()
+            } else {
+                vector.len()
+            };
+            number_of_elements * core::mem::size_of::<T>()
+        }
+
+        let number_of_entries = if flags.contains(SizeFlags::CAPACITY) {
+            self.ngram_to_keys.capacity()
+        } else {
+            self.ngram_to_keys.len()
+        };
+
+        core::mem::size_of::<Self>()
+            + vec_size(&self.ngrams, flags)
+            + vec_size(&self.sorted_ngrams, flags)
+            + number_of_entries * core::mem::size_of::<(usize, Vec<usize>)>()
+            + self
+                .ngram_to_keys
+                .values()
+                .map(|keys| vec_size(keys, flags))
+                .sum::<usize>()
+            + vec_size(&self.key_offsets, flags)
+            + vec_size(&self.key_ngrams, flags)
+    }
+}
+
+impl<NG> MemDbgImpl for OverflowGraph<NG> {}
+
+impl<NG: Ngram> OverflowGraph<NG> {
+    #[inline(always)]
+    /// Returns the number of overflow keys.
+    pub(crate) fn number_of_keys(&self) -> usize {
+        self.key_offsets.len() - 1
+    }
+
+    #[inline(always)]
+    /// Returns whether there are no overflow keys.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.number_of_keys() == 0
+    }
+
+    #[inline(always)]
+    /// Returns the number of ngrams of the overflow keys missing from the vocabulary.
+    pub(crate) fn number_of_ngrams(&self) -> usize {
+        self.ngrams.len()
+    }
+
+    #[inline(always)]
+    /// Returns the position of the provided ngram among the ngrams missing from the vocabulary.
+    ///
+    /// # Arguments
+    /// * `ngram` - The ngram to look up.
+    pub(crate) fn ngram_position(&self, ngram: NG) -> Option<usize> {
+        self.sorted_ngrams
+            .binary_search_by(|&position| self.ngrams[position].cmp(&ngram))
+            .ok()
+            .map(|index| self.sorted_ngrams[index])
+    }
+
+    #[inline(always)]
+    /// Returns the ngram at the provided position among the ngrams missing from the vocabulary.
+    ///
+    /// # Arguments
+    /// * `position` - The position of the ngram, i.e. its id minus the size of the vocabulary.
+    pub(crate) fn ngram(&self, position: usize) -> NG {
+        self.ngrams[position]
+    }
+
+    /// Appends a new overflow key with the provided ngram counts.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key in the corpus.
+    /// * `ngram_counts` - The counts of the ngrams of the key.
+    /// * `vocabulary` - The sorted ngrams of the compressed graph.
+    ///
+    /// # Implementation details
+    /// The ids of the ngrams are resolved once here, so that the accessors of the key
+    /// return them without looking up its ngrams again.
+    pub(crate) fn push(
+        &mut self,
+        key_id: usize,
+        ngram_counts: HashMap<NG, usize, FxBuildHasher>,
+        vocabulary: &NG::SortedStorage,
+    ) {
+        let mut ngram_ids_and_counts: Vec<(usize, usize)> = ngram_counts
+            .into_iter()
+            .map(|(ngram, count)| {
+                let ngram_id = vocabulary.index_of(ngram).unwrap_or_else(|| {
+                    let position = match self
+                        .sorted_ngrams
+                        .binary_search_by(|&position| self.ngrams[position].cmp(&ngram))
+                    {
+                        Ok(index) => self.sorted_ngrams[index],
+                        Err(index) => {
+                            self.sorted_ngrams.insert(index, self.ngrams.len());
+                            self.ngrams.push(ngram);
+                            self.ngrams.len() - 1
+                        }
+                    };
+                    vocabulary.len() + position
+                });
+                (ngram_id, count)
+            })
+            .collect();
+        ngram_ids_and_counts.sort_unstable_by_key(|(ngram_id, _)| *ngram_id);
+
+        for (ngram_id, _) in ngram_ids_and_counts.iter() {
+            self.ngram_to_keys
+                .entry(*ngram_id)
+                .or_default()
+                .push(key_id);
+        }
+
+        self.key_ngrams.extend(ngram_ids_and_counts);
+        self.key_offsets.push(self.key_ngrams.len());
+    }
+
+    #[inline(always)]
+    /// Returns the sorted ids of the overflow keys containing the provided ngram.
+    ///
+    /// # Arguments
+    /// * `ngram_id` - The id of the ngram.
+    pub(crate) fn key_ids_from_ngram_id(&self, ngram_id: usize) -> &[usize] {
+        if self.is_empty() {
+            return &[];
+        }
+        self.ngram_to_keys
+            .get(&ngram_id)
+            .map_or(&[], |key_ids| key_ids.as_slice())
+    }
+
+    #[inline(always)]
+    /// Returns the ngram ids of an overflow key and their counts, sorted by ngram id.
+    ///
+    /// # Arguments
+    /// * `key` - The position of the key among the overflow keys.
+    pub(crate) fn ngram_ids_and_counts(&self, key: usize) -> &[(usize, usize)] {
+        &self.key_ngrams[self.key_offsets[key]..self.key_offsets[key + 1]]
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the number of keys inserted after the construction of the corpus and not yet compacted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// assert_eq!(corpus.number_of_overflow_keys(), 0);
+    ///
+    /// corpus.insert("cow");
+    /// assert_eq!(corpus.number_of_overflow_keys(), 1);
+    /// assert_eq!(corpus.number_of_keys(), 3);
+    ///
+    /// corpus.compact();
+    /// assert_eq!(corpus.number_of_overflow_keys(), 0);
+    /// assert_eq!(corpus.number_of_keys(), 3);
+    /// ```
+    pub fn number_of_overflow_keys(&self) -> usize {
+        self.overflow.number_of_keys()
+    }
+}
+
+impl<S, NG, K> Corpus<Vec<S>, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    Vec<S>: Keys<NG>,
+    for<'a> <Vec<S> as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Inserts a new key in the corpus, without rebuilding the compressed graph.
+    ///
+    /// # Arguments
+    /// * `key` - The key to insert, which receives the id `number_of_key_ids() - 1`.
+    ///
+    /// # Implementation details
+    /// The key is appended to the keys of the corpus, while its ngrams are stored in an
+    /// overflow graph backed by vectors. Inserting a key costs a lookup and a sort of its
+    /// ngrams and, for each ngram missing from the vocabulary of the corpus, a shift of
+    /// the ngrams of the overflow graph, so it is much cheaper than rebuilding the corpus.
+    /// The ngrams missing from the vocabulary receive the ids following the ones of the
+    /// vocabulary, which `ngram_from_id` and `ngram_id_from_ngram` resolve, while
+    /// `number_of_ngrams` and `ngrams` only cover the vocabulary until the corpus is
+    /// compacted. The accessors of the keys and all of the searches work on the overflow
+    /// keys as on the compressed ones, but the overflow graph is less compact and slower
+    /// to walk: call `compact` once enough keys have been inserted. Until then, the
+    /// average key length of the corpus is not updated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    ///
+    /// corpus.insert("catfish");
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 3);
+    /// assert_eq!(corpus.key_from_id(2), &"catfish");
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("catfish", NgramSearchConfig::default());
+    /// assert_eq!(results[0].key(), &"catfish");
+    /// assert_eq!(results[0].score(), 1.0);
+    /// ```
+    pub fn insert(&mut self, key: S) {
+        self.keys.push(key);
+        let key_id = self.keys.len() - 1;
+        let ngram_counts = {
+            let key = self.key_from_id(key_id);
+            self.key_counts(key.as_ref())
+        };
+        self.overflow.push(key_id, ngram_counts, &self.ngrams);
+    }
+
+    /// Folds the overflow keys back into the compressed graph.
+    ///
+    /// # Implementation details
+    /// The corpus is rebuilt from all of its keys, so that the ids of the keys are
+    /// unchanged, and the removed keys stay removed. The cost is the same of building
+    /// the corpus from scratch, and no work is done when there are no overflow keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+    /// corpus.insert("catfish");
+    /// corpus.compact();
+    ///
+    /// let expected: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "dog", "catfish"]);
+    ///
+    /// assert_eq!(corpus.number_of_ngrams(), expected.number_of_ngrams());
+    /// assert!(corpus.ngram_ids_from_key(2).eq(expected.ngram_ids_from_key(2)));
+    /// ```
+    pub fn compact(&mut self) {
+        if self.overflow.is_empty() {
+            return;
+        }
+        let keys = std::mem::take(&mut self.keys);
+        let tombstones = std::mem::replace(&mut self.tombstones, BitVec::new(0));
+        let number_of_removed_keys = self.number_of_removed_keys;
+        *self = Corpus::from_with_padding(keys, self.padding);
+        self.tombstones = tombstones;
+        self.number_of_removed_keys = number_of_removed_keys;
+    }
+}
+
+/// Inserts the keys of an iterator into the corpus, as the `insert` method does.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog"]);
+///
+/// corpus.extend(["catfish", "dogfish"]);
+///
+/// assert_eq!(corpus.number_of_keys(), 4);
+/// assert_eq!(corpus.number_of_overflow_keys(), 2);
+/// assert_eq!(corpus.key_from_id(3), &"dogfish");
+/// ```
+impl<S, NG, K> Extend<S> for Corpus<Vec<S>, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    Vec<S>: Keys<NG>,
+    for<'a> <Vec<S> as Keys<NG>>::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    fn extend<I: IntoIterator<Item = S>>(&mut self, keys: I) {
+        for key in keys {
+            self.insert(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sux::bits::BitVec;
+
+    use crate::prelude::*;
+
+    /// The corpus of the animals whose last keys are inserted, and the one built from all of them.
+    type AnimalsCorpus = Corpus<Vec<&'static str>, TriGram<char>>;
+
+    /// Returns a corpus built from the first animals with the other ones inserted, and
+    /// the corpus built from all of the animals, which has the same key ids.
+    fn inserted_and_rebuilt() -> (AnimalsCorpus, AnimalsCorpus) {
+        let (initial, inserted) = ANIMALS.split_at(600);
+        let mut corpus: AnimalsCorpus = Corpus::from(initial.to_vec());
+        corpus.extend(inserted.iter().copied());
+        (corpus, Corpus::from(ANIMALS.to_vec()))
+    }
+
+    #[test]
+    fn test_accessors_reach_the_overflow_keys() {
+        let (corpus, rebuilt) = inserted_and_rebuilt();
+
+        for key_id in 0..corpus.number_of_key_ids() {
+            // The ids of the ngrams differ between the two corpora, but not the ngrams.
+            let mut ngrams: Vec<([char; 3], usize)> =
+                corpus.ngrams_and_cooccurrences_from_key(key_id).collect();
+            ngrams.sort_unstable();
+            let expected: Vec<([char; 3], usize)> =
+                rebuilt.ngrams_and_cooccurrences_from_key(key_id).collect();
+            assert_eq!(ngrams, expected, "Key {}", key_id);
+
+            assert_eq!(corpus.number_of_ngrams_from_key_id(key_id), expected.len());
+            assert_eq!(corpus.ngram_ids_from_key(key_id).len(), expected.len());
+            assert_eq!(
+                corpus.ngram_cooccurrences_from_key(key_id).sum::<usize>(),
+                rebuilt.ngram_cooccurrences_from_key(key_id).sum::<usize>()
+            );
+            for (ngram_id, cooccurrence) in corpus.ngram_ids_and_cooccurrences_from_key(key_id) {
+                assert_eq!(corpus.cooccurrence(key_id, ngram_id), Some(cooccurrence));
+                let ngram = corpus.ngram_from_id(ngram_id);
+                assert_eq!(corpus.ngram_id_from_ngram(ngram), Some(ngram_id));
+                assert_eq!(
+                    corpus.number_of_keys_from_ngram(ngram),
+                    rebuilt.number_of_keys_from_ngram(ngram)
+                );
+                assert!(corpus
+                    .key_ids_from_ngram_id(ngram_id)
+                    .any(|other_key_id| other_key_id == key_id));
+            }
+        }
+
+        let mut edges: Vec<(usize, [char; 3], usize)> = corpus
+            .iter_edges()
+            .map(|(key_id, ngram_id, cooccurrence)| {
+                (key_id, corpus.ngram_from_id(ngram_id), cooccurrence)
+            })
+            .collect();
+        edges.sort_unstable();
+        let expected: Vec<(usize, [char; 3], usize)> = rebuilt
+            .iter_edges()
+            .map(|(key_id, ngram_id, cooccurrence)| {
+                (key_id, rebuilt.ngram_from_id(ngram_id), cooccurrence)
+            })
+            .collect();
+        assert_eq!(edges, expected);
+
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default();
+        let expected: Vec<SearchResults<&&str, f32>> = rebuilt.cross_search(&rebuilt, config);
+        let results: Vec<SearchResults<&&str, f32>> = corpus.cross_search(&rebuilt, config);
+        assert_eq!(results, expected);
+        let results: Vec<SearchResults<&&str, f32>> = rebuilt.cross_search(&corpus, config);
+        assert_eq!(results, expected);
+
+        let mut corpus = corpus;
+        assert_eq!(
+            corpus.add_weights_from(&rebuilt),
+            Err("The overflow keys of the two corpora must be compacted first.")
+        );
+        corpus.compact();
+        assert!(corpus.add_weights_from(&rebuilt).is_ok());
+    }
+
+    #[test]
+    fn test_searches_score_the_overflow_keys() {
+        let (corpus, rebuilt) = inserted_and_rebuilt();
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.2)
+            .unwrap();
+        let mut allowed = BitVec::new(corpus.number_of_key_ids());
+        for key_id in (0..corpus.number_of_key_ids()).step_by(2) {
+            allowed.set(key_id, true);
+        }
+        let boosts: HashMap<usize, f32> = [(650, 1.5), (10, 1.5)].into_iter().collect();
+
+        for query in ["Zebra", "Wolverine", "Sea lion", "Tiger", "Cat"] {
+            assert_eq!(
+                corpus.ngram_search_within(query, config, &allowed),
+                rebuilt.ngram_search_within(query, config, &allowed)
+            );
+            assert_eq!(
+                corpus.prefix_search(query, "W", config),
+                rebuilt.prefix_search(query, "W", config)
+            );
+            assert_eq!(
+                corpus.search_boosted(query, 0.2, 10, &boosts),
+                rebuilt.search_boosted(query, 0.2, 10, &boosts)
+            );
+            assert_eq!(
+                corpus.ngram_search_with_metric(query, config, DiceMetric),
+                rebuilt.ngram_search_with_metric(query, config, DiceMetric)
+            );
+            assert_eq!(
+                corpus.jaccard_search(query, 0.2_f32, 10),
+                rebuilt.jaccard_search(query, 0.2_f32, 10)
+            );
+            assert_eq!(
+                corpus.cosine_search(query, config),
+                rebuilt.cosine_search(query, config)
+            );
+            let options: SearchOptions<JaccardMetric, f32> =
+                SearchOptions::default().set_metric(JaccardMetric);
+            assert_eq!(
+                corpus.search_with(query, options),
+                rebuilt.search_with(query, options)
+            );
+            #[cfg(feature = "rayon")]
+            assert_eq!(
+                corpus.ngram_par_search(query, config),
+                rebuilt.ngram_par_search(query, config)
+            );
+        }
+
+        // The average key length of the corpus is only updated by compacting it, so
+        // the TF-IDF scores differ, but the overflow keys are still scored.
+        let results: SearchResults<&&str, f32> =
+            corpus.tf_idf_search("Zebra", TFIDFSearchConfig::default());
+        assert_eq!(results[0].key(), &"Zebra");
+        assert!(results[0].key_id() >= 600);
+    }
+
+    #[test]
+    fn test_insert_matches_rebuilt_corpus() {
+        let (initial, inserted) = ANIMALS.split_at(600);
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>, Lowercase<str>> =
+            Corpus::from(initial.to_vec());
+        for key in inserted {
+            corpus.insert(*key);
+        }
+        let rebuilt: Corpus<Vec<&str>, TriGram<char>, Lowercase<str>> =
+            Corpus::from(ANIMALS.to_vec());
+
+        assert_eq!(corpus.number_of_keys(), rebuilt.number_of_keys());
+        assert_eq!(corpus.number_of_overflow_keys(), inserted.len());
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.3_f32)
+            .unwrap()
+            .set_maximum_number_of_results(20)
+            .set_max_ngram_degree(MaxNgramDegree::None);
+
+        for query in ["cat", "zebra", "yak", "wolverine", "aardvark"] {
+            let results: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+            let expected: SearchResults<&&str, f32> = rebuilt.ngram_search(query, config);
+            assert_eq!(results.len(), expected.len(), "Query {}", query);
+            for (result, expected) in results.iter().zip(expected.iter()) {
+                assert_eq!(result.score(), expected.score(), "Query {}", query);
+            }
+        }
+
+        corpus.compact();
+        assert_eq!(corpus.number_of_overflow_keys(), 0);
+        assert!(corpus.ngrams().eq(rebuilt.ngrams()));
+        assert!(corpus.cooccurrences().eq(rebuilt.cooccurrences()));
+    }
+}
@@ -0,0 +1,282 @@
+//! Submodule providing the padding modes of the keys of a corpus.
+//!
+//! # Implementation details
+//! The grams of the keys are padded by their `Key` implementation, which for the string
+//! keys adds `ARITY - 1` null grams on both sides. The padding modes other than `Auto`
+//! remove this padding, and optionally replace it with a different gram, so that the
+//! same key types can be used with or without padding artifacts, such as when matching
+//! fixed-width codes whose first and last grams are not more relevant than the others.
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+use fxhash::FxBuildHasher;
+use mem_dbg::{MemDbgImpl, MemSize, SizeFlags};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::traits::iter_ngrams::IntoNgrams;
+use crate::{Gram, Key, Ngram};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// The padding added to both sides of the grams of the keys before building their ngrams.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let key = "ab";
+///
+/// let padded = PaddingMode::Auto.counts::<TriGram<char>, str>(key);
+/// assert_eq!(padded.len(), 4);
+/// assert_eq!(padded.get(&['\0', '\0', 'a']), Some(&1));
+///
+/// let custom = PaddingMode::Pad('$').counts::<TriGram<char>, str>(key);
+/// assert_eq!(custom.len(), 4);
+/// assert_eq!(custom.get(&['$', '$', 'a']), Some(&1));
+///
+/// // Without padding, a key shorter than the arity has no ngrams.
+/// let unpadded = PaddingMode::None.counts::<TriGram<char>, str>(key);
+/// assert!(unpadded.is_empty());
+///
+/// let unpadded = PaddingMode::None.counts::<TriGram<char>, str>("abcd");
+/// assert_eq!(unpadded.len(), 2);
+/// assert_eq!(unpadded.get(&['a', 'b', 'c']), Some(&1));
+/// ```
+pub enum PaddingMode<G> {
+    /// No padding, so that the grams at the sides of the keys appear in fewer ngrams.
+    None,
+    /// The padding of the key type, which is `ARITY - 1` null grams on both sides.
+    #[default]
+    Auto,
+    /// The padding of `ARITY - 1` copies of the provided gram on both sides.
+    Pad(G),
+}
+
+impl<G> MemSize for PaddingMode<G> {
+    fn mem_size(&self, _flags: SizeFlags) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
+
+impl<G> MemDbgImpl for PaddingMode<G> {}
+
+impl<G: Gram> PaddingMode<G> {
+    /// Returns the counts of the ngrams of the provided key, padded according to the mode.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted.
+    pub fn counts<NG, K>(&self, key: &K) -> HashMap<NG, usize, FxBuildHasher>
+    where
+        NG: Ngram<G = G>,
+        K: Key<NG, G> + ?Sized,
+    {
+        self.counts_with_hasher(key)
+    }
+
+    /// Returns the counts of the ngrams of the provided key, padded according to the mode,
+    /// in a map with the provided hasher.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::collections::HashMap;
+    ///
+    /// let counts: HashMap<TriGram<char>, usize, RandomState> =
+    ///     PaddingMode::Pad('$').counts_with_hasher::<TriGram<char>, str, _>("ab");
+    /// assert_eq!(counts.len(), 4);
+    /// assert_eq!(counts.get(&['$', '$', 'a']), Some(&1));
+    /// ```
+    pub fn counts_with_hasher<NG, K, S>(&self, key: &K) -> HashMap<NG, usize, S>
+    where
+        NG: Ngram<G = G>,
+        K: Key<NG, G> + ?Sized,
+        S: BuildHasher + Default,
+    {
+        match self {
+            PaddingMode::Auto => key.counts_with_hasher(),
+            _ => Self::count_ngrams(self.grams(key)),
+        }
+    }
+
+    /// Returns the counts of the ngrams built from at most the first
+    /// `maximum_number_of_grams` grams of the key, padded according to the mode.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted.
+    /// * `maximum_number_of_grams` - The maximum number of grams to consider.
+    pub fn truncated_counts<NG, K>(
+        &self,
+        key: &K,
+        maximum_number_of_grams: usize,
+    ) -> HashMap<NG, usize, FxBuildHasher>
+    where
+        NG: Ngram<G = G>,
+        K: Key<NG, G> + ?Sized,
+    {
+        self.truncated_counts_with_hasher(key, maximum_number_of_grams)
+    }
+
+    /// Returns the counts of the ngrams built from at most the first
+    /// `maximum_number_of_grams` grams of the key, padded according to the mode,
+    /// in a map with the provided hasher.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose ngrams are counted.
+    /// * `maximum_number_of_grams` - The maximum number of grams to consider.
+    pub fn truncated_counts_with_hasher<NG, K, S>(
+        &self,
+        key: &K,
+        maximum_number_of_grams: usize,
+    ) -> HashMap<NG, usize, S>
+    where
+        NG: Ngram<G = G>,
+        K: Key<NG, G> + ?Sized,
+        S: BuildHasher + Default,
+    {
+        match self {
+            PaddingMode::Auto => key.truncated_counts_with_hasher(maximum_number_of_grams),
+            _ => {
+                let mut grams = self.grams(key);
+                grams.truncate(maximum_number_of_grams);
+                Self::count_ngrams(grams)
+            }
+        }
+    }
+
+    /// Returns the grams of the provided key, padded according to the mode.
+    ///
+    /// # Arguments
+    /// * `key` - The key whose grams are returned.
+    ///
+    /// # Implementation details
+    /// The padding of the key type is removed by skipping up to `ARITY - 1` padding
+    /// grams at each side of the key. Keys whose type does not pad their grams are
+    /// therefore supported as well, as long as they do not start or end with the
+    /// padding gram.
+    pub(crate) fn grams<NG, K>(&self, key: &K) -> Vec<G>
+    where
+        NG: Ngram<G = G>,
+        K: Key<NG, G> + ?Sized,
+    {
+        let mut grams: Vec<G> = key.grams().collect();
+        let leading_padding = NG::PADDING
+            .into_iter()
+            .zip(grams.iter())
+            .take_while(|(padding, gram)| padding == *gram)
+            .count();
+        grams.drain(..leading_padding);
+        let trailing_padding = NG::PADDING
+            .into_iter()
+            .zip(grams.iter().rev())
+            .take_while(|(padding, gram)| padding == *gram)
+            .count();
+        grams.truncate(grams.len() - trailing_padding);
+
+        if let PaddingMode::Pad(gram) = *self {
+            let padding = std::iter::repeat(gram).take(NG::ARITY - 1);
+            grams.splice(0..0, padding.clone());
+            grams.extend(padding);
+        }
+
+        grams
+    }
+
+    /// Returns the counts of the ngrams of the provided grams.
+    ///
+    /// # Arguments
+    /// * `grams` - The grams whose ngrams are counted.
+    fn count_ngrams<NG, S>(grams: Vec<G>) -> HashMap<NG, usize, S>
+    where
+        NG: Ngram<G = G>,
+        S: BuildHasher + Default,
+    {
+        let mut ngram_counts: HashMap<NG, usize, S> = HashMap::with_hasher(S::default());
+
+        // Fewer grams than the arity do not make any ngram.
+        if grams.len() < NG::ARITY {
+            return ngram_counts;
+        }
+
+        for ngram in grams.into_iter().ngrams::<NG>() {
+            ngram_counts
+                .entry(ngram)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+
+        ngram_counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_no_padding_emits_nothing_for_keys_shorter_than_the_arity() {
+        for key in ["", "a", "ab"] {
+            let counts = PaddingMode::None.counts::<TriGram<char>, str>(key);
+            assert!(counts.is_empty(), "Key {:?}", key);
+        }
+        let counts = PaddingMode::None.counts::<TriGram<char>, str>("abc");
+        assert_eq!(counts.len(), 1);
+
+        // Unigrams are never padded, so all of the modes agree.
+        for padding in [PaddingMode::None, PaddingMode::Auto, PaddingMode::Pad('$')] {
+            let counts = padding.counts::<UniGram<char>, str>("ab");
+            assert_eq!(counts.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_unpadded_corpus_pads_queries_as_keys() {
+        let keys = ["a", "abc", "abcd", "xbcd"];
+        let corpus: Corpus<[&str; 4], TriGram<char>> =
+            Corpus::from_with_padding(keys, PaddingMode::None);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        // The key shorter than the arity has no ngrams, and it is never returned.
+        assert_eq!(corpus.number_of_ngrams_from_key_id(0), 0);
+        assert!(corpus.ngram_search("a", config).is_empty());
+
+        let results = corpus.ngram_search("abcd", config);
+        assert_eq!(results[0].key(), &"abcd");
+        assert_eq!(results[0].score(), 1.0);
+        assert!(results.iter().all(|result| result.key() != &"a"));
+
+        // Without padding, the inner trigram "bcd" weighs as much as the first one.
+        let results: SearchResults<&&str, f32> =
+            corpus.ngram_search_with_metric("abcd", config, JaccardMetric);
+        let xbcd = results
+            .iter()
+            .find(|result| result.key() == &"xbcd")
+            .unwrap();
+        assert_eq!(xbcd.score(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_padding_modes_on_byte_keys() {
+        let auto = PaddingMode::Auto.counts::<BiGram<u8>, str>("abc");
+        let none = PaddingMode::None.counts::<BiGram<u8>, str>("abc");
+        let pad = PaddingMode::Pad(b'_').counts::<BiGram<u8>, str>("abc");
+
+        assert_eq!(auto.len(), 4);
+        assert_eq!(none.len(), 2);
+        assert_eq!(pad.len(), 4);
+        assert_eq!(pad.get(&[b'_', b'a']), Some(&1));
+        assert_eq!(pad.get(&[b'c', b'_']), Some(&1));
+
+        let truncated = PaddingMode::None.truncated_counts::<BiGram<u8>, str>("abcdef", 3);
+        assert_eq!(truncated.len(), 2);
+        assert_eq!(truncated.get(&[b'b', b'c']), Some(&1));
+    }
+}
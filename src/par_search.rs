@@ -6,8 +6,10 @@ use crate::traits::key::Key;
 use crate::NgramIdsAndCooccurrences;
 use crate::SearchResults;
 use crate::SearchResultsHeap;
-use crate::{Corpus, Float, Keys, Ngram, SearchResult, WeightedBipartiteGraph};
+use crate::{Corpus, Float, Keys, Ngram, WeightedBipartiteGraph};
+use fxhash::FxBuildHasher;
 use rayon::prelude::*;
+use std::collections::HashMap;
 
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
 where
@@ -30,58 +32,185 @@ where
     /// * `config` - The configuration for the search.
     /// * `similarity` - A function that computes the similarity between the query hashmap
     /// and the ngram ids and cooccurrences.
+    ///
+    /// # Implementation details
+    /// The candidate keys are gathered in parallel from the query ngrams that are not too
+    /// common, as described in `par_candidate_key_ids`, before being scored in parallel.
+    /// Each thread keeps its best results in its own heap, and the heaps are merged at the
+    /// end, so that the results are the same top results of the `search` method.
     pub(crate) fn par_search<KR, F: Float>(
         &self,
         key: KR,
         config: SearchConfig<F>,
         similarity: impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F + Send + Sync,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F>
     where
         KR: AsRef<K> + Send + Sync,
     {
         let key: &K = key.as_ref();
-        let query_hashmap = self.ngram_ids_from_ngram_counts(key.counts());
-        let query_hashmap_ref = &query_hashmap;
-        let max_ngram_degree = config.compute_max_ngram_degree(self.number_of_keys());
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(key));
+        let candidate_ngram_ids = self.candidate_ngram_ids(&query_hashmap, config);
+        let scoring_query_hashmap =
+            self.scoring_query_hashmap(&query_hashmap, &candidate_ngram_ids, config);
+        let scoring_query_hashmap = scoring_query_hashmap.as_ref().unwrap_or(&query_hashmap);
 
-        // We identify all of the ngrams to be considered in the search, which
-        // are the set of ngrams that contain any of the grams in the ngram
-        let mut matches = query_hashmap_ref
-            .par_ngram_ids()
-            .enumerate()
-            .flat_map(|(ngram_number, ngram_id)| {
-                // If this term is too common, we can skip it as it does not provide
-                // much information associated to the rarity of this term.
-                if self.number_of_keys_from_ngram_id(ngram_id) > max_ngram_degree {
-                    return Vec::new();
-                }
-                let mut heap = SearchResultsHeap::new(config.maximum_number_of_results());
-                self.key_ids_from_ngram_id(ngram_id).for_each(|key_id| {
-                    if self.contains_any_ngram_ids(
-                        query_hashmap_ref.ngram_ids().take(ngram_number),
+        // We gather the keys sharing enough of the ngrams to be considered in the search,
+        // each appearing once so that it is scored exactly once.
+        let candidate_key_ids =
+            self.par_candidate_key_ids(&candidate_ngram_ids, config.minimum_shared_ngrams());
+
+        // Each thread keeps the best results of the keys it scores in its own heap,
+        // and the heaps are merged at the end.
+        candidate_key_ids
+            .par_iter()
+            .fold(
+                || SearchResultsHeap::new(config.maximum_number_of_results()),
+                |mut heap, &key_id| {
+                    let score = self.score_key_id(
+                        &query_hashmap,
+                        scoring_query_hashmap,
+                        config,
+                        &similarity,
                         key_id,
-                    ) {
-                        // If it has found any gram in the ngram, excluding the one we are currently
-                        // looking at, then we can exclude it as it will be included by the other
-                        // ngrams
-                        return;
-                    }
-                    // At this point, we can compute the similarity.
-                    let score = similarity(
-                        query_hashmap_ref,
-                        self.ngram_ids_and_cooccurrences_from_key(key_id),
                     );
                     if score >= config.minimum_similarity_score() {
-                        heap.push(SearchResult::new(self.key_from_id(key_id), score));
+                        heap.push(key_id, score);
+                    }
+                    heap
+                },
+            )
+            .reduce(
+                || SearchResultsHeap::new(config.maximum_number_of_results()),
+                SearchResultsHeap::merge,
+            )
+            .into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    /// Returns the sorted ids of the keys containing at least the provided number of the
+    /// provided ngrams.
+    ///
+    /// # Arguments
+    /// * `ngram_ids` - The ids of the ngrams whose keys are gathered.
+    /// * `minimum_shared_ngrams` - The minimum number of the ngrams a key must contain.
+    ///
+    /// # Implementation details
+    /// For long queries sharing thousands of ngrams with many keys, gathering the candidates
+    /// dominates the search, as it is linear in the sum of the lengths of the posting lists
+    /// of the ngrams. The posting list of each ngram is therefore folded in parallel into
+    /// a counting map keyed by key id, one per thread, and the maps are merged at the end.
+    /// The counts are only used to drop the keys sharing too few ngrams before scoring:
+    /// the score weighs the co-occurrences of the ngrams, so it cannot be derived from them.
+    pub(crate) fn par_candidate_key_ids(
+        &self,
+        ngram_ids: &[usize],
+        minimum_shared_ngrams: usize,
+    ) -> Vec<usize> {
+        let counts: HashMap<usize, usize, FxBuildHasher> = ngram_ids
+            .par_iter()
+            .fold(
+                HashMap::default,
+                |mut counts: HashMap<usize, usize, FxBuildHasher>, &ngram_id| {
+                    for key_id in self.key_ids_from_ngram_id(ngram_id) {
+                        *counts.entry(key_id).or_insert(0) += 1;
                     }
-                });
-                heap.into_sorted_vec()
-            })
-            .collect::<SearchResults<'_, KS, NG, F>>();
+                    counts
+                },
+            )
+            .reduce(HashMap::default, |left, right| {
+                // We merge the smaller map into the larger one.
+                let (mut larger, smaller) = if left.len() >= right.len() {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                for (key_id, count) in smaller {
+                    *larger.entry(key_id).or_insert(0) += count;
+                }
+                larger
+            });
+
+        let mut key_ids: Vec<usize> = counts
+            .into_iter()
+            .filter(|&(_, count)| count >= minimum_shared_ngrams)
+            .map(|(key_id, _)| key_id)
+            .collect();
+        key_ids.par_sort_unstable();
+        key_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::search::SearchConfig;
+
+    #[test]
+    fn test_par_candidate_key_ids_agree_with_serial_gather() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+
+        for (query, minimum_shared_ngrams) in [
+            "Cat",
+            "Sea lion",
+            "Greater white-fronted goose",
+            "Xylophone",
+        ]
+        .into_iter()
+        .flat_map(|query| [1, 2, 4].map(|minimum_shared_ngrams| (query, minimum_shared_ngrams)))
+        {
+            let config: SearchConfig<f32> = NgramSearchConfig::default()
+                .set_minimum_similarity_score(0.0_f32)
+                .unwrap()
+                .set_max_ngram_degree(MaxNgramDegree::None)
+                .set_minimum_shared_ngrams(minimum_shared_ngrams)
+                .into();
+            let query_hashmap = corpus.ngram_ids_from_ngram_counts(corpus.key_counts(query));
+            let candidate_ngram_ids = corpus.candidate_ngram_ids(&query_hashmap, config);
+
+            // The serial gather scores each candidate key exactly once.
+            let mut serial_key_ids = Vec::new();
+            corpus.for_each_scored_key(
+                &query_hashmap,
+                config,
+                |_, _| 1.0_f32,
+                |_| true,
+                |key_id, _| serial_key_ids.push(key_id),
+            );
+            serial_key_ids.sort_unstable();
+
+            let key_ids = corpus.par_candidate_key_ids(&candidate_ngram_ids, minimum_shared_ngrams);
+            assert_eq!(key_ids, serial_key_ids, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_par_search_agrees_with_search() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for query in [
+            "Cat",
+            "Catt",
+            "Dog",
+            "Sea lion",
+            "Zebra",
+            "Owl",
+            "Xylophone",
+        ] {
+            for maximum_number_of_results in [1, 10, ANIMALS.len()] {
+                let config = config.set_maximum_number_of_results(maximum_number_of_results);
+                let results: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+                let par_results: SearchResults<&&str, f32> = corpus.ngram_par_search(query, config);
 
-        // Sort highest similarity to lowest
-        matches.par_sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
-        matches.truncate(config.maximum_number_of_results());
-        matches
+                // The ties are broken by key id, so the rankings must be identical,
+                // including the truncated ones.
+                assert_eq!(results, par_results, "{}", query);
+            }
+        }
     }
 }
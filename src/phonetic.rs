@@ -0,0 +1,294 @@
+//! Submodule providing the phonetic pre-bucketing of the keys of a corpus.
+//!
+//! # Implementation details
+//! Names are often spelled in several ways that sound the same, such as "Smith" and
+//! "Smyth", and the ngram search alone may rank such variants below unrelated keys that
+//! happen to share more ngrams. The phonetic index assigns each key to the buckets of the
+//! Soundex codes of its words, and `search_phonetic` only scores the keys sharing at least
+//! one bucket with the query, combining the phonetic matching with the ngram scoring.
+//!
+//! The Soundex code of a word is its first letter followed by three digits encoding the
+//! following consonants, where similar sounding consonants share the same digit. Only
+//! the ASCII letters of a word are encoded, so the other characters are ignored.
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// The Soundex code of a word, made of an uppercase ASCII letter and three ASCII digits.
+pub type PhoneticCode = [u8; 4];
+
+#[inline(always)]
+/// Returns the Soundex digit of the provided uppercase letter, or zero for the vowels.
+///
+/// # Arguments
+/// * `letter` - The uppercase ASCII letter to encode.
+fn soundex_digit(letter: char) -> u8 {
+    match letter {
+        'B' | 'F' | 'P' | 'V' => b'1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => b'2',
+        'D' | 'T' => b'3',
+        'L' => b'4',
+        'M' | 'N' => b'5',
+        'R' => b'6',
+        _ => 0,
+    }
+}
+
+/// Returns the Soundex code of the provided word, if it contains any ASCII letter.
+///
+/// # Arguments
+/// * `word` - The characters of the word to encode.
+///
+/// # Implementation details
+/// The consonants with the same digit are encoded once when they are adjacent, or when
+/// they are only separated by an 'h' or a 'w', while the vowels separate them. Codes
+/// with fewer than three digits are padded with zeros.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::phonetic::soundex;
+///
+/// assert_eq!(soundex("Smith".chars()), Some(*b"S530"));
+/// assert_eq!(soundex("Smyth".chars()), Some(*b"S530"));
+/// assert_eq!(soundex("Ashcraft".chars()), Some(*b"A261"));
+/// assert_eq!(soundex("Lee".chars()), Some(*b"L000"));
+/// assert_eq!(soundex("42".chars()), None);
+/// ```
+pub fn soundex<I>(word: I) -> Option<PhoneticCode>
+where
+    I: IntoIterator<Item = char>,
+{
+    let mut letters = word
+        .into_iter()
+        .filter(char::is_ascii_alphabetic)
+        .map(|letter| letter.to_ascii_uppercase());
+    let first = letters.next()?;
+    let mut code = [first as u8, b'0', b'0', b'0'];
+    let mut length = 1;
+    let mut previous = soundex_digit(first);
+
+    for letter in letters {
+        if length == code.len() {
+            break;
+        }
+        // The 'h' and the 'w' do not separate consonants with the same digit.
+        if letter == 'H' || letter == 'W' {
+            continue;
+        }
+        let digit = soundex_digit(letter);
+        if digit != 0 && digit != previous {
+            code[length] = digit;
+            length += 1;
+        }
+        previous = digit;
+    }
+
+    Some(code)
+}
+
+/// Returns the sorted and unique Soundex codes of the words of the provided text.
+///
+/// # Arguments
+/// * `text` - The characters of the text, whose words are separated by any character
+/// that is not a letter.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::phonetic::phonetic_codes;
+///
+/// assert_eq!(
+///     phonetic_codes("John Smith".chars()),
+///     vec![*b"J500", *b"S530"]
+/// );
+/// assert!(phonetic_codes("".chars()).is_empty());
+/// ```
+pub fn phonetic_codes<I>(text: I) -> Vec<PhoneticCode>
+where
+    I: IntoIterator<Item = char>,
+{
+    let text: String = text.into_iter().collect();
+    let mut codes: Vec<PhoneticCode> = text
+        .split(|character: char| !character.is_alphabetic())
+        .filter_map(|word| soundex(word.chars()))
+        .collect();
+    codes.sort_unstable();
+    codes.dedup();
+    codes
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The phonetic buckets of the keys of a corpus, used by the phonetic search.
+///
+/// # Implementation details
+/// Each bucket stores the sorted ids of the keys with a word with its Soundex code, so
+/// that a key with several words appears in several buckets. The keys inserted after the
+/// index was built are in no bucket, and are therefore never returned by `search_phonetic`
+/// until the index is built again, while the removed keys are skipped by the search.
+pub struct PhoneticIndex {
+    /// The sorted key ids of each bucket.
+    buckets: HashMap<PhoneticCode, Vec<usize>>,
+}
+
+impl PhoneticIndex {
+    #[inline(always)]
+    /// Returns the number of non-empty buckets.
+    pub fn number_of_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    #[inline(always)]
+    /// Returns the sorted ids of the keys in the bucket with the provided code.
+    ///
+    /// # Arguments
+    /// * `code` - The Soundex code of the bucket.
+    pub fn bucket(&self, code: &PhoneticCode) -> &[usize] {
+        self.buckets.get(code).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the sorted and unique ids of the keys sharing a bucket with the provided codes.
+    ///
+    /// # Arguments
+    /// * `codes` - The Soundex codes of the buckets.
+    fn candidates(&self, codes: &[PhoneticCode]) -> Vec<usize> {
+        let mut candidates: Vec<usize> = codes
+            .iter()
+            .flat_map(|code| self.bucket(code).iter().copied())
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    NG::G: Into<char>,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the Soundex codes of the words of the provided key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to encode.
+    fn phonetic_codes_from_key(key: &K) -> Vec<PhoneticCode> {
+        phonetic_codes(key.grams().map(Into::into))
+    }
+
+    /// Returns the phonetic index of the keys of the corpus.
+    ///
+    /// # Implementation details
+    /// The words of each key are read from its grams, after the normalizations applied by
+    /// the key type, such as the lowercasing. The removed keys are not indexed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["Smith", "Smyth", "Jones"]);
+    /// let index = corpus.build_phonetic_index();
+    ///
+    /// assert_eq!(index.number_of_buckets(), 2);
+    /// assert_eq!(index.bucket(b"S530"), &[0, 1]);
+    /// assert_eq!(index.bucket(b"J520"), &[2]);
+    /// ```
+    pub fn build_phonetic_index(&self) -> PhoneticIndex {
+        let mut index = PhoneticIndex::default();
+        for key_id in self.iter_key_ids() {
+            let key = self.key_from_id(key_id);
+            for code in Self::phonetic_codes_from_key(key.as_ref()) {
+                index.buckets.entry(code).or_default().push(key_id);
+            }
+        }
+        index
+    }
+
+    /// Returns the best matches among the keys sharing a phonetic bucket with the query.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `index` - The phonetic index built from this corpus with `build_phonetic_index`.
+    ///
+    /// # Implementation details
+    /// The candidates are the keys with at least one word with the same Soundex code of a
+    /// word of the query, and they are scored as in the `ngram_search_with_warp` method.
+    /// A query without any ASCII letter has no bucket, and returns no results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["Smith", "Smyth", "Smithers", "Jones"]);
+    /// let index = corpus.build_phonetic_index();
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.search_phonetic("Smith", config, &index);
+    ///
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].key(), &"Smith");
+    /// assert_eq!(results[1].key(), &"Smyth");
+    /// ```
+    pub fn search_phonetic<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        index: &PhoneticIndex,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let candidates = index.candidates(&Self::phonetic_codes_from_key(key.as_ref()));
+        if candidates.is_empty() {
+            return SearchResults::default();
+        }
+
+        self.warped_search_with_filter(key, config, |key_id| {
+            candidates.binary_search(&key_id).is_ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smith_and_smyth_share_a_bucket_and_match() {
+        assert_eq!(soundex("Smith".chars()), soundex("Smyth".chars()));
+        assert_eq!(soundex("Robert".chars()), Some(*b"R163"));
+        assert_eq!(soundex("Rupert".chars()), Some(*b"R163"));
+        assert_eq!(soundex("Tymczak".chars()), Some(*b"T522"));
+        assert_eq!(soundex("Pfister".chars()), Some(*b"P236"));
+
+        let corpus: Corpus<[&str; 5], TriGram<char>> =
+            Corpus::from(["John Smith", "Jane Smyth", "Smithson", "Jones", "Schmidt"]);
+        let index = corpus.build_phonetic_index();
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        for query in ["Smith", "Smyth"] {
+            let results: SearchResults<&&str, f32> = corpus.search_phonetic(query, config, &index);
+            let keys: Vec<&str> = results.iter().map(|result| *result.key()).collect();
+            assert_eq!(keys.len(), 2);
+            assert!(keys.contains(&"John Smith"));
+            assert!(keys.contains(&"Jane Smyth"));
+        }
+
+        let results: SearchResults<&&str, f32> = corpus.search_phonetic("42", config, &index);
+        assert!(results.is_empty());
+    }
+}
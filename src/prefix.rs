@@ -0,0 +1,277 @@
+//! Submodule providing the prefix-constrained search of the keys of a corpus.
+//!
+//! # Implementation details
+//! When completing a partially typed key, such as in an autocomplete box, only the keys
+//! starting with the typed prefix are relevant. The prefix search scores only the keys
+//! starting with the provided prefix, which are either checked one at a time as they
+//! are reached by the search, or looked up in a prefix index, which stores the ids of
+//! the keys sorted by their value so that the keys with a given prefix are contiguous.
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The ids of the keys of a corpus sorted by the value of the keys, used by the prefix search.
+///
+/// # Implementation details
+/// The index stores a `usize` for each key of the corpus, so for instance about 20 MB
+/// for a corpus of 2.5 million keys, on top of the memory of the corpus itself. The keys
+/// are not copied, and are read from the corpus when looking up a prefix: the binary
+/// searches rely on the ids being sorted by the keys they have in the corpus, which no
+/// longer holds once `purge_tombstones` shifts the ids, so the index must then be built
+/// again.
+pub struct PrefixIndex {
+    /// The ids of the keys, sorted by the value of the keys.
+    key_ids: Vec<usize>,
+}
+
+impl PrefixIndex {
+    #[inline(always)]
+    /// Returns the number of indexed keys.
+    pub fn len(&self) -> usize {
+        self.key_ids.len()
+    }
+
+    #[inline(always)]
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.key_ids.is_empty()
+    }
+
+    #[inline(always)]
+    /// Returns the ids of the keys, sorted by the value of the keys.
+    pub fn key_ids(&self) -> &[usize] {
+        &self.key_ids
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    <K as Key<NG, NG::G>>::Ref: AsRef<str>,
+    G: WeightedBipartiteGraph,
+{
+    #[inline(always)]
+    /// Returns the value of the provided key, which is compared with the prefixes.
+    ///
+    /// # Arguments
+    /// * `key` - The reference to the key.
+    pub(crate) fn key_str<'b, KR: AsRef<K>>(key: &'b KR) -> &'b str {
+        let key: &K = key.as_ref();
+        let key: &<K as Key<NG, NG::G>>::Ref = key.as_ref();
+        key.as_ref()
+    }
+
+    #[inline(always)]
+    /// Returns whether the key with the provided id starts with the provided prefix.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key.
+    /// * `prefix` - The prefix to check.
+    pub(crate) fn key_starts_with(&self, key_id: usize, prefix: &str) -> bool {
+        Self::key_str(&self.key_from_id(key_id)).starts_with(prefix)
+    }
+
+    /// Returns the prefix index of the keys of the corpus.
+    ///
+    /// # Implementation details
+    /// The keys are sorted by their value as stored in the corpus, before any of the
+    /// normalizations applied by the key type, such as the lowercasing. The removed keys
+    /// are not indexed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cattle", "dog", "cat"]);
+    /// let index = corpus.build_prefix_index();
+    ///
+    /// assert_eq!(index.key_ids(), &[2, 0, 1]);
+    /// ```
+    pub fn build_prefix_index(&self) -> PrefixIndex {
+        let mut key_ids: Vec<usize> = self.iter_key_ids().collect();
+        key_ids.sort_unstable_by(|&left, &right| {
+            let left = self.key_from_id(left);
+            let right = self.key_from_id(right);
+            Self::key_str(&left).cmp(Self::key_str(&right))
+        });
+        PrefixIndex { key_ids }
+    }
+
+    /// Returns the ids of the keys starting with the provided prefix, sorted by their value.
+    ///
+    /// # Arguments
+    /// * `index` - The prefix index built from this corpus with `build_prefix_index`.
+    /// * `prefix` - The prefix of the keys.
+    ///
+    /// # Implementation details
+    /// Since the keys with the same prefix are contiguous in the index, their range is
+    /// found with two binary searches, reading a logarithmic number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["cattle", "dog", "cat", "cow"]);
+    /// let index = corpus.build_prefix_index();
+    ///
+    /// assert_eq!(corpus.key_ids_with_prefix(&index, "cat"), &[2, 0]);
+    /// assert_eq!(corpus.key_ids_with_prefix(&index, "c"), &[2, 0, 3]);
+    /// assert!(corpus.key_ids_with_prefix(&index, "bird").is_empty());
+    /// ```
+    pub fn key_ids_with_prefix<'a>(
+        &self,
+        index: &'a PrefixIndex,
+        prefix: &<K as Key<NG, NG::G>>::Ref,
+    ) -> &'a [usize] {
+        let prefix: &str = prefix.as_ref();
+        let start = index
+            .key_ids
+            .partition_point(|&key_id| Self::key_str(&self.key_from_id(key_id)) < prefix);
+        let end = start
+            + index.key_ids[start..]
+                .partition_point(|&key_id| self.key_starts_with(key_id, prefix));
+        &index.key_ids[start..end]
+    }
+
+    /// Returns the best matches among the keys starting with the provided prefix.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `prefix` - The prefix of the keys to score.
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The keys are scored as in the `ngram_search_with_warp` method, but the keys not
+    /// starting with the prefix are skipped before being scored. The prefix is compared
+    /// with the keys as stored in the corpus, so the comparison is case sensitive even
+    /// when the key type lowercases the keys. When searching repeatedly, the
+    /// `prefix_search_with_index` method avoids reading the keys that are reached
+    /// by the search but do not start with the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["catfish", "cattle", "bobcat", "cat"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.prefix_search("cat", "catt", config);
+    ///
+    /// assert_eq!(results.into_keys(), vec![&"cattle"]);
+    /// ```
+    pub fn prefix_search<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        prefix: &<K as Key<NG, NG::G>>::Ref,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let prefix: &str = prefix.as_ref();
+        self.warped_search_with_filter(key, config, |key_id| self.key_starts_with(key_id, prefix))
+    }
+
+    /// Returns the best matches among the keys starting with the provided prefix, using
+    /// the provided prefix index.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `prefix` - The prefix of the keys to score.
+    /// * `config` - The configuration for the search.
+    /// * `index` - The prefix index built from this corpus with `build_prefix_index`.
+    ///
+    /// # Implementation details
+    /// The ids of the keys starting with the prefix are looked up in the index and sorted,
+    /// so that the keys reached by the search are filtered with a binary search instead of
+    /// being read. The results are the same ones of the `prefix_search` method.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["catfish", "cattle", "bobcat", "cat"]);
+    /// let index = corpus.build_prefix_index();
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.prefix_search_with_index("cat", "cat", config, &index);
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// assert_eq!(results[0].key(), &"cat");
+    /// ```
+    pub fn prefix_search_with_index<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        prefix: &<K as Key<NG, NG::G>>::Ref,
+        config: NgramSearchConfig<W, F>,
+        index: &PrefixIndex,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let mut candidates = self.key_ids_with_prefix(index, prefix).to_vec();
+        if candidates.is_empty() {
+            return SearchResults::default();
+        }
+        candidates.sort_unstable();
+
+        self.warped_search_with_filter(key, config, |key_id| {
+            candidates.binary_search(&key_id).is_ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_search_with_and_without_index() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let index = corpus.build_prefix_index();
+        assert_eq!(index.len(), ANIMALS.len());
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(ANIMALS.len());
+
+        for (query, prefix) in [
+            ("Cat", "Ca"),
+            ("Sea lion", "Sea"),
+            ("Dog", "D"),
+            ("Cat", ""),
+        ] {
+            let expected = ANIMALS
+                .iter()
+                .filter(|animal| animal.starts_with(prefix))
+                .count();
+            assert_eq!(corpus.key_ids_with_prefix(&index, prefix).len(), expected);
+
+            let results: SearchResults<&&str, f32> = corpus.prefix_search(query, prefix, config);
+            let indexed_results: SearchResults<&&str, f32> =
+                corpus.prefix_search_with_index(query, prefix, config, &index);
+
+            assert!(results
+                .iter()
+                .all(|result| result.key().starts_with(prefix)));
+            assert!(results.scores().eq(indexed_results.scores()));
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Submodule providing the pruning of the ngrams of a corpus by their degree.
+//!
+//! # Implementation details
+//! An ngram appearing in a single key cannot relate it to any other key, while an ngram
+//! appearing in most of the keys drags in a large number of candidates and carries
+//! little information about them. Dropping both kinds of ngrams after the construction
+//! shrinks the vocabulary and the graph. The graph is rebuilt from its own edges rather
+//! than from the keys, so that the choices made at construction time, such as the
+//! truncation of the keys or the ngrams excluded by the configuration, are preserved.
+use std::collections::HashMap;
+
+use fxhash::FxBuildHasher;
+
+use crate::{
+    bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, corpus_from::KeysParser,
+    overflow_graph::OverflowGraph, traits::*, Corpus, ReusableCorpusBuilder,
+};
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Removes the ngrams appearing in fewer than `min_degree` or in more than
+    /// `max_degree` keys, rebuilding the graph without them.
+    ///
+    /// # Arguments
+    /// * `min_degree` - The minimum number of keys an ngram must appear in to be kept.
+    /// * `max_degree` - The maximum number of keys an ngram may appear in to be kept.
+    ///
+    /// # Implementation details
+    /// The degree of an ngram is the number of keys containing it, as returned by
+    /// `number_of_keys_from_ngram_id`, so that the removed keys are not counted. The kept
+    /// ngrams keep their relative order, so that the ngram with the old id `ngram_id`
+    /// receives the new id `ngram_id` minus the number of pruned ngrams with a smaller id.
+    /// The ngram ids obtained before the pruning must therefore not be used afterwards,
+    /// while `ngram_id_from_ngram` returns the new ids of the kept ngrams and `None` for
+    /// the pruned ones, which are treated as unknown by the searches.
+    ///
+    /// The ids of the keys are unchanged, the removed keys stay removed, and the keys
+    /// whose ngrams were all pruned are kept without edges, so that they are no longer
+    /// returned by the searches. The cooccurrences of the kept edges are unchanged, while
+    /// the average key length is recomputed on the kept ngrams. The ngrams of the overflow
+    /// keys are not pruned, and their ids are resolved again against the new vocabulary.
+    /// No work is done when no ngram is pruned.
+    ///
+    /// # Raises
+    /// * If `min_degree` is greater than `max_degree`.
+    /// * If all of the ngrams of the corpus would be pruned, since a corpus needs at least
+    /// one ngram. In both cases, the corpus is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cats", "dog"]);
+    /// assert!(corpus.contains_ngram(['a', 't', 's']));
+    ///
+    /// // We drop the ngrams appearing in a single key.
+    /// corpus.prune_ngrams(2, usize::MAX).unwrap();
+    ///
+    /// assert_eq!(corpus.number_of_ngrams(), 3);
+    /// assert!(!corpus.contains_ngram(['a', 't', 's']));
+    /// assert_eq!(corpus.ngram_id_from_ngram(['c', 'a', 't']), Some(2));
+    /// assert_eq!(corpus.number_of_ngrams_from_key_id(2), 0);
+    ///
+    /// // Pruning all of the ngrams is an error, which leaves the corpus unchanged.
+    /// assert!(corpus.prune_ngrams(usize::MAX, usize::MAX).is_err());
+    /// assert_eq!(corpus.number_of_ngrams(), 3);
+    /// ```
+    pub fn prune_ngrams(
+        &mut self,
+        min_degree: usize,
+        max_degree: usize,
+    ) -> Result<(), &'static str> {
+        if min_degree > max_degree {
+            return Err("The minimum degree must not be greater than the maximum degree.");
+        }
+
+        let kept: Vec<bool> = (0..self.number_of_ngrams())
+            .map(|ngram_id| {
+                let degree = self.number_of_keys_from_ngram_id(ngram_id);
+                degree >= min_degree && degree <= max_degree
+            })
+            .collect();
+        if kept.iter().all(|&keep| keep) {
+            return Ok(());
+        }
+        if !kept.contains(&true) {
+            return Err("The degree bounds would prune all of the ngrams of the corpus.");
+        }
+
+        debug!(
+            "Pruning {} ngrams out of {}.",
+            kept.iter().filter(|&&keep| !keep).count(),
+            kept.len()
+        );
+
+        let number_of_graph_keys = self.graph.number_of_source_nodes();
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let mut parser = KeysParser::new(number_of_graph_keys, builder);
+        for key_id in 0..number_of_graph_keys {
+            // The ngrams of a key are sorted by id, and therefore by ngram.
+            builder.ngram_counts.clear();
+            builder.ngram_counts.extend(
+                self.ngram_ids_and_cooccurrences_from_key(key_id)
+                    .filter(|&(ngram_id, _)| kept[ngram_id])
+                    .map(|(ngram_id, cooccurrence)| (self.ngram_from_id(ngram_id), cooccurrence)),
+            );
+            parser.digest_ngram_counts(builder);
+        }
+        let (cooccurrences_builder, average_key_length, key_offsets) = parser.finish(builder);
+
+        // The ngram ids of the overflow keys depend on the vocabulary being replaced.
+        let overflow_ngram_counts: Vec<HashMap<NG, usize, FxBuildHasher>> = (number_of_graph_keys
+            ..self.number_of_key_ids())
+            .map(|key_id| self.ngrams_and_cooccurrences_from_key(key_id).collect())
+            .collect();
+
+        let (ngrams, graph) = Self::graph_from_parsed_keys(
+            number_of_graph_keys,
+            cooccurrences_builder,
+            key_offsets,
+            builder,
+        );
+        self.ngrams = ngrams;
+        self.graph = graph;
+        self.average_key_length = average_key_length.max(1.0);
+        self.overflow = OverflowGraph::default();
+        for (key_id, ngram_counts) in (number_of_graph_keys..).zip(overflow_ngram_counts) {
+            self.overflow.push(key_id, ngram_counts, &self.ngrams);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_pruning_the_ngrams_of_a_single_key() {
+        let keys = ["cat", "cats", "dog", "dogs", "zebra"];
+        let mut corpus: Corpus<[&str; 5], TriGram<char>> = Corpus::from(keys);
+        let original: Corpus<[&str; 5], TriGram<char>> = Corpus::from(keys);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        corpus.prune_ngrams(2, usize::MAX).unwrap();
+
+        assert_eq!(original.number_of_ngrams(), 22);
+        assert_eq!(corpus.number_of_ngrams(), 7);
+        assert!(corpus.document_frequencies().all(|degree| degree >= 2));
+
+        // The new ids are consistent with the vocabulary.
+        for (ngram_id, ngram) in corpus.iter_ngrams_with_ids() {
+            assert_eq!(corpus.ngram_id_from_ngram(ngram), Some(ngram_id));
+            assert_eq!(
+                corpus.number_of_keys_from_ngram_id(ngram_id),
+                original.number_of_keys_from_ngram(ngram).unwrap()
+            );
+        }
+        assert_eq!(corpus.ngram_id_from_ngram(['a', 't', '\0']), None);
+
+        // The keys keep the cooccurrences of their ngrams that were not pruned.
+        for key_id in 0..keys.len() {
+            let expected: Vec<(TriGram<char>, usize)> = original
+                .ngrams_and_cooccurrences_from_key(key_id)
+                .filter(|(ngram, _)| original.number_of_keys_from_ngram(*ngram).unwrap() >= 2)
+                .collect();
+            assert!(corpus
+                .ngrams_and_cooccurrences_from_key(key_id)
+                .eq(expected.into_iter()));
+        }
+
+        // The best matches of the distinctive queries are unchanged.
+        for query in ["cat", "cats", "dog", "dogs"] {
+            assert_eq!(
+                corpus.ngram_search(query, config)[0].key(),
+                original.ngram_search(query, config)[0].key(),
+            );
+        }
+        assert!(corpus.ngram_search("zebra", config).is_empty());
+
+        // Pruning again with the same bounds has no effect.
+        let ngrams: Vec<TriGram<char>> = corpus.ngrams().collect();
+        corpus.prune_ngrams(2, usize::MAX).unwrap();
+        assert!(corpus.ngrams().eq(ngrams.into_iter()));
+
+        // The ngrams shared by more than one key can be pruned as well.
+        let mut corpus: Corpus<[&str; 5], TriGram<char>> = Corpus::from(keys);
+        corpus.prune_ngrams(0, 1).unwrap();
+        assert_eq!(corpus.number_of_ngrams(), 15);
+        assert!(!corpus.contains_ngram(['c', 'a', 't']));
+        assert_eq!(corpus.ngram_search("zebra", config)[0].key(), &"zebra");
+    }
+
+    #[test]
+    fn test_invalid_pruning_leaves_the_corpus_unchanged() {
+        let keys = ["cat", "cats", "dog"];
+        let mut corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(keys);
+        let ngrams: Vec<TriGram<char>> = corpus.ngrams().collect();
+
+        assert!(corpus.prune_ngrams(3, 2).is_err());
+        assert!(corpus.prune_ngrams(usize::MAX, usize::MAX).is_err());
+        // No ngram appears in more than two keys, so that none is left.
+        assert!(corpus.prune_ngrams(3, usize::MAX).is_err());
+
+        assert!(corpus.ngrams().eq(ngrams.into_iter()));
+        assert!(corpus.graph().number_of_edges() > 0);
+        assert_eq!(
+            corpus.ngram_search("cat", NgramSearchConfig::<i32, f32>::default())[0].key(),
+            &"cat"
+        );
+    }
+
+    #[test]
+    fn test_pruning_keeps_the_overflow_keys() {
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+            Corpus::from(vec!["cat", "cats", "dog", "dogs"]);
+        corpus.insert("catfish");
+        corpus.insert("zebra");
+        let expected: Vec<Vec<(TriGram<char>, usize)>> = (0..corpus.number_of_key_ids())
+            .map(|key_id| corpus.ngrams_and_cooccurrences_from_key(key_id).collect())
+            .collect();
+
+        corpus.prune_ngrams(2, usize::MAX).unwrap();
+
+        for (key_id, ngrams) in expected.into_iter().enumerate().skip(4) {
+            let mut pruned: Vec<(TriGram<char>, usize)> =
+                corpus.ngrams_and_cooccurrences_from_key(key_id).collect();
+            pruned.sort_unstable();
+            let mut ngrams = ngrams;
+            ngrams.sort_unstable();
+            assert_eq!(pruned, ngrams);
+        }
+        for key_id in 4..corpus.number_of_key_ids() {
+            for ngram_id in corpus.ngram_ids_from_key(key_id) {
+                let ngram = corpus.ngram_from_id(ngram_id);
+                assert_eq!(corpus.ngram_id_from_ngram(ngram), Some(ngram_id));
+            }
+        }
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default();
+        assert_eq!(corpus.ngram_search("zebra", config)[0].key(), &"zebra");
+        assert_eq!(corpus.ngram_search("catfish", config)[0].key(), &"catfish");
+    }
+}
@@ -0,0 +1,260 @@
+//! Submodule providing a cache of the results of the ngram searches of a corpus.
+//!
+//! # Implementation details
+//! In interactive search the same query is often repeated, as with the prefixes typed
+//! by a user. The `CachedCorpus` borrows the corpus it searches, which therefore cannot
+//! be modified while the cache is alive, so that the cached results never need to be
+//! invalidated. The queries are keyed by their sorted ngram counts, so that the queries
+//! normalized into the same ngrams, such as by the `Lowercase` wrapper, share an entry.
+//! When the cache is full, the least recently used entry is found by scanning the
+//! entries, whose cost is negligible compared to the search of a cache miss.
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use fxhash::FxBuildHasher;
+
+use crate::prelude::*;
+use crate::search::SearchConfig;
+
+/// An entry of the query cache.
+struct CacheEntry<KR, F: Float> {
+    /// The configuration of the search that produced the results.
+    config: SearchConfig<F>,
+    /// The results of the search.
+    results: Rc<SearchResults<KR, F>>,
+    /// The value of the clock of the cache when the entry was last used.
+    last_used: u64,
+}
+
+/// A corpus wrapper memoizing the results of its ngram searches.
+pub struct CachedCorpus<'a, KS, NG, K, G, F = f32>
+where
+    NG: Ngram,
+    KS: Keys<NG> + 'a,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+    F: Float,
+{
+    /// The corpus to search.
+    corpus: &'a Corpus<KS, NG, K, G>,
+    /// The maximum number of cached queries.
+    capacity: usize,
+    /// The cached results, keyed by the sorted ngram counts of the query.
+    entries: HashMap<Vec<(NG, usize)>, CacheEntry<KS::KeyRef<'a>, F>, FxBuildHasher>,
+    /// A counter increased at each search, used to find the least recently used entry.
+    clock: u64,
+    /// The number of searches answered from the cache.
+    hits: usize,
+    /// The number of searches that were not answered from the cache.
+    misses: usize,
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns a wrapper of the corpus caching the results of up to `capacity` queries.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of cached queries. With a capacity of zero,
+    /// nothing is cached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let mut cached: CachedCorpus<_, _, _, _, f32> = corpus.with_query_cache(100);
+    ///
+    /// let results = cached.ngram_search("Cat", NgramSearchConfig::default());
+    /// let cached_results = cached.ngram_search("Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert!(std::rc::Rc::ptr_eq(&results, &cached_results));
+    /// assert_eq!(cached.hits(), 1);
+    /// assert_eq!(cached.misses(), 1);
+    /// ```
+    pub fn with_query_cache<F: Float>(&self, capacity: usize) -> CachedCorpus<'_, KS, NG, K, G, F> {
+        CachedCorpus {
+            corpus: self,
+            capacity,
+            entries: HashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl<'a, KS, NG, K, G, F> CachedCorpus<'a, KS, NG, K, G, F>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'b> KS::KeyRef<'b>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+    F: Float,
+{
+    /// Returns the results of the ngram search of the provided key, from the cache if
+    /// the same query was searched with the same configuration.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The results are the ones of `Corpus::ngram_search`, and are shared with the cache,
+    /// so that they are cheap to clone. Each query has a single entry: searching it with
+    /// a different configuration is a miss, and replaces the cached results.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>, Lowercase<str>> =
+    ///     Corpus::from(["Cat", "Cattle", "Dog"]);
+    /// let mut cached: CachedCorpus<_, _, _, _, f32> = corpus.with_query_cache(10);
+    ///
+    /// // The queries with the same normalized ngrams share their entry.
+    /// cached.ngram_search("cat", NgramSearchConfig::default());
+    /// cached.ngram_search("CAT", NgramSearchConfig::default());
+    /// assert_eq!(cached.hits(), 1);
+    ///
+    /// // While changing the configuration is a miss.
+    /// let config = NgramSearchConfig::default().set_maximum_number_of_results(1);
+    /// let results = cached.ngram_search("cat", config);
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(cached.misses(), 2);
+    /// assert_eq!(cached.len(), 1);
+    /// ```
+    pub fn ngram_search(
+        &mut self,
+        key: impl AsRef<K>,
+        config: NgramSearchConfig<i32, F>,
+    ) -> Rc<SearchResults<KS::KeyRef<'a>, F>> {
+        let config = config.set_warp(2).unwrap();
+        let search_config: SearchConfig<F> = config.into();
+        let ngram_counts = self.corpus.key_counts(key.as_ref());
+        let mut query: Vec<(NG, usize)> = ngram_counts
+            .iter()
+            .map(|(ngram, count)| (*ngram, *count))
+            .collect();
+        query.sort_unstable();
+
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(&query) {
+            if entry.config == search_config {
+                self.hits += 1;
+                entry.last_used = self.clock;
+                return entry.results.clone();
+            }
+        }
+
+        self.misses += 1;
+        let results = Rc::new(self.corpus.ngram_search_ngram_counts(ngram_counts, config));
+        if self.capacity == 0 {
+            return results;
+        }
+        if self.entries.len() == self.capacity && !self.entries.contains_key(&query) {
+            self.evict_least_recently_used();
+        }
+        self.entries.insert(
+            query,
+            CacheEntry {
+                config: search_config,
+                results: results.clone(),
+                last_used: self.clock,
+            },
+        );
+        results
+    }
+
+    /// Removes the least recently used entry from the cache.
+    fn evict_least_recently_used(&mut self) {
+        let least_recently_used = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(query, _)| query.clone());
+        if let Some(query) = least_recently_used {
+            self.entries.remove(&query);
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the cached corpus.
+    pub fn corpus(&self) -> &'a Corpus<KS, NG, K, G> {
+        self.corpus
+    }
+
+    #[inline(always)]
+    /// Returns the maximum number of cached queries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline(always)]
+    /// Returns the number of cached queries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline(always)]
+    /// Returns whether no query is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline(always)]
+    /// Returns the number of searches answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    #[inline(always)]
+    /// Returns the number of searches that were not answered from the cache.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Removes all of the cached queries and resets the hit and miss counters.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.clock = 0;
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_query_cache_evicts_the_least_recently_used_query() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default();
+        let mut cached = corpus.with_query_cache(2);
+
+        let cat = cached.ngram_search("Cat", config);
+        cached.ngram_search("Dog", config);
+        // Searching "Cat" again makes "Dog" the least recently used query.
+        cached.ngram_search("Cat", config);
+        cached.ngram_search("Owl", config);
+        assert_eq!(cached.len(), 2);
+        assert_eq!((cached.hits(), cached.misses()), (1, 3));
+
+        assert_eq!(cached.ngram_search("Cat", config), cat);
+        assert_eq!(cached.hits(), 2);
+        cached.ngram_search("Dog", config);
+        assert_eq!(cached.misses(), 4);
+
+        assert_eq!(*cat, corpus.ngram_search("Cat", config));
+    }
+}
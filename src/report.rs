@@ -5,6 +5,9 @@
 use std::fmt;
 use std::fmt::Display;
 
+use mem_dbg::{MemDbg, MemSize, SizeFlags};
+
+use crate::bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph;
 use crate::prelude::*;
 
 /// A struct containing several informations regarding the corpus.
@@ -37,6 +40,77 @@ impl Display for CorpusReport {
     }
 }
 
+/// A struct containing the aggregate statistics of the degrees of the graph of the corpus.
+#[derive(Debug, Clone, PartialEq, MemSize, MemDbg)]
+pub struct CorpusStats {
+    /// The number of keys in the graph of the corpus.
+    pub number_of_keys: usize,
+    /// The number of ngrams in the corpus.
+    pub number_of_ngrams: usize,
+    /// The number of edges between the keys and the ngrams, counted once.
+    pub number_of_edges: usize,
+    /// The average number of distinct ngrams of a key.
+    pub average_key_degree: f64,
+    /// The minimum number of distinct ngrams of a key.
+    pub minimum_key_degree: usize,
+    /// The maximum number of distinct ngrams of a key.
+    pub maximum_key_degree: usize,
+    /// The average number of keys containing an ngram.
+    pub average_ngram_degree: f64,
+    /// The minimum number of keys containing an ngram.
+    pub minimum_ngram_degree: usize,
+    /// The maximum number of keys containing an ngram.
+    pub maximum_ngram_degree: usize,
+    /// The number of ngrams with each degree, indexed by the degree.
+    pub ngram_degree_histogram: Vec<usize>,
+}
+
+/// The size in bytes of each of the structures composing a corpus.
+///
+/// # Implementation details
+/// Each size is the one returned by the `MemSize` implementation of the structure with
+/// the default flags, so that it includes the stack size of the structure, and the
+/// capacity of the vectors is not counted. The sizes of the fields not listed here,
+/// such as the average key length, and the padding of the corpus struct are excluded,
+/// so that the `total` is at most the size returned by `mem_size` for the whole corpus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    /// The size of the keys of the corpus.
+    pub keys: usize,
+    /// The size of the sorted ngrams of the corpus.
+    pub ngrams: usize,
+    /// The size of the offsets of the edges from the keys to the ngrams.
+    pub key_offsets: usize,
+    /// The size of the offsets of the edges from the ngrams to the keys.
+    pub ngram_offsets: usize,
+    /// The size of the destinations of the edges from the keys to the ngrams.
+    pub key_to_ngram_edges: usize,
+    /// The size of the sources of the edges from the ngrams to the keys.
+    pub ngram_to_key_edges: usize,
+    /// The size of the cooccurrences weighting the edges from the keys to the ngrams.
+    pub weights: usize,
+    /// The size of the graph of the keys inserted after the construction.
+    pub overflow: usize,
+    /// The size of the bitmap of the removed keys.
+    pub tombstones: usize,
+}
+
+impl MemoryBreakdown {
+    /// Returns the size of the graph of the corpus, excluding the overflow keys.
+    pub fn graph(&self) -> usize {
+        self.key_offsets
+            + self.ngram_offsets
+            + self.key_to_ngram_edges
+            + self.ngram_to_key_edges
+            + self.weights
+    }
+
+    /// Returns the sum of the sizes of the structures of the corpus.
+    pub fn total(&self) -> usize {
+        self.keys + self.ngrams + self.graph() + self.overflow + self.tombstones
+    }
+}
+
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
 where
     NG: Ngram,
@@ -69,4 +143,170 @@ where
             number_of_edges,
         }
     }
+
+    /// Returns the aggregate statistics of the degrees of the graph of the corpus.
+    ///
+    /// # Implementation details
+    /// The degrees are read in a single pass of the `degrees` method of the graph, which
+    /// yields the degrees of the keys followed by the degrees of the ngrams. The removed
+    /// keys are still part of the graph, and are included in the statistics, while the
+    /// overflow keys are not. The minimum degrees are zero for an empty graph, and the
+    /// histogram has an entry for each degree up to the maximum ngram degree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    /// let stats = animals.stats();
+    ///
+    /// assert_eq!(stats.number_of_keys, 699);
+    /// assert_eq!(stats.number_of_ngrams, 2534);
+    /// assert_eq!(stats.number_of_edges, 9040);
+    /// assert_eq!(stats.ngram_degree_histogram.len(), stats.maximum_ngram_degree + 1);
+    /// assert_eq!(stats.ngram_degree_histogram.iter().sum::<usize>(), 2534);
+    /// assert!(stats.minimum_key_degree <= stats.maximum_key_degree);
+    /// ```
+    pub fn stats(&self) -> CorpusStats {
+        let number_of_keys = self.graph.number_of_source_nodes();
+        let number_of_ngrams = self.graph.number_of_destination_nodes();
+        let number_of_edges = self.graph.number_of_edges();
+
+        let mut minimum_key_degree = usize::MAX;
+        let mut maximum_key_degree = 0;
+        let mut minimum_ngram_degree = usize::MAX;
+        let mut maximum_ngram_degree = 0;
+        let mut ngram_degree_histogram = Vec::new();
+
+        for (node_id, degree) in self.graph.degrees().enumerate() {
+            if node_id < number_of_keys {
+                minimum_key_degree = minimum_key_degree.min(degree);
+                maximum_key_degree = maximum_key_degree.max(degree);
+            } else {
+                minimum_ngram_degree = minimum_ngram_degree.min(degree);
+                maximum_ngram_degree = maximum_ngram_degree.max(degree);
+                if ngram_degree_histogram.len() <= degree {
+                    ngram_degree_histogram.resize(degree + 1, 0);
+                }
+                ngram_degree_histogram[degree] += 1;
+            }
+        }
+
+        CorpusStats {
+            number_of_keys,
+            number_of_ngrams,
+            number_of_edges,
+            average_key_degree: if number_of_keys == 0 {
+                0.0
+            } else {
+                number_of_edges as f64 / number_of_keys as f64
+            },
+            minimum_key_degree: minimum_key_degree.min(maximum_key_degree),
+            maximum_key_degree,
+            average_ngram_degree: if number_of_ngrams == 0 {
+                0.0
+            } else {
+                number_of_edges as f64 / number_of_ngrams as f64
+            },
+            minimum_ngram_degree: minimum_ngram_degree.min(maximum_ngram_degree),
+            maximum_ngram_degree,
+            ngram_degree_histogram,
+        }
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: Keys<NG> + MemSize,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Returns the size in bytes of each of the structures composing the corpus.
+    ///
+    /// # Implementation details
+    /// The sizes are computed with the `MemSize` implementations of the structures, as
+    /// described in the documentation of `MemoryBreakdown`. The breakdown is meant to
+    /// find which of the structures dominates the memory footprint of a corpus, for
+    /// instance the offsets of a corpus of many short keys or the edges of one of long
+    /// keys, while `mem_dbg` prints the same information as a tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mem_dbg::{MemSize, SizeFlags};
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let animals: Corpus<_, TriGram<char>> = Corpus::from(ANIMALS);
+    /// let breakdown = animals.memory_breakdown();
+    ///
+    /// assert!(breakdown.ngrams > 0);
+    /// assert!(breakdown.graph() > breakdown.weights);
+    /// assert!(breakdown.total() <= animals.mem_size(SizeFlags::default()));
+    /// ```
+    pub fn memory_breakdown(&self) -> MemoryBreakdown {
+        let flags = SizeFlags::default();
+        MemoryBreakdown {
+            keys: self.keys.mem_size(flags),
+            ngrams: self.ngrams.mem_size(flags),
+            key_offsets: self.graph.srcs_offsets.mem_size(flags),
+            ngram_offsets: self.graph.dsts_offsets.mem_size(flags),
+            key_to_ngram_edges: self.graph.srcs_to_dsts.mem_size(flags),
+            ngram_to_key_edges: self.graph.dsts_to_srcs.mem_size(flags),
+            weights: self.graph.srcs_to_dsts_weights.mem_size(flags),
+            overflow: self.overflow.mem_size(flags),
+            tombstones: self.tombstones.mem_size(flags),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::report::MemoryBreakdown;
+    use mem_dbg::{MemSize, SizeFlags};
+
+    #[test]
+    fn test_memory_breakdown_of_the_animals() {
+        let mut animals: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+        let breakdown: MemoryBreakdown = animals.memory_breakdown();
+        let flags = SizeFlags::default();
+
+        assert_eq!(breakdown.keys, animals.keys.mem_size(flags));
+        for size in [
+            breakdown.ngrams,
+            breakdown.key_offsets,
+            breakdown.ngram_offsets,
+            breakdown.key_to_ngram_edges,
+            breakdown.ngram_to_key_edges,
+            breakdown.weights,
+        ] {
+            assert!(size > core::mem::size_of::<usize>());
+        }
+        assert_eq!(
+            breakdown.weights,
+            animals.graph.srcs_to_dsts_weights.mem_size(flags)
+        );
+        assert_eq!(breakdown.overflow, animals.overflow.mem_size(flags));
+
+        let debug = format!("{:?}", breakdown);
+        for field in [
+            "keys",
+            "ngrams",
+            "key_offsets",
+            "weights",
+            "overflow",
+            "tombstones",
+        ] {
+            assert!(debug.contains(field));
+        }
+
+        // The graph and the ngrams are unchanged by an insertion.
+        animals.insert("Zonkey");
+        let grown = animals.memory_breakdown();
+        assert!(grown.overflow > breakdown.overflow);
+        assert!(grown.keys > breakdown.keys);
+        assert_eq!(grown.graph(), breakdown.graph());
+        assert_eq!(grown.ngrams, breakdown.ngrams);
+    }
 }
@@ -0,0 +1,340 @@
+//! Submodule providing the re-ranking of the ngram search results by edit distance.
+//!
+//! # Implementation details
+//! The ngram similarity is a good filter to find the candidates of a query, but it ranks
+//! poorly the short keys, whose few ngrams are dominated by the padding. For instance,
+//! the key "abcabc" contains all of the trigrams of the query "abc", and is therefore
+//! ranked above "abcd", even though it takes three insertions to obtain it from the query
+//! instead of one. The re-ranked search scores the best candidates of the ngram search
+//! with an edit distance, which is quadratic in the length of the keys and therefore
+//! only computed on a small number of candidates.
+use crate::prelude::*;
+
+/// Trait defining an edit distance between two strings.
+pub trait EditDistance {
+    /// Returns the edit distance between the two provided strings.
+    ///
+    /// # Arguments
+    /// * `left` - The first string.
+    /// * `right` - The second string.
+    fn distance(&self, left: &str, right: &str) -> usize;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The Levenshtein distance, the minimum number of insertions, deletions and
+/// substitutions of characters turning a string into the other.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// assert_eq!(Levenshtein.distance("kitten", "sitting"), 3);
+/// assert_eq!(Levenshtein.distance("abc", "acb"), 2);
+/// assert_eq!(Levenshtein.distance("", "abc"), 3);
+/// ```
+pub struct Levenshtein;
+
+impl EditDistance for Levenshtein {
+    fn distance(&self, left: &str, right: &str) -> usize {
+        let right: Vec<char> = right.chars().collect();
+        let mut previous: Vec<usize> = (0..=right.len()).collect();
+        let mut current = vec![0; right.len() + 1];
+
+        for (i, left_char) in left.chars().enumerate() {
+            current[0] = i + 1;
+            for (j, right_char) in right.iter().enumerate() {
+                let substitution = previous[j] + usize::from(left_char != *right_char);
+                current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            }
+            core::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[right.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The Damerau-Levenshtein distance, which also counts the transposition of two
+/// adjacent characters as a single edit.
+///
+/// # Implementation details
+/// This is the restricted variant of the distance, also known as the optimal string
+/// alignment distance, where no substring is edited more than once. Therefore, the
+/// distance between "ca" and "abc" is three, and not two as in the unrestricted variant.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// assert_eq!(DamerauLevenshtein.distance("kitten", "sitting"), 3);
+/// assert_eq!(DamerauLevenshtein.distance("abc", "acb"), 1);
+/// assert_eq!(DamerauLevenshtein.distance("ca", "abc"), 3);
+/// ```
+pub struct DamerauLevenshtein;
+
+impl EditDistance for DamerauLevenshtein {
+    fn distance(&self, left: &str, right: &str) -> usize {
+        let left: Vec<char> = left.chars().collect();
+        let right: Vec<char> = right.chars().collect();
+        let mut before_previous = vec![0; right.len() + 1];
+        let mut previous: Vec<usize> = (0..=right.len()).collect();
+        let mut current = vec![0; right.len() + 1];
+
+        for i in 0..left.len() {
+            current[0] = i + 1;
+            for j in 0..right.len() {
+                let substitution = previous[j] + usize::from(left[i] != right[j]);
+                current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+                if i > 0 && j > 0 && left[i] == right[j - 1] && left[i - 1] == right[j] {
+                    current[j + 1] = current[j + 1].min(before_previous[j - 1] + 1);
+                }
+            }
+            core::mem::swap(&mut before_previous, &mut previous);
+            core::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[right.len()]
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Holds a result of the re-ranked search, with its ngram similarity and its edit
+/// distance from the query.
+///
+/// # Implementation details
+/// The re-ranked results are sorted by their edit distance and not by their score, so
+/// they are not returned as `SearchResults`, whose first result is the one with the
+/// highest score.
+pub struct RerankedResult<K, F: Float> {
+    /// The result of the ngram search.
+    result: SearchResult<K, F>,
+    /// The edit distance of the key from the query.
+    edit_distance: usize,
+}
+
+impl<K, F: Float> RerankedResult<K, F> {
+    #[inline(always)]
+    /// Returns a reference to the key of the result, without cloning it.
+    pub fn key_ref(&self) -> &K {
+        self.result.key_ref()
+    }
+
+    #[inline(always)]
+    /// Returns the key of the result.
+    pub fn into_key(self) -> K {
+        self.result.into_key()
+    }
+
+    #[inline(always)]
+    /// Returns the id of the key in the searched corpus.
+    pub fn key_id(&self) -> usize {
+        self.result.key_id()
+    }
+
+    #[inline(always)]
+    /// Returns the edit distance of the key from the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["kitten", "sitting"]);
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results = corpus.search_reranked("kitten", config, 10, Levenshtein);
+    ///
+    /// assert_eq!(results[0].key(), &"kitten");
+    /// assert_eq!(results[0].edit_distance(), 0);
+    /// assert_eq!(results[0].score(), 1.0);
+    /// ```
+    pub fn edit_distance(&self) -> usize {
+        self.edit_distance
+    }
+
+    #[inline(always)]
+    /// Returns the result of the ngram search, without the edit distance.
+    pub fn into_search_result(self) -> SearchResult<K, F> {
+        self.result
+    }
+}
+
+impl<K: Clone, F: Float> RerankedResult<K, F> {
+    #[inline(always)]
+    /// Returns the key of the result.
+    pub fn key(&self) -> K {
+        self.result.key()
+    }
+
+    #[inline(always)]
+    /// Returns the ngram similarity of the key to the query.
+    pub fn score(&self) -> F {
+        self.result.score()
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    <K as Key<NG, NG::G>>::Ref: AsRef<str>,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the best matches of the provided key, re-ranked by their edit distance
+    /// from the key.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `rerank_k` - The number of candidates of the ngram search to re-rank.
+    /// * `edit_distance` - The edit distance to re-rank the candidates with.
+    ///
+    /// # Implementation details
+    /// The best `rerank_k` candidates of the ngram search, with the minimum similarity of
+    /// the configuration, are sorted by their edit distance from the key, from the
+    /// smallest, and then truncated to the maximum number of results of the configuration.
+    /// The candidates with the same edit distance keep their order in the ngram search.
+    /// The results keep their ngram similarity as their score, so that the scores are not
+    /// sorted, and their edit distance is returned by `edit_distance`. The distance is
+    /// computed between the values of the query and of the keys as stored, before any of
+    /// the normalizations applied by the key type, such as the lowercasing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["abcabc", "abcd", "xbc"]);
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.3)
+    ///     .unwrap()
+    ///     .set_maximum_number_of_results(2);
+    ///
+    /// // All of the trigrams of the query appear in "abcabc", which is ranked first.
+    /// let results = corpus.ngram_search("abc", config.set_maximum_number_of_results(3));
+    /// assert_eq!(results.into_keys(), vec![&"abcabc", &"abcd", &"xbc"]);
+    ///
+    /// // While its edit distance from the query is the largest one.
+    /// let results = corpus.search_reranked("abc", config, 3, Levenshtein);
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].key(), &"abcd");
+    /// assert_eq!(results[1].key(), &"xbc");
+    /// assert_eq!(results[0].edit_distance(), 1);
+    /// assert_eq!(results[1].edit_distance(), 1);
+    /// ```
+    pub fn search_reranked<KR, W: Copy, F: Float, D: EditDistance>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        rerank_k: usize,
+        edit_distance: D,
+    ) -> Vec<RerankedResult<KS::KeyRef<'_>, F>>
+    where
+        KR: AsRef<K>,
+        Warp<W>: NgramSimilarity + Copy,
+    {
+        let query = Self::key_str(&key);
+        let maximum_number_of_results = config.maximum_number_of_results();
+        let candidates =
+            self.ngram_search_with_warp(&key, config.set_maximum_number_of_results(rerank_k));
+
+        let mut results: Vec<RerankedResult<KS::KeyRef<'_>, F>> = candidates
+            .into_iter()
+            .map(|result| RerankedResult {
+                edit_distance: edit_distance.distance(query, Self::key_str(result.key_ref())),
+                result,
+            })
+            .collect();
+        // The sort is stable, so that the ties keep their order in the ngram search.
+        results.sort_by_key(RerankedResult::edit_distance);
+        results.truncate(maximum_number_of_results);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_edit_distances() {
+        for (left, right, levenshtein, damerau_levenshtein) in [
+            ("", "", 0, 0),
+            ("abc", "abc", 0, 0),
+            ("abc", "", 3, 3),
+            ("abc", "abcd", 1, 1),
+            ("abc", "xbc", 1, 1),
+            ("abc", "abcabc", 3, 3),
+            ("abc", "bac", 2, 1),
+            ("abcd", "badc", 3, 2),
+            ("ca", "abc", 3, 3),
+            ("gatto", "gattò", 1, 1),
+        ] {
+            assert_eq!(Levenshtein.distance(left, right), levenshtein);
+            assert_eq!(Levenshtein.distance(right, left), levenshtein);
+            assert_eq!(
+                DamerauLevenshtein.distance(left, right),
+                damerau_levenshtein
+            );
+            assert_eq!(
+                DamerauLevenshtein.distance(right, left),
+                damerau_levenshtein
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_reranked_fixes_the_ranking_of_short_keys() {
+        let corpus: Corpus<[&str; 4], TriGram<char>> =
+            Corpus::from(["abcabc", "xbc", "abcd", "bac"]);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        let ngram_results = corpus.ngram_search("abc", config);
+        assert_eq!(ngram_results[0].key(), &"abcabc");
+
+        let results = corpus.search_reranked("abc", config, 10, Levenshtein);
+        assert_eq!(results.len(), ngram_results.len());
+        assert_eq!(results[0].key(), &"abcd");
+        assert_eq!(results[1].key(), &"xbc");
+        assert_eq!(results.last().unwrap().key(), &"abcabc");
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].edit_distance() <= pair[1].edit_distance()));
+        for result in results.iter() {
+            assert_eq!(
+                result.edit_distance(),
+                Levenshtein.distance("abc", result.key())
+            );
+            // The score is still the ngram similarity of the key.
+            let ngram_result = ngram_results
+                .iter()
+                .find(|ngram_result| ngram_result.key_id() == result.key_id())
+                .unwrap();
+            assert_eq!(result.score(), ngram_result.score());
+        }
+
+        // The transposition is a single edit for the Damerau-Levenshtein distance.
+        let results = corpus.search_reranked("abc", config, 10, DamerauLevenshtein);
+        assert_eq!(results.last().unwrap().key(), &"abcabc");
+        assert_eq!(
+            results
+                .iter()
+                .find(|result| result.key() == &"bac")
+                .map(|result| result.edit_distance()),
+            Some(1)
+        );
+
+        // Only the best candidates of the ngram search are re-ranked.
+        let results = corpus.search_reranked("abc", config, 1, Levenshtein);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key(), &"abcabc");
+    }
+}
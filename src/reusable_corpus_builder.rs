@@ -0,0 +1,223 @@
+//! Submodule providing a builder of corpora that retains its buffers between builds.
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+use fxhash::FxBuildHasher;
+
+use crate::{bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, traits::*, Corpus};
+
+#[derive(Debug, Clone)]
+/// Builder of corpora that retains the capacity of its intermediate buffers between builds.
+///
+/// # Implementation details
+/// Building a corpus requires several intermediate buffers, such as the set of the
+/// unique ngrams and the vector of the ngrams of each key, which are otherwise allocated
+/// from scratch at each build. When the index is rebuilt periodically, for instance in a
+/// server, the same builder can be reused so that these allocations are only grown and
+/// never released.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let mut builder: ReusableCorpusBuilder<TriGram<char>> = ReusableCorpusBuilder::new();
+///
+/// let corpus: Corpus<Vec<&str>, TriGram<char>> = builder.build(vec!["cat", "dog"]);
+/// assert_eq!(corpus.number_of_keys(), 2);
+///
+/// builder.clear();
+///
+/// let corpus: Corpus<Vec<&str>, TriGram<char>> = builder.build(vec!["cat", "dog", "bird"]);
+/// assert_eq!(corpus.number_of_keys(), 3);
+/// ```
+///
+/// The hasher of the sets and maps of the ngrams defaults to the `FxBuildHasher`, and it
+/// can be replaced, for instance with the `RandomState` of the standard library when the
+/// keys may be crafted to collide:
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+/// use std::collections::hash_map::RandomState;
+///
+/// let mut builder: ReusableCorpusBuilder<TriGram<char>, RandomState> =
+///     ReusableCorpusBuilder::new();
+///
+/// let corpus: Corpus<Vec<&str>, TriGram<char>> = builder.build(vec!["cat", "dog"]);
+/// assert_eq!(corpus.number_of_keys(), 2);
+/// ```
+pub struct ReusableCorpusBuilder<NG, S = FxBuildHasher> {
+    /// The set of the unique ngrams found in the keys.
+    pub(crate) unique_ngrams: HashSet<NG, S>,
+    /// The unique ngrams found in the keys, sorted before building the corpus.
+    pub(crate) ngrams: Vec<NG>,
+    /// The ngrams of each key, in key order.
+    pub(crate) key_to_ngrams: Vec<NG>,
+    /// The ngrams and their counts of the key being currently parsed.
+    pub(crate) ngram_counts: Vec<(NG, usize)>,
+}
+
+impl<NG, S: Default> Default for ReusableCorpusBuilder<NG, S> {
+    fn default() -> Self {
+        Self {
+            unique_ngrams: HashSet::with_hasher(S::default()),
+            ngrams: Vec::new(),
+            key_to_ngrams: Vec::new(),
+            ngram_counts: Vec::new(),
+        }
+    }
+}
+
+impl<NG: Ngram, S: BuildHasher + Default> ReusableCorpusBuilder<NG, S> {
+    #[inline(always)]
+    /// Creates a new builder with empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new builder with buffers of the provided capacities.
+    ///
+    /// # Arguments
+    /// * `number_of_ngrams` - The expected number of unique ngrams in the corpus.
+    /// * `number_of_edges` - The expected number of edges between keys and ngrams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let builder: ReusableCorpusBuilder<TriGram<char>> =
+    ///     ReusableCorpusBuilder::with_capacity(100, 1000);
+    ///
+    /// assert!(builder.ngrams_capacity() >= 100);
+    /// assert!(builder.edges_capacity() >= 1000);
+    /// ```
+    pub fn with_capacity(number_of_ngrams: usize, number_of_edges: usize) -> Self {
+        Self {
+            unique_ngrams: HashSet::with_capacity_and_hasher(number_of_ngrams, S::default()),
+            ngrams: Vec::with_capacity(number_of_ngrams),
+            key_to_ngrams: Vec::with_capacity(number_of_edges),
+            ngram_counts: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    /// Clears the buffers of the builder, retaining their capacity.
+    pub fn clear(&mut self) {
+        self.unique_ngrams.clear();
+        self.ngrams.clear();
+        self.key_to_ngrams.clear();
+        self.ngram_counts.clear();
+    }
+
+    #[inline(always)]
+    /// Returns the number of unique ngrams the builder can hold without reallocating.
+    pub fn ngrams_capacity(&self) -> usize {
+        self.unique_ngrams.capacity().min(self.ngrams.capacity())
+    }
+
+    #[inline(always)]
+    /// Returns the number of edges between keys and ngrams the builder can hold without reallocating.
+    pub fn edges_capacity(&self) -> usize {
+        self.key_to_ngrams.capacity()
+    }
+
+    /// Builds a new corpus from the provided keys, reusing the buffers of the builder.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    ///
+    /// # Implementation details
+    /// The resulting corpus is identical to the one obtained with `Corpus::from`.
+    pub fn build<KS, K>(&mut self, keys: KS) -> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+    where
+        KS: Keys<NG>,
+        for<'a> KS::KeyRef<'a>: AsRef<K>,
+        K: Key<NG, NG::G> + ?Sized,
+    {
+        Corpus::from_keys(keys, None, self)
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Builds a new corpus from the provided keys in parallel, reusing the buffers of the builder.
+    ///
+    /// # Arguments
+    /// * `keys` - The keys to create the corpus from.
+    ///
+    /// # Implementation details
+    /// The resulting corpus is identical to the one obtained with `Corpus::par_from`.
+    pub fn par_build<KS, K>(
+        &mut self,
+        keys: KS,
+    ) -> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+    where
+        NG: Send + Sync,
+        KS: Keys<NG>,
+        for<'a> KS::KeyRef<'a>: AsRef<K>,
+        K: Key<NG, NG::G> + ?Sized,
+    {
+        Corpus::par_from_keys(keys, None, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::collections::hash_map::RandomState;
+
+    /// Asserts that the two provided corpora are identical.
+    fn assert_same_corpus(
+        left: &Corpus<Vec<&str>, TriGram<char>>,
+        right: &Corpus<Vec<&str>, TriGram<char>>,
+    ) {
+        assert_eq!(left.number_of_keys(), right.number_of_keys());
+        assert_eq!(left.number_of_ngrams(), right.number_of_ngrams());
+        assert_eq!(left.average_key_length(), right.average_key_length());
+        assert!(left.ngrams().eq(right.ngrams()));
+        assert!(left.cooccurrences().eq(right.cooccurrences()));
+        for key_id in 0..left.number_of_keys() {
+            assert!(left
+                .ngram_ids_from_key(key_id)
+                .eq(right.ngram_ids_from_key(key_id)));
+        }
+        for ngram_id in 0..left.number_of_ngrams() {
+            assert!(left
+                .key_ids_from_ngram_id(ngram_id)
+                .eq(right.key_ids_from_ngram_id(ngram_id)));
+        }
+    }
+
+    #[test]
+    fn test_reusable_corpus_builder() {
+        let mut builder: ReusableCorpusBuilder<TriGram<char>> = ReusableCorpusBuilder::new();
+
+        let first: Corpus<Vec<&str>, TriGram<char>> = builder.build(ANIMALS.to_vec());
+        let ngrams_capacity = builder.ngrams_capacity();
+        let edges_capacity = builder.edges_capacity();
+
+        builder.clear();
+
+        assert!(builder.ngrams_capacity() >= ngrams_capacity);
+        assert_eq!(builder.edges_capacity(), edges_capacity);
+        assert!(edges_capacity > 0);
+
+        let second: Corpus<Vec<&str>, TriGram<char>> = builder.build(ANIMALS.to_vec());
+        let reference: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+
+        assert_same_corpus(&first, &second);
+        assert_same_corpus(&first, &reference);
+        assert_eq!(builder.edges_capacity(), edges_capacity);
+    }
+
+    #[test]
+    fn test_reusable_corpus_builder_with_another_hasher() {
+        let mut builder: ReusableCorpusBuilder<TriGram<char>, RandomState> =
+            ReusableCorpusBuilder::new();
+
+        let corpus: Corpus<Vec<&str>, TriGram<char>> = builder.build(ANIMALS.to_vec());
+        let reference: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+
+        assert_same_corpus(&corpus, &reference);
+    }
+}
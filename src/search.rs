@@ -8,7 +8,7 @@ use std::collections::HashMap;
 use std::iter::{Copied, Map};
 
 use crate::traits::key::Key;
-use crate::{Corpus, Float, Keys, Ngram, SearchResult, WeightedBipartiteGraph};
+use crate::{Corpus, Float, Keys, Ngram, WeightedBipartiteGraph};
 
 use mem_dbg::{MemDbg, MemSize};
 
@@ -21,6 +21,10 @@ pub struct QueryHashmap {
     total_unknown_count: usize,
     /// A total count of the identified ngrams.
     total_identified_count: usize,
+    #[cfg(feature = "simd")]
+    /// The identified ngram ids and their counts as `u32`, converted once for the SIMD
+    /// intersections with the candidates, or `None` if any of them does not fit.
+    simd_ngram_ids_and_counts: Option<(Vec<u32>, Vec<u32>)>,
 }
 
 /// A parallel iterator over the identified ngram ids.
@@ -41,12 +45,7 @@ mod test_ngram_similarity {
     /// Test that the trigram similarity of a series with itself is 1.
     fn test_simmetric_ngram_similarity() {
         let ngrams = vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)];
-        let total_identified_count = ngrams.iter().map(|(_, count)| count).sum();
-        let query = QueryHashmap {
-            ngram_ids: ngrams.clone(),
-            total_unknown_count: 0,
-            total_identified_count,
-        };
+        let query = QueryHashmap::from_ngram_ids(ngrams.clone(), 0);
 
         for warp in 1..=3 {
             let warp = Warp::try_from(warp).unwrap();
@@ -57,6 +56,48 @@ mod test_ngram_similarity {
 }
 
 impl QueryHashmap {
+    /// Creates a new query hashmap from the provided sorted ngram ids and counts.
+    ///
+    /// # Arguments
+    /// * `ngram_ids` - The identified ngram ids and their counts, sorted by ngram id.
+    /// * `total_unknown_count` - The total count of the unknown ngrams.
+    /// * `total_identified_count` - The total count of the identified ngrams.
+    fn new(
+        ngram_ids: Vec<(usize, usize)>,
+        total_unknown_count: usize,
+        total_identified_count: usize,
+    ) -> Self {
+        #[cfg(feature = "simd")]
+        let simd_ngram_ids_and_counts = ngram_ids
+            .iter()
+            .map(|&(ngram_id, count)| {
+                Some((u32::try_from(ngram_id).ok()?, u32::try_from(count).ok()?))
+            })
+            .collect::<Option<Vec<(u32, u32)>>>()
+            .map(|ngram_ids| ngram_ids.into_iter().unzip());
+        Self {
+            ngram_ids,
+            total_unknown_count,
+            total_identified_count,
+            #[cfg(feature = "simd")]
+            simd_ngram_ids_and_counts,
+        }
+    }
+
+    /// Creates a new query hashmap from the provided ngram ids and counts.
+    ///
+    /// # Arguments
+    /// * `ngram_ids` - The identified ngram ids and their counts, in any order.
+    /// * `total_unknown_count` - The total count of the unknown ngrams.
+    pub(crate) fn from_ngram_ids(
+        mut ngram_ids: Vec<(usize, usize)>,
+        total_unknown_count: usize,
+    ) -> Self {
+        ngram_ids.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let total_identified_count = ngram_ids.iter().map(|(_, count)| count).sum();
+        Self::new(ngram_ids, total_unknown_count, total_identified_count)
+    }
+
     #[inline(always)]
     /// Returns the identified ngram ids.
     pub fn ngram_ids(&self) -> NgramIds<'_> {
@@ -78,11 +119,48 @@ impl QueryHashmap {
         self.ngram_ids.iter().copied()
     }
 
+    #[cfg(feature = "simd")]
+    #[inline(always)]
+    /// Returns the ngram ids and their counts as `u32`, if all of them fit.
+    pub(crate) fn simd_ngram_ids_and_counts(&self) -> Option<(&[u32], &[u32])> {
+        self.simd_ngram_ids_and_counts
+            .as_ref()
+            .map(|(ngram_ids, counts)| (ngram_ids.as_slice(), counts.as_slice()))
+    }
+
+    #[inline(always)]
+    /// Returns the total count of the ngrams that are not in the corpus.
+    pub fn total_unknown_count(&self) -> usize {
+        self.total_unknown_count
+    }
+
     #[inline(always)]
     /// Returns the total number of unique ngrams, including the unknown ngrams.
     pub fn total_count(&self) -> usize {
         self.total_unknown_count + self.total_identified_count
     }
+
+    /// Returns the query where the ngrams rejected by the provided predicate are unknown.
+    ///
+    /// # Arguments
+    /// * `retain` - A function that returns whether a given ngram id should be kept.
+    ///
+    /// # Implementation details
+    /// The counts of the rejected ngrams are added to the count of the unknown ngrams, so
+    /// that the total count of the query is unchanged.
+    pub(crate) fn retain_ngram_ids(&self, retain: impl Fn(usize) -> bool) -> Self {
+        let (ngram_ids, rejected): (Vec<(usize, usize)>, Vec<(usize, usize)>) = self
+            .ngram_ids
+            .iter()
+            .copied()
+            .partition(|(ngram_id, _)| retain(*ngram_id));
+        let rejected_count: usize = rejected.iter().map(|(_, count)| count).sum();
+        Self::new(
+            ngram_ids,
+            self.total_unknown_count + rejected_count,
+            self.total_identified_count - rejected_count,
+        )
+    }
 }
 
 /// We test that the QueryHashmap struct is working as expected.
@@ -109,6 +187,178 @@ mod tests {
             "The total count is incorrect"
         );
     }
+
+    #[test]
+    fn test_queries_without_grams_return_no_results() {
+        // The keys normalizing to nothing share the padding ngrams of the empty query.
+        let corpus: Corpus<[&str; 4], TriGram<char>> = Corpus::from(["", "cat", "!!!", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        // The spaces are trimmed and deduplicated, and the other characters are not
+        // alphanumeric, so that all of these queries normalize to nothing.
+        for query in ["", "   ", " \t \n ", "!?!", "- -"] {
+            assert!(corpus.has_no_grams(query), "Query {:?}", query);
+            assert!(
+                corpus.ngram_search(query, config).is_empty(),
+                "Query {:?}",
+                query
+            );
+            assert!(
+                corpus.ngram_search_small(query, config).is_empty(),
+                "Query {:?}",
+                query
+            );
+            #[cfg(feature = "rayon")]
+            assert!(
+                corpus.ngram_par_search(query, config).is_empty(),
+                "Query {:?}",
+                query
+            );
+        }
+
+        assert!(!corpus.has_no_grams(" cat "));
+        assert_eq!(corpus.ngram_search(" cat ", config)[0].key(), &"cat");
+
+        // Unigrams have no padding, so that only the queries without grams are empty.
+        let unigrams: Corpus<[&str; 2], UniGram<char>> = Corpus::from(["a", "b"]);
+        assert!(unigrams.has_no_grams(""));
+        assert!(!unigrams.has_no_grams("a"));
+    }
+
+    #[test]
+    fn test_search_small_agrees_with_graph_search() {
+        let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for query in ["cat", "catt", "dogs", "cattle"] {
+            let graph_results: Vec<&str> = corpus
+                .ngram_search(query, config)
+                .into_iter()
+                .map(|result| *result.key())
+                .collect();
+            let small_results: Vec<&str> = corpus
+                .ngram_search_small(query, config)
+                .into_iter()
+                .map(|result| *result.key())
+                .collect();
+            assert_eq!(
+                graph_results, small_results,
+                "Rankings differ for {}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_minimum_shared_ngrams_skips_the_candidates_sharing_fewer_ngrams() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(ANIMALS.len())
+            .set_max_ngram_degree(MaxNgramDegree::None);
+        // The keys with the same score may be ranked in any order.
+        fn sorted_keys<'a>(results: SearchResults<&&'a str, f32>) -> Vec<&'a str> {
+            let mut keys: Vec<&str> = results.into_iter().map(|result| *result.key()).collect();
+            keys.sort_unstable();
+            keys
+        }
+
+        for query in ["Cat", "Doggo", "Sea Lion", "Hippopotamus"] {
+            let query_ngrams = corpus.key_counts(query);
+            let all_results = corpus.ngram_search(query, config);
+            for minimum_shared_ngrams in 1..=5 {
+                let config = config.set_minimum_shared_ngrams(minimum_shared_ngrams);
+                let mut expected: Vec<&str> = all_results
+                    .iter()
+                    .map(|result| *result.key())
+                    .filter(|key| {
+                        let key_ngrams = corpus.key_counts(key);
+                        query_ngrams
+                            .keys()
+                            .filter(|ngram| key_ngrams.contains_key(ngram))
+                            .count()
+                            >= minimum_shared_ngrams
+                    })
+                    .collect();
+                expected.sort_unstable();
+
+                let results = corpus.ngram_search(query, config);
+                assert!(results
+                    .iter()
+                    .all(|result| all_results.iter().any(|other| other == result)));
+                assert_eq!(sorted_keys(results), expected, "{}", query);
+                assert_eq!(
+                    sorted_keys(corpus.ngram_search_small(query, config)),
+                    expected
+                );
+                #[cfg(feature = "rayon")]
+                assert_eq!(
+                    sorted_keys(corpus.ngram_par_search(query, config)),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_small_uses_the_candidates_of_the_graph_search() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(ANIMALS.len());
+
+        for query in ["Cat", "Doggo", "Sea Lion", "Hippopotamus"] {
+            for max_ngram_degree in [MaxNgramDegree::Default, MaxNgramDegree::Custom(5)] {
+                for minimum_shared_ngrams in 1..=3 {
+                    let config = config
+                        .set_max_ngram_degree(max_ngram_degree)
+                        .set_minimum_shared_ngrams(minimum_shared_ngrams);
+                    let results: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+                    let small_results: SearchResults<&&str, f32> =
+                        corpus.ngram_search_small(query, config);
+                    assert_eq!(results, small_results, "{}", query);
+                    assert!(results.keys().eq(small_results.keys()), "{}", query);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_ngram_degree_caps_the_candidates_of_common_ngrams() {
+        let corpus: Corpus<[&str; 8], TriGram<char>> =
+            Corpus::from(["cab", "cad", "cam", "can", "cap", "car", "cat", "bobcat"]);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap();
+
+        let results: SearchResults<&&str, f32> =
+            corpus.ngram_search("cat", config.set_max_ngram_degree(MaxNgramDegree::None));
+        assert_eq!(results.len(), 8);
+
+        // The padded ngrams shared by the keys starting with "ca" do not generate
+        // candidates, so that only the keys sharing the rarer ngrams of the query are
+        // scored. Since "cat" also contains the common ngrams, this checks that the
+        // skipped ngrams do not cause the candidates to be deduplicated away.
+        let config = config.set_max_ngram_degree(MaxNgramDegree::Custom(2));
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+        let mut keys = results.keys().copied().collect::<Vec<&str>>();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["bobcat", "cat"]);
+        assert_eq!(results[0].key(), &"cat");
+        assert_eq!(results.best_score(), Some(1.0));
+
+        let results: SearchResults<&&str, f32> =
+            corpus.ngram_search("cat", config.set_score_common_ngrams(false));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key(), &"cat");
+        assert!(results[0].score() < 1.0);
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -118,6 +368,13 @@ mod tests {
 /// The reasoning is, when an ngram is too common, it does not provide much information
 /// about the rarity of the key, while adding a significant amount of computation time
 /// since it will be present in a large number of keys.
+///
+/// # Implementation details
+/// The excluded ngrams do not generate candidates, which bounds the number of keys
+/// scored per query ngram, but the candidates found through the rarer ngrams are still
+/// scored with all of the query ngrams, unless the scoring of the common ngrams is
+/// disabled in the search configuration. This trades recall for latency: a key sharing
+/// only excluded ngrams with the query, such as a padding ngram, is never returned.
 pub enum MaxNgramDegree {
     /// Leave it to the default value, which is either 100 or 1/10 of the number of keys.
     Default,
@@ -160,6 +417,13 @@ pub(crate) struct SearchConfig<F: Float = f64> {
     minimum_similarity_score: F,
     /// The maximum number of ngrams to consider in the search.
     max_ngram_degree: MaxNgramDegree,
+    /// The penalty applied to the score for each ngram of difference between
+    /// the length of the query and the length of the candidate key.
+    length_mismatch_penalty: F,
+    /// Whether the ngrams exceeding the maximum degree are used to score the candidates.
+    score_common_ngrams: bool,
+    /// The minimum number of distinct ngrams a candidate must share with the query to be scored.
+    minimum_shared_ngrams: usize,
 }
 
 impl<F: Float> Default for SearchConfig<F> {
@@ -170,6 +434,9 @@ impl<F: Float> Default for SearchConfig<F> {
             maximum_number_of_results: 10,
             minimum_similarity_score: F::from_f64(0.7_f64),
             max_ngram_degree: MaxNgramDegree::Default,
+            length_mismatch_penalty: F::ZERO,
+            score_common_ngrams: true,
+            minimum_shared_ngrams: 1,
         }
     }
 }
@@ -240,6 +507,90 @@ impl<F: Float> SearchConfig<F> {
         self.max_ngram_degree = max_ngram_degree;
         self
     }
+
+    #[inline(always)]
+    /// Returns the length mismatch penalty.
+    pub fn length_mismatch_penalty(&self) -> F {
+        self.length_mismatch_penalty
+    }
+
+    #[inline(always)]
+    /// Set the penalty applied to the score for each ngram of difference between
+    /// the length of the query and the length of the candidate key.
+    ///
+    /// # Arguments
+    /// * `length_mismatch_penalty` - The penalty, zero to disable it.
+    pub fn set_length_mismatch_penalty(
+        mut self,
+        length_mismatch_penalty: F,
+    ) -> Result<Self, &'static str> {
+        if length_mismatch_penalty.is_nan() {
+            return Err("The length mismatch penalty must not be NaN");
+        }
+        if length_mismatch_penalty < F::ZERO {
+            return Err("The length mismatch penalty must be greater than or equal to 0.0");
+        }
+        self.length_mismatch_penalty = length_mismatch_penalty;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns whether the ngrams exceeding the maximum degree are used to score the candidates.
+    pub fn score_common_ngrams(&self) -> bool {
+        self.score_common_ngrams
+    }
+
+    #[inline(always)]
+    /// Set whether the ngrams exceeding the maximum degree are used to score the candidates.
+    ///
+    /// # Arguments
+    /// * `score_common_ngrams` - Whether to score the candidates with the common ngrams.
+    pub fn set_score_common_ngrams(mut self, score_common_ngrams: bool) -> Self {
+        self.score_common_ngrams = score_common_ngrams;
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the minimum number of distinct ngrams a candidate must share with the query.
+    pub fn minimum_shared_ngrams(&self) -> usize {
+        self.minimum_shared_ngrams
+    }
+
+    #[inline(always)]
+    /// Set the minimum number of distinct ngrams a candidate must share with the query.
+    ///
+    /// # Arguments
+    /// * `minimum_shared_ngrams` - The minimum number of shared ngrams, where zero and one
+    /// are equivalent as every candidate shares at least one ngram with the query.
+    pub fn set_minimum_shared_ngrams(mut self, minimum_shared_ngrams: usize) -> Self {
+        self.minimum_shared_ngrams = minimum_shared_ngrams.max(1);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns whether a length mismatch penalty should be applied.
+    pub(crate) fn has_length_mismatch_penalty(&self) -> bool {
+        self.length_mismatch_penalty > F::ZERO
+    }
+
+    #[inline(always)]
+    /// Returns the multiplier to apply to the score of a candidate.
+    ///
+    /// # Arguments
+    /// * `query_length` - The number of ngrams in the query.
+    /// * `candidate_length` - The number of ngrams in the candidate key.
+    ///
+    /// # Implementation details
+    /// The multiplier is computed as `1 / (1 + penalty * |query_length - candidate_length|)`,
+    /// so that it is equal to one when the lengths match and decreases as they diverge.
+    pub(crate) fn length_mismatch_multiplier(
+        &self,
+        query_length: usize,
+        candidate_length: usize,
+    ) -> F {
+        let difference = query_length.abs_diff(candidate_length) as f64;
+        F::from_f64(1.0 / (1.0 + self.length_mismatch_penalty.to_f64() * difference))
+    }
 }
 
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
@@ -288,11 +639,59 @@ where
         // We sort the ngram_ids inplace by the first element of the tuple
         ngram_ids.sort_unstable_by(|a, b| a.0.cmp(&b.0));
 
-        QueryHashmap {
-            ngram_ids,
-            total_unknown_count,
-            total_identified_count,
-        }
+        QueryHashmap::new(ngram_ids, total_unknown_count, total_identified_count)
+    }
+
+    #[inline(always)]
+    /// Returns whether the provided query has no grams other than the padding ones.
+    ///
+    /// # Arguments
+    /// * `key` - The query to check.
+    ///
+    /// # Implementation details
+    /// A query normalizing to nothing, such as an empty string, a string of spaces or of
+    /// non-alphanumeric characters, still has the ngrams made of its padding, which would
+    /// match any key normalizing to nothing as well. The searches therefore return no
+    /// results for these queries, without looking up their ngrams.
+    pub(crate) fn has_no_grams(&self, key: &K) -> bool {
+        key.grams()
+            .all(|gram| NG::PADDING.into_iter().any(|padding| padding == gram))
+    }
+
+    /// Returns the ngrams of the provided query that do not appear in the corpus.
+    ///
+    /// # Arguments
+    /// * `key` - The query to extract the ngrams from.
+    ///
+    /// # Implementation details
+    /// Each ngram of the query is looked up in the vocabulary of the corpus, and the
+    /// ngrams without an id are returned sorted and without duplicates. These highlight
+    /// the substrings of the query that are novel to the corpus, which is useful for
+    /// diagnostics such as "did you mean" suggestions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "dog"]);
+    ///
+    /// assert!(corpus.unmatched_query_ngrams("cat").is_empty());
+    ///
+    /// let unmatched = corpus.unmatched_query_ngrams("catz");
+    /// assert_eq!(unmatched, vec![['a', 't', 'z'], ['t', 'z', '\0'], ['z', '\0', '\0']]);
+    /// ```
+    pub fn unmatched_query_ngrams<KR>(&self, key: KR) -> Vec<NG>
+    where
+        KR: AsRef<K>,
+    {
+        let mut unmatched: Vec<NG> = self
+            .key_counts(key.as_ref())
+            .into_keys()
+            .filter(|ngram| self.ngram_id_from_ngram(*ngram).is_none())
+            .collect();
+        unmatched.sort_unstable();
+        unmatched
     }
 
     #[inline(always)]
@@ -309,31 +708,169 @@ where
         key: KR,
         config: SearchConfig<F>,
         similarity: impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        self.search_with_filter(key, config, similarity, |_| true)
+    }
+
+    #[inline(always)]
+    /// Perform a fuzzy search of the `Corpus`, only scoring the keys accepted by the provided filter.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - A function that computes the similarity between the query hashmap
+    /// and the ngram ids and cooccurrences.
+    /// * `filter` - A function that returns whether a given key id should be scored.
+    pub(crate) fn search_with_filter<KR, F: Float>(
+        &self,
+        key: KR,
+        config: SearchConfig<F>,
+        similarity: impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F,
+        filter: impl Fn(usize) -> bool,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
     where
         KR: AsRef<K>,
     {
         let key: &K = key.as_ref();
-        let query_hashmap = self.ngram_ids_from_ngram_counts(key.counts());
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        self.search_ngram_counts(self.key_counts(key), config, similarity, filter)
+    }
 
-        let query_hashmap_ref = &query_hashmap;
+    /// Perform a fuzzy search of the `Corpus` for the query with the provided ngram counts.
+    ///
+    /// # Arguments
+    /// * `ngram_counts` - The counts of the ngrams of the query.
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - A function that computes the similarity between the query hashmap
+    /// and the ngram ids and cooccurrences.
+    /// * `filter` - A function that returns whether a given key id should be scored.
+    pub(crate) fn search_ngram_counts<F: Float>(
+        &self,
+        ngram_counts: HashMap<NG, usize, FxBuildHasher>,
+        config: SearchConfig<F>,
+        similarity: impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F,
+        filter: impl Fn(usize) -> bool,
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
+        let query_hashmap = self.ngram_ids_from_ngram_counts(ngram_counts);
         let mut heap = SearchResultsHeap::new(config.maximum_number_of_results());
+
+        self.for_each_scored_key(
+            &query_hashmap,
+            config,
+            similarity,
+            filter,
+            |key_id, score| {
+                heap.push(key_id, score);
+            },
+        );
+
+        // Sort highest similarity to lowest
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
+    }
+
+    /// Returns the ids of the query ngrams that generate the candidates of the search.
+    ///
+    /// # Arguments
+    /// * `query_hashmap` - The ngram ids and counts of the query.
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// The ngrams whose number of keys exceeds the maximum ngram degree are skipped, as
+    /// they do not provide much information associated to the rarity of the keys, while
+    /// they would drag in a large number of candidates.
+    pub(crate) fn candidate_ngram_ids<F: Float>(
+        &self,
+        query_hashmap: &QueryHashmap,
+        config: SearchConfig<F>,
+    ) -> Vec<usize> {
         let max_ngram_degree = config.compute_max_ngram_degree(self.number_of_keys());
+        query_hashmap
+            .ngram_ids()
+            .filter(|&ngram_id| self.number_of_keys_from_ngram_id(ngram_id) <= max_ngram_degree)
+            .collect()
+    }
+
+    /// Returns the query to score the candidates with, if it differs from the provided one.
+    ///
+    /// # Arguments
+    /// * `query_hashmap` - The ngram ids and counts of the query.
+    /// * `candidate_ngram_ids` - The sorted ids of the query ngrams generating the candidates.
+    /// * `config` - The configuration for the search.
+    ///
+    /// # Implementation details
+    /// When the common ngrams are not scored, they are counted as if they were unknown to
+    /// the corpus, so that the candidates are not rewarded for sharing them.
+    pub(crate) fn scoring_query_hashmap<F: Float>(
+        &self,
+        query_hashmap: &QueryHashmap,
+        candidate_ngram_ids: &[usize],
+        config: SearchConfig<F>,
+    ) -> Option<QueryHashmap> {
+        if config.score_common_ngrams()
+            || candidate_ngram_ids.len() == query_hashmap.ngram_ids.len()
+        {
+            return None;
+        }
+        Some(
+            query_hashmap
+                .retain_ngram_ids(|ngram_id| candidate_ngram_ids.binary_search(&ngram_id).is_ok()),
+        )
+    }
+
+    #[inline(always)]
+    /// Scores the keys sharing at least one ngram with the query, calling the provided
+    /// callback on each key whose score is at least the minimum similarity score.
+    ///
+    /// # Arguments
+    /// * `query_hashmap` - The ngram ids and counts of the query.
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - A function that computes the similarity between the query hashmap
+    /// and the ngram ids and cooccurrences.
+    /// * `filter` - A function that returns whether a given key id should be scored.
+    /// * `callback` - A function called with the id and the score of each accepted key.
+    ///
+    /// # Implementation details
+    /// Each key is scored exactly once, from the first of the query ngrams it contains.
+    /// When the candidates must share at least `m` of the `n` ngrams generating them, their
+    /// first shared ngram is one of the first `n - m + 1`, so that only these are visited.
+    pub(crate) fn for_each_scored_key<F: Float>(
+        &self,
+        query_hashmap: &QueryHashmap,
+        config: SearchConfig<F>,
+        similarity: impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F,
+        filter: impl Fn(usize) -> bool,
+        mut callback: impl FnMut(usize, F),
+    ) {
+        let candidate_ngram_ids = self.candidate_ngram_ids(query_hashmap, config);
+        let scoring_query_hashmap =
+            self.scoring_query_hashmap(query_hashmap, &candidate_ngram_ids, config);
+        let scoring_query_hashmap = scoring_query_hashmap.as_ref().unwrap_or(query_hashmap);
+        let minimum_shared_ngrams = config.minimum_shared_ngrams();
+        if candidate_ngram_ids.len() < minimum_shared_ngrams {
+            // No key can share enough ngrams with the query.
+            return;
+        }
+        let number_of_generating_ngrams = candidate_ngram_ids.len() + 1 - minimum_shared_ngrams;
 
         // We identify all of the ngrams to be considered in the search, which
         // are the set of ngrams that contain any of the grams in the ngram
-        query_hashmap_ref
-            .ngram_ids()
+        candidate_ngram_ids[..number_of_generating_ngrams]
+            .iter()
             .enumerate()
-            .for_each(|(ngram_number, ngram_id)| {
-                // If this term is too common, we can skip it as it does not provide
-                // much information associated to the rarity of this term.
-                if self.number_of_keys_from_ngram_id(ngram_id) > max_ngram_degree {
-                    return;
-                }
+            .for_each(|(ngram_number, &ngram_id)| {
                 self.key_ids_from_ngram_id(ngram_id).for_each(|key_id| {
+                    // We skip the keys that are not accepted by the filter.
+                    if !filter(key_id) {
+                        return;
+                    }
                     if self.contains_any_ngram_ids(
-                        query_hashmap_ref.ngram_ids().take(ngram_number),
+                        candidate_ngram_ids[..ngram_number].iter().copied(),
                         key_id,
                     ) {
                         // If it has found any gram in the ngram, excluding the one we are currently
@@ -341,18 +878,142 @@ where
                         // ngrams
                         return;
                     }
+                    // We skip the keys sharing too few ngrams with the query, counting from the
+                    // current ngram as the previous ones are not in the key.
+                    if minimum_shared_ngrams > 1
+                        && self.number_of_shared_ngram_ids(
+                            &candidate_ngram_ids[ngram_number..],
+                            key_id,
+                        ) < minimum_shared_ngrams
+                    {
+                        return;
+                    }
                     // At this point, we can compute the similarity.
-                    let score = similarity(
-                        query_hashmap_ref,
-                        self.ngram_ids_and_cooccurrences_from_key(key_id),
+                    let score = self.score_key_id(
+                        query_hashmap,
+                        scoring_query_hashmap,
+                        config,
+                        &similarity,
+                        key_id,
                     );
                     if score >= config.minimum_similarity_score() {
-                        heap.push(SearchResult::new(self.key_from_id(key_id), score));
+                        callback(key_id, score);
                     }
                 });
             });
+    }
+
+    #[inline(always)]
+    /// Returns the score of the provided key against the query.
+    ///
+    /// # Arguments
+    /// * `query_hashmap` - The ngram ids and counts of the query.
+    /// * `scoring_query_hashmap` - The query to score the key with, as returned by
+    /// the `scoring_query_hashmap` method.
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - A function that computes the similarity between the query hashmap
+    /// and the ngram ids and cooccurrences.
+    /// * `key_id` - The id of the key to score.
+    pub(crate) fn score_key_id<F: Float>(
+        &self,
+        query_hashmap: &QueryHashmap,
+        scoring_query_hashmap: &QueryHashmap,
+        config: SearchConfig<F>,
+        similarity: &impl Fn(&QueryHashmap, NgramIdsAndCooccurrences<'_, G>) -> F,
+        key_id: usize,
+    ) -> F {
+        let score = similarity(
+            scoring_query_hashmap,
+            self.ngram_ids_and_cooccurrences_from_key(key_id),
+        );
+        if config.has_length_mismatch_penalty() {
+            score
+                * config.length_mismatch_multiplier(
+                    query_hashmap.total_count(),
+                    self.ngram_cooccurrences_from_key(key_id).sum(),
+                )
+        } else {
+            score
+        }
+    }
+
+    #[inline(always)]
+    /// Perform a fuzzy search of the `Corpus` by directly scoring every key, without
+    /// consulting the bipartite graph.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `config` - The configuration for the search.
+    /// * `similarity` - A function that computes the similarity between the query hashmap
+    /// and the query hashmap of a candidate key.
+    ///
+    /// # Implementation details
+    /// Each key of the corpus is re-tokenized from `key_from_id` and compared against the
+    /// query, so the cost is linear in the total length of the keys. The shared ngrams are
+    /// counted among the query ngrams generating the candidates of the graph-based search,
+    /// as returned by `candidate_ngram_ids`, and the keys are scored against the query of
+    /// `scoring_query_hashmap`, so that the same keys are skipped and scored as in the
+    /// graph-based search: the keys sharing none of these ngrams, or fewer of them than
+    /// the minimum number of shared ngrams of the configuration, are skipped.
+    pub(crate) fn search_small<KR, F: Float>(
+        &self,
+        key: KR,
+        config: SearchConfig<F>,
+        similarity: impl Fn(&QueryHashmap, &QueryHashmap) -> F,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        let key: &K = key.as_ref();
+        if self.has_no_grams(key) {
+            return SearchResults::default();
+        }
+        let query_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(key));
+        let candidate_ngram_ids = self.candidate_ngram_ids(&query_hashmap, config);
+        let scoring_query_hashmap =
+            self.scoring_query_hashmap(&query_hashmap, &candidate_ngram_ids, config);
+        let scoring_query_hashmap = scoring_query_hashmap.as_ref().unwrap_or(&query_hashmap);
+        // As in the graph-based search, a key must share at least one candidate ngram.
+        let minimum_shared_ngrams = config.minimum_shared_ngrams().max(1);
+        let mut heap = SearchResultsHeap::new(config.maximum_number_of_results());
+
+        for key_id in 0..self.number_of_key_ids() {
+            if self.is_removed(key_id) {
+                continue;
+            }
+            let candidate = self.key_from_id(key_id);
+            let candidate: &K = candidate.as_ref();
+            let candidate_hashmap = self.ngram_ids_from_ngram_counts(self.key_counts(candidate));
+
+            // We skip the keys that do not share enough candidate ngrams with the query.
+            let number_of_shared_ngrams = candidate_ngram_ids
+                .iter()
+                .filter(|&&ngram_id| {
+                    candidate_hashmap
+                        .ngram_ids
+                        .binary_search_by(|(candidate_id, _)| candidate_id.cmp(&ngram_id))
+                        .is_ok()
+                })
+                .count();
+            if number_of_shared_ngrams < minimum_shared_ngrams {
+                continue;
+            }
+
+            let mut score = similarity(scoring_query_hashmap, &candidate_hashmap);
+            if config.has_length_mismatch_penalty() {
+                score = score
+                    * config.length_mismatch_multiplier(
+                        query_hashmap.total_count(),
+                        candidate_hashmap.total_count(),
+                    );
+            }
+            if score >= config.minimum_similarity_score() {
+                heap.push(key_id, score);
+            }
+        }
 
         // Sort highest similarity to lowest
-        heap.into_sorted_vec()
+        heap.into_sorted_vec(|key_id| self.key_from_id(key_id))
+            .into()
     }
 }
@@ -0,0 +1,448 @@
+//! Submodule providing the options of the `search_with` method of the corpus.
+//!
+//! # Implementation details
+//! The searches of the corpus grew a method for each combination of their parameters,
+//! such as `ngram_search_with_metric` or `prefix_search`. The `SearchOptions` gather the
+//! configuration of the search, the metric scoring the candidates and the prefix of the
+//! keys to score, so that the `search_with` method covers all of their combinations, and
+//! the `search_with_prefix` method the ones with a prefix. The prefix is part of the type
+//! of the options, so that only the searches with a prefix require the keys of the corpus
+//! to be comparable with a `str`.
+//! The case folding is not an option, as it is selected by the key type of the corpus,
+//! such as `Lowercase<str>`, since the ngrams of the corpus must be normalized as the
+//! ones of the queries.
+use std::cell::RefCell;
+
+use crate::metric::metric_similarity;
+use crate::prelude::*;
+use crate::search::SearchConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The options of a search of the corpus.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+///
+/// // The default options reproduce the `ngram_search` method.
+/// let options: SearchOptions = SearchOptions::default();
+/// let results: SearchResults<&&str, f32> = corpus.search_with("Cat", options);
+/// assert_eq!(results, corpus.ngram_search("Cat", NgramSearchConfig::default()));
+///
+/// let options = SearchOptions::default()
+///     .set_minimum_similarity_score(0.3)
+///     .unwrap()
+///     .set_maximum_number_of_results(5)
+///     .set_metric(CosineMetric)
+///     .set_prefix("Cat");
+/// let results: SearchResults<&&str, f32> = corpus.search_with_prefix("Cat", options);
+///
+/// assert_eq!(results[0].key(), &"Cat");
+/// assert!(results.len() <= 5);
+/// assert!(results.iter().all(|result| result.key().starts_with("Cat")));
+/// ```
+pub struct SearchOptions<M = WarpMetric<i32>, F: Float = f32, P = ()> {
+    /// The configuration of the search.
+    config: SearchConfig<F>,
+    /// The metric scoring the candidates.
+    metric: M,
+    /// The prefix of the keys to score, or `()` to score all of the keys.
+    prefix: P,
+}
+
+impl<F: Float> Default for SearchOptions<WarpMetric<i32>, F> {
+    #[inline(always)]
+    /// Returns the options of the `ngram_search` method with its default configuration.
+    fn default() -> Self {
+        Self {
+            config: SearchConfig::default(),
+            metric: WarpMetric::default(),
+            prefix: (),
+        }
+    }
+}
+
+impl<W: Copy, F: Float> From<NgramSearchConfig<W, F>> for SearchOptions<WarpMetric<W>, F> {
+    #[inline(always)]
+    /// Returns the options searching with the provided configuration and its warp factor.
+    fn from(config: NgramSearchConfig<W, F>) -> Self {
+        Self {
+            config: config.into(),
+            metric: WarpMetric::from(config.warp()),
+            prefix: (),
+        }
+    }
+}
+
+impl<M, F: Float, P> SearchOptions<M, F, P> {
+    #[inline(always)]
+    /// Set the minimum similarity value for a result to be included in the output.
+    ///
+    /// # Arguments
+    /// * `minimum_similarity_score` - The minimum similarity value, 0.7 by default.
+    ///
+    /// # Implementation details
+    /// Every candidate is scored regardless of the threshold, so that raising it does
+    /// not speed up the search, while it reduces the results to sort.
+    ///
+    /// # Raises
+    /// * If the minimum similarity score is negative or NaN.
+    pub fn set_minimum_similarity_score(
+        mut self,
+        minimum_similarity_score: F,
+    ) -> Result<Self, &'static str> {
+        self.config = self
+            .config
+            .set_minimum_similarity_score(minimum_similarity_score)?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns the minimum similarity value for a result to be included in the output.
+    pub fn minimum_similarity_score(&self) -> F {
+        self.config.minimum_similarity_score()
+    }
+
+    #[inline(always)]
+    /// Set the maximum number of results to return.
+    ///
+    /// # Arguments
+    /// * `maximum_number_of_results` - The maximum number of results, 10 by default.
+    ///
+    /// # Implementation details
+    /// The best results are kept in a heap of this size, so that pushing a candidate
+    /// costs a time logarithmic in the maximum number of results.
+    pub fn set_maximum_number_of_results(mut self, maximum_number_of_results: usize) -> Self {
+        self.config = self
+            .config
+            .set_maximum_number_of_results(maximum_number_of_results);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the maximum number of results to return.
+    pub fn maximum_number_of_results(&self) -> usize {
+        self.config.maximum_number_of_results()
+    }
+
+    #[inline(always)]
+    /// Set the maximum degree of the ngrams generating the candidates.
+    ///
+    /// # Arguments
+    /// * `max_ngram_degree` - The maximum degree of the ngrams to consider in the search.
+    ///
+    /// # Implementation details
+    /// The common ngrams would drag in a large number of candidates, so that capping
+    /// their degree is the main lever on the latency of the search, at the cost of
+    /// missing the keys sharing only common ngrams with the query.
+    pub fn set_max_ngram_degree(mut self, max_ngram_degree: MaxNgramDegree) -> Self {
+        self.config = self.config.set_max_ngram_degree(max_ngram_degree);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the maximum degree of the ngrams generating the candidates.
+    pub fn max_ngram_degree(&self) -> MaxNgramDegree {
+        self.config.max_ngram_degree()
+    }
+
+    #[inline(always)]
+    /// Set the minimum number of distinct ngrams a candidate must share with the query.
+    ///
+    /// # Arguments
+    /// * `minimum_shared_ngrams` - The minimum number of shared ngrams, one by default.
+    ///
+    /// # Implementation details
+    /// The candidates sharing fewer ngrams are skipped with an integer comparison before
+    /// being scored, with the recall loss described in
+    /// `NgramSearchConfig::set_minimum_shared_ngrams`.
+    pub fn set_minimum_shared_ngrams(mut self, minimum_shared_ngrams: usize) -> Self {
+        self.config = self.config.set_minimum_shared_ngrams(minimum_shared_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the minimum number of distinct ngrams a candidate must share with the query.
+    pub fn minimum_shared_ngrams(&self) -> usize {
+        self.config.minimum_shared_ngrams()
+    }
+
+    #[inline(always)]
+    /// Set the penalty applied to the score for each ngram of difference between
+    /// the length of the query and the length of the candidate key.
+    ///
+    /// # Arguments
+    /// * `length_mismatch_penalty` - The penalty, zero by default to disable it.
+    ///
+    /// # Implementation details
+    /// The penalty requires the total number of ngrams of each scored candidate, which is
+    /// summed from its cooccurrences, so that it roughly doubles the cost of the scoring.
+    ///
+    /// # Raises
+    /// * If the penalty is NaN or negative.
+    pub fn set_length_mismatch_penalty(
+        mut self,
+        length_mismatch_penalty: F,
+    ) -> Result<Self, &'static str> {
+        self.config = self
+            .config
+            .set_length_mismatch_penalty(length_mismatch_penalty)?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns the length mismatch penalty.
+    pub fn length_mismatch_penalty(&self) -> F {
+        self.config.length_mismatch_penalty()
+    }
+
+    #[inline(always)]
+    /// Set whether the ngrams exceeding the maximum degree are used to score the candidates.
+    ///
+    /// # Arguments
+    /// * `score_common_ngrams` - Whether to score the candidates with the common ngrams.
+    ///
+    /// # Implementation details
+    /// Not scoring the common ngrams builds a reduced copy of the query, and does not
+    /// change the number of scored candidates.
+    pub fn set_score_common_ngrams(mut self, score_common_ngrams: bool) -> Self {
+        self.config = self.config.set_score_common_ngrams(score_common_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns whether the ngrams exceeding the maximum degree are used to score the candidates.
+    pub fn score_common_ngrams(&self) -> bool {
+        self.config.score_common_ngrams()
+    }
+
+    #[inline(always)]
+    /// Set the metric scoring the candidates.
+    ///
+    /// # Arguments
+    /// * `metric` - The metric, the `WarpMetric` with a warp factor of two by default.
+    ///
+    /// # Implementation details
+    /// The metrics other than the `WarpMetric` collect the counts of the shared ngrams of
+    /// each candidate before scoring it, which is slightly slower.
+    pub fn set_metric<M2>(self, metric: M2) -> SearchOptions<M2, F, P> {
+        SearchOptions {
+            config: self.config,
+            metric,
+            prefix: self.prefix,
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the metric scoring the candidates.
+    pub fn metric(&self) -> &M {
+        &self.metric
+    }
+
+    #[inline(always)]
+    /// Set the metric scoring the candidates to the `WarpMetric` with the provided warp factor.
+    ///
+    /// # Arguments
+    /// * `warp` - The warp factor, in the range 1 to 3.
+    ///
+    /// # Raises
+    /// * If the warp factor is not in the range 1 to 3.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    /// let options = SearchOptions::default().set_warp(3).unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.search_with("Catt", options);
+    /// let config = NgramSearchConfig::default();
+    ///
+    /// assert_eq!(results, corpus.search_warp("Catt", 3, config).unwrap());
+    /// assert!(SearchOptions::default().set_warp(0).is_err());
+    /// ```
+    pub fn set_warp<W>(self, warp: W) -> Result<SearchOptions<WarpMetric<W>, F, P>, &'static str>
+    where
+        W: Copy + TryInto<Warp<W>, Error = &'static str>,
+    {
+        Ok(self.set_metric(WarpMetric::new(warp)?))
+    }
+
+    #[inline(always)]
+    /// Set the prefix of the keys to score.
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix the keys must start with, compared with the keys as stored.
+    ///
+    /// # Implementation details
+    /// Each candidate is read from the corpus to check its prefix before being scored,
+    /// as in the `prefix_search` method. When searching repeatedly with the same prefix,
+    /// the `prefix_search_with_index` method avoids these reads. The options with a
+    /// prefix are searched with the `search_with_prefix` method.
+    pub fn set_prefix(self, prefix: &str) -> SearchOptions<M, F, &str> {
+        SearchOptions {
+            config: self.config,
+            metric: self.metric,
+            prefix,
+        }
+    }
+}
+
+impl<'p, M, F: Float> SearchOptions<M, F, &'p str> {
+    #[inline(always)]
+    /// Returns the prefix of the keys to score.
+    pub fn prefix(&self) -> &'p str {
+        self.prefix
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the best matches of the provided key with the provided options.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `options` - The options of the search, without a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["catfish", "cattle", "bobcat", "cat"]);
+    /// let options = SearchOptions::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap()
+    ///     .set_metric(JaccardMetric);
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.search_with("cat", options);
+    ///
+    /// assert_eq!(results[0].key(), &"cat");
+    /// ```
+    pub fn search_with<KR, M: Metric<F>, F: Float>(
+        &self,
+        key: KR,
+        options: SearchOptions<M, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        self.search_with_options_and_filter(key, options, |_| true)
+    }
+
+    /// Returns the best matches of the provided key with the provided options, scoring
+    /// only the keys accepted by the provided filter.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `options` - The options of the search, whose prefix is ignored.
+    /// * `filter` - A function that returns whether a given key id should be scored.
+    fn search_with_options_and_filter<KR, M: Metric<F>, F: Float, P>(
+        &self,
+        key: KR,
+        options: SearchOptions<M, F, P>,
+        filter: impl Fn(usize) -> bool,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        let SearchOptions { config, metric, .. } = options;
+        let shared = RefCell::new(Vec::new());
+        self.search_with_filter(
+            key,
+            config,
+            |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                metric_similarity(&metric, query, ngrams, &mut shared.borrow_mut())
+            },
+            filter,
+        )
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    <K as Key<NG, NG::G>>::Ref: AsRef<str>,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the best matches of the provided key with the provided options, among the
+    /// keys starting with the prefix of the options.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus
+    /// * `options` - The options of the search, with a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 4], TriGram<char>> =
+    ///     Corpus::from(["catfish", "cattle", "bobcat", "cat"]);
+    /// let options = SearchOptions::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap()
+    ///     .set_prefix("catt");
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.search_with_prefix("cat", options);
+    ///
+    /// assert_eq!(results.into_keys(), vec![&"cattle"]);
+    /// ```
+    pub fn search_with_prefix<KR, M: Metric<F>, F: Float>(
+        &self,
+        key: KR,
+        options: SearchOptions<M, F, &str>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        let prefix = options.prefix();
+        self.search_with_options_and_filter(key, options, |key_id| {
+            self.key_starts_with(key_id, prefix)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_search_with_agrees_with_the_specialized_searches() {
+        let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+        let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.2)
+            .unwrap()
+            .set_maximum_number_of_results(20);
+
+        for query in ["Cat", "Catt", "Sea lion", "Hippopotamus"] {
+            let options: SearchOptions<WarpMetric<i32>, f32> = config.into();
+            assert_eq!(
+                corpus.search_with(query, options),
+                corpus.ngram_search(query, config)
+            );
+            assert_eq!(
+                corpus.search_with(query, options.set_metric(JaccardMetric)),
+                corpus.ngram_search_with_metric(query, config, JaccardMetric)
+            );
+            assert_eq!(
+                corpus.search_with_prefix(query, options.set_prefix("C")),
+                corpus.prefix_search(query, "C", config)
+            );
+        }
+    }
+}
@@ -2,53 +2,370 @@
 
 use crate::prelude::*;
 use std::cmp::{Ordering, Reverse};
+use std::ops::Deref;
 
 use mem_dbg::{MemDbg, MemSize};
 
-/// Holds a collection of search results.
-pub type SearchResults<'a, KS, NG, F> = Vec<SearchResult<<KS as Keys<NG>>::KeyRef<'a>, F>>;
+#[derive(Debug, Clone)]
+/// Holds a collection of search results, sorted from the highest to the lowest score.
+///
+/// # Implementation details
+/// The results dereference to a slice, so they can be indexed and iterated as before,
+/// and they can be converted from and into a `Vec` of search results.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+///
+/// let results: SearchResults<&&str, f32> =
+///     corpus.ngram_search("Cat", NgramSearchConfig::default());
+///
+/// assert_eq!(results.best_score(), Some(1.0));
+/// assert_eq!(results[0].key(), &"Cat");
+///
+/// let results: Vec<SearchResult<&&str, f32>> = results.into();
+/// assert!(!results.is_empty());
+/// ```
+pub struct SearchResults<K, F: Float, E = ()> {
+    /// The search results.
+    results: Vec<SearchResult<K, F, E>>,
+}
+
+impl<K, F: Float, E> Default for SearchResults<K, F, E> {
+    #[inline(always)]
+    /// Returns an empty collection of search results.
+    fn default() -> Self {
+        Self {
+            results: Vec::new(),
+        }
+    }
+}
+
+impl<K, F: Float, E> PartialEq for SearchResults<K, F, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.results == other.results
+    }
+}
+
+impl<K, F: Float, E> Deref for SearchResults<K, F, E> {
+    type Target = [SearchResult<K, F, E>];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.results
+    }
+}
+
+impl<K, F: Float, E> From<Vec<SearchResult<K, F, E>>> for SearchResults<K, F, E> {
+    #[inline(always)]
+    fn from(results: Vec<SearchResult<K, F, E>>) -> Self {
+        Self { results }
+    }
+}
+
+impl<K, F: Float, E> From<SearchResults<K, F, E>> for Vec<SearchResult<K, F, E>> {
+    #[inline(always)]
+    fn from(results: SearchResults<K, F, E>) -> Self {
+        results.results
+    }
+}
+
+impl<K, F: Float, E> FromIterator<SearchResult<K, F, E>> for SearchResults<K, F, E> {
+    #[inline(always)]
+    fn from_iter<I: IntoIterator<Item = SearchResult<K, F, E>>>(iter: I) -> Self {
+        Self {
+            results: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<K, F: Float, E> IntoIterator for SearchResults<K, F, E> {
+    type Item = SearchResult<K, F, E>;
+    type IntoIter = std::vec::IntoIter<SearchResult<K, F, E>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+impl<'a, K, F: Float, E> IntoIterator for &'a SearchResults<K, F, E> {
+    type Item = &'a SearchResult<K, F, E>;
+    type IntoIter = std::slice::Iter<'a, SearchResult<K, F, E>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.iter()
+    }
+}
+
+impl<K: Clone, F: Float, E> SearchResults<K, F, E> {
+    #[inline(always)]
+    /// Returns the result with the highest score, if any.
+    pub fn best(&self) -> Option<&SearchResult<K, F, E>> {
+        self.results.first()
+    }
+
+    #[inline(always)]
+    /// Returns the highest score, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+    /// assert_eq!(results.best_score(), Some(1.0));
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("zebra", config);
+    /// assert_eq!(results.best_score(), None);
+    /// ```
+    pub fn best_score(&self) -> Option<F> {
+        self.best().map(SearchResult::score)
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the keys of the results, from the best one.
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = K> + '_ {
+        self.results.iter().map(SearchResult::key)
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the scores of the results, from the best one.
+    pub fn scores(&self) -> impl ExactSizeIterator<Item = F> + '_ {
+        self.results.iter().map(SearchResult::score)
+    }
+
+    /// Returns the results with their keys mapped by the provided function.
+    ///
+    /// # Arguments
+    /// * `map` - The function mapping each key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("cat", NgramSearchConfig::default());
+    /// let results: SearchResults<String, f32> = results.map_keys(|key| key.to_uppercase());
+    ///
+    /// assert_eq!(results[0].key(), "CAT");
+    /// ```
+    pub fn map_keys<K2, M>(self, mut map: M) -> SearchResults<K2, F, E>
+    where
+        M: FnMut(K) -> K2,
+    {
+        self.results
+            .into_iter()
+            .map(|result| SearchResult {
+                key: map(result.key),
+                key_id: result.key_id,
+                score: result.score,
+                explanation: result.explanation,
+            })
+            .collect()
+    }
+
+    /// Returns the keys of the results, from the best one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> = corpus.ngram_search("cat", config);
+    ///
+    /// assert_eq!(results.into_keys(), vec![&"cat", &"cattle"]);
+    /// ```
+    pub fn into_keys(self) -> Vec<K> {
+        self.results.into_iter().map(|result| result.key).collect()
+    }
+}
 
 /// Holds a fuzzy match search result string, and its associated similarity
 /// to the query text.
+///
+/// # Implementation details
+/// The explanation of the score, such as the ngrams shared with the query, is only
+/// recorded by the searches that explain their results. By default, it is the unit
+/// type, so that the results of the other searches do not pay for it. The results are
+/// ordered by their score, and the results with the same score by their key id, where the
+/// smallest key id ranks first, so that the searches return the ties in a reproducible
+/// order. Two results are equal when they have the same score and key id, while their
+/// keys and explanations are not compared. A NaN score is smaller than any other score,
+/// so that a faulty metric does not cause panics. The key id is carried along with the
+/// key, so that the data associated to the keys can be looked up by id without searching
+/// the key in the corpus.
 #[derive(Debug, Clone, MemSize, MemDbg)]
-pub struct SearchResult<K, F: Float> {
+pub struct SearchResult<K, F: Float, E = ()> {
     /// The key of a fuzzy match
     key: K,
+    /// The id of the key in the searched corpus.
+    key_id: usize,
     /// A similarity score value indicating how closely the other term matched
     score: F,
+    /// The explanation of the score.
+    explanation: E,
 }
 
-impl<K, F: Float> Eq for SearchResult<K, F> {}
+impl<K, F: Float, E> Eq for SearchResult<K, F, E> {}
 
-impl<K, F: Float> Ord for SearchResult<K, F> {
+impl<K, F: Float, E> Ord for SearchResult<K, F, E> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.score.partial_cmp(&other.score).unwrap()
+        // A NaN score, which a faulty metric may produce, is smaller than any other score.
+        let score_ordering = match (self.score.is_nan(), other.score.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self.score.partial_cmp(&other.score).unwrap(),
+        };
+        // Among the results with the same score, the smallest key id ranks first.
+        score_ordering.then_with(|| other.key_id.cmp(&self.key_id))
     }
 }
 
-impl<K, F: Float> PartialOrd for SearchResult<K, F> {
+impl<K, F: Float, E> PartialOrd for SearchResult<K, F, E> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<K, F: Float> PartialEq for SearchResult<K, F> {
+impl<K, F: Float, E> PartialEq for SearchResult<K, F, E> {
     fn eq(&self, other: &Self) -> bool {
-        self.score == other.score
+        (self.score == other.score || (self.score.is_nan() && other.score.is_nan()))
+            && self.key_id == other.key_id
     }
 }
 
 impl<K: Clone, F: Float> SearchResult<K, F> {
-    /// Trivial constructor used internally to build search results
+    #[cfg(test)]
+    /// Trivial constructor used in the tests to build search results of the key with id zero.
     ///
     /// # Arguments
     /// * `key` - The key of a fuzzy match
     /// * `score` - A similarity score value indicating how closely the other term matched
     pub(crate) fn new(key: K, score: F) -> Self {
-        Self { key, score }
+        Self::with_key_id(key, 0, score)
+    }
+
+    /// Trivial constructor used internally to build search results
+    ///
+    /// # Arguments
+    /// * `key` - The key of a fuzzy match
+    /// * `key_id` - The id of the key in the searched corpus.
+    /// * `score` - A similarity score value indicating how closely the other term matched
+    pub(crate) fn with_key_id(key: K, key_id: usize, score: F) -> Self {
+        Self {
+            key,
+            key_id,
+            score,
+            explanation: (),
+        }
+    }
+
+    /// Returns the search result with the provided explanation of its score.
+    ///
+    /// # Arguments
+    /// * `explanation` - The explanation of the score.
+    pub(crate) fn explained<E>(self, explanation: E) -> SearchResult<K, F, E> {
+        SearchResult {
+            key: self.key,
+            key_id: self.key_id,
+            score: self.score,
+            explanation,
+        }
+    }
+}
+
+impl<K, F: Float, E> SearchResult<K, F, E> {
+    #[inline(always)]
+    /// Returns a reference to the key of a fuzzy match, without cloning it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<Vec<String>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat".to_string(), "dog".to_string()]);
+    ///
+    /// let results: SearchResults<&String, f32> =
+    ///     corpus.ngram_search("cat", NgramSearchConfig::default());
+    ///
+    /// let key: &&String = results[0].key_ref();
+    /// assert_eq!(key.as_str(), "cat");
+    /// ```
+    pub fn key_ref(&self) -> &K {
+        &self.key
     }
 
+    #[inline(always)]
+    /// Returns the key of a fuzzy match, consuming the search result.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("cat", NgramSearchConfig::default());
+    /// let best: SearchResult<&&str, f32> = results.into_iter().next().unwrap();
+    ///
+    /// assert_eq!(best.into_key(), &"cat");
+    /// ```
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    #[inline(always)]
+    /// Returns the id of the key of a fuzzy match in the searched corpus.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let keys = ["cat", "cattle", "dog"];
+    /// let payloads = ["feline", "bovine", "canine"];
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(keys);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("dog", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key_id(), 2);
+    /// assert_eq!(payloads[results[0].key_id()], "canine");
+    /// assert_eq!(corpus.key_from_id(results[0].key_id()), results[0].key());
+    /// ```
+    pub fn key_id(&self) -> usize {
+        self.key_id
+    }
+}
+
+impl<K: Clone, F: Float, E> SearchResult<K, F, E> {
+    #[inline(always)]
     /// Returns the key of a fuzzy match
+    ///
+    /// # Implementation details
+    /// This method clones the key, which is cheap for the references returned by the
+    /// searches, while `key_ref` or `into_key` avoid the clone for owned key types.
     pub fn key(&self) -> K {
         self.key.clone()
     }
@@ -59,47 +376,168 @@ impl<K: Clone, F: Float> SearchResult<K, F> {
     }
 }
 
+impl<K, F: Float, NG> SearchResult<K, F, Vec<NG>> {
+    #[inline(always)]
+    /// Returns the ngrams shared by the key and the query, from the most frequent in the key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["banana", "cherry"]);
+    /// let config: NgramSearchConfig<i32, f32> = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap();
+    ///
+    /// let results = corpus.search_with_explanation("bananas", config);
+    ///
+    /// assert_eq!(results[0].key(), &"banana");
+    /// assert_eq!(results[0].shared_ngrams()[0], ['a', 'n', 'a']);
+    /// ```
+    pub fn shared_ngrams(&self) -> &[NG] {
+        &self.explanation
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The number of distinct ngrams a key shares with the query, explaining the results
+/// of the `search_by_count` method.
+pub struct SharedNgramCount(pub(crate) usize);
+
+impl<K, F: Float> SearchResult<K, F, SharedNgramCount> {
+    #[inline(always)]
+    /// Returns the number of distinct ngrams the key shares with the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["cat", "cattle"]);
+    ///
+    /// let results: SearchResults<&&str, f32, SharedNgramCount> =
+    ///     corpus.search_by_count("cat", 1, 10);
+    ///
+    /// assert_eq!(results[0].key(), &"cat");
+    /// assert_eq!(results[0].shared_ngram_count(), 5);
+    /// ```
+    pub fn shared_ngram_count(&self) -> usize {
+        self.explanation.0
+    }
+}
+
 /// Holds the top n best search results.
-pub(crate) struct SearchResultsHeap<K, F: Float> {
-    /// The k best search results
-    heap: std::collections::BinaryHeap<Reverse<SearchResult<K, F>>>,
+///
+/// # Implementation details
+/// The heap holds the ids of the keys rather than the keys themselves, so that only the
+/// keys of the top n results are ever resolved, and it never clones them. The results
+/// with the same score are ranked by their key id, from the smallest one, so that the
+/// results are reproducible across runs, including which keys are kept when the ties
+/// exceed n, and regardless of the order in which the keys are pushed. The results can
+/// also be ranked by a priority before their score, such as their number of shared ngrams,
+/// which is then returned as their explanation. By default, the priority is the unit type,
+/// so that the results are only ranked by their score.
+pub(crate) struct SearchResultsHeap<F: Float, P = ()> {
+    /// The n best search results with their priority, whose ties are broken by their key id.
+    heap: std::collections::BinaryHeap<Reverse<(P, SearchResult<usize, F>)>>,
     /// The maximum number of results to return
     n: usize,
 }
 
-impl<K, F: Float> SearchResultsHeap<K, F> {
+impl<F: Float> SearchResultsHeap<F> {
     /// Creates a new `SearchResultsHeap` with a maximum number of results to return
     ///
     /// # Arguments
     /// * `n` - The maximum number of results to return
     pub(crate) fn new(n: usize) -> Self {
+        Self::prioritized(n)
+    }
+
+    /// Pushes a new search result onto the heap
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key of the search result.
+    /// * `score` - The score of the search result.
+    pub(crate) fn push(&mut self, key_id: usize, score: F) {
+        self.push_with_priority((), key_id, score);
+    }
+
+    /// Returns the top n best search results, resolving their keys from their ids.
+    ///
+    /// # Arguments
+    /// * `key_from_id` - The function returning the key with the provided id.
+    pub(crate) fn into_sorted_vec<K, M>(self, key_from_id: M) -> Vec<SearchResult<K, F>>
+    where
+        M: FnMut(usize) -> K,
+    {
+        self.into_explained_sorted_vec(key_from_id)
+    }
+}
+
+impl<F: Float, P: Ord> SearchResultsHeap<F, P> {
+    /// Creates a new `SearchResultsHeap` ranking the results by their priority, with a
+    /// maximum number of results to return
+    ///
+    /// # Arguments
+    /// * `n` - The maximum number of results to return
+    pub(crate) fn prioritized(n: usize) -> Self {
         Self {
             heap: std::collections::BinaryHeap::with_capacity(n),
             n,
         }
     }
 
-    /// Pushes a new search result onto the heap
+    /// Pushes a new search result with the provided priority onto the heap
     ///
     /// # Arguments
-    /// * `search_result` - The search result to push onto the heap
-    pub(crate) fn push(&mut self, search_result: SearchResult<K, F>) {
+    /// * `priority` - The priority of the search result, ranked before its score.
+    /// * `key_id` - The id of the key of the search result.
+    /// * `score` - The score of the search result.
+    pub(crate) fn push_with_priority(&mut self, priority: P, key_id: usize, score: F) {
+        let entry = (priority, SearchResult::with_key_id(key_id, key_id, score));
         if self.heap.len() < self.n {
-            self.heap.push(Reverse(search_result));
+            self.heap.push(Reverse(entry));
         } else if let Some(min) = self.heap.peek() {
-            if search_result > min.0 {
+            if entry > min.0 {
                 self.heap.pop();
-                self.heap.push(Reverse(search_result));
+                self.heap.push(Reverse(entry));
             }
         }
     }
 
-    /// Returns the top n best search results
-    pub(crate) fn into_sorted_vec(self) -> Vec<SearchResult<K, F>> {
+    /// Merges the search results of another heap into this one, keeping the top n.
+    ///
+    /// # Arguments
+    /// * `other` - The heap whose search results are merged into this one.
+    pub(crate) fn merge(mut self, other: Self) -> Self {
+        for Reverse((priority, search_result)) in other.heap {
+            self.push_with_priority(priority, search_result.key, search_result.score);
+        }
+        self
+    }
+
+    /// Returns the top n best search results, resolving their keys from their ids, with
+    /// their priority as their explanation.
+    ///
+    /// # Arguments
+    /// * `key_from_id` - The function returning the key with the provided id.
+    pub(crate) fn into_explained_sorted_vec<K, M>(
+        self,
+        mut key_from_id: M,
+    ) -> Vec<SearchResult<K, F, P>>
+    where
+        M: FnMut(usize) -> K,
+    {
         self.heap
             .into_sorted_vec()
             .into_iter()
-            .map(|Reverse(x)| x)
+            .map(|Reverse((priority, result))| SearchResult {
+                key: key_from_id(result.key),
+                key_id: result.key,
+                score: result.score,
+                explanation: priority,
+            })
             .collect()
     }
 }
@@ -115,30 +553,157 @@ mod tests {
         let search_result = SearchResult::new(&key, score);
 
         assert_eq!(search_result.key(), &key);
+        assert_eq!(search_result.key_ref(), &&key);
         assert_eq!(search_result.score(), score);
+        assert_eq!(search_result.into_key(), &key);
+    }
+
+    #[test]
+    fn test_search_results_borrow_the_keys_of_the_corpus() {
+        // The searches return the `KeyRef` of the keys, which for a corpus of owned
+        // strings is a reference into the corpus, so that no key is ever cloned.
+        let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(vec![
+            "cat".to_string(),
+            "cattle".to_string(),
+            "dog".to_string(),
+        ]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let results: SearchResults<&String, f32> = corpus.ngram_search("cat", config);
+        assert_eq!(results.len(), 2);
+        for result in results.iter() {
+            let stored: &String = corpus.key_from_id(result.key_id());
+            assert!(std::ptr::eq(*result.key_ref(), stored));
+            // Cloning the result key only copies the reference.
+            assert!(std::ptr::eq(result.key(), stored));
+            assert_eq!(result.key().as_ptr(), stored.as_ptr());
+        }
     }
 
     #[test]
     fn test_search_results_heap() {
+        let keys = ["key0", "key1", "key2", "key3", "key4", "key5"];
         let mut search_results_heap = SearchResultsHeap::new(3);
 
-        let search_result1 = SearchResult::new(&"key1", 0.1);
-        let search_result2 = SearchResult::new(&"key2", 0.2);
-        let search_result3 = SearchResult::new(&"key3", 0.3);
-        let search_result4 = SearchResult::new(&"key4", 0.4);
-        let search_result5 = SearchResult::new(&"key5", 0.5);
+        search_results_heap.push(1, 0.1);
+        search_results_heap.push(2, 0.2);
+        search_results_heap.push(3, 0.3);
+        search_results_heap.push(4, 0.4);
+        search_results_heap.push(5, 0.5);
 
-        search_results_heap.push(search_result1);
-        search_results_heap.push(search_result2);
-        search_results_heap.push(search_result3);
-        search_results_heap.push(search_result4);
-        search_results_heap.push(search_result5);
-
-        let sorted_search_results = search_results_heap.into_sorted_vec();
+        let sorted_search_results: SearchResults<&&str, f64> = search_results_heap
+            .into_sorted_vec(|key_id| &keys[key_id])
+            .into();
 
         assert_eq!(sorted_search_results.len(), 3);
         assert_eq!(sorted_search_results[0].key(), &"key5");
         assert_eq!(sorted_search_results[1].key(), &"key4");
         assert_eq!(sorted_search_results[2].key(), &"key3");
+        assert_eq!(sorted_search_results.best_score(), Some(0.5));
+        assert!(sorted_search_results.scores().eq([0.5, 0.4, 0.3]));
+        assert_eq!(
+            sorted_search_results.into_keys(),
+            vec![&"key5", &"key4", &"key3"]
+        );
+    }
+
+    #[test]
+    fn test_search_results_heap_breaks_ties_by_key_id() {
+        // The results with the same score are ranked from the smallest key id, and the
+        // ties exceeding the maximum number of results drop the largest key ids, however
+        // the results are pushed, as it happens when the heaps of the threads are merged.
+        for key_ids in [[0, 1, 2, 3, 4], [4, 3, 2, 1, 0], [2, 4, 0, 3, 1]] {
+            let mut left = SearchResultsHeap::new(3);
+            let mut right = SearchResultsHeap::new(3);
+            for (position, key_id) in key_ids.into_iter().enumerate() {
+                if position % 2 == 0 {
+                    left.push(key_id, 0.5);
+                } else {
+                    right.push(key_id, 0.5);
+                }
+            }
+            right.push(5, 0.25);
+
+            let results: SearchResults<usize, f32> =
+                left.merge(right).into_sorted_vec(|key_id| key_id).into();
+
+            assert_eq!(results.into_keys(), vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_search_results_heap_ranks_by_priority() {
+        // The priority ranks before the score, and the NaN scores do not cause panics.
+        let mut heap = SearchResultsHeap::prioritized(3);
+        heap.push_with_priority(SharedNgramCount(1), 0, 0.9);
+        heap.push_with_priority(SharedNgramCount(2), 1, f32::NAN);
+        heap.push_with_priority(SharedNgramCount(2), 2, 0.1);
+        heap.push_with_priority(SharedNgramCount(3), 3, 0.2);
+        heap.push_with_priority(SharedNgramCount(1), 4, 0.5);
+
+        let results = heap.into_explained_sorted_vec(|key_id| key_id);
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| (result.key_id(), result.shared_ngram_count()))
+                .collect::<Vec<_>>(),
+            vec![(3, 3), (2, 2), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_nan_scores_are_the_smallest() {
+        let nan = SearchResult::new("nan", f32::NAN);
+        let zero = SearchResult::new("zero", 0.0);
+
+        assert_eq!(nan.cmp(&zero), Ordering::Less);
+        assert_eq!(zero.cmp(&nan), Ordering::Greater);
+        assert_eq!(nan.cmp(&nan.clone()), Ordering::Equal);
+        assert_eq!(nan, nan.clone());
+
+        let mut heap = SearchResultsHeap::new(2);
+        heap.push(0, f32::NAN);
+        heap.push(1, 0.5);
+        heap.push(2, f32::NAN);
+        heap.push(3, 0.25);
+
+        let results: SearchResults<usize, f32> = heap.into_sorted_vec(|key_id| key_id).into();
+        assert_eq!(results.into_keys(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_search_results_carry_the_key_ids() {
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+        let cat_id = ANIMALS.iter().position(|animal| *animal == "Cat").unwrap();
+        corpus.remove_key(cat_id + 1);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.2_f32)
+            .unwrap();
+
+        let results = corpus.ngram_search("Cat", config);
+        assert_eq!(results[0].key_id(), cat_id);
+        assert!(!results.is_empty());
+        for result in results.iter() {
+            assert_eq!(corpus.key_from_id(result.key_id()), result.key());
+            assert!(!corpus.is_removed(result.key_id()));
+        }
+
+        // The key ids are kept by the transformations of the results.
+        let explained = corpus.search_with_explanation("Cat", config);
+        let mapped = results.clone().map_keys(|key| key.len());
+        for (position, result) in results.iter().enumerate() {
+            assert_eq!(explained[position].key_id(), result.key_id());
+            assert_eq!(mapped[position].key_id(), result.key_id());
+        }
+
+        #[cfg(feature = "rayon")]
+        assert!(corpus
+            .ngram_par_search("Cat", config)
+            .iter()
+            .zip(results.iter())
+            .all(|(parallel, serial)| parallel.key_id() == serial.key_id()));
     }
 }
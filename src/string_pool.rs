@@ -0,0 +1,183 @@
+//! Submodule providing a compact container of string keys.
+//!
+//! # Implementation details
+//! A `Vec<String>` stores a pointer, a length and a capacity for each key, besides
+//! allocating each key separately. The `StringPool` instead concatenates all of the
+//! keys in a single buffer, and only stores the offset where each key ends, which
+//! for short keys such as names more than halves the memory required by the keys.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use mem_dbg::{MemDbg, MemSize};
+
+use crate::{GrowableKeys, Key, Keys, Ngram, RetainKeys};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, MemSize, MemDbg)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// A container of strings, stored contiguously in a single buffer.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let mut pool = StringPool::default();
+/// pool.push_str("cat");
+/// pool.push_str("");
+/// pool.push_str("dog");
+///
+/// assert_eq!(pool.len(), 3);
+/// assert_eq!(pool.get(0), "cat");
+/// assert_eq!(pool.get(1), "");
+/// assert_eq!(pool.get(2), "dog");
+/// assert_eq!(pool.iter().collect::<Vec<&str>>(), vec!["cat", "", "dog"]);
+/// ```
+pub struct StringPool {
+    /// The concatenation of the strings.
+    data: String,
+    /// The offset in the buffer where each string ends.
+    ends: Vec<usize>,
+}
+
+impl StringPool {
+    #[inline(always)]
+    /// Creates a new pool with the provided capacities.
+    ///
+    /// # Arguments
+    /// * `number_of_strings` - The number of strings to reserve space for.
+    /// * `number_of_bytes` - The total length of the strings to reserve space for.
+    pub fn with_capacity(number_of_strings: usize, number_of_bytes: usize) -> Self {
+        Self {
+            data: String::with_capacity(number_of_bytes),
+            ends: Vec::with_capacity(number_of_strings),
+        }
+    }
+
+    #[inline(always)]
+    /// Appends a copy of the provided string at the end of the pool.
+    ///
+    /// # Arguments
+    /// * `string` - The string to append.
+    pub fn push_str(&mut self, string: &str) {
+        self.data.push_str(string);
+        self.ends.push(self.data.len());
+    }
+
+    #[inline(always)]
+    /// Returns the number of strings in the pool.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    #[inline(always)]
+    /// Returns whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    #[inline(always)]
+    /// Returns the string with the provided index.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the string.
+    ///
+    /// # Panics
+    /// * If the index is out of bounds.
+    pub fn get(&self, index: usize) -> &str {
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        &self.data[start..self.ends[index]]
+    }
+
+    #[inline(always)]
+    /// Returns an iterator over the strings of the pool.
+    pub fn iter(&self) -> StringPoolIter<'_> {
+        StringPoolIter {
+            pool: self,
+            index: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Iterator over the strings of a `StringPool`.
+pub struct StringPoolIter<'a> {
+    /// The pool to iterate.
+    pool: &'a StringPool,
+    /// The index of the next string.
+    index: usize,
+}
+
+impl<'a> Iterator for StringPoolIter<'a> {
+    type Item = &'a str;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.pool.len() {
+            return None;
+        }
+        self.index += 1;
+        Some(self.pool.get(self.index - 1))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.pool.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for StringPoolIter<'a> {}
+
+impl<NG: Ngram> Keys<NG> for StringPool
+where
+    str: Key<NG, <NG as Ngram>::G>,
+    String: Key<NG, <NG as Ngram>::G>,
+{
+    type K = String;
+    type KeyRef<'a>
+        = &'a str
+    where
+        Self: 'a;
+    type IterKeys<'a>
+        = StringPoolIter<'a>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get_ref(&self, index: usize) -> Self::KeyRef<'_> {
+        self.get(index)
+    }
+
+    fn iter(&self) -> Self::IterKeys<'_> {
+        self.iter()
+    }
+}
+
+impl<NG: Ngram> GrowableKeys<NG> for StringPool
+where
+    str: Key<NG, <NG as Ngram>::G>,
+    String: Key<NG, <NG as Ngram>::G>,
+{
+    fn push(&mut self, key: String) {
+        self.push_str(&key);
+    }
+}
+
+impl<NG: Ngram> RetainKeys<NG> for StringPool
+where
+    str: Key<NG, <NG as Ngram>::G>,
+    String: Key<NG, <NG as Ngram>::G>,
+{
+    fn retain_indices(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let mut retained = StringPool::default();
+        for index in 0..self.len() {
+            if keep(index) {
+                retained.push_str(self.get(index));
+            }
+        }
+        *self = retained;
+    }
+}
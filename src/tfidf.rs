@@ -1,4 +1,6 @@
 //! Submodule providing a term frequency-inverse document frequency (TF-IDF) implementation.
+use crate::math;
+use crate::metric::{merge_ngram_counts, MergedNgram};
 use crate::prelude::*;
 use std::cmp::Ordering;
 
@@ -163,6 +165,99 @@ impl<W: Copy, F: Float> TFIDFSearchConfig<W, F> {
         self.search_config.max_ngram_degree()
     }
 
+    #[inline(always)]
+    /// Set the penalty applied to the score for each ngram of difference between
+    /// the length of the query and the length of the candidate key.
+    ///
+    /// # Arguments
+    /// * `length_mismatch_penalty` - The penalty, zero to disable it.
+    ///
+    /// # Raises
+    /// * If the penalty is NaN or negative.
+    ///
+    /// # Examples
+    /// In this example, both keys share the same two ngrams with the query, and
+    /// since we disable the length normalization of the TF-IDF they receive the
+    /// same score. By adding a length mismatch penalty, the key whose length is
+    /// closer to the length of the query is ranked first.
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 2], TriGram<char>> = Corpus::from(["xyzabc", "abc"]);
+    ///
+    /// let config: TFIDFSearchConfig<i32, f32> = TFIDFSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.0)
+    ///     .unwrap()
+    ///     .set_b(0.0)
+    ///     .unwrap();
+    ///
+    /// let results = corpus.tf_idf_search("bc", config);
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].score(), results[1].score());
+    ///
+    /// let results = corpus.tf_idf_search("bc", config.set_length_mismatch_penalty(0.5).unwrap());
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].key(), &"abc");
+    /// assert!(results[0].score() > results[1].score());
+    /// ```
+    pub fn set_length_mismatch_penalty(
+        mut self,
+        length_mismatch_penalty: F,
+    ) -> Result<Self, &'static str> {
+        self.search_config = self
+            .search_config
+            .set_length_mismatch_penalty(length_mismatch_penalty)?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    /// Returns the length mismatch penalty.
+    pub fn length_mismatch_penalty(&self) -> F {
+        self.search_config.length_mismatch_penalty()
+    }
+
+    #[inline(always)]
+    /// Set whether the ngrams exceeding the maximum degree are used to score the candidates.
+    ///
+    /// # Arguments
+    /// * `score_common_ngrams` - Whether to score the candidates with the common ngrams.
+    pub fn set_score_common_ngrams(mut self, score_common_ngrams: bool) -> Self {
+        self.search_config = self
+            .search_config
+            .set_score_common_ngrams(score_common_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns whether the ngrams exceeding the maximum degree are used to score the candidates.
+    pub fn score_common_ngrams(&self) -> bool {
+        self.search_config.score_common_ngrams()
+    }
+
+    #[inline(always)]
+    /// Set the minimum number of distinct ngrams a candidate must share with the query
+    /// to be scored.
+    ///
+    /// # Arguments
+    /// * `minimum_shared_ngrams` - The minimum number of shared ngrams, one by default.
+    ///
+    /// # Implementation details
+    /// The candidates sharing fewer ngrams are skipped before being scored, as described
+    /// in `NgramSearchConfig::set_minimum_shared_ngrams`.
+    pub fn set_minimum_shared_ngrams(mut self, minimum_shared_ngrams: usize) -> Self {
+        self.search_config = self
+            .search_config
+            .set_minimum_shared_ngrams(minimum_shared_ngrams);
+        self
+    }
+
+    #[inline(always)]
+    /// Returns the minimum number of distinct ngrams a candidate must share with the query.
+    pub fn minimum_shared_ngrams(&self) -> usize {
+        self.search_config.minimum_shared_ngrams()
+    }
+
     #[inline(always)]
     /// Set the K1 constant.
     ///
@@ -306,10 +401,11 @@ where
     /// * `ngram_id` - The id of the ngram to get the IDF of.
     pub(crate) fn inverse_document_frequency(&self, ngram_id: usize) -> f64 {
         let number_of_keys = self.number_of_keys() as f64;
-        ((number_of_keys - self.number_of_keys_from_ngram_id(ngram_id) as f64 + 0.5_f64)
-            / (self.number_of_keys_from_ngram_id(ngram_id) as f64 + 0.5_f64)
-            + 1.0_f64)
-            .ln()
+        math::log(
+            (number_of_keys - self.number_of_keys_from_ngram_id(ngram_id) as f64 + 0.5_f64)
+                / (self.number_of_keys_from_ngram_id(ngram_id) as f64 + 0.5_f64)
+                + 1.0_f64,
+        )
     }
 
     #[inline(always)]
@@ -406,24 +502,21 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.tf_idf_search("Cat", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.tf_idf_search("Catt", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     /// ```
-    pub fn tf_idf_search<KR, F: Float>(
+    pub fn tf_idf_search<F: Float>(
         &self,
-        key: KR,
+        key: impl AsRef<K>,
         config: TFIDFSearchConfig<i32, F>,
-    ) -> SearchResults<'_, KS, NG, F>
-    where
-        KR: AsRef<K>,
-    {
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
         let k1 = config.k1().to_f64();
         let b = config.b().to_f64();
 
@@ -450,12 +543,12 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.warped_tf_idf_search("Cat", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.warped_tf_idf_search("Catt", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
@@ -464,7 +557,7 @@ where
         &self,
         key: KR,
         config: TFIDFSearchConfig<W, F>,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F>
     where
         KR: AsRef<K>,
         W: Copy + TryInto<Warp<W>, Error = &'static str>,
@@ -486,6 +579,252 @@ where
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// Precomputed inverse document frequencies of the ngrams of a corpus, used by the
+/// cosine TF-IDF search.
+///
+/// # Implementation details
+/// The inverse document frequency of an ngram appearing in `df` of the `n` keys of the
+/// corpus is `ln(1 + n / df)`, which is smoothed so that ngrams appearing in every key
+/// still have a positive weight. The frequencies are counted once, when the index is
+/// built, so that the ngrams first seen in the keys inserted afterwards are weighted as
+/// the unknown ngrams, and the removed keys keep counting until the index is rebuilt.
+pub struct TfidfIndex<F: Float = f32> {
+    /// The inverse document frequency of each ngram, indexed by ngram id.
+    inverse_document_frequencies: Vec<F>,
+    /// The inverse document frequency of the ngrams missing from the corpus.
+    unknown_inverse_document_frequency: F,
+}
+
+impl<F: Float> TfidfIndex<F> {
+    #[inline(always)]
+    /// Returns the inverse document frequency of the provided ngram.
+    ///
+    /// # Arguments
+    /// * `ngram_id` - The id of the ngram.
+    ///
+    /// # Implementation details
+    /// The ngrams without an inverse document frequency, such as the ones only found in
+    /// the keys inserted in the corpus after the index was built, receive the one of the
+    /// unknown ngrams.
+    pub fn inverse_document_frequency(&self, ngram_id: usize) -> F {
+        self.inverse_document_frequencies
+            .get(ngram_id)
+            .copied()
+            .unwrap_or(self.unknown_inverse_document_frequency)
+    }
+
+    #[inline(always)]
+    /// Returns the inverse document frequency assigned to the query ngrams missing from the corpus.
+    ///
+    /// # Implementation details
+    /// Missing ngrams are weighted as if they appeared in a single key, so that
+    /// they lower the similarity of the query with every key. Since the query only
+    /// keeps their total count, each of them is assumed to appear once in the query.
+    pub fn unknown_inverse_document_frequency(&self) -> F {
+        self.unknown_inverse_document_frequency
+    }
+
+    #[inline(always)]
+    /// Returns the number of ngrams in the index.
+    pub fn number_of_ngrams(&self) -> usize {
+        self.inverse_document_frequencies.len()
+    }
+
+    /// Returns the cosine similarity between the TF-IDF vectors of the query and of a key.
+    ///
+    /// # Arguments
+    /// * `query` - The query hashmap.
+    /// * `ngrams` - The ngram ids and cooccurrences of the key.
+    fn cosine_similarity<G: WeightedBipartiteGraph>(
+        &self,
+        query: &QueryHashmap,
+        ngrams: NgramIdsAndCooccurrences<'_, G>,
+    ) -> F {
+        let mut dot_product = F::ZERO;
+        let mut key_squared_norm = F::ZERO;
+
+        merge_ngram_counts(query.ngram_ids_and_counts(), ngrams, |merged| {
+            let (ngram_id, count, cooccurrence) = match merged {
+                MergedNgram::Query(..) => return,
+                MergedNgram::Key(ngram_id, cooccurrence) => (ngram_id, 0, cooccurrence),
+                MergedNgram::Shared(ngram_id, count, cooccurrence) => {
+                    (ngram_id, count, cooccurrence)
+                }
+            };
+            let inverse_document_frequency = self.inverse_document_frequency(ngram_id);
+            let key_weight = F::from_f64(cooccurrence as f64) * inverse_document_frequency;
+            key_squared_norm = key_squared_norm + key_weight * key_weight;
+            dot_product =
+                dot_product + F::from_f64(count as f64) * inverse_document_frequency * key_weight;
+        });
+
+        let query_squared_norm = query
+            .ngram_ids_and_counts()
+            .map(|(ngram_id, count)| {
+                let query_weight =
+                    F::from_f64(count as f64) * self.inverse_document_frequency(ngram_id);
+                query_weight * query_weight
+            })
+            .fold(F::ZERO, |total, squared_weight| total + squared_weight);
+        // Only the total count of the unknown ngrams is known, so we treat them as
+        // distinct ngrams appearing once each in the query.
+        let query_squared_norm = query_squared_norm
+            + F::from_f64(query.total_unknown_count() as f64)
+                * self.unknown_inverse_document_frequency
+                * self.unknown_inverse_document_frequency;
+
+        let denominator = (query_squared_norm * key_squared_norm).sqrt();
+        if denominator.is_zero() {
+            return F::ZERO;
+        }
+        dot_product / denominator
+    }
+}
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Returns the precomputed inverse document frequencies of the ngrams of the corpus.
+    ///
+    /// # Implementation details
+    /// Building the index requires a pass over all of the ngrams of the corpus, which
+    /// the `cosine_tfidf_search` method would otherwise repeat at each call. When the
+    /// corpus is searched repeatedly, the index should be built once and passed to the
+    /// `cosine_tfidf_search_with_index` method.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<[&str; 3], TriGram<char>> = Corpus::from(["cat", "cattle", "dog"]);
+    /// let index: TfidfIndex<f32> = corpus.build_tfidf();
+    ///
+    /// assert_eq!(index.number_of_ngrams(), corpus.number_of_ngrams());
+    ///
+    /// // The ngram shared by "cat" and "cattle" is less informative than
+    /// // the ngrams only found in "dog".
+    /// let shared = corpus.ngram_id_from_ngram(['\0', 'c', 'a']).unwrap();
+    /// let rare = corpus.ngram_id_from_ngram(['d', 'o', 'g']).unwrap();
+    /// assert!(index.inverse_document_frequency(shared) < index.inverse_document_frequency(rare));
+    /// ```
+    pub fn build_tfidf<F: Float>(&self) -> TfidfIndex<F> {
+        let number_of_keys = self.number_of_keys() as f64;
+        TfidfIndex {
+            inverse_document_frequencies: (0..self.number_of_ngrams())
+                .map(|ngram_id| {
+                    let number_of_keys_from_ngram_id =
+                        self.number_of_keys_from_ngram_id(ngram_id) as f64;
+                    F::from_f64(math::log(
+                        1.0 + number_of_keys / number_of_keys_from_ngram_id,
+                    ))
+                })
+                .collect(),
+            unknown_inverse_document_frequency: F::from_f64(math::log(1.0 + number_of_keys)),
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the best matches using the cosine similarity between TF-IDF vectors.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus.
+    /// * `config` - The configuration for the search. Its warp factor is ignored.
+    ///
+    /// # Implementation details
+    /// The query and each key are represented as vectors of ngram counts, weighted by
+    /// the inverse document frequencies of the ngrams, and scored by the cosine of the
+    /// angle between them. This method rebuilds the `TfidfIndex` at each call: use the
+    /// `cosine_tfidf_search_with_index` method to reuse a precomputed one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>> = Corpus::from(&ANIMALS);
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.cosine_tfidf_search("Cat", NgramSearchConfig::default());
+    ///
+    /// assert_eq!(results[0].key(), &"Cat");
+    /// assert!((results[0].score() - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn cosine_tfidf_search<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        let index = self.build_tfidf();
+        self.cosine_tfidf_search_with_index(key, config, &index)
+    }
+
+    #[inline(always)]
+    /// Returns the best matches using the cosine similarity between TF-IDF vectors,
+    /// with the provided precomputed inverse document frequencies.
+    ///
+    /// # Arguments
+    /// * `key` - The key to search for in the corpus.
+    /// * `config` - The configuration for the search. Its warp factor is ignored.
+    /// * `index` - The inverse document frequencies built with `build_tfidf` on this corpus.
+    ///
+    /// # Panics
+    /// * If the index was built from a corpus with fewer ngrams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let corpus: Corpus<&[&str; 699], TriGram<char>, Lowercase<str>> = Corpus::from(&ANIMALS);
+    /// let index = corpus.build_tfidf();
+    /// let config = NgramSearchConfig::default()
+    ///     .set_minimum_similarity_score(0.3_f32)
+    ///     .unwrap();
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.cosine_tfidf_search_with_index("cat", config, &index);
+    /// assert_eq!(results[0].key(), &"Cat");
+    ///
+    /// // The same index can be reused by any number of searches.
+    /// for query in ["catt", "dog", "kat"] {
+    ///     let results: SearchResults<&&str, f32> =
+    ///         corpus.cosine_tfidf_search_with_index(query, config, &index);
+    ///     let expected: SearchResults<&&str, f32> = corpus.cosine_tfidf_search(query, config);
+    ///     assert_eq!(results.len(), expected.len());
+    ///     for (result, expected) in results.iter().zip(expected.iter()) {
+    ///         assert_eq!(result.score(), expected.score());
+    ///     }
+    /// }
+    /// ```
+    pub fn cosine_tfidf_search_with_index<KR, W: Copy, F: Float>(
+        &self,
+        key: KR,
+        config: NgramSearchConfig<W, F>,
+        index: &TfidfIndex<F>,
+    ) -> SearchResults<KS::KeyRef<'_>, F>
+    where
+        KR: AsRef<K>,
+    {
+        self.search(
+            key,
+            config.into(),
+            move |query: &QueryHashmap, ngrams: NgramIdsAndCooccurrences<'_, G>| {
+                index.cosine_similarity(query, ngrams)
+            },
+        )
+    }
+}
+
 #[cfg(feature = "rayon")]
 impl<KS, NG, K, G> Corpus<KS, NG, K, G>
 where
@@ -515,24 +854,21 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.tf_idf_par_search("Cat", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.tf_idf_par_search("Catt", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     /// ```
-    pub fn tf_idf_par_search<KR, F: Float>(
+    pub fn tf_idf_par_search<F: Float>(
         &self,
-        key: KR,
+        key: impl AsRef<K> + Send + Sync,
         config: TFIDFSearchConfig<i32, F>,
-    ) -> SearchResults<'_, KS, NG, F>
-    where
-        KR: AsRef<K> + Send + Sync,
-    {
+    ) -> SearchResults<KS::KeyRef<'_>, F> {
         let k1 = config.k1.to_f64();
         let b = config.b.to_f64();
         self.par_search(
@@ -556,12 +892,12 @@ where
     ///
     /// let corpus: Corpus<&[&str; 699], BiGram<char>> = Corpus::par_from(&ANIMALS);
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.warped_tf_idf_par_search("Cat", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
     ///
-    /// let results: Vec<SearchResult<&&str, f32>> =
+    /// let results: SearchResults<&&str, f32> =
     ///     corpus.warped_tf_idf_par_search("Catt", TFIDFSearchConfig::default());
     ///
     /// assert_eq!(results[0].key(), &"Cat");
@@ -570,7 +906,7 @@ where
         &self,
         key: KR,
         config: TFIDFSearchConfig<W, F>,
-    ) -> SearchResults<'_, KS, NG, F>
+    ) -> SearchResults<KS::KeyRef<'_>, F>
     where
         KR: AsRef<K> + Send + Sync,
         W: Copy + TryInto<Warp<W>, Error = &'static str>,
@@ -0,0 +1,255 @@
+//! Submodule providing the removal of keys from a corpus, marking them with tombstones.
+//!
+//! # Implementation details
+//! The compressed bipartite graph of a corpus cannot be shrunk in place, so removing a
+//! key only sets its bit in a bitvector of tombstones stored alongside the graph. The
+//! removed keys are still stored, but they are skipped by the searches and by the
+//! iterators over the keys of an ngram, and are not counted by `number_of_keys`. The
+//! space of the removed keys is reclaimed by `purge_tombstones`, which rebuilds the
+//! graph from the edges of the keys that were not removed.
+use sux::bits::BitVec;
+
+use crate::{
+    bit_field_bipartite_graph::WeightedBitFieldBipartiteGraph, corpus_from::KeysParser,
+    overflow_graph::OverflowGraph, traits::*, Corpus, ReusableCorpusBuilder,
+};
+
+impl<KS, NG, K, G> Corpus<KS, NG, K, G>
+where
+    NG: Ngram,
+    KS: Keys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+    G: WeightedBipartiteGraph,
+{
+    /// Removes the key with the provided id from the corpus.
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the key to remove.
+    ///
+    /// # Implementation details
+    /// The key is marked with a tombstone and is skipped from then on by the searches,
+    /// by `key_ids_from_ngram_id` and by `number_of_keys`, while the ids of the other
+    /// keys are unchanged. Removing a key that was already removed has no effect.
+    ///
+    /// # Panics
+    /// * If the key id is not smaller than `number_of_key_ids`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "catfish", "dog"]);
+    ///
+    /// corpus.remove_key(0);
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 2);
+    /// assert!(corpus.is_removed(0));
+    /// assert_eq!(corpus.key_from_id(1), &"catfish");
+    ///
+    /// let results: SearchResults<&&str, f32> =
+    ///     corpus.ngram_search("cat", NgramSearchConfig::default());
+    /// assert!(results.iter().all(|result| result.key() != &"cat"));
+    /// ```
+    pub fn remove_key(&mut self, key_id: usize) {
+        assert!(
+            key_id < self.number_of_key_ids(),
+            "The key id {} is out of bounds, as there are {} key ids.",
+            key_id,
+            self.number_of_key_ids()
+        );
+
+        // The tombstones are allocated lazily, and grown when keys were inserted since.
+        if self.tombstones.len() < self.number_of_key_ids() {
+            let mut tombstones = BitVec::new(self.number_of_key_ids());
+            for removed_key_id in 0..self.tombstones.len() {
+                if self.tombstones.get(removed_key_id) {
+                    tombstones.set(removed_key_id, true);
+                }
+            }
+            self.tombstones = tombstones;
+        }
+
+        if !self.tombstones.get(key_id) {
+            self.tombstones.set(key_id, true);
+            self.number_of_removed_keys += 1;
+        }
+    }
+}
+
+impl<KS, NG, K> Corpus<KS, NG, K, WeightedBitFieldBipartiteGraph>
+where
+    NG: Ngram,
+    KS: RetainKeys<NG>,
+    for<'a> KS::KeyRef<'a>: AsRef<K>,
+    K: Key<NG, NG::G> + ?Sized,
+{
+    /// Rebuilds the corpus dropping the removed keys.
+    ///
+    /// # Implementation details
+    /// The keys that were not removed keep their relative order, so that after the purge
+    /// the key with the old id `key_id` receives the new id `key_id` minus the number of
+    /// removed keys with a smaller id, and `key_from_id` called with the new id returns
+    /// the same key that was returned with the old one. As `prune_ngrams` does, the graph
+    /// is rebuilt from the ngrams and cooccurrences of the kept keys rather than from the
+    /// keys, so that the padding, the truncation of the keys and the ngrams excluded by
+    /// the configuration are preserved. The overflow keys, if any, are folded into the
+    /// compressed graph as well. No work is done when no key was removed, or when all of
+    /// the keys were removed, as a corpus cannot be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let mut corpus: Corpus<Vec<&str>, TriGram<char>> =
+    ///     Corpus::from(vec!["cat", "catfish", "dog"]);
+    ///
+    /// corpus.remove_key(1);
+    /// corpus.purge_tombstones();
+    ///
+    /// assert_eq!(corpus.number_of_keys(), 2);
+    /// assert_eq!(corpus.number_of_key_ids(), 2);
+    /// assert_eq!(corpus.number_of_removed_keys(), 0);
+    /// assert_eq!(corpus.key_from_id(0), &"cat");
+    /// assert_eq!(corpus.key_from_id(1), &"dog");
+    /// ```
+    pub fn purge_tombstones(&mut self) {
+        if self.number_of_removed_keys == 0 || self.number_of_keys() == 0 {
+            return;
+        }
+
+        let number_of_graph_keys = self.graph.number_of_source_nodes();
+        let builder = &mut ReusableCorpusBuilder::<NG>::default();
+        let mut parser = KeysParser::new(self.number_of_keys(), builder);
+        for key_id in (0..self.number_of_key_ids()).filter(|&key_id| !self.is_removed(key_id)) {
+            builder.ngram_counts.clear();
+            builder
+                .ngram_counts
+                .extend(self.ngrams_and_cooccurrences_from_key(key_id));
+            if key_id >= number_of_graph_keys {
+                // The ngrams of the overflow keys missing from the vocabulary have ids
+                // in order of insertion rather than in order of ngram.
+                builder
+                    .ngram_counts
+                    .sort_unstable_by(|(ngram_a, _), (ngram_b, _)| ngram_a.cmp(ngram_b));
+            }
+            parser.digest_ngram_counts(builder);
+        }
+        let (cooccurrences_builder, average_key_length, key_offsets) = parser.finish(builder);
+
+        let (ngrams, graph) = Self::graph_from_parsed_keys(
+            self.number_of_keys(),
+            cooccurrences_builder,
+            key_offsets,
+            builder,
+        );
+
+        let tombstones = &self.tombstones;
+        self.keys
+            .retain_indices(|key_id| key_id >= tombstones.len() || !tombstones.get(key_id));
+        self.ngrams = ngrams;
+        self.graph = graph;
+        self.average_key_length = average_key_length.max(1.0);
+        self.overflow = OverflowGraph::default();
+        self.tombstones = BitVec::new(0);
+        self.number_of_removed_keys = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_removed_keys_are_skipped_and_purged() {
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(ANIMALS.to_vec());
+        let removed: Vec<usize> = (0..corpus.number_of_keys()).step_by(3).collect();
+        for &key_id in &removed {
+            corpus.remove_key(key_id);
+        }
+        // Removing a key twice has no effect.
+        corpus.remove_key(removed[0]);
+        assert_eq!(corpus.number_of_removed_keys(), removed.len());
+        assert_eq!(corpus.number_of_keys(), ANIMALS.len() - removed.len());
+
+        for ngram_id in 0..corpus.number_of_ngrams() {
+            assert!(corpus
+                .key_ids_from_ngram_id(ngram_id)
+                .all(|key_id| key_id % 3 != 0));
+        }
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0)
+            .unwrap()
+            .set_maximum_number_of_results(ANIMALS.len());
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("Cat", config);
+        assert!(!results.is_empty());
+        let removed_keys: Vec<&str> = removed.iter().map(|&key_id| ANIMALS[key_id]).collect();
+        assert!(results
+            .iter()
+            .all(|result| !removed_keys.contains(result.key())));
+
+        let kept: Vec<&str> = ANIMALS
+            .iter()
+            .enumerate()
+            .filter(|(key_id, _)| key_id % 3 != 0)
+            .map(|(_, animal)| *animal)
+            .collect();
+        corpus.purge_tombstones();
+        let expected: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(kept.clone());
+
+        assert_eq!(corpus.number_of_keys(), kept.len());
+        assert_eq!(corpus.number_of_ngrams(), expected.number_of_ngrams());
+        for (key_id, key) in kept.iter().enumerate() {
+            assert_eq!(corpus.key_from_id(key_id), key);
+            assert!(corpus
+                .ngram_ids_from_key(key_id)
+                .eq(expected.ngram_ids_from_key(key_id)));
+        }
+    }
+
+    #[test]
+    fn test_purge_keeps_the_padding_and_the_overflow_keys() {
+        let mut pool = StringPool::default();
+        for key in ["cat", "catfish", "dog", "dogfish", "cow"] {
+            pool.push_str(key);
+        }
+        let mut corpus: Corpus<StringPool, TriGram<char>> =
+            Corpus::from_with_padding(pool, PaddingMode::None);
+        corpus.remove_key(1);
+        corpus.remove_key(3);
+        corpus.purge_tombstones();
+
+        let kept = vec!["cat", "dog", "cow"];
+        let expected: Corpus<Vec<&str>, TriGram<char>> =
+            Corpus::from_with_padding(kept.clone(), PaddingMode::None);
+        assert_eq!(corpus.number_of_key_ids(), kept.len());
+        assert!(corpus.ngrams().eq(expected.ngrams()));
+        assert!(!corpus.contains_ngram(['\0', '\0', 'c']));
+        for (key_id, key) in kept.iter().enumerate() {
+            assert_eq!(corpus.key_from_id(key_id), *key);
+            assert!(corpus
+                .ngram_ids_and_cooccurrences_from_key(key_id)
+                .eq(expected.ngram_ids_and_cooccurrences_from_key(key_id)));
+        }
+
+        // The overflow keys are folded into the graph.
+        let mut corpus: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "catfish"]);
+        corpus.extend(["dog", "zebra"]);
+        corpus.remove_key(1);
+        corpus.purge_tombstones();
+
+        let expected: Corpus<Vec<&str>, TriGram<char>> = Corpus::from(vec!["cat", "dog", "zebra"]);
+        assert_eq!(corpus.number_of_overflow_keys(), 0);
+        assert!(corpus.ngrams().eq(expected.ngrams()));
+        for key_id in 0..expected.number_of_key_ids() {
+            assert_eq!(corpus.key_from_id(key_id), expected.key_from_id(key_id));
+            assert!(corpus
+                .ngram_ids_and_cooccurrences_from_key(key_id)
+                .eq(expected.ngram_ids_and_cooccurrences_from_key(key_id)));
+        }
+    }
+}
@@ -17,6 +17,8 @@ pub mod char_normalizer;
 pub use char_normalizer::*;
 pub mod ascii_char;
 pub use ascii_char::*;
+pub mod u16_char;
+pub use u16_char::*;
 pub mod padder;
 pub use padder::*;
 pub mod paddable;
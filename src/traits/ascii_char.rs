@@ -37,6 +37,13 @@ impl From<ASCIIChar> for u8 {
     }
 }
 
+impl From<ASCIIChar> for char {
+    #[inline(always)]
+    fn from(ascii_char: ASCIIChar) -> char {
+        ascii_char.character as char
+    }
+}
+
 impl TryFrom<char> for ASCIIChar {
     type Error = &'static str;
 
@@ -1,6 +1,6 @@
 //! Submodule defining char-like types.
 
-use crate::ASCIIChar;
+use crate::{ASCIIChar, U16Char};
 
 /// Trait defining a char-like type.
 pub trait CharLike:
@@ -113,3 +113,28 @@ impl CharLike for ASCIIChar {
         self.is_alphanumeric()
     }
 }
+
+impl CharLike for U16Char {
+    const SPACE: Self = U16Char::SPACE;
+    const NUL: Self = U16Char::NUL;
+
+    #[inline(always)]
+    fn to_lowercase(self) -> Self {
+        self.to_lowercase()
+    }
+
+    #[inline(always)]
+    fn to_uppercase(self) -> Self {
+        self.to_uppercase()
+    }
+
+    #[inline(always)]
+    fn is_space_like(self) -> bool {
+        self.is_space_like()
+    }
+
+    #[inline(always)]
+    fn is_alphanumeric(self) -> bool {
+        self.is_alphanumeric()
+    }
+}
@@ -1,9 +1,13 @@
 //! Submodule providing traits to normalize iterators of char-like items.
 
-use std::{iter::Rev, mem::transmute};
+use std::{iter::Rev, marker::PhantomData, mem::transmute};
 
 use crate::CharLike;
+#[cfg(feature = "stemming")]
+use rust_stemmers::{Algorithm, Stemmer};
 use std::iter::Peekable;
+#[cfg(feature = "normalization")]
+use unicode_normalization::{Decompositions, Recompositions, UnicodeNormalization};
 
 /// Struct defining an iterator to lowercase.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -189,6 +193,618 @@ where
     }
 }
 
+/// Trait defining the characters kept by `AlphanumericPlus` along with the alphanumeric ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// struct Underscore;
+///
+/// impl ExtraChars for Underscore {
+///     const EXTRA_CHARS: &'static [char] = &['_'];
+/// }
+///
+/// let normalized: String = "snake_case-key"
+///     .chars()
+///     .alphanumeric_plus::<Underscore>()
+///     .collect();
+/// assert_eq!(normalized, "snake_case key");
+/// ```
+pub trait ExtraChars {
+    /// The characters to keep.
+    const EXTRA_CHARS: &'static [char];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// The typewriter and the typographic apostrophes, as in "O'Brien".
+pub struct Apostrophes;
+
+impl ExtraChars for Apostrophes {
+    const EXTRA_CHARS: &'static [char] = &['\'', '\u{2019}'];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// The hyphen, as in "Jean-Luc".
+pub struct Hyphen;
+
+impl ExtraChars for Hyphen {
+    const EXTRA_CHARS: &'static [char] = &['-'];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+/// The apostrophes and the hyphen, which are part of many names of people and places.
+pub struct NameChars;
+
+impl ExtraChars for NameChars {
+    const EXTRA_CHARS: &'static [char] = &['\'', '\u{2019}', '-'];
+}
+
+/// Struct defining an iterator that replaces characters that are neither alphanumeric
+/// nor among the extra characters `X` with spaces.
+///
+/// # Implementation details
+/// As for the other normalizers, the struct is also used as the key type of a corpus,
+/// so that the extra characters are part of the type, as in `AlphanumericPlus<NameChars>`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct AlphanumericPlus<X, I: ?Sized = str>(PhantomData<X>, I);
+
+impl<X, E: ?Sized, I: ?Sized> AsRef<I> for AlphanumericPlus<X, E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.1.as_ref()
+    }
+}
+
+impl<X, E: ?Sized> AsRef<AlphanumericPlus<X, E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &AlphanumericPlus<X, E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<X, E: ?Sized> AsRef<AlphanumericPlus<X, E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &AlphanumericPlus<X, E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<X, I: ?Sized> AlphanumericPlus<X, I> {
+    #[inline(always)]
+    /// Returns a reference to the inner iterator.
+    pub fn inner(&self) -> &I {
+        &self.1
+    }
+}
+
+impl<X, I> From<I> for AlphanumericPlus<X, I> {
+    #[inline(always)]
+    fn from(iter: I) -> Self {
+        AlphanumericPlus(PhantomData, iter)
+    }
+}
+
+#[inline(always)]
+/// Returns the provided character if it is alphanumeric or among the extra characters,
+/// and a space otherwise.
+///
+/// # Arguments
+/// * `character` - The character to normalize.
+fn alphanumeric_plus_char<X: ExtraChars>(character: char) -> char {
+    if character.is_alphanumeric() || X::EXTRA_CHARS.contains(&character) {
+        character
+    } else {
+        ' '
+    }
+}
+
+impl<X, I> Iterator for AlphanumericPlus<X, I>
+where
+    X: ExtraChars,
+    I: Iterator<Item = char>,
+{
+    type Item = char;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.1.next().map(alphanumeric_plus_char::<X>)
+    }
+}
+
+impl<X, I> DoubleEndedIterator for AlphanumericPlus<X, I>
+where
+    X: ExtraChars,
+    I: DoubleEndedIterator<Item = char>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.1.next_back().map(alphanumeric_plus_char::<X>)
+    }
+}
+
+impl<X, I> ExactSizeIterator for AlphanumericPlus<X, I>
+where
+    X: ExtraChars,
+    I: ExactSizeIterator<Item = char>,
+{
+    fn len(&self) -> usize {
+        self.1.len()
+    }
+}
+
+/// The ASCII base letters of the Latin-1 Supplement letters, from U+00C0 to U+00FF.
+///
+/// The null bytes mark the characters without a single base letter, such as the 'ß'.
+const LATIN_1_BASES: &[u8; 64] =
+    b"AAAAAA\0CEEEEIIIIDNOOOOO\0OUUUUY\0\0aaaaaa\0ceeeeiiiidnooooo\0ouuuuy\0y";
+
+/// The ASCII base letters of the Latin Extended-A letters, from U+0100 to U+017F.
+///
+/// The null bytes mark the characters without a single base letter, such as the 'œ'.
+const LATIN_EXTENDED_A_BASES: &[u8; 128] = b"AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGgGgGgHhHhIiIiIiIiIi\0\0JjKk\0LlLlLlLlLlNnNnNnn\0\0OoOoOo\0\0RrRrRrSsSsSsSsTtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+#[inline(always)]
+/// Returns the ASCII base letter of the provided accented Latin letter, or the letter itself.
+///
+/// # Arguments
+/// * `character` - The character to fold.
+fn unaccent_char(character: char) -> char {
+    let base = match character as u32 {
+        0xC0..=0xFF => LATIN_1_BASES[character as usize - 0xC0],
+        0x100..=0x17F => LATIN_EXTENDED_A_BASES[character as usize - 0x100],
+        _ => 0,
+    };
+    if base == 0 {
+        character
+    } else {
+        base as char
+    }
+}
+
+/// Struct defining an iterator that folds the accented Latin letters to their ASCII base.
+///
+/// # Implementation details
+/// The letters of the Latin-1 Supplement and of the Latin Extended-A blocks are mapped
+/// to their base letter, such as the 'é' to the 'e' and the 'Ł' to the 'L', while the
+/// ligatures such as the 'æ' and the characters of the other scripts are left untouched.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct Unaccent<I: ?Sized = str>(I);
+
+impl<E: ?Sized, I: ?Sized> AsRef<I> for Unaccent<E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.0.as_ref()
+    }
+}
+
+impl<E: ?Sized> AsRef<Unaccent<E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Unaccent<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<E: ?Sized> AsRef<Unaccent<E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Unaccent<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<I: ?Sized> Unaccent<I> {
+    #[inline(always)]
+    /// Returns a reference to the inner iterator.
+    pub fn inner(&self) -> &I {
+        &self.0
+    }
+}
+
+impl<I> From<I> for Unaccent<I> {
+    #[inline(always)]
+    fn from(iter: I) -> Self {
+        Unaccent(iter)
+    }
+}
+
+impl<I> Iterator for Unaccent<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = char;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(unaccent_char)
+    }
+}
+
+impl<I> DoubleEndedIterator for Unaccent<I>
+where
+    I: DoubleEndedIterator<Item = char>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(unaccent_char)
+    }
+}
+
+impl<I> ExactSizeIterator for Unaccent<I>
+where
+    I: ExactSizeIterator<Item = char>,
+{
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Struct defining an iterator that maps the characters with a user-provided function.
+///
+/// # Implementation details
+/// The characters for which the function returns `None` are dropped. Since closures
+/// cannot be named, a `Key` implementation whose grams use this adaptor should use a
+/// function pointer, such as `fn(char) -> Option<char>`, as the type of the function.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// fn fold_sharp_s(character: char) -> Option<char> {
+///     match character {
+///         'ß' => Some('s'),
+///         '-' => None,
+///         _ => Some(character),
+///     }
+/// }
+///
+/// let folded: MapChars<std::str::Chars<'_>, fn(char) -> Option<char>> = "Straße-Nord"
+///     .chars()
+///     .map_chars(fold_sharp_s as fn(char) -> Option<char>);
+/// assert_eq!(folded.collect::<String>(), "StraseNord");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MapChars<I, F> {
+    /// The iterator of the characters to map.
+    iter: I,
+    /// The function mapping each character, returning `None` to drop it.
+    map: F,
+}
+
+impl<I, F> MapChars<I, F> {
+    #[inline(always)]
+    /// Creates a new iterator mapping the characters with the provided function.
+    ///
+    /// # Arguments
+    /// * `iter` - The iterator of the characters to map.
+    /// * `map` - The function mapping each character, returning `None` to drop it.
+    pub fn new(iter: I, map: F) -> Self {
+        MapChars { iter, map }
+    }
+}
+
+impl<I, F> Iterator for MapChars<I, F>
+where
+    I: Iterator<Item = char>,
+    F: Fn(char) -> Option<char>,
+{
+    type Item = char;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        for character in self.iter.by_ref() {
+            if let Some(mapped) = (self.map)(character) {
+                return Some(mapped);
+            }
+        }
+        None
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<I, F> DoubleEndedIterator for MapChars<I, F>
+where
+    I: DoubleEndedIterator<Item = char>,
+    F: Fn(char) -> Option<char>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(character) = self.iter.next_back() {
+            if let Some(mapped) = (self.map)(character) {
+                return Some(mapped);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "normalization")]
+/// Struct defining a key normalized to the Unicode Normalization Form C.
+///
+/// # Implementation details
+/// The precomposed and the decomposed representations of the same characters, such as
+/// the 'é' and the 'e' followed by a combining acute accent, are composed into the same
+/// characters before the default normalizations of the key are applied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct Nfc<I: ?Sized = str>(I);
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized, I: ?Sized> AsRef<I> for Nfc<E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized> AsRef<Nfc<E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Nfc<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized> AsRef<Nfc<E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Nfc<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<I: ?Sized> Nfc<I> {
+    #[inline(always)]
+    /// Returns a reference to the inner key.
+    pub fn inner(&self) -> &I {
+        &self.0
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<I> From<I> for Nfc<I> {
+    #[inline(always)]
+    fn from(key: I) -> Self {
+        Nfc(key)
+    }
+}
+
+#[cfg(feature = "normalization")]
+/// Struct defining a key normalized to the Unicode Normalization Form D.
+///
+/// # Implementation details
+/// The characters are decomposed into their base characters followed by the combining
+/// marks. As the combining marks are not alphanumeric, the default normalizations of the
+/// key replace them with spaces, so that "café" and "cafe" share most of their ngrams.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct Nfd<I: ?Sized = str>(I);
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized, I: ?Sized> AsRef<I> for Nfd<E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized> AsRef<Nfd<E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Nfd<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<E: ?Sized> AsRef<Nfd<E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Nfd<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<I: ?Sized> Nfd<I> {
+    #[inline(always)]
+    /// Returns a reference to the inner key.
+    pub fn inner(&self) -> &I {
+        &self.0
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<I> From<I> for Nfd<I> {
+    #[inline(always)]
+    fn from(key: I) -> Self {
+        Nfd(key)
+    }
+}
+
+#[cfg(feature = "stemming")]
+/// Struct defining a key whose English words are stemmed.
+///
+/// # Implementation details
+/// The key is split into words at the spaces after the default normalizations, and
+/// each lowercased word is replaced by its stem, so that "running" and "run" share
+/// the same ngrams. Stemming only makes sense with keys made of words delimited by
+/// whitespace, such as product titles, and not with names or codes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct Stemmed<I: ?Sized = str>(I);
+
+#[cfg(feature = "stemming")]
+impl<E: ?Sized, I: ?Sized> AsRef<I> for Stemmed<E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<E: ?Sized> AsRef<Stemmed<E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Stemmed<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<E: ?Sized> AsRef<Stemmed<E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Stemmed<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<I: ?Sized> Stemmed<I> {
+    #[inline(always)]
+    /// Returns a reference to the inner key.
+    pub fn inner(&self) -> &I {
+        &self.0
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<I> From<I> for Stemmed<I> {
+    #[inline(always)]
+    fn from(key: I) -> Self {
+        Stemmed(key)
+    }
+}
+
+#[cfg(feature = "stemming")]
+/// Struct defining an iterator that replaces each word with its English stem.
+///
+/// # Implementation details
+/// As the stem of a word depends on all of its characters, the characters of each word
+/// are buffered until the following space, or the end of the iterator, and the stem is
+/// then emitted character by character, followed by the space.
+pub struct Stem<I> {
+    /// The iterator of characters.
+    iter: I,
+    /// The English Porter stemmer.
+    stemmer: Stemmer,
+    /// The characters of the word being read.
+    word: String,
+    /// The characters of the last stemmed word that are yet to be emitted.
+    stemmed: std::vec::IntoIter<char>,
+    /// The space following the last stemmed word, if any.
+    space: Option<char>,
+}
+
+#[cfg(feature = "stemming")]
+impl<I> From<I> for Stem<I> {
+    #[inline(always)]
+    fn from(iter: I) -> Self {
+        Stem {
+            iter,
+            stemmer: Stemmer::create(Algorithm::English),
+            word: String::new(),
+            stemmed: Vec::new().into_iter(),
+            space: None,
+        }
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<I> Iterator for Stem<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(c) = self.stemmed.next() {
+            return Some(c);
+        }
+        if let Some(space) = self.space.take() {
+            return Some(space);
+        }
+
+        for c in self.iter.by_ref() {
+            if c.is_space_like() {
+                self.space = Some(c);
+                break;
+            }
+            self.word.push(c);
+        }
+
+        if self.word.is_empty() {
+            return self.space.take();
+        }
+
+        let stemmed: Vec<char> = self.stemmer.stem(&self.word).chars().collect();
+        self.word.clear();
+        self.stemmed = stemmed.into_iter();
+        self.stemmed.next()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// Struct defining an iterator that removes subsequent spaces.
 pub struct SpaceNormalizer<I> {
@@ -969,6 +1585,167 @@ where
         Alphanumeric::from(self)
     }
 
+    #[inline(always)]
+    /// Converts all characters that are neither alphanumeric nor among the extra
+    /// characters `X` to spaces.
+    ///
+    /// # Examples
+    ///
+    /// The following example demonstrates how to keep the apostrophes and the hyphens
+    /// of the names of people:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let string = "O'Brien & Jean-Luc";
+    /// let normalized: String = string.chars().alphanumeric_plus::<NameChars>().collect();
+    /// assert_eq!(normalized, "O'Brien   Jean-Luc");
+    ///
+    /// let normalized: String = string.chars().alphanumeric_plus::<Hyphen>().collect();
+    /// assert_eq!(normalized, "O Brien   Jean-Luc");
+    /// ```
+    fn alphanumeric_plus<X: ExtraChars>(self) -> AlphanumericPlus<X, Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        AlphanumericPlus::from(self)
+    }
+
+    #[inline(always)]
+    /// Folds the accented Latin letters to their ASCII base letter.
+    ///
+    /// # Examples
+    ///
+    /// The following example demonstrates how to fold the accents of a string
+    /// composed of `char`:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let string = "José Łukasz Ærø Ωmega";
+    /// let unaccented: String = string.chars().unaccent().collect();
+    /// assert_eq!(unaccented, "Jose Lukasz Æro Ωmega");
+    /// ```
+    ///
+    /// The following example demonstrates how folding the accents before the conversion
+    /// to `ASCIIChar` keeps the base letters, which would otherwise be discarded:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let folded: String = "José".chars().unaccent().ascii().collect();
+    /// let discarded: String = "José".chars().ascii().collect();
+    /// assert_eq!(folded, "Jose");
+    /// assert_eq!(discarded, "Jos");
+    /// ```
+    fn unaccent(self) -> Unaccent<Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        Unaccent::from(self)
+    }
+
+    #[inline(always)]
+    /// Maps the characters with the provided function, dropping those mapped to `None`.
+    ///
+    /// # Arguments
+    /// * `map` - The function mapping each character.
+    ///
+    /// # Examples
+    ///
+    /// The following example demonstrates how to fold the 'ß' to an 's' and drop the
+    /// apostrophes before lowercasing the characters:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let string = "O'Straßer";
+    /// let folded: String = string
+    ///     .chars()
+    ///     .map_chars(|character| match character {
+    ///         'ß' => Some('s'),
+    ///         '\'' => None,
+    ///         _ => Some(character),
+    ///     })
+    ///     .lower()
+    ///     .collect();
+    /// assert_eq!(folded, "ostraser");
+    /// ```
+    fn map_chars<F>(self, map: F) -> MapChars<Self, F>
+    where
+        Self: Iterator<Item = char>,
+        F: Fn(char) -> Option<char>,
+    {
+        MapChars::new(self, map)
+    }
+
+    #[cfg(feature = "stemming")]
+    #[inline(always)]
+    /// Replaces each word with its English stem.
+    ///
+    /// # Implementation details
+    /// The words are delimited by the spaces, so this normalizer should follow the
+    /// `dedup_spaces` and the `lower` ones, as the stemmer only recognizes lowercase
+    /// suffixes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let string = "Running  shoes";
+    /// let stemmed: String = string.chars().dedup_spaces().lower().stem().collect();
+    /// assert_eq!(stemmed, "run shoe");
+    /// ```
+    fn stem(self) -> Stem<Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        Stem::from(self)
+    }
+
+    #[cfg(feature = "normalization")]
+    #[inline(always)]
+    /// Composes the characters to the Unicode Normalization Form C.
+    ///
+    /// # Examples
+    ///
+    /// The following example demonstrates how the precomposed and the decomposed
+    /// representations of a string are normalized to the same characters:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let precomposed: String = "caf\u{e9}".chars().nfc().lower().collect();
+    /// let decomposed: String = "CAFE\u{301}".chars().nfc().lower().collect();
+    /// assert_eq!(precomposed, "caf\u{e9}");
+    /// assert_eq!(decomposed, "caf\u{e9}");
+    /// ```
+    fn nfc(self) -> Recompositions<Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        UnicodeNormalization::nfc(self)
+    }
+
+    #[cfg(feature = "normalization")]
+    #[inline(always)]
+    /// Decomposes the characters to the Unicode Normalization Form D.
+    ///
+    /// # Examples
+    ///
+    /// The following example demonstrates how the precomposed and the decomposed
+    /// representations of a string are normalized to the same characters:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let precomposed: String = "caf\u{e9}".chars().nfd().collect();
+    /// let decomposed: String = "cafe\u{301}".chars().nfd().collect();
+    /// assert_eq!(precomposed, "cafe\u{301}");
+    /// assert_eq!(decomposed, "cafe\u{301}");
+    /// ```
+    fn nfd(self) -> Decompositions<Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        UnicodeNormalization::nfd(self)
+    }
+
     #[inline(always)]
     /// Normalizes spaces, removing subsequent spaces.
     ///
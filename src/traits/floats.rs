@@ -1,5 +1,6 @@
 //! Trait definition for floating point numbers.
 
+use crate::math;
 use crate::{One, Three, Zero};
 
 /// Trait defining a floating point number.
@@ -21,6 +22,37 @@ pub trait Float:
     /// Returns the absolute value of the float.
     fn abs(self) -> Self;
 
+    /// Returns the square root of the float.
+    ///
+    /// # Implementation details
+    /// The default implementation computes the square root in `f64`, so that the
+    /// custom float types only need to provide the conversions.
+    fn sqrt(self) -> Self {
+        Self::from_f64(math::sqrt(self.to_f64()))
+    }
+
+    /// Returns the float raised to the provided power.
+    ///
+    /// # Arguments
+    /// * `n` - The exponent.
+    ///
+    /// # Implementation details
+    /// The default implementation computes the power in `f64`, so that the
+    /// custom float types only need to provide the conversions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// assert_eq!(Float::powf(2.0_f32, 3.0), 8.0);
+    /// assert_eq!(Float::powf(9.0_f64, 0.5), 3.0);
+    /// assert_eq!(Float::sqrt(16.0_f64), 4.0);
+    /// ```
+    fn powf(self, n: Self) -> Self {
+        Self::from_f64(math::pow(self.to_f64(), n.to_f64()))
+    }
+
     /// Returns an f64 from the provided value.
     fn to_f64(self) -> f64;
 
@@ -135,6 +167,16 @@ impl Float for half::f16 {
         Self::from_bits(self.to_bits() & 0x7FFF)
     }
 
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        Self::from_f32(math::sqrtf(self.to_f32()))
+    }
+
+    #[inline(always)]
+    fn powf(self, n: Self) -> Self {
+        Self::from_f32(math::powf(self.to_f32(), n.to_f32()))
+    }
+
     #[inline(always)]
     fn to_f64(self) -> f64 {
         f64::from(self)
@@ -151,7 +193,17 @@ impl Float for half::f16 {
 impl Float for half::bf16 {
     #[inline(always)]
     fn abs(self) -> Self {
-        Self::from_f32(self.to_f32().abs())
+        Self::from_f32(math::fabsf(self.to_f32()))
+    }
+
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        Self::from_f32(math::sqrtf(self.to_f32()))
+    }
+
+    #[inline(always)]
+    fn powf(self, n: Self) -> Self {
+        Self::from_f32(math::powf(self.to_f32(), n.to_f32()))
     }
 
     #[inline(always)]
@@ -168,7 +220,17 @@ impl Float for half::bf16 {
 impl Float for f32 {
     #[inline(always)]
     fn abs(self) -> Self {
-        f32::abs(self)
+        math::fabsf(self)
+    }
+
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        math::sqrtf(self)
+    }
+
+    #[inline(always)]
+    fn powf(self, n: Self) -> Self {
+        math::powf(self, n)
     }
 
     #[inline(always)]
@@ -184,7 +246,17 @@ impl Float for f32 {
 impl Float for f64 {
     #[inline(always)]
     fn abs(self) -> Self {
-        f64::abs(self)
+        math::fabs(self)
+    }
+
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        math::sqrt(self)
+    }
+
+    #[inline(always)]
+    fn powf(self, n: Self) -> Self {
+        math::pow(self, n)
     }
 
     #[inline(always)]
@@ -19,7 +19,7 @@ use sux::{
     traits::IndexedDict,
 };
 
-use crate::{ASCIIChar, IntoUsize, Paddable};
+use crate::{ASCIIChar, IntoUsize, Paddable, U16Char};
 
 /// Type alias for a monogram.
 pub type UniGram<T> = [T; 1];
@@ -37,6 +37,43 @@ pub type HexaGram<T> = [T; 6];
 pub type HeptaGram<T> = [T; 7];
 /// Type alias for an octagram.
 pub type OctaGram<T> = [T; 8];
+/// Type alias for a nonagram.
+///
+/// # Implementation details
+/// The ngrams from the nonagram to the hexadecagram are only implemented for the
+/// `u8`-based and the `ASCIIChar`-based grams, whose up to 16 grams fit in the 128 bits
+/// of a u128. These ngrams do not fit in the `usize` values of the Elias-Fano storage,
+/// so they are stored in a sorted vector, where each ngram takes as many bytes as its
+/// arity, i.e. at most the 16 bytes of a u128, and they sort as their big-endian u128
+/// packing would.
+pub type NonaGram<T> = [T; 9];
+/// Type alias for a decagram.
+pub type DecaGram<T> = [T; 10];
+/// Type alias for a hendecagram.
+pub type HendecaGram<T> = [T; 11];
+/// Type alias for a dodecagram.
+pub type DodecaGram<T> = [T; 12];
+/// Type alias for a tridecagram.
+pub type TridecaGram<T> = [T; 13];
+/// Type alias for a tetradecagram.
+pub type TetradecaGram<T> = [T; 14];
+/// Type alias for a pentadecagram.
+pub type PentadecaGram<T> = [T; 15];
+/// Type alias for a hexadecagram.
+///
+/// # Examples
+/// The long ngrams are useful to match k-mers of DNA sequences.
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let sequences = vec!["ACGTACGTACGTACGTAA", "ACGTACGTACGTACGTAC", "TTTTGGGGCCCCAAAATT"];
+/// let corpus: Corpus<Vec<&str>, HexadecaGram<u8>> = Corpus::from(sequences);
+///
+/// assert_eq!(corpus.number_of_keys(), 3);
+/// assert!(corpus.ngrams().any(|ngram| &ngram == b"ACGTACGTACGTACGT"));
+/// ```
+pub type HexadecaGram<T> = [T; 16];
 
 /// Trait defining
 pub trait Gram: Copy + Clone + Default + Hash + Eq + PartialEq + Ord {}
@@ -47,6 +84,8 @@ impl Gram for char {}
 
 impl Gram for ASCIIChar {}
 
+impl Gram for U16Char {}
+
 /// Trait defining a builder of a sorted storage for Ngrams.
 pub trait SortedNgramStorageBuilder<NG: Ngram> {
     /// The type of the storage.
@@ -366,6 +405,43 @@ pub trait Ngram:
 
     /// Rotate the ngram to the left.
     fn rotate_left(&mut self);
+
+    #[inline(always)]
+    /// Returns whether the last `ARITY - 1` grams of `self` equal the first `ARITY - 1` grams of `other`.
+    ///
+    /// # Arguments
+    /// * `other` - The ngram that may follow `self`.
+    ///
+    /// # Implementation details
+    /// This is the adjacency condition for chaining ngrams back into a string: two
+    /// consecutive ngrams of a key always overlap on `ARITY - 1` grams. Note that, for
+    /// unigrams, the condition is trivially satisfied by any pair of ngrams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let chars: Vec<char> = "abcd".chars().collect();
+    /// let bigrams: Vec<BiGram<char>> = chars.windows(2).map(|w| [w[0], w[1]]).collect();
+    ///
+    /// // Consecutive ngrams are adjacent.
+    /// assert!(bigrams[0].shares_suffix_prefix(&bigrams[1]));
+    /// assert!(bigrams[1].shares_suffix_prefix(&bigrams[2]));
+    ///
+    /// // Non-adjacent or reversed ngrams are not.
+    /// assert!(!bigrams[0].shares_suffix_prefix(&bigrams[2]));
+    /// assert!(!bigrams[1].shares_suffix_prefix(&bigrams[0]));
+    ///
+    /// let trigrams: Vec<TriGram<char>> = chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect();
+    ///
+    /// assert!(trigrams[0].shares_suffix_prefix(&trigrams[1]));
+    /// assert!(!trigrams[1].shares_suffix_prefix(&trigrams[0]));
+    /// assert!(!trigrams[0].shares_suffix_prefix(&trigrams[0]));
+    /// ```
+    fn shares_suffix_prefix(&self, other: &Self) -> bool {
+        (1..Self::ARITY).all(|i| self[i] == other[i - 1])
+    }
 }
 
 impl Ngram for UniGram<u8> {
@@ -410,6 +486,20 @@ impl Ngram for UniGram<char> {
     }
 }
 
+impl Ngram for UniGram<U16Char> {
+    const ARITY: usize = 1;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 0];
+    const PADDING: Self::Pad = [Self::G::PADDING; 0];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        // Do nothing.
+    }
+}
+
 impl Ngram for BiGram<u8> {
     const ARITY: usize = 2;
     type G = u8;
@@ -452,6 +542,20 @@ impl Ngram for BiGram<char> {
     }
 }
 
+impl Ngram for BiGram<U16Char> {
+    const ARITY: usize = 2;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 1];
+    const PADDING: Self::Pad = [Self::G::PADDING; 1];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for TriGram<u8> {
     const ARITY: usize = 3;
     type G = u8;
@@ -494,6 +598,20 @@ impl Ngram for TriGram<char> {
     }
 }
 
+impl Ngram for TriGram<U16Char> {
+    const ARITY: usize = 3;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 2];
+    const PADDING: Self::Pad = [Self::G::PADDING; 2];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for TetraGram<u8> {
     const ARITY: usize = 4;
     type G = u8;
@@ -536,6 +654,20 @@ impl Ngram for TetraGram<char> {
     }
 }
 
+impl Ngram for TetraGram<U16Char> {
+    const ARITY: usize = 4;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 3];
+    const PADDING: Self::Pad = [Self::G::PADDING; 3];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for PentaGram<u8> {
     const ARITY: usize = 5;
     type G = u8;
@@ -578,6 +710,20 @@ impl Ngram for PentaGram<char> {
     }
 }
 
+impl Ngram for PentaGram<U16Char> {
+    const ARITY: usize = 5;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 4];
+    const PADDING: Self::Pad = [Self::G::PADDING; 4];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for HexaGram<u8> {
     const ARITY: usize = 6;
     type G = u8;
@@ -620,6 +766,20 @@ impl Ngram for HexaGram<char> {
     }
 }
 
+impl Ngram for HexaGram<U16Char> {
+    const ARITY: usize = 6;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 5];
+    const PADDING: Self::Pad = [Self::G::PADDING; 5];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for HeptaGram<u8> {
     const ARITY: usize = 7;
     type G = u8;
@@ -662,6 +822,20 @@ impl Ngram for HeptaGram<char> {
     }
 }
 
+impl Ngram for HeptaGram<U16Char> {
+    const ARITY: usize = 7;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 6];
+    const PADDING: Self::Pad = [Self::G::PADDING; 6];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
 impl Ngram for OctaGram<u8> {
     const ARITY: usize = 8;
     type G = u8;
@@ -703,3 +877,241 @@ impl Ngram for OctaGram<char> {
         <[char]>::rotate_left(self, 1);
     }
 }
+
+impl Ngram for OctaGram<U16Char> {
+    const ARITY: usize = 8;
+    type G = U16Char;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 7];
+    const PADDING: Self::Pad = [Self::G::PADDING; 7];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[U16Char]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for NonaGram<u8> {
+    const ARITY: usize = 9;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 8];
+    const PADDING: Self::Pad = [Self::G::PADDING; 8];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for NonaGram<ASCIIChar> {
+    const ARITY: usize = 9;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 8];
+    const PADDING: Self::Pad = [Self::G::PADDING; 8];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for DecaGram<u8> {
+    const ARITY: usize = 10;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 9];
+    const PADDING: Self::Pad = [Self::G::PADDING; 9];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for DecaGram<ASCIIChar> {
+    const ARITY: usize = 10;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 9];
+    const PADDING: Self::Pad = [Self::G::PADDING; 9];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for HendecaGram<u8> {
+    const ARITY: usize = 11;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 10];
+    const PADDING: Self::Pad = [Self::G::PADDING; 10];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for HendecaGram<ASCIIChar> {
+    const ARITY: usize = 11;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 10];
+    const PADDING: Self::Pad = [Self::G::PADDING; 10];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for DodecaGram<u8> {
+    const ARITY: usize = 12;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 11];
+    const PADDING: Self::Pad = [Self::G::PADDING; 11];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for DodecaGram<ASCIIChar> {
+    const ARITY: usize = 12;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 11];
+    const PADDING: Self::Pad = [Self::G::PADDING; 11];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for TridecaGram<u8> {
+    const ARITY: usize = 13;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 12];
+    const PADDING: Self::Pad = [Self::G::PADDING; 12];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for TridecaGram<ASCIIChar> {
+    const ARITY: usize = 13;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 12];
+    const PADDING: Self::Pad = [Self::G::PADDING; 12];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for TetradecaGram<u8> {
+    const ARITY: usize = 14;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 13];
+    const PADDING: Self::Pad = [Self::G::PADDING; 13];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for TetradecaGram<ASCIIChar> {
+    const ARITY: usize = 14;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 13];
+    const PADDING: Self::Pad = [Self::G::PADDING; 13];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for PentadecaGram<u8> {
+    const ARITY: usize = 15;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 14];
+    const PADDING: Self::Pad = [Self::G::PADDING; 14];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for PentadecaGram<ASCIIChar> {
+    const ARITY: usize = 15;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 14];
+    const PADDING: Self::Pad = [Self::G::PADDING; 14];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for HexadecaGram<u8> {
+    const ARITY: usize = 16;
+    type G = u8;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 15];
+    const PADDING: Self::Pad = [Self::G::PADDING; 15];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[u8]>::rotate_left(self, 1);
+    }
+}
+
+impl Ngram for HexadecaGram<ASCIIChar> {
+    const ARITY: usize = 16;
+    type G = ASCIIChar;
+    type SortedStorage = Vec<Self>;
+
+    type Pad = [Self::G; 15];
+    const PADDING: Self::Pad = [Self::G::PADDING; 15];
+
+    #[inline(always)]
+    fn rotate_left(&mut self) {
+        <[ASCIIChar]>::rotate_left(self, 1);
+    }
+}
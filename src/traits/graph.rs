@@ -1,6 +1,18 @@
 //! Submodule defining the weighted bipartite graph trait.
+use std::ops::Range;
 
 /// Trait defining a weighted bipartite graph.
+///
+/// # Implementation details
+/// The corpus uses the graph to link its keys, the sources, to their ngrams, the
+/// destinations. The source with id `i` is the key with id `i`, and the destination with
+/// id `j` is the `j`-th ngram of the sorted ngrams of the corpus. The weight of an edge is
+/// the number of times the ngram appears in the key MINUS ONE, as each edge appears at
+/// least once. The dsts of each src must be sorted, as well as the srcs of each dst, and
+/// the weights of each src are in the same order of its dsts.
+///
+/// The trait can be implemented outside of this crate to provide a custom backend,
+/// and the corpus built around it with `Corpus::new`.
 pub trait WeightedBipartiteGraph {
     /// Returns the number of source nodes.
     fn number_of_source_nodes(&self) -> usize;
@@ -41,6 +53,18 @@ pub trait WeightedBipartiteGraph {
     /// * `dst_id` - The destination node id.
     fn srcs_from_dst(&self, dst_id: usize) -> Self::Srcs<'_>;
 
+    /// Type of the iterator over the srcs of all the dsts.
+    type SrcsFromDsts<'a>: Iterator<Item = Self::Srcs<'a>>
+    where
+        Self: 'a;
+
+    /// Returns the srcs associated to each dst, in the order of the dst ids.
+    ///
+    /// # Implementation details
+    /// The result is the same one of calling `srcs_from_dst` for each dst id,
+    /// but the implementations may walk their structures sequentially.
+    fn srcs_from_dsts(&self) -> Self::SrcsFromDsts<'_>;
+
     /// Type of the dst iterator.
     type Dsts<'a>: ExactSizeIterator<Item = usize> + Clone
     where
@@ -52,6 +76,22 @@ pub trait WeightedBipartiteGraph {
     /// * `src_id` - The source node id.
     fn dsts_from_src(&self, src_id: usize) -> Self::Dsts<'_>;
 
+    /// Returns the position of a given dst among the dsts of a given src, if any.
+    ///
+    /// # Arguments
+    /// * `src_id` - The source node id.
+    /// * `dst_id` - The destination node id.
+    ///
+    /// # Implementation details
+    /// The dsts of each src are sorted, so the default implementation stops scanning
+    /// them at the first dst greater than the provided one. The implementations with
+    /// random access to the dsts may binary search them instead.
+    fn dst_position_from_src(&self, src_id: usize, dst_id: usize) -> Option<usize> {
+        self.dsts_from_src(src_id)
+            .take_while(|&candidate| candidate <= dst_id)
+            .position(|candidate| candidate == dst_id)
+    }
+
     /// Type of the weights iterator.
     type WeightsSrc<'a>: Iterator<Item = usize> + Clone
     where
@@ -68,7 +108,11 @@ pub trait WeightedBipartiteGraph {
     where
         Self: 'a;
 
-    /// Returns weights associated to the links between a given dst and its srcs.
+    /// Returns the weights of all of the edges, in the order of the srcs.
+    ///
+    /// # Implementation details
+    /// The result is the same one of chaining the results of `weights_from_src`
+    /// for each src id.
     fn weights(&self) -> Self::Weights<'_>;
 
     /// Type of the degrees iterator.
@@ -82,3 +126,43 @@ pub trait WeightedBipartiteGraph {
     /// are the degrees of the destination nodes.
     fn degrees(&self) -> Self::Degrees<'_>;
 }
+
+/// Iterator over the srcs of a range of dsts, calling `srcs_from_dst` for each dst.
+pub struct SrcsFromDstIds<'a, G> {
+    /// The graph.
+    graph: &'a G,
+    /// The remaining dst ids.
+    dst_ids: Range<usize>,
+}
+
+impl<'a, G: WeightedBipartiteGraph> SrcsFromDstIds<'a, G> {
+    #[inline(always)]
+    /// Returns the iterator over the srcs of all the dsts of the provided graph.
+    ///
+    /// # Arguments
+    /// * `graph` - The graph whose dsts are iterated.
+    pub fn new(graph: &'a G) -> Self {
+        SrcsFromDstIds {
+            graph,
+            dst_ids: 0..graph.number_of_destination_nodes(),
+        }
+    }
+}
+
+impl<'a, G: WeightedBipartiteGraph> Iterator for SrcsFromDstIds<'a, G> {
+    type Item = G::Srcs<'a>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dst_ids
+            .next()
+            .map(|dst_id| self.graph.srcs_from_dst(dst_id))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.dst_ids.size_hint()
+    }
+}
+
+impl<'a, G: WeightedBipartiteGraph> ExactSizeIterator for SrcsFromDstIds<'a, G> {}
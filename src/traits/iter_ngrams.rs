@@ -1,4 +1,14 @@
 //! Submodule providing an iterator to convert an iterator of grams to an iterator of n-grams.
+//!
+//! # Implementation details
+//! The n-grams are either overlapping, as built by `ngrams`, where each gram starts a new
+//! n-gram, or tiled, as built by `tiled_ngrams`, where each gram belongs to exactly one
+//! n-gram. The keys wrapped in `Tiled` are split into tiled n-grams, so that the corpus
+//! of such keys and its queries use the tiled n-grams.
+
+use std::iter::Fuse;
+use std::marker::PhantomData;
+use std::mem::transmute;
 
 use crate::{Gram, Ngram};
 
@@ -59,6 +69,63 @@ where
     }
 }
 
+/// Struct implementing an iterator to convert an iterator
+/// of grams to an iterator of non-overlapping n-grams.
+pub struct IterTiledNgrams<I, NG>
+where
+    I: Iterator<Item = <NG as Ngram>::G>,
+    NG: Ngram,
+{
+    /// Iterator of grams.
+    iter: Fuse<I>,
+    /// The type of the n-grams.
+    _phantom: PhantomData<NG>,
+}
+
+impl<I, NG> From<I> for IterTiledNgrams<I, NG>
+where
+    I: Iterator<Item = <NG as Ngram>::G>,
+    NG: Ngram,
+{
+    #[inline(always)]
+    fn from(iter: I) -> Self {
+        IterTiledNgrams {
+            iter: iter.fuse(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, NG> Iterator for IterTiledNgrams<I, NG>
+where
+    I: Iterator<Item = <NG as Ngram>::G>,
+    NG: Ngram,
+{
+    type Item = NG;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut ngram: NG = Default::default();
+        ngram[0] = self.iter.next()?;
+        // A trailing partial window misses at most ARITY - 1 grams,
+        // which are exactly as many as the padding grams.
+        let mut padding = NG::PADDING.into_iter();
+        for i in 1..NG::ARITY {
+            ngram[i] = self.iter.next().or_else(|| padding.next()).unwrap();
+        }
+        Some(ngram)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (
+            lower.div_ceil(NG::ARITY),
+            upper.map(|upper| upper.div_ceil(NG::ARITY)),
+        )
+    }
+}
+
 /// Trait defining an iterator to convert an
 /// iterator of grams to an iterator of n-grams.
 pub trait IntoNgrams: Iterator
@@ -168,6 +235,47 @@ where
     {
         IterNgrams::from(self)
     }
+
+    #[inline(always)]
+    /// Converts an iterator of grams to an iterator of non-overlapping n-grams.
+    ///
+    /// # Implementation details
+    /// The grams are split into consecutive windows of `ARITY` grams, so that each gram
+    /// appears in exactly one n-gram. When the number of grams is not a multiple of the
+    /// arity, the trailing partial window is completed with padding grams rather than
+    /// dropped, so that no gram is lost.
+    ///
+    /// # Examples
+    ///
+    /// An example for when using an iterator of `char` trigrams:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let ngrams: Vec<_> = "abcdef".chars().tiled_ngrams::<TriGram<char>>().collect();
+    /// assert_eq!(ngrams, vec![['a', 'b', 'c'], ['d', 'e', 'f']]);
+    ///
+    /// let ngrams: Vec<_> = "abcdefg".chars().tiled_ngrams::<TriGram<char>>().collect();
+    /// assert_eq!(
+    ///     ngrams,
+    ///     vec![['a', 'b', 'c'], ['d', 'e', 'f'], ['g', '\0', '\0']]
+    /// );
+    /// ```
+    ///
+    /// An example for when using an iterator of `u8` bigrams:
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let iter = vec![b'a', b'b', b'c'].into_iter();
+    /// let ngrams: Vec<_> = iter.tiled_ngrams::<BiGram<u8>>().collect();
+    /// assert_eq!(ngrams, vec![[b'a', b'b'], [b'c', 0]]);
+    /// ```
+    fn tiled_ngrams<NG>(self) -> IterTiledNgrams<Self, NG>
+    where
+        NG: Ngram<G = <Self as Iterator>::Item>,
+        Self: Sized,
+    {
+        IterTiledNgrams::from(self)
+    }
 }
 
 impl<I> IntoNgrams for I
@@ -176,3 +284,85 @@ where
     <I as std::iter::Iterator>::Item: Gram,
 {
 }
+
+/// Struct defining a key split into non-overlapping n-grams.
+///
+/// # Implementation details
+/// The grams of the wrapped key are normalized and padded by the wrapped key type, as
+/// usual. The padding is then removed, and the remaining grams are split with
+/// `tiled_ngrams`, so that for instance the trigrams of "abcdef" are "abc" and "def".
+/// Since the tiles do not overlap, a key has about `ARITY` times fewer n-grams, and
+/// the shifted variants of a key, such as "xabcdef", share none of its n-grams.
+/// The other key wrappers, as well as the padding modes other than `PaddingMode::Auto`,
+/// build the overlapping n-grams of the grams of the key, so that `Tiled` must be the
+/// outermost wrapper, as in `Tiled<Lowercase<str>>`, and the corpus must be padded
+/// automatically.
+///
+/// # Examples
+///
+/// ```rust
+/// use ngrammatic::prelude::*;
+///
+/// let corpus: Corpus<[&str; 3], TriGram<char>, Tiled<str>> =
+///     Corpus::from(["abcdef", "abcxyz", "xabcdef"]);
+///
+/// assert_eq!(corpus.number_of_ngrams_from_key_id(0), 2);
+/// // The shifted key is split into "xab", "cde" and a padded "f".
+/// assert_eq!(corpus.number_of_ngrams(), 6);
+///
+/// let config = NgramSearchConfig::default()
+///     .set_minimum_similarity_score(0.0)
+///     .unwrap();
+/// let results: SearchResults<&&str, f32> = corpus.ngram_search("abcdef", config);
+/// assert_eq!(results.into_keys(), vec![&"abcdef", &"abcxyz"]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct Tiled<K: ?Sized = str>(K);
+
+impl<E: ?Sized, I: ?Sized> AsRef<I> for Tiled<E>
+where
+    E: AsRef<I>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &I {
+        self.0.as_ref()
+    }
+}
+
+impl<E: ?Sized> AsRef<Tiled<E>> for String
+where
+    String: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Tiled<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<E: ?Sized> AsRef<Tiled<E>> for str
+where
+    str: AsRef<E>,
+{
+    #[inline(always)]
+    fn as_ref(&self) -> &Tiled<E> {
+        let reference: &E = self.as_ref();
+        unsafe { transmute(reference) }
+    }
+}
+
+impl<K: ?Sized> Tiled<K> {
+    #[inline(always)]
+    /// Returns a reference to the inner key.
+    pub fn inner(&self) -> &K {
+        &self.0
+    }
+}
+
+impl<K> From<K> for Tiled<K> {
+    #[inline(always)]
+    fn from(key: K) -> Self {
+        Tiled(key)
+    }
+}
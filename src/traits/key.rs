@@ -1,13 +1,23 @@
 //! Trait defining a key and its hasher.
 
+use crate::padding_mode::PaddingMode;
 use crate::traits::ascii_char::ToASCIICharIterator;
 use crate::traits::iter_ngrams::IntoNgrams;
+use crate::traits::u16_char::ToU16CharIterator;
 use crate::{
-    ASCIIChar, ASCIICharIterator, Alphanumeric, BothPadding, CharLike, CharNormalizer, Gram,
-    IntoPadder, Lowercase, Ngram, SpaceNormalizer, Trim, TrimNull,
+    ASCIIChar, ASCIICharIterator, Alphanumeric, AlphanumericPlus, BothPadding, CharLike,
+    CharNormalizer, ExtraChars, Gram, IntoPadder, Lowercase, Ngram, SpaceNormalizer, Tiled, Trim,
+    TrimNull, U16Char, U16CharIterator, Unaccent,
 };
+#[cfg(feature = "normalization")]
+use crate::{Nfc, Nfd};
+#[cfg(feature = "stemming")]
+use crate::{Stem, Stemmed};
 use fxhash::FxBuildHasher;
 use std::collections::HashMap;
+use std::hash::BuildHasher;
+#[cfg(feature = "normalization")]
+use unicode_normalization::{Decompositions, Recompositions};
 
 /// Trait defining a key.
 pub trait Key<NG: Ngram<G = G>, G: Gram>: AsRef<<Self as Key<NG, G>>::Ref> {
@@ -150,8 +160,35 @@ pub trait Key<NG: Ngram<G = G>, G: Gram>: AsRef<<Self as Key<NG, G>>::Ref> {
     /// assert_eq!(counts.len(), 4);
     /// ```
     fn counts(&self) -> HashMap<NG, usize, FxBuildHasher> {
-        let mut ngram_counts: HashMap<NG, usize, FxBuildHasher> =
-            HashMap::with_hasher(FxBuildHasher::default());
+        self.counts_with_hasher()
+    }
+
+    /// Returns the counts of the ngrams, in a map with the provided hasher.
+    ///
+    /// # Implementation details
+    /// The `counts` method uses the `FxBuildHasher`, which is fast but not resistant to
+    /// adversarial inputs crafted to collide. This method lets the hasher be chosen, such
+    /// as the `RandomState` of the standard library, or a hasher whose output does not
+    /// depend on the platform, and to compare the speed of the hashers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::collections::HashMap;
+    ///
+    /// let key = "Cat";
+    /// let counts: HashMap<BiGram<char>, usize, RandomState> =
+    ///     <&str as Key<BiGram<char>, char>>::counts_with_hasher(&key);
+    /// assert_eq!(counts.get(&['C', 'a']), Some(&1));
+    /// assert_eq!(counts.len(), 4);
+    ///
+    /// let fx_counts = <&str as Key<BiGram<char>, char>>::counts(&key);
+    /// assert!(fx_counts.iter().all(|(ngram, count)| counts.get(ngram) == Some(count)));
+    /// ```
+    fn counts_with_hasher<S: BuildHasher + Default>(&self) -> HashMap<NG, usize, S> {
+        let mut ngram_counts: HashMap<NG, usize, S> = HashMap::with_hasher(S::default());
 
         // We populate it with the ngrams of the key.
         for ngram in self.grams().ngrams::<NG>() {
@@ -163,6 +200,75 @@ pub trait Key<NG: Ngram<G = G>, G: Gram>: AsRef<<Self as Key<NG, G>>::Ref> {
 
         ngram_counts
     }
+
+    /// Returns the counts of the ngrams built from at most the first
+    /// `maximum_number_of_grams` grams of the key.
+    ///
+    /// # Arguments
+    /// * `maximum_number_of_grams` - The maximum number of grams to consider.
+    ///
+    /// # Implementation details
+    /// The truncation is lossy: any ngram appearing exclusively after the
+    /// first `maximum_number_of_grams` grams, including the right padding,
+    /// is not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let key = "abc";
+    /// let counts = <&str as Key<BiGram<char>, char>>::truncated_counts(&key, 3);
+    /// assert_eq!(counts.get(&['\0', 'a']), Some(&1));
+    /// assert_eq!(counts.get(&['a', 'b']), Some(&1));
+    /// assert_eq!(counts.get(&['b', 'c']), None);
+    /// assert_eq!(counts.len(), 2);
+    /// ```
+    fn truncated_counts(
+        &self,
+        maximum_number_of_grams: usize,
+    ) -> HashMap<NG, usize, FxBuildHasher> {
+        self.truncated_counts_with_hasher(maximum_number_of_grams)
+    }
+
+    /// Returns the counts of the ngrams built from at most the first
+    /// `maximum_number_of_grams` grams of the key, in a map with the provided hasher.
+    ///
+    /// # Arguments
+    /// * `maximum_number_of_grams` - The maximum number of grams to consider.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::collections::HashMap;
+    ///
+    /// let key = "abc";
+    /// let counts: HashMap<BiGram<char>, usize, RandomState> =
+    ///     <&str as Key<BiGram<char>, char>>::truncated_counts_with_hasher(&key, 3);
+    /// assert_eq!(counts.get(&['a', 'b']), Some(&1));
+    /// assert_eq!(counts.len(), 2);
+    /// ```
+    fn truncated_counts_with_hasher<S: BuildHasher + Default>(
+        &self,
+        maximum_number_of_grams: usize,
+    ) -> HashMap<NG, usize, S> {
+        debug_assert!(
+            maximum_number_of_grams >= NG::ARITY,
+            "The maximum number of grams must be at least the arity of the ngram."
+        );
+        let mut ngram_counts: HashMap<NG, usize, S> = HashMap::with_hasher(S::default());
+
+        for ngram in self.grams().take(maximum_number_of_grams).ngrams::<NG>() {
+            ngram_counts
+                .entry(ngram)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+
+        ngram_counts
+    }
 }
 
 impl<NG> Key<NG, char> for String
@@ -267,6 +373,73 @@ where
     }
 }
 
+impl<NG> Key<NG, U16Char> for str
+where
+    NG: Ngram<G = U16Char>,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<TrimNull<Trim<U16CharIterator<std::str::Chars<'a>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.chars()
+            .bmp()
+            .trim()
+            .trim_null()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+impl<NG> Key<NG, U16Char> for String
+where
+    NG: Ngram<G = U16Char>,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<TrimNull<Trim<U16CharIterator<std::str::Chars<'a>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.chars()
+            .bmp()
+            .trim()
+            .trim_null()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+/// The raw bytes of a binary key are used as grams without any normalization, so
+/// that any sequence of bytes, including invalid UTF-8, can be a key. The padding
+/// is the null byte, as for the bytes of the strings.
+impl<NG> Key<NG, u8> for [u8]
+where
+    NG: Ngram<G = u8>,
+{
+    type Grams<'a> = BothPadding<NG, std::iter::Copied<std::slice::Iter<'a, u8>>> where Self: 'a;
+    type Ref = [u8];
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.iter().copied().both_padding::<NG>()
+    }
+}
+
+impl<NG> Key<NG, u8> for Vec<u8>
+where
+    NG: Ngram<G = u8>,
+{
+    type Grams<'a> = BothPadding<NG, std::iter::Copied<std::slice::Iter<'a, u8>>>;
+    type Ref = [u8];
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.iter().copied().both_padding::<NG>()
+    }
+}
+
 impl<R, NG> Key<NG, NG::G> for &R
 where
     R: Key<NG, NG::G> + ?Sized,
@@ -298,6 +471,56 @@ where
     }
 }
 
+impl<W, NG> Key<NG, NG::G> for Tiled<W>
+where
+    NG: Ngram,
+    W: Key<NG, NG::G> + ?Sized,
+    Self: AsRef<<W as Key<NG, <NG as Ngram>::G>>::Ref>,
+{
+    type Grams<'a> = W::Grams<'a> where Self: 'a;
+    type Ref = W::Ref;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.inner().grams()
+    }
+
+    fn counts_with_hasher<S: BuildHasher + Default>(&self) -> HashMap<NG, usize, S> {
+        let mut ngram_counts: HashMap<NG, usize, S> = HashMap::with_hasher(S::default());
+
+        let grams = PaddingMode::None.grams::<NG, W>(self.inner());
+        for ngram in grams.into_iter().tiled_ngrams::<NG>() {
+            ngram_counts
+                .entry(ngram)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+
+        ngram_counts
+    }
+
+    fn truncated_counts_with_hasher<S: BuildHasher + Default>(
+        &self,
+        maximum_number_of_grams: usize,
+    ) -> HashMap<NG, usize, S> {
+        let mut ngram_counts: HashMap<NG, usize, S> = HashMap::with_hasher(S::default());
+
+        let grams = PaddingMode::None.grams::<NG, W>(self.inner());
+        for ngram in grams
+            .into_iter()
+            .take(maximum_number_of_grams)
+            .tiled_ngrams::<NG>()
+        {
+            ngram_counts
+                .entry(ngram)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+
+        ngram_counts
+    }
+}
+
 impl<W, NG> Key<NG, NG::G> for Alphanumeric<W>
 where
     NG: Ngram,
@@ -313,3 +536,527 @@ where
         self.inner().grams().alphanumeric()
     }
 }
+
+impl<X, W, NG> Key<NG, char> for AlphanumericPlus<X, W>
+where
+    NG: Ngram<G = char>,
+    X: ExtraChars,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<AlphanumericPlus<X, TrimNull<Trim<std::str::Chars<'a>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        // The extra characters must be kept in place of the alphanumeric filter of the
+        // string keys, which would otherwise have already replaced them with spaces.
+        self.inner()
+            .as_ref()
+            .chars()
+            .trim()
+            .trim_null()
+            .alphanumeric_plus::<X>()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+impl<W, NG> Key<NG, char> for Unaccent<W>
+where
+    NG: Ngram<G = char>,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<TrimNull<Trim<Unaccent<std::str::Chars<'a>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.inner()
+            .as_ref()
+            .chars()
+            .unaccent()
+            .trim()
+            .trim_null()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+impl<W, NG> Key<NG, ASCIIChar> for Unaccent<W>
+where
+    NG: Ngram<G = ASCIIChar>,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<TrimNull<Trim<ASCIICharIterator<Unaccent<std::str::Chars<'a>>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        // The accents are folded before the conversion to ASCII, which would
+        // otherwise discard the accented letters.
+        self.inner()
+            .as_ref()
+            .chars()
+            .unaccent()
+            .ascii()
+            .trim()
+            .trim_null()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<W, NG> Key<NG, char> for Nfc<W>
+where
+    NG: Ngram<G = char>,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<Recompositions<TrimNull<Trim<std::str::Chars<'a>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.inner()
+            .as_ref()
+            .chars()
+            .trim()
+            .trim_null()
+            .nfc()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+#[cfg(feature = "normalization")]
+impl<W, NG> Key<NG, char> for Nfd<W>
+where
+    NG: Ngram<G = char>,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, SpaceNormalizer<Alphanumeric<Decompositions<TrimNull<Trim<std::str::Chars<'a>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.inner()
+            .as_ref()
+            .chars()
+            .trim()
+            .trim_null()
+            .nfd()
+            .alphanumeric()
+            .dedup_spaces()
+            .both_padding::<NG>()
+    }
+}
+
+#[cfg(feature = "stemming")]
+impl<W, NG> Key<NG, char> for Stemmed<W>
+where
+    NG: Ngram<G = char>,
+    W: AsRef<str> + ?Sized,
+{
+    type Grams<'a> = BothPadding<NG, Stem<Lowercase<SpaceNormalizer<Alphanumeric<TrimNull<Trim<std::str::Chars<'a>>>>>>>> where Self: 'a;
+    type Ref = str;
+
+    #[inline(always)]
+    fn grams(&self) -> Self::Grams<'_> {
+        self.inner()
+            .as_ref()
+            .chars()
+            .trim()
+            .trim_null()
+            .alphanumeric()
+            .dedup_spaces()
+            .lower()
+            .stem()
+            .both_padding::<NG>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    /// Returns the grams of the provided key, as tokenized for trigrams of chars.
+    fn char_grams<K: Key<TriGram<char>, char> + ?Sized>(key: &K) -> Vec<char> {
+        key.grams().collect()
+    }
+
+    /// Returns the total number of trigrams of chars of the provided key.
+    fn number_of_trigrams<K: Key<TriGram<char>, char> + ?Sized>(key: &K) -> usize {
+        key.counts().values().sum()
+    }
+
+    #[test]
+    fn test_char_grams_of_bmp_and_astral_characters() {
+        // CJK ideographs from the BMP and from the supplementary plane, and
+        // mathematical alphanumeric symbols, which are all outside of the ASCII range
+        // and, except for the BMP ideographs, encoded with four bytes in UTF-8.
+        for (key, expected) in [
+            ("漢字", vec!['漢', '字']),
+            ("𠀀𠀁", vec!['𠀀', '𠀁']),
+            ("𝐀𝑥𝟎", vec!['𝐀', '𝑥', '𝟎']),
+            ("a𠀀b漢", vec!['a', '𠀀', 'b', '漢']),
+            ("  𝐀 \t 漢  ", vec!['𝐀', ' ', '漢']),
+            // Emoji are not alphanumeric, and are replaced by a single space.
+            ("cat😀😀dog", vec!['c', 'a', 't', ' ', 'd', 'o', 'g']),
+        ] {
+            let mut padded = vec!['\0', '\0'];
+            padded.extend(expected.iter().copied());
+            padded.extend(['\0', '\0']);
+
+            assert_eq!(char_grams(key), padded, "Wrong grams for {:?}", key);
+            assert_eq!(char_grams(&key.to_string()), padded);
+            // Each gram, including the padding, starts an ngram except the last two.
+            assert_eq!(number_of_trigrams(key), expected.len() + 2);
+        }
+    }
+
+    #[test]
+    fn test_alphanumeric_keeps_astral_alphanumerics() {
+        for c in ['漢', '𠀀', '𝐀', '𝑥', '𝟎', 'ｱ', '①'] {
+            assert!(CharLike::is_alphanumeric(c), "{:?} is alphanumeric", c);
+        }
+        for c in ['😀', '🐈', '→', '∑'] {
+            assert!(!CharLike::is_alphanumeric(c), "{:?} is not alphanumeric", c);
+        }
+
+        let key: &Alphanumeric<str> = "𝐀-𠀀".as_ref();
+        let grams: Vec<char> = char_grams(key);
+        assert!(grams.contains(&'𝐀'));
+        assert!(grams.contains(&'𠀀'));
+        assert!(!grams.contains(&'-'));
+    }
+
+    #[test]
+    fn test_corpus_search_mixing_bmp_and_astral_characters() {
+        let keys = ["東京都", "京都府", "北京", "𝐀𝐁𝐂", "𝐀𝐁𝐃", "𠀀𠀁𠀂", "cat😀"];
+        let corpus: Corpus<[&str; 7], TriGram<char>> = Corpus::from(keys);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for key in keys {
+            let results: SearchResults<&&str, f32> = corpus.ngram_search(key, config);
+            assert_eq!(results[0].key(), &key);
+            assert_eq!(results[0].score(), 1.0);
+        }
+
+        // The ngrams of the mathematical symbols are shared between the similar keys.
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("𝐀𝐁", config);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.key().starts_with("𝐀𝐁")));
+    }
+
+    #[test]
+    fn test_u16_char_keys_skip_astral_characters() {
+        assert_eq!(
+            std::mem::size_of::<TriGram<U16Char>>() * 2,
+            std::mem::size_of::<TriGram<char>>()
+        );
+
+        let keys = ["東京都", "京都府", "北京", "cat😀"];
+        let corpus: Corpus<[&str; 4], TriGram<U16Char>> = Corpus::from(keys);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for key in keys {
+            let results: SearchResults<&&str, f32> = corpus.ngram_search(key, config);
+            assert_eq!(results[0].key(), &key);
+            assert_eq!(results[0].score(), 1.0);
+        }
+
+        // The emoji is outside of the BMP, so it is skipped.
+        let cat: Vec<U16Char> = <str as Key<TriGram<U16Char>, U16Char>>::grams("cat").collect();
+        let emoji: Vec<U16Char> = <str as Key<TriGram<U16Char>, U16Char>>::grams("cat😀").collect();
+        assert_eq!(cat, emoji);
+    }
+
+    #[test]
+    fn test_unaccent_matches_unaccented_queries() {
+        let keys = ["José", "Łódź", "Maria", "Jos"];
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        let corpus: Corpus<[&str; 4], TriGram<char>, Unaccent<str>> = Corpus::from(keys);
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("Jose", config);
+        assert_eq!(results[0].key(), &"José");
+        assert_eq!(results[0].score(), 1.0);
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("Lodz", config);
+        assert_eq!(results[0].key(), &"Łódź");
+        assert_eq!(results[0].score(), 1.0);
+
+        // The accented letters are folded rather than discarded by the ASCII conversion.
+        let corpus: Corpus<[&str; 4], TriGram<ASCIIChar>, Lowercase<Unaccent<str>>> =
+            Corpus::from(keys);
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("jose", config);
+        assert_eq!(results[0].key(), &"José");
+        assert_eq!(results[0].score(), 1.0);
+        assert!(results.scores().skip(1).all(|score| score < 1.0));
+
+        // The characters of the other scripts are left untouched.
+        let key: &Unaccent<str> = "Ωμέγα".as_ref();
+        assert_eq!(char_grams(key), char_grams("Ωμέγα"));
+    }
+
+    #[test]
+    fn test_alphanumeric_plus_keeps_the_extra_characters() {
+        let key: &AlphanumericPlus<Apostrophes> = "O'Brien".as_ref();
+        assert!(char_grams(key).contains(&'\''));
+        assert!(!char_grams("O'Brien").contains(&'\''));
+
+        // The apostrophe takes the place of the space of the default normalization, so
+        // the key without it shares as many trigrams with either normalization.
+        let without: HashMap<TriGram<char>, usize, FxBuildHasher> = "OBrien".counts();
+        let retained: HashMap<TriGram<char>, usize, FxBuildHasher> = key.counts();
+        let replaced: HashMap<TriGram<char>, usize, FxBuildHasher> = "O'Brien".counts();
+        assert_eq!(
+            retained
+                .keys()
+                .filter(|ngram| without.contains_key(ngram))
+                .count(),
+            replaced
+                .keys()
+                .filter(|ngram| without.contains_key(ngram))
+                .count()
+        );
+
+        let keys = ["O'Brien", "O Brien", "OBrien", "Jean-Luc", "Jean Luc"];
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        // By default, the apostrophe and the hyphen are replaced with spaces.
+        let corpus: Corpus<[&str; 5], TriGram<char>> = Corpus::from(keys);
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("O'Brien", config);
+        assert_eq!(results[0].score(), 1.0);
+        assert_eq!(results[1].score(), 1.0);
+
+        // When they are retained, the keys with and without them are told apart.
+        let corpus: Corpus<[&str; 5], TriGram<char>, Lowercase<AlphanumericPlus<NameChars>>> =
+            Corpus::from(keys);
+        for (query, expected) in [("o'brien", "O'Brien"), ("jean-luc", "Jean-Luc")] {
+            let results: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+            assert_eq!(results[0].key(), &expected);
+            assert_eq!(results[0].score(), 1.0);
+            assert!(results.scores().skip(1).all(|score| score < 1.0));
+        }
+    }
+
+    #[cfg(feature = "normalization")]
+    #[test]
+    fn test_nfc_composed_and_decomposed_keys_share_ngrams() {
+        let precomposed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_ne!(precomposed, decomposed);
+
+        let composed_key: &Nfc<str> = precomposed.as_ref();
+        let decomposed_key: &Nfc<str> = decomposed.as_ref();
+        assert_eq!(char_grams(composed_key), char_grams(decomposed_key));
+
+        let corpus: Corpus<[&str; 3], TriGram<char>, Lowercase<Nfc<str>>> =
+            Corpus::from([precomposed, "cafeteria", "CAFE\u{301}"]);
+        let precomposed_corpus: Corpus<[&str; 2], TriGram<char>, Lowercase<Nfc<str>>> =
+            Corpus::from([precomposed, "cafeteria"]);
+        // Both of the representations land in the same ngrams of the corpus.
+        assert_eq!(
+            corpus.number_of_ngrams(),
+            precomposed_corpus.number_of_ngrams()
+        );
+
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+        for query in [precomposed, decomposed] {
+            let results: SearchResults<&&str, f32> = corpus.ngram_search(query, config);
+            let perfect_matches: Vec<&str> = results
+                .iter()
+                .filter(|result| result.score() == 1.0)
+                .map(|result| *result.key())
+                .collect();
+            assert_eq!(perfect_matches.len(), 2, "Query {:?}", query);
+            assert!(perfect_matches.contains(&precomposed));
+            assert!(perfect_matches.contains(&"CAFE\u{301}"));
+        }
+    }
+
+    #[cfg(feature = "stemming")]
+    #[test]
+    fn test_stemmed_keys_share_the_ngrams_of_their_stems() {
+        for (word, stem) in [
+            ("running", "run"),
+            ("connected", "connect"),
+            ("connection", "connect"),
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+        ] {
+            let stemmed: String = word.chars().stem().collect();
+            assert_eq!(stemmed, stem, "Wrong stem for {:?}", word);
+        }
+
+        // The spaces are preserved and the words are stemmed independently.
+        let stemmed: String = "  running \tcats "
+            .chars()
+            .trim()
+            .dedup_spaces()
+            .stem()
+            .collect();
+        assert_eq!(stemmed, "run cat");
+
+        let running: &Stemmed<str> = "Running Shoes".as_ref();
+        let run: &Stemmed<str> = "run shoe".as_ref();
+        assert_eq!(char_grams(running), char_grams(run));
+
+        let corpus: Corpus<[&str; 3], TriGram<char>, Stemmed<str>> =
+            Corpus::from(["Running Shoes", "Red Dress", "Connected Speakers"]);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("run shoe", config);
+        assert_eq!(results[0].key(), &"Running Shoes");
+        assert_eq!(results[0].score(), 1.0);
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("connection speaker", config);
+        assert_eq!(results[0].key(), &"Connected Speakers");
+        assert_eq!(results[0].score(), 1.0);
+    }
+
+    /// A key folding the 'ß' to an 's' and dropping the apostrophes.
+    struct Folded(String);
+
+    impl AsRef<str> for Folded {
+        fn as_ref(&self) -> &str {
+            &self.0
+        }
+    }
+
+    /// Returns the folded character, or `None` for the apostrophes.
+    fn fold(character: char) -> Option<char> {
+        match character {
+            'ß' => Some('s'),
+            '\'' => None,
+            _ => Some(character),
+        }
+    }
+
+    /// The characters of a `Folded` key, mapped with a function pointer that can be named.
+    type FoldedChars<'a> = MapChars<std::str::Chars<'a>, fn(char) -> Option<char>>;
+
+    impl Key<TriGram<char>, char> for Folded {
+        type Grams<'a> = BothPadding<
+            TriGram<char>,
+            SpaceNormalizer<Alphanumeric<TrimNull<Trim<FoldedChars<'a>>>>>,
+        >;
+        type Ref = str;
+
+        fn grams(&self) -> Self::Grams<'_> {
+            self.0
+                .chars()
+                .map_chars(fold as fn(char) -> Option<char>)
+                .trim()
+                .trim_null()
+                .alphanumeric()
+                .dedup_spaces()
+                .both_padding::<TriGram<char>>()
+        }
+    }
+
+    #[test]
+    fn test_map_chars_in_a_custom_key() {
+        let key = Lowercase::from(Folded("O'Straße".to_string()));
+        assert_eq!(
+            char_grams(&key),
+            vec!['\0', '\0', 'o', 's', 't', 'r', 'a', 's', 'e', '\0', '\0']
+        );
+        assert_eq!(
+            key.counts(),
+            <&str as Key<TriGram<char>, char>>::counts(&"ostrase")
+        );
+    }
+
+    #[test]
+    fn test_tiled_keys_split_the_grams_into_non_overlapping_ngrams() {
+        let key: &Tiled<str> = "abcdefg".as_ref();
+
+        let counts = <Tiled<str> as Key<TriGram<char>, char>>::counts(key);
+        assert_eq!(counts.len(), 3);
+        for ngram in [['a', 'b', 'c'], ['d', 'e', 'f'], ['g', '\0', '\0']] {
+            assert_eq!(counts.get(&ngram), Some(&1));
+        }
+        let counts = <Tiled<str> as Key<TriGram<char>, char>>::truncated_counts(key, 4);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts.get(&['d', '\0', '\0']), Some(&1));
+
+        let counts = <Tiled<str> as Key<BiGram<u8>, u8>>::counts(key);
+        assert_eq!(counts.len(), 4);
+        for ngram in [*b"ab", *b"cd", *b"ef", [b'g', 0]] {
+            assert_eq!(counts.get(&ngram), Some(&1));
+        }
+        // The repeated tiles are counted as many times as they appear.
+        let key: &Tiled<str> = "ababab".as_ref();
+        let counts = <Tiled<str> as Key<BiGram<u8>, u8>>::counts(key);
+        assert_eq!(counts.get(b"ab"), Some(&3));
+
+        // The tiling is applied to the normalized grams of the wrapped key.
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+        let corpus: Corpus<[&str; 3], TriGram<char>, Tiled<Lowercase<str>>> =
+            Corpus::from(["ABCdef", "abcxyz", "xabcdef"]);
+        assert!(corpus.ngrams().eq([
+            ['a', 'b', 'c'],
+            ['c', 'd', 'e'],
+            ['d', 'e', 'f'],
+            ['f', '\0', '\0'],
+            ['x', 'a', 'b'],
+            ['x', 'y', 'z']
+        ]));
+        let results: SearchResults<&&str, f32> = corpus.ngram_search("abcDEF", config);
+        assert_eq!(results[0].key(), &"ABCdef");
+        assert_eq!(results[0].score(), 1.0);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_corpus_of_binary_keys() {
+        // The bytes are not normalized, and need not be valid UTF-8.
+        let grams: Vec<u8> = <[u8] as Key<TriGram<u8>, u8>>::grams(b"\xffA\x00").collect();
+        assert_eq!(grams, vec![0, 0, 0xff, b'A', 0, 0, 0]);
+        let owned: Vec<u8> =
+            <Vec<u8> as Key<TriGram<u8>, u8>>::grams(&b"\xffA\x00".to_vec()).collect();
+        assert_eq!(owned, grams);
+
+        let keys: [&[u8]; 4] = [b"cat", b"cats", b"\xff\xfe\xfd\xfc", b"dog"];
+        let corpus: Corpus<[&[u8]; 4], TriGram<u8>> = Corpus::from(keys);
+        let config = NgramSearchConfig::default()
+            .set_minimum_similarity_score(0.0_f32)
+            .unwrap();
+
+        for key in keys {
+            let results: SearchResults<&&[u8], f32> = corpus.ngram_search(key, config);
+            assert_eq!(results[0].key(), &key);
+            assert_eq!(results[0].score(), 1.0);
+        }
+
+        let results: SearchResults<&&[u8], f32> =
+            corpus.ngram_search(b"\xff\xfe\xfd".as_slice(), config);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key(), &&b"\xff\xfe\xfd\xfc"[..]);
+
+        // The owned binary keys are grammed in the same way.
+        let owned_keys: Vec<Vec<u8>> = keys.iter().map(|key| key.to_vec()).collect();
+        let owned: Corpus<Vec<Vec<u8>>, TriGram<u8>> = Corpus::from(owned_keys);
+        assert!(owned.ngrams().eq(corpus.ngrams()));
+        let results = owned.ngram_search(b"cats".as_slice(), config);
+        assert_eq!(results[0].key().as_slice(), b"cats");
+        assert_eq!(results[0].score(), 1.0);
+    }
+}
@@ -57,6 +57,40 @@ impl<NG: Ngram, K: Key<NG, NG::G>> Keys<NG> for Vec<K> {
     }
 }
 
+/// Trait defining a container of keys that can be grown one key at a time.
+pub trait GrowableKeys<NG: Ngram>: Keys<NG> + Default {
+    /// Pushes the provided key at the end of the container.
+    ///
+    /// # Arguments
+    /// * `key` - The key to push.
+    fn push(&mut self, key: Self::K);
+}
+
+impl<NG: Ngram, K: Key<NG, NG::G>> GrowableKeys<NG> for Vec<K> {
+    fn push(&mut self, key: K) {
+        Vec::push(self, key)
+    }
+}
+
+/// Trait defining a container of keys from which keys can be dropped.
+pub trait RetainKeys<NG: Ngram>: Keys<NG> {
+    /// Retains the keys whose index satisfies the provided predicate, in their order.
+    ///
+    /// # Arguments
+    /// * `keep` - The predicate called with the index of each key.
+    fn retain_indices(&mut self, keep: impl FnMut(usize) -> bool);
+}
+
+impl<NG: Ngram, K: Key<NG, NG::G>> RetainKeys<NG> for Vec<K> {
+    fn retain_indices(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let mut index = 0;
+        self.retain(|_| {
+            index += 1;
+            keep(index - 1)
+        });
+    }
+}
+
 impl<const N: usize, NG: Ngram, K: Key<NG, NG::G>> Keys<NG> for [K; N] {
     type K = K;
     type KeyRef<'a> = &'a K where K: 'a, Self: 'a;
@@ -33,3 +33,7 @@ impl Paddable for char {
 impl Paddable for crate::ASCIIChar {
     const PADDING: Self = crate::ASCIIChar::NUL;
 }
+
+impl Paddable for crate::U16Char {
+    const PADDING: Self = crate::U16Char::NUL;
+}
@@ -0,0 +1,236 @@
+//! Submodule providing a character of the Basic Multilingual Plane stored in a `u16`.
+//!
+//! # Implementative details
+//! A `char` takes four bytes, while most of the characters of the Chinese, Japanese and
+//! Korean texts are in the Basic Multilingual Plane (BMP), whose code points fit in two.
+//! Using the `U16Char` as gram halves the memory of the ngrams with respect to `char`,
+//! while keeping the non-ASCII characters that the `ASCIIChar` discards. The characters
+//! outside of the BMP, such as most emojis and the rarest CJK ideographs, are skipped.
+
+use std::fmt::Debug;
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use mem_dbg::{MemDbg, MemSize};
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(MemSize, MemDbg)]
+#[repr(transparent)]
+/// Represents a character of the Basic Multilingual Plane.
+pub struct U16Char {
+    /// The code point of the character.
+    character: u16,
+}
+
+impl From<U16Char> for u16 {
+    #[inline(always)]
+    fn from(u16_char: U16Char) -> u16 {
+        u16_char.character
+    }
+}
+
+impl From<U16Char> for char {
+    #[inline(always)]
+    fn from(u16_char: U16Char) -> char {
+        // The code point comes from a char, so it is never a surrogate.
+        char::from_u32(u32::from(u16_char.character)).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl TryFrom<char> for U16Char {
+    type Error = &'static str;
+
+    #[inline(always)]
+    fn try_from(character: char) -> Result<Self, Self::Error> {
+        u16::try_from(u32::from(character))
+            .map(|character| U16Char { character })
+            .map_err(|_| "Character is not in the Basic Multilingual Plane")
+    }
+}
+
+impl Display for U16Char {
+    #[inline(always)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+impl Debug for U16Char {
+    #[inline(always)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "U16Char({})", char::from(*self))
+    }
+}
+
+/// Provides character operations by conversion to the corresponding `char`.
+impl U16Char {
+    /// The NUL character.
+    pub const NUL: Self = U16Char { character: 0 };
+    /// The space character.
+    pub const SPACE: Self = U16Char {
+        character: b' ' as u16,
+    };
+
+    #[inline(always)]
+    /// Returns the lowercase version of the character.
+    ///
+    /// # Implementation details
+    /// As for `char`, only the ASCII characters are lowercased.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let u16_char = U16Char::try_from('A').unwrap();
+    /// assert_eq!(u16_char.to_lowercase(), U16Char::try_from('a').unwrap());
+    /// ```
+    pub fn to_lowercase(self) -> Self {
+        U16Char {
+            character: u8::try_from(self.character)
+                .map_or(self.character, |byte| u16::from(byte.to_ascii_lowercase())),
+        }
+    }
+
+    #[inline(always)]
+    /// Returns the uppercase version of the character.
+    ///
+    /// # Implementation details
+    /// As for `char`, only the ASCII characters are uppercased.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let u16_char = U16Char::try_from('a').unwrap();
+    /// assert_eq!(u16_char.to_uppercase(), U16Char::try_from('A').unwrap());
+    /// ```
+    pub fn to_uppercase(self) -> Self {
+        U16Char {
+            character: u8::try_from(self.character)
+                .map_or(self.character, |byte| u16::from(byte.to_ascii_uppercase())),
+        }
+    }
+
+    #[inline(always)]
+    /// Returns whether the current character is a space-like.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// assert!(U16Char::try_from('\u{3000}').unwrap().is_space_like());
+    /// assert!(!U16Char::try_from('猫').unwrap().is_space_like());
+    /// ```
+    pub fn is_space_like(self) -> bool {
+        char::from(self).is_whitespace()
+    }
+
+    #[inline(always)]
+    /// Returns whether the current character is alphanumeric.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// assert!(U16Char::try_from('猫').unwrap().is_alphanumeric());
+    /// assert!(!U16Char::try_from('。').unwrap().is_alphanumeric());
+    /// ```
+    pub fn is_alphanumeric(self) -> bool {
+        char::from(self).is_alphanumeric()
+    }
+}
+
+/// Iterator that converts an iterator of `char` to an iterator of `U16Char`.
+///
+/// # Implementative details
+/// Since not all of the characters in the iterator are in the Basic Multilingual Plane, we
+/// FILTER OUT all the characters that are not, as the `ASCIICharIterator` does with the
+/// characters that are not ASCII.
+pub struct U16CharIterator<I> {
+    /// The iterator of characters.
+    iterator: I,
+}
+
+impl<I> From<I> for U16CharIterator<I> {
+    #[inline(always)]
+    fn from(iterator: I) -> Self {
+        U16CharIterator { iterator }
+    }
+}
+
+impl<I> Iterator for U16CharIterator<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = U16Char;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator
+            .by_ref()
+            .find_map(|character| U16Char::try_from(character).ok())
+    }
+}
+
+impl<I> DoubleEndedIterator for U16CharIterator<I>
+where
+    I: DoubleEndedIterator<Item = char>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iterator
+            .by_ref()
+            .rev()
+            .find_map(|character| U16Char::try_from(character).ok())
+    }
+}
+
+/// Trait to be implemented for all iterators that yield `char`
+/// so that they can be converted to `U16CharIterator`.
+pub trait ToU16CharIterator: IntoIterator<Item = char> {
+    /// Converts the iterator to an `U16CharIterator`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::prelude::*;
+    ///
+    /// let bmp = "猫🐈a".chars().bmp().collect::<String>();
+    ///
+    /// assert_eq!(bmp, "猫a");
+    /// ```
+    fn bmp(self) -> U16CharIterator<Self>
+    where
+        Self: Sized;
+}
+
+impl<I> ToU16CharIterator for I
+where
+    I: IntoIterator<Item = char>,
+{
+    #[inline(always)]
+    fn bmp(self) -> U16CharIterator<Self>
+    where
+        Self: Sized,
+    {
+        U16CharIterator::from(self)
+    }
+}
+
+/// Implements the collect to string of an iterator of `U16Char`.
+impl std::iter::FromIterator<U16Char> for String {
+    #[inline(always)]
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = U16Char>,
+    {
+        iter.into_iter().map(char::from).collect()
+    }
+}
@@ -4,6 +4,8 @@
 
 use dsi_bitstream::prelude::*;
 use mem_dbg::{MemDbg, MemSize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::io::{Cursor, Write};
 use sux::prelude::*;
 use webgraph::prelude::*;
@@ -12,6 +14,9 @@ type Writer<W> = BufBitWriter<LittleEndian, WordAdapter<u32, W>>;
 type Reader<R> = BufBitReader<LittleEndian, WordAdapter<u32, R>>;
 type EF = EliasFano<SelectFixed2>;
 
+/// The number of offsets between two consecutive samples of the gap-coded offsets.
+const OFFSETS_SAMPLING: usize = 32;
+
 /// A factory that can create a reader.
 /// The factory own the data and the reader borrows it.
 pub trait ReaderFactory {
@@ -24,9 +29,13 @@ pub trait ReaderFactory {
 }
 
 /// A factory that creates a reader from vec of u8.
+///
+/// Each reader borrows the data and keeps its own position in the bitstream, so that
+/// any number of readers can be created at the same time from a factory shared
+/// between threads.
 #[derive(Clone, Debug, MemSize, MemDbg)]
 pub struct CursorReaderFactory {
-    data: Vec<u8>,
+    pub(crate) data: Vec<u8>,
 }
 
 impl CursorReaderFactory {
@@ -53,6 +62,94 @@ impl ReaderFactory for CursorReaderFactory {
     }
 }
 
+/// Offsets stored as the gaps between consecutive offsets, which can be used in place of
+/// the default Elias-Fano offsets of `Weights` with `WeightsBuilder::build_gap_coded`.
+///
+/// # Implementation details
+/// The gaps are stored minus the minimum gap, with the number of bits of the largest
+/// one, so that the offsets of the nodes whose weights take a near-uniform number of
+/// bits take few bits each, and no bit at all when the number of bits is uniform.
+/// One every `OFFSETS_SAMPLING` offsets is stored in an Elias-Fano representation, and
+/// the other offsets are decoded by adding the following gaps to the previous sample.
+#[derive(Clone, Debug, MemSize, MemDbg)]
+pub struct GapCodedOffsets {
+    /// One every `OFFSETS_SAMPLING` offsets.
+    samples: EF,
+    /// The bitstream of the gaps minus the minimum gap.
+    gaps: CursorReaderFactory,
+    /// The minimum gap between consecutive offsets.
+    minimum_gap: usize,
+    /// The number of bits of each gap in the bitstream.
+    bit_width: usize,
+    /// How many offsets we have
+    len: usize,
+}
+
+impl GapCodedOffsets {
+    /// Returns the gap-coded representation of the provided sorted offsets.
+    ///
+    /// # Arguments
+    /// * `offsets` - The sorted offsets to store.
+    ///
+    /// # Raises
+    /// * If the offsets are not sorted.
+    pub fn from_sorted(offsets: &[usize]) -> Result<Self, &'static str> {
+        if offsets.windows(2).any(|window| window[0] > window[1]) {
+            return Err("The gap-coded offsets must be sorted.");
+        }
+        let gaps = || offsets.windows(2).map(|window| window[1] - window[0]);
+        let minimum_gap = gaps().min().unwrap_or(0);
+        let maximum_gap = gaps().max().unwrap_or(0);
+        let bit_width = (usize::BITS - (maximum_gap - minimum_gap).leading_zeros()) as usize;
+
+        let mut writer: Writer<Cursor<Vec<u8>>> =
+            BufBitWriter::new(WordAdapter::new(Cursor::new(Vec::new())));
+        // When all of the gaps are equal, there is nothing to write.
+        if bit_width > 0 {
+            for gap in gaps() {
+                writer
+                    .write_bits((gap - minimum_gap) as u64, bit_width)
+                    .unwrap();
+            }
+        }
+        let samples: Vec<usize> = offsets.iter().copied().step_by(OFFSETS_SAMPLING).collect();
+
+        Ok(GapCodedOffsets {
+            samples: elias_fano_from_sorted(&samples)?,
+            gaps: CursorReaderFactory::new(writer.into_inner().unwrap().into_inner().into_inner()),
+            minimum_gap,
+            bit_width,
+            len: offsets.len(),
+        })
+    }
+}
+
+impl IndexedDict for GapCodedOffsets {
+    type Input = usize;
+    type Output = usize;
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let sample = index / OFFSETS_SAMPLING;
+        let number_of_gaps = index % OFFSETS_SAMPLING;
+        let mut offset = IndexedDict::get_unchecked(&self.samples, sample);
+        offset += number_of_gaps * self.minimum_gap;
+        if self.bit_width > 0 && number_of_gaps > 0 {
+            // The gap preceding the offset of index `i` is the `i - 1`-th one.
+            let mut reader = self
+                .gaps
+                .get_reader(sample * OFFSETS_SAMPLING * self.bit_width);
+            for _ in 0..number_of_gaps {
+                offset += reader.read_bits(self.bit_width).unwrap() as usize;
+            }
+        }
+        offset
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
 /// A builder on which you can push the weights of a document.
 /// The compression is highly dependent on **our** weights distribution and thus
 /// it's not recommended to use this builder for other purposes.
@@ -158,6 +255,39 @@ impl WeightsBuilder {
         }
     }
 
+    /// Finishes the writing and returns the reader, with gap-coded offsets.
+    ///
+    /// # Implementation details
+    /// The gap-coded offsets are denser than the Elias-Fano ones when the weights of most
+    /// nodes take a similar number of bits, at the cost of decoding up to 31 gaps to
+    /// access the weights of a node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ngrammatic::weights::WeightsBuilder;
+    /// use webgraph::prelude::*;
+    ///
+    /// let mut builder = WeightsBuilder::new();
+    /// for node in 0..100 {
+    ///     builder.push(vec![1, 0, node % 3].into_iter()).unwrap();
+    /// }
+    /// let weights = builder.build_gap_coded();
+    ///
+    /// assert_eq!(weights.labels(50).collect::<Vec<_>>(), vec![1, 0, 2]);
+    /// ```
+    pub fn build_gap_coded(self) -> Weights<CursorReaderFactory, GapCodedOffsets> {
+        Weights {
+            num_nodes: self.num_nodes,
+            num_weights: self.num_weights,
+            // The offsets are sorted, as they are the increasing lengths of the bitstream.
+            offsets: GapCodedOffsets::from_sorted(&self.offsets).unwrap(),
+            reader_factory: CursorReaderFactory::new(
+                self.writer.into_inner().unwrap().into_inner().into_inner(),
+            ),
+        }
+    }
+
     #[cfg(feature = "rayon")]
     /// Finishes the writing and returns the reader.
     pub fn par_build(self) -> Weights {
@@ -188,16 +318,19 @@ impl WeightsBuilder {
 /// A builder on which you can push the weights of a document.
 /// The compression is highly dependent on **our** weights distribution and thus
 /// it's not recommended to use this builder for other purposes.
+///
+/// The weights are only read through the readers of the reader factory, so that they
+/// are `Send` and `Sync` whenever the reader factory and the offsets are.
 #[derive(Clone, Debug, MemSize, MemDbg)]
 pub struct Weights<RF = CursorReaderFactory, OFF = EF> {
     /// The factory of bitstream readers
-    reader_factory: RF,
+    pub(crate) reader_factory: RF,
     /// A vec of offsets gaps
-    offsets: OFF,
+    pub(crate) offsets: OFF,
     /// how many nodes we have
-    num_nodes: usize,
+    pub(crate) num_nodes: usize,
     /// how many weights we have
-    num_weights: usize,
+    pub(crate) num_weights: usize,
 }
 
 impl<RF, OFF> Weights<RF, OFF> {
@@ -409,7 +542,11 @@ impl<RF: ReaderFactory, OFF: IndexedDict<Input = usize, Output = usize>> Sequent
 {
     type Label = usize;
 
-    type Lender<'node> = Lender<<RF as ReaderFactory>::Reader<'node>> where RF: 'node, OFF: 'node;
+    type Lender<'node>
+        = Lender<<RF as ReaderFactory>::Reader<'node>>
+    where
+        RF: 'node,
+        OFF: 'node;
 
     fn num_nodes(&self) -> usize {
         self.num_nodes
@@ -429,7 +566,11 @@ impl<RF: ReaderFactory, OFF: IndexedDict<Input = usize, Output = usize>> Sequent
 impl<RF: ReaderFactory, OFF: IndexedDict<Input = usize, Output = usize>> RandomAccessLabeling
     for Weights<RF, OFF>
 {
-    type Labels<'succ> = Succ<<RF as ReaderFactory>::Reader<'succ>> where RF: 'succ, OFF: 'succ;
+    type Labels<'succ>
+        = Succ<<RF as ReaderFactory>::Reader<'succ>>
+    where
+        RF: 'succ,
+        OFF: 'succ;
 
     fn num_arcs(&self) -> u64 {
         self.num_weights as u64
@@ -456,22 +597,103 @@ impl<RF: ReaderFactory, OFF: IndexedDict<Input = usize, Output = usize>> Weights
     }
 }
 
+/// Returns an Elias-Fano representation of the provided sorted values.
+///
+/// # Arguments
+/// * `values` - The sorted values to store.
+///
+/// # Raises
+/// * If the values are not sorted.
+pub(crate) fn elias_fano_from_sorted(values: &[usize]) -> Result<EF, &'static str> {
+    if values.windows(2).any(|window| window[0] > window[1]) {
+        return Err("The Elias-Fano values must be sorted.");
+    }
+    let mut builder = EliasFanoBuilder::new(values.len(), values.last().copied().unwrap_or(0));
+    for &value in values {
+        // SAFETY: the values are sorted and bounded by the last one.
+        unsafe { builder.push_unchecked(value) };
+    }
+    Ok(builder.build().convert_to().unwrap())
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+/// Serializable representation of `Weights`, holding the raw bitstream and the offsets.
+struct SerdeWeights {
+    /// The bitstream of the weights.
+    data: Vec<u8>,
+    /// The bit offset where the weights of each node start.
+    offsets: Vec<usize>,
+    /// How many nodes we have
+    num_nodes: usize,
+    /// How many weights we have
+    num_weights: usize,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Weights {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeWeights {
+            data: self.reader_factory.data.clone(),
+            offsets: self.offsets.into_iter_from(0).collect(),
+            num_nodes: self.num_nodes,
+            num_weights: self.num_weights,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Weights {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let weights = SerdeWeights::deserialize(deserializer)?;
+        if weights.offsets.len() != weights.num_nodes {
+            return Err(D::Error::custom(
+                "The number of offsets must be equal to the number of nodes.",
+            ));
+        }
+        if weights
+            .offsets
+            .last()
+            .is_some_and(|&offset| offset > weights.data.len() * 8)
+        {
+            return Err(D::Error::custom(
+                "The offsets must be within the bitstream.",
+            ));
+        }
+        Ok(Weights {
+            reader_factory: CursorReaderFactory::new(weights.data),
+            offsets: elias_fano_from_sorted(&weights.offsets).map_err(D::Error::custom)?,
+            num_nodes: weights.num_nodes,
+            num_weights: weights.num_weights,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use lender::Lender;
+    use mem_dbg::SizeFlags;
 
     use super::*;
 
-    #[test]
-    fn test_weights() {
-        let weights = vec![
+    /// Returns the small dataset of weights used in the tests.
+    fn test_weights_dataset() -> Vec<Vec<usize>> {
+        vec![
             vec![1, 2, 3, 4, 5],
             vec![0, 0, 0, 0, 0],
             vec![1, 1, 1, 1, 1],
             vec![1, 0, 3, 2, 2],
             vec![0],
             vec![],
-        ];
+        ]
+    }
+
+    #[test]
+    fn test_weights() {
+        let weights = test_weights_dataset();
 
         let mut writer = WeightsBuilder::new();
         for row in weights.iter() {
@@ -517,4 +739,93 @@ mod test {
             assert_eq!(row, &weights);
         }
     }
+
+    #[test]
+    fn test_gap_coded_offsets() {
+        // Nodes whose weights take a uniform number of bits, and skewed random weights.
+        let uniform_weights: Vec<Vec<usize>> = (0..1_000).map(|_| vec![1, 0, 2]).collect();
+        let skewed_weights: Vec<Vec<usize>> = (0..1_000)
+            .map(|node| (0..node % 23).map(|i| (i * node) % 7).collect())
+            .collect();
+
+        for weights in [test_weights_dataset(), uniform_weights, skewed_weights] {
+            let mut elias_fano_writer = WeightsBuilder::new();
+            let mut gaps_writer = WeightsBuilder::new();
+            for row in weights.iter() {
+                elias_fano_writer.push(row.iter().copied()).unwrap();
+                gaps_writer.push(row.iter().copied()).unwrap();
+            }
+            let elias_fano = elias_fano_writer.build();
+            let gaps = gaps_writer.build_gap_coded();
+
+            assert_eq!(elias_fano.offsets.len(), gaps.offsets.len());
+            for index in 0..elias_fano.offsets.len() {
+                assert_eq!(elias_fano.offsets.get(index), gaps.offsets.get(index));
+            }
+            for (i, row) in weights.iter().enumerate() {
+                assert_eq!(&gaps.labels(i).collect::<Vec<_>>(), row);
+                assert_eq!(gaps.outdegree(i), row.len());
+            }
+            assert!(elias_fano.weights().eq(gaps.weights()));
+        }
+
+        // With a uniform number of bits per node, only the samples are stored.
+        let mut elias_fano_writer = WeightsBuilder::new();
+        let mut gaps_writer = WeightsBuilder::new();
+        for _ in 0..10_000 {
+            elias_fano_writer.push([1, 0, 2].into_iter()).unwrap();
+            gaps_writer.push([1, 0, 2].into_iter()).unwrap();
+        }
+        let elias_fano = elias_fano_writer.build().offsets;
+        let gaps = gaps_writer.build_gap_coded().offsets;
+        assert!(gaps.mem_size(SizeFlags::default()) < elias_fano.mem_size(SizeFlags::default()));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_build_and_par_build_are_identical() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let random_weights: Vec<Vec<usize>> = (0..10_000)
+            .map(|_| {
+                let degree = rng.gen_range(0..40);
+                (0..degree)
+                    .map(|_| {
+                        // We skew the weights towards zero, as in the corpus co-occurrences.
+                        if rng.gen_bool(0.7) {
+                            0
+                        } else {
+                            rng.gen_range(1..20)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for weights in [test_weights_dataset(), random_weights] {
+            let mut serial_writer = WeightsBuilder::new();
+            let mut parallel_writer = WeightsBuilder::new();
+            for row in weights.iter() {
+                serial_writer.push(row.iter().copied()).unwrap();
+                parallel_writer.push(row.iter().copied()).unwrap();
+            }
+
+            let serial = serial_writer.build();
+            let parallel = parallel_writer.par_build();
+
+            assert_eq!(serial.num_nodes(), parallel.num_nodes());
+            assert_eq!(serial.num_weights(), parallel.num_weights());
+
+            let (serial_factory, serial_offsets) = serial.into_inner();
+            let (parallel_factory, parallel_offsets) = parallel.into_inner();
+
+            assert_eq!(serial_factory.into_inner(), parallel_factory.into_inner());
+            assert_eq!(serial_offsets.len(), parallel_offsets.len());
+            for index in 0..serial_offsets.len() {
+                assert_eq!(serial_offsets.get(index), parallel_offsets.get(index));
+            }
+        }
+    }
 }
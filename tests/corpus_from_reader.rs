@@ -0,0 +1,48 @@
+//! Integration tests building a corpus from the lines of a reader.
+#![cfg(feature = "mmap")]
+use ngrammatic::prelude::*;
+use std::io::Cursor;
+
+#[test]
+/// Test that the corpus built from a reader is the same one built from the collected lines.
+fn test_from_reader_matches_from_lines() {
+    let text = ANIMALS.join("\n");
+    let corpus: Corpus<StringPool, TriGram<char>> =
+        Corpus::from_reader(Cursor::new(text.as_bytes())).unwrap();
+    let expected: Corpus<Vec<String>, TriGram<char>> =
+        Corpus::from(text.lines().map(str::to_string).collect::<Vec<String>>());
+
+    assert_eq!(corpus.number_of_keys(), ANIMALS.len());
+    assert_eq!(corpus.number_of_keys(), expected.number_of_keys());
+    assert_eq!(corpus.average_key_length(), expected.average_key_length());
+    assert!(corpus.ngrams().eq(expected.ngrams()));
+    assert!(corpus.cooccurrences().eq(expected.cooccurrences()));
+    for key_id in 0..corpus.number_of_keys() {
+        assert_eq!(corpus.key_from_id(key_id), expected.key_from_id(key_id));
+        assert!(corpus
+            .ngram_ids_from_key(key_id)
+            .eq(expected.ngram_ids_from_key(key_id)));
+    }
+
+    let config = NgramSearchConfig::default()
+        .set_minimum_similarity_score(0.5_f32)
+        .unwrap();
+    for query in ["Cat", "Catt", "Sea lion"] {
+        let results = corpus.ngram_search(query, config);
+        let expected_results = expected.ngram_search(query, config);
+        assert!(results.scores().eq(expected_results.scores()));
+    }
+}
+
+#[test]
+/// Test that a reader error is returned instead of panicking.
+fn test_from_reader_returns_the_reader_errors() {
+    let bytes: &[u8] = b"cat\n\xff\xfe\ndog\n";
+    let corpus: std::io::Result<Corpus<StringPool, TriGram<char>>> =
+        Corpus::from_reader(Cursor::new(bytes));
+
+    assert_eq!(
+        corpus.err().map(|error| error.kind()),
+        Some(std::io::ErrorKind::InvalidData)
+    );
+}
@@ -0,0 +1,180 @@
+//! Integration tests building a corpus around a graph implemented outside of the crate.
+use ngrammatic::prelude::*;
+use std::iter::Copied;
+use std::slice::Iter;
+
+/// A trivial weighted bipartite graph storing its edges in vectors.
+struct VecGraph {
+    /// The sorted dsts of each src.
+    dsts: Vec<Vec<usize>>,
+    /// The weights of the edges of each src.
+    weights: Vec<Vec<usize>>,
+    /// The sorted srcs of each dst.
+    srcs: Vec<Vec<usize>>,
+    /// The weights of all of the edges, in the order of the srcs.
+    all_weights: Vec<usize>,
+    /// The degrees of the srcs followed by the ones of the dsts.
+    degrees: Vec<usize>,
+}
+
+impl WeightedBipartiteGraph for VecGraph {
+    fn number_of_source_nodes(&self) -> usize {
+        self.dsts.len()
+    }
+
+    fn number_of_destination_nodes(&self) -> usize {
+        self.srcs.len()
+    }
+
+    fn number_of_edges(&self) -> usize {
+        self.all_weights.len()
+    }
+
+    fn src_degree(&self, src_id: usize) -> usize {
+        self.dsts[src_id].len()
+    }
+
+    fn dst_degree(&self, dst_id: usize) -> usize {
+        self.srcs[dst_id].len()
+    }
+
+    type Srcs<'a> = Copied<Iter<'a, usize>>;
+
+    fn srcs_from_dst(&self, dst_id: usize) -> Self::Srcs<'_> {
+        self.srcs[dst_id].iter().copied()
+    }
+
+    type SrcsFromDsts<'a> = SrcsFromDstIds<'a, Self>;
+
+    fn srcs_from_dsts(&self) -> Self::SrcsFromDsts<'_> {
+        SrcsFromDstIds::new(self)
+    }
+
+    type Dsts<'a> = Copied<Iter<'a, usize>>;
+
+    fn dsts_from_src(&self, src_id: usize) -> Self::Dsts<'_> {
+        self.dsts[src_id].iter().copied()
+    }
+
+    type WeightsSrc<'a> = Copied<Iter<'a, usize>>;
+
+    fn weights_from_src(&self, src_id: usize) -> Self::WeightsSrc<'_> {
+        self.weights[src_id].iter().copied()
+    }
+
+    type Weights<'a> = Copied<Iter<'a, usize>>;
+
+    fn weights(&self) -> Self::Weights<'_> {
+        self.all_weights.iter().copied()
+    }
+
+    type Degrees<'a> = Copied<Iter<'a, usize>>;
+
+    fn degrees(&self) -> Self::Degrees<'_> {
+        self.degrees.iter().copied()
+    }
+}
+
+/// Returns the corpus of the provided keys built around a `VecGraph`.
+fn build_vec_graph_corpus<const N: usize>(
+    keys: [&str; N],
+) -> Corpus<[&str; N], TriGram<char>, str, VecGraph> {
+    let key_ngrams: Vec<Vec<(TriGram<char>, usize)>> = keys
+        .iter()
+        .map(|key| {
+            let mut counts: Vec<(TriGram<char>, usize)> = Key::<TriGram<char>, char>::counts(*key)
+                .into_iter()
+                .collect();
+            counts.sort_unstable();
+            counts
+        })
+        .collect();
+
+    let mut ngrams: Vec<TriGram<char>> = key_ngrams
+        .iter()
+        .flat_map(|counts| counts.iter().map(|(ngram, _)| *ngram))
+        .collect();
+    ngrams.sort_unstable();
+    ngrams.dedup();
+
+    let mut graph = VecGraph {
+        dsts: Vec::new(),
+        weights: Vec::new(),
+        srcs: vec![Vec::new(); ngrams.len()],
+        all_weights: Vec::new(),
+        degrees: Vec::new(),
+    };
+    for (key_id, counts) in key_ngrams.iter().enumerate() {
+        let mut dsts = Vec::new();
+        let mut weights = Vec::new();
+        for (ngram, count) in counts {
+            let ngram_id = ngrams.binary_search(ngram).unwrap();
+            dsts.push(ngram_id);
+            // The weights are the co-occurrences minus one.
+            weights.push(count - 1);
+            graph.srcs[ngram_id].push(key_id);
+        }
+        graph.all_weights.extend(weights.iter().copied());
+        graph.dsts.push(dsts);
+        graph.weights.push(weights);
+    }
+    graph.degrees = graph
+        .dsts
+        .iter()
+        .chain(graph.srcs.iter())
+        .map(Vec::len)
+        .collect();
+
+    // The length of the keys counts each occurrence of their ngrams.
+    let total_key_length: usize = graph.weights().map(|weight| weight + 1).sum();
+    let average_key_length = total_key_length as f64 / keys.len() as f64;
+
+    type Builder =
+        <<TriGram<char> as Ngram>::SortedStorage as SortedNgramStorage<TriGram<char>>>::Builder;
+    let mut builder: Builder =
+        SortedNgramStorageBuilder::new_storage_builder(ngrams.len(), *ngrams.last().unwrap());
+    for ngram in ngrams {
+        // SAFETY: the ngrams are sorted, unique and at most the last one.
+        unsafe { SortedNgramStorageBuilder::push_unchecked(&mut builder, ngram) };
+    }
+
+    Corpus::new(
+        keys,
+        SortedNgramStorageBuilder::build(builder),
+        average_key_length,
+        graph,
+    )
+}
+
+#[test]
+/// Test that a corpus built around a custom graph yields the same search results.
+fn test_custom_graph_corpus_matches_default_corpus() {
+    let keys = ["cat", "cattle", "dog", "doggo", "catfish", "banana"];
+    let corpus = build_vec_graph_corpus(keys);
+    let expected: Corpus<[&str; 6], TriGram<char>> = Corpus::from(keys);
+
+    assert_eq!(corpus.number_of_keys(), expected.number_of_keys());
+    assert_eq!(corpus.number_of_ngrams(), expected.number_of_ngrams());
+    assert!(corpus.ngrams().eq(expected.ngrams()));
+    assert_eq!(corpus.average_key_length(), expected.average_key_length());
+    assert!(corpus.cooccurrences().eq(expected.cooccurrences()));
+
+    let ana = corpus.ngram_id_from_ngram(['a', 'n', 'a']).unwrap();
+    assert_eq!(corpus.cooccurrence(5, ana), Some(2));
+    assert_eq!(corpus.cooccurrence(0, ana), None);
+
+    let config = NgramSearchConfig::default()
+        .set_minimum_similarity_score(0.3_f32)
+        .unwrap();
+    for query in ["cat", "catt", "doge", "bananas"] {
+        let results = corpus.ngram_search(query, config);
+        let expected_results = expected.ngram_search(query, config);
+
+        assert!(!results.is_empty(), "Query {:?}", query);
+        assert_eq!(results.len(), expected_results.len());
+        for (result, expected_result) in results.iter().zip(expected_results.iter()) {
+            assert_eq!(result.key(), expected_result.key());
+            assert_eq!(result.score(), expected_result.score());
+        }
+    }
+}
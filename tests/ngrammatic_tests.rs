@@ -1 +1,180 @@
+//! Integration tests comparing the new corpus with the legacy one.
+use ngrammatic::prelude::*;
 
+/// Returns the ranking of the legacy corpus, as the keys and the scores of its results.
+///
+/// # Arguments
+/// * `legacy_corpus` - The legacy corpus to search.
+/// * `query` - The query to search for.
+/// * `threshold` - The minimum similarity of the results.
+/// * `keys` - The keys the legacy corpus was built from, in the order they were added.
+///
+/// # Implementation details
+/// The legacy corpus ranks the results with the same score in an arbitrary order, while
+/// the new corpus ranks them by key id, which is the position of the key in the keys the
+/// corpus was built from. The ties of the legacy ranking are therefore sorted by the
+/// position of their key, which is the only intended divergence between the two rankings.
+/// The legacy corpus may store the lowercased keys, so the keys are mapped back to the
+/// provided ones ignoring their case.
+fn legacy_ranking(
+    legacy_corpus: &ngrammatic_old::Corpus,
+    query: &str,
+    threshold: f32,
+    keys: &[&str],
+) -> Vec<(String, f32)> {
+    let mut ranking: Vec<(usize, f32)> = legacy_corpus
+        .search(query, threshold)
+        .into_iter()
+        .map(|result| {
+            let position = keys
+                .iter()
+                .position(|key| key.to_lowercase() == result.text.to_lowercase())
+                .unwrap();
+            (position, result.similarity)
+        })
+        .collect();
+    ranking.sort_by(
+        |(left_position, left_score), (right_position, right_score)| {
+            right_score
+                .total_cmp(left_score)
+                .then(left_position.cmp(right_position))
+        },
+    );
+    ranking
+        .into_iter()
+        .map(|(position, score)| (keys[position].to_string(), score))
+        .collect()
+}
+
+/// Asserts that the two rankings have the same keys in the same order, and the same scores.
+///
+/// # Arguments
+/// * `query` - The query of the rankings, reported when they differ.
+/// * `ranking` - The ranking of the new corpus.
+/// * `legacy_ranking` - The ranking of the legacy corpus.
+fn assert_same_ranking(query: &str, ranking: &[(String, f32)], legacy_ranking: &[(String, f32)]) {
+    let keys: Vec<&str> = ranking.iter().map(|(key, _)| key.as_str()).collect();
+    let legacy_keys: Vec<&str> = legacy_ranking.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, legacy_keys, "Query {:?}", query);
+    for ((_, score), (_, legacy_score)) in ranking.iter().zip(legacy_ranking) {
+        // The legacy corpus computes the scores in single precision.
+        assert!(
+            (score - legacy_score).abs() < 1e-5,
+            "Query {:?}: score {} against the legacy score {}",
+            query,
+            score,
+            legacy_score
+        );
+    }
+}
+
+#[test]
+/// Test that migrating the keys of a legacy corpus yields equivalent search results.
+fn test_legacy_corpus_migration() {
+    let keys = ["cat", "cattle", "dog", "doggo", "catfish", "dogfish", "cow"];
+    let threshold = 0.2_f32;
+
+    let mut legacy_corpus = ngrammatic_old::CorpusBuilder::new()
+        .arity(3)
+        .pad_full(ngrammatic_old::Pad::Auto)
+        .finish();
+
+    for key in keys {
+        legacy_corpus.add_text(key);
+    }
+
+    let corpus: Corpus<Vec<String>, TriGram<char>> = Corpus::from(
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<String>>(),
+    );
+
+    // The legacy corpus scores every key sharing an ngram with the query, so that no
+    // ngram is excluded from the candidates, however common it is.
+    let config = NgramSearchConfig::default()
+        .set_minimum_similarity_score(threshold)
+        .unwrap()
+        .set_max_ngram_degree(MaxNgramDegree::None);
+
+    for query in ["catt", "dogo", "catfsh", "cat", "dogfish", "cw", "zebra"] {
+        let ranking: Vec<(String, f32)> = corpus
+            .ngram_search(query, config)
+            .iter()
+            .map(|result| (result.key().to_string(), result.score()))
+            .collect();
+        assert_same_ranking(
+            query,
+            &ranking,
+            &legacy_ranking(&legacy_corpus, query, threshold, &keys),
+        );
+    }
+
+    // The legacy case insensitive option maps to the `Lowercase` key marker.
+    let mut legacy_corpus = ngrammatic_old::CorpusBuilder::new()
+        .arity(3)
+        .pad_full(ngrammatic_old::Pad::Auto)
+        .case_insensitive()
+        .finish();
+
+    let keys = ["Cat", "Cattle", "Dog", "Doggo", "Catfish", "Dogfish", "Cow"];
+    for key in keys {
+        legacy_corpus.add_text(key);
+    }
+
+    let corpus: Corpus<Vec<String>, TriGram<char>, Lowercase<str>> = Corpus::from(
+        keys.iter()
+            .map(|key| key.to_string())
+            .collect::<Vec<String>>(),
+    );
+
+    for query in ["CATT", "Dogo", "catFSH", "cat", "DOGFISH"] {
+        let ranking: Vec<(String, f32)> = corpus
+            .ngram_search(query, config)
+            .iter()
+            .map(|result| (result.key().to_string(), result.score()))
+            .collect();
+        assert_same_ranking(
+            query,
+            &ranking,
+            &legacy_ranking(&legacy_corpus, query, threshold, &keys),
+        );
+    }
+}
+
+#[test]
+/// Test that the corpus builder mirrors the case insensitive legacy builder.
+fn test_corpus_builder_mirrors_legacy_builder() {
+    let keys = ["Cat", "Cattle", "Dog", "Doggo", "Catfish"];
+
+    let mut legacy_corpus = ngrammatic_old::CorpusBuilder::new()
+        .arity(2)
+        .pad_full(ngrammatic_old::Pad::Auto)
+        .case_insensitive()
+        .finish();
+
+    for key in keys {
+        legacy_corpus.add_text(key);
+    }
+
+    let owned_keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+    let corpus = CorpusBuilder::new()
+        .arity::<2>()
+        .case_insensitive()
+        .build(owned_keys.clone());
+    let expected: Corpus<Vec<String>, BiGram<char>, Lowercase<str>> = Corpus::from(owned_keys);
+    assert!(corpus.ngrams().eq(expected.ngrams()));
+
+    let config = NgramSearchConfig::default()
+        .set_minimum_similarity_score(0.5_f32)
+        .unwrap();
+
+    for key in keys {
+        let query = key.to_uppercase();
+        let legacy_results = legacy_corpus.search(&query, 0.5);
+        let results = corpus.ngram_search(query.as_str(), config);
+
+        // The legacy corpus may store the lowercased keys.
+        assert_eq!(legacy_results[0].text.to_lowercase(), key.to_lowercase());
+        assert_eq!(results[0].key().as_str(), key);
+    }
+}